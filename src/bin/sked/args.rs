@@ -0,0 +1,239 @@
+use std::path::PathBuf;
+
+use super::error::Error;
+use super::inspect::Tool;
+
+pub const USAGE: &str = "\
+Usage: sked [--config FILE] [--json] <command>
+
+Commands:
+  status <space> [--at TIME]   Whether a space is open
+  next <space> [--at TIME]     When a space next opens or closes
+  week <space> [--from DATE]   A space's hours for a week
+  open-now [--at TIME]         The spaces which are open
+  import <file.pdf> [--out FILE] [--dry-run] [--offset OFFSET]
+                               Read spaces' hours from a schedule PDF
+  pdf ops|text|objects|grid <file.pdf> [--page N]
+                               Show a PDF's operations, positioned text, object
+                               graph or reconstructed tables
+
+Options:
+  --config FILE   The spaces' hours, as TOML [default: $SKED_CONFIG or sked.toml]
+  --json          Print JSON rather than text
+  --at TIME       A time, e.g. 2019-11-28T09:00 or 2019-11-28T09:00:00-06:00 [default: now]
+  --from DATE     The first day, e.g. 2019-11-25 [default: today]
+  --out FILE      Where to write imported hours [default: print them]
+  --dry-run       Print the tables and hours read, rather than writing them
+  --offset OFFSET The time zone of imported dates, e.g. -05:00 [default: local]
+  --page N        Only page N, counting from 1 [default: every page]
+  -h, --help      Print this
+";
+
+/// What to do.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Command {
+	Status {
+		space: String,
+		at: Option<String>,
+	},
+	Next {
+		space: String,
+		at: Option<String>,
+	},
+	Week {
+		space: String,
+		from: Option<String>,
+	},
+	OpenNow {
+		at: Option<String>,
+	},
+	Import {
+		file: PathBuf,
+		out: Option<PathBuf>,
+		dry_run: bool,
+		offset: Option<String>,
+	},
+	Pdf {
+		tool: Tool,
+		file: PathBuf,
+		page: Option<u32>,
+	},
+	Help,
+}
+
+/// A parsed command line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Args {
+	pub command: Command,
+	pub config: Option<PathBuf>,
+	pub json: bool,
+}
+
+/// The value of the option `name`, which must follow it.
+fn value(name: &str, args: &mut impl Iterator<Item = String>) -> Result<String, Error> {
+	args
+		.next()
+		.ok_or_else(|| Error::Usage(format!("{name} needs a value")))
+}
+
+impl Args {
+	/// Parse the arguments after the program's name.  Options may come
+	/// anywhere.
+	pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, Error> {
+		let mut args = args.into_iter();
+		let mut positional: Vec<String> = Vec::new();
+		let (mut config, mut json, mut at, mut from, mut help) = (None, false, None, None, false);
+		let (mut out, mut dry_run, mut offset, mut page) = (None, false, None, None);
+
+		while let Some(arg) = args.next() {
+			match arg.as_str() {
+				"--config" => config = Some(PathBuf::from(value(&arg, &mut args)?)),
+				"--json" => json = true,
+				"--at" => at = Some(value(&arg, &mut args)?),
+				"--from" => from = Some(value(&arg, &mut args)?),
+				"--out" => out = Some(PathBuf::from(value(&arg, &mut args)?)),
+				"--dry-run" => dry_run = true,
+				"--offset" => offset = Some(value(&arg, &mut args)?),
+				"--page" => {
+					let number: String = value(&arg, &mut args)?;
+					page = Some(
+						number
+							.parse::<u32>()
+							.ok()
+							.filter(|page| *page > 0)
+							.ok_or_else(|| Error::Usage(format!("--page {number} isn't a page number")))?,
+					);
+				}
+				"-h" | "--help" => help = true,
+				option if option.starts_with('-') && option.len() > 1 => {
+					return Err(Error::Usage(format!("unknown option {option}")));
+				}
+				_ => positional.push(arg),
+			}
+		}
+
+		let command: Command = match (help, positional.as_slice()) {
+			(true, _) | (false, []) => Command::Help,
+			(false, [command, space]) if command == "status" => Command::Status {
+				space: space.clone(),
+				at,
+			},
+			(false, [command, space]) if command == "next" => Command::Next {
+				space: space.clone(),
+				at,
+			},
+			(false, [command, space]) if command == "week" => Command::Week {
+				space: space.clone(),
+				from,
+			},
+			(false, [command]) if command == "open-now" => Command::OpenNow { at },
+			(false, [command, file]) if command == "import" => Command::Import {
+				file: PathBuf::from(file),
+				out,
+				dry_run,
+				offset,
+			},
+			(false, [command, tool, file]) if command == "pdf" => Command::Pdf {
+				tool: Tool::parse(tool).ok_or_else(|| Error::Usage(format!("unknown pdf tool {tool}")))?,
+				file: PathBuf::from(file),
+				page,
+			},
+			(false, [command, ..]) => {
+				return Err(Error::Usage(match command.as_str() {
+					"status" | "next" | "week" => format!("{command} takes one space"),
+					"import" => "import takes one file".to_string(),
+					"pdf" => "pdf takes a tool and a file, e.g. pdf text hours.pdf".to_string(),
+					"open-now" => "open-now doesn't take a space".to_string(),
+					_ => format!("unknown command {command}"),
+				}));
+			}
+		};
+
+		Ok(Self {
+			command,
+			config,
+			json,
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{Args, Command, Tool};
+	use std::path::PathBuf;
+
+	fn parse(args: &[&str]) -> Result<Args, String> {
+		Args::parse(args.iter().map(ToString::to_string)).map_err(|error| error.to_string())
+	}
+
+	#[test]
+	fn commands() {
+		assert_eq!(
+			parse(&["--json", "status", "Library", "--at", "2019-11-28T09:00"]).unwrap(),
+			Args {
+				command: Command::Status {
+					space: "Library".to_string(),
+					at: Some("2019-11-28T09:00".to_string()),
+				},
+				config: None,
+				json: true,
+			}
+		);
+		assert_eq!(
+			parse(&["open-now", "--config", "hours.toml"])
+				.unwrap()
+				.config
+				.unwrap()
+				.to_str(),
+			Some("hours.toml")
+		);
+		assert_eq!(
+			parse(&["import", "hours.pdf", "--dry-run"])
+				.unwrap()
+				.command,
+			Command::Import {
+				file: PathBuf::from("hours.pdf"),
+				out: None,
+				dry_run: true,
+				offset: None,
+			}
+		);
+		assert_eq!(
+			parse(&["pdf", "ops", "hours.pdf", "--page", "2"])
+				.unwrap()
+				.command,
+			Command::Pdf {
+				tool: Tool::Ops,
+				file: PathBuf::from("hours.pdf"),
+				page: Some(2),
+			}
+		);
+		assert_eq!(parse(&[]).unwrap().command, Command::Help);
+	}
+
+	#[test]
+	fn errors() {
+		assert_eq!(parse(&["week"]).unwrap_err(), "week takes one space");
+		assert_eq!(
+			parse(&["open-now", "Library"]).unwrap_err(),
+			"open-now doesn't take a space"
+		);
+		assert_eq!(parse(&["close"]).unwrap_err(), "unknown command close");
+		assert_eq!(
+			parse(&["pdf", "fonts", "hours.pdf"]).unwrap_err(),
+			"unknown pdf tool fonts"
+		);
+		assert_eq!(
+			parse(&["pdf", "ops", "hours.pdf", "--page", "0"]).unwrap_err(),
+			"--page 0 isn't a page number"
+		);
+		assert_eq!(
+			parse(&["status", "Library", "--at"]).unwrap_err(),
+			"--at needs a value"
+		);
+		assert_eq!(
+			parse(&["--verbose"]).unwrap_err(),
+			"unknown option --verbose"
+		);
+	}
+}
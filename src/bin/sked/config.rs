@@ -0,0 +1,74 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::FixedOffset;
+use serde::{Deserialize, Serialize};
+use sked::Space;
+
+use super::error::Error;
+
+/// The spaces `sked` knows about, e.g.
+///
+/// ```toml
+/// [[spaces]]
+/// name = "Library"
+///
+/// [[spaces.schedules]]
+/// effective = "2019-08-21T00:00:00-05:00"
+///
+/// [[spaces.schedules.parts]]
+/// open = { weekly = { day = "Mon", time = "07:00" } }
+/// close = { weekly = { day = "Mon", time = "23:00" } }
+/// ```
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+	#[serde(default)]
+	pub spaces: Vec<Space<'static, FixedOffset>>,
+}
+
+impl Config {
+	/// Where the config is: `path` if given, or else `$SKED_CONFIG`, or else
+	/// `sked.toml`.
+	pub fn path(path: Option<&Path>) -> PathBuf {
+		path
+			.map(Path::to_path_buf)
+			.or_else(|| env::var_os("SKED_CONFIG").map(PathBuf::from))
+			.unwrap_or_else(|| PathBuf::from("sked.toml"))
+	}
+
+	/// Read the config at `path`.  Every weekly and daily specifier's day
+	/// and time are checked as it's read, so a bad one is reported here,
+	/// with where it is, rather than failing when hours are worked out.
+	pub fn load(path: &Path) -> Result<Self, Error> {
+		let text: String = fs::read_to_string(path)
+			.map_err(|error| Error::Failed(format!("couldn't read {}: {error}", path.display())))?;
+		toml::from_str(&text).map_err(|error| {
+			Error::Failed(format!(
+				"couldn't read {}: {}",
+				path.display(),
+				error.to_string().trim_end()
+			))
+		})
+	}
+
+	/// The space called `name`, ignoring case.
+	pub fn space(&self, name: &str) -> Result<&Space<'static, FixedOffset>, Error> {
+		self
+			.spaces
+			.iter()
+			.find(|space| space.name().eq_ignore_ascii_case(name))
+			.ok_or_else(|| {
+				let names: Vec<&str> = self.spaces.iter().map(Space::name).collect();
+				Error::Failed(format!(
+					"no space called {name}; there's {}",
+					if names.is_empty() {
+						"none".to_string()
+					} else {
+						names.join(", ")
+					}
+				))
+			})
+	}
+}
@@ -0,0 +1,31 @@
+use core::fmt;
+
+/// Why a command couldn't run.
+#[derive(Debug)]
+pub enum Error {
+	/// The command line didn't make sense.
+	Usage(String),
+
+	/// The command made sense but failed, e.g. the config couldn't be read.
+	Failed(String),
+}
+
+impl Error {
+	/// The status to exit with: 2 for usage errors and 1 for failures.
+	pub fn code(&self) -> u8 {
+		match self {
+			Error::Usage(_) => 2,
+			Error::Failed(_) => 1,
+		}
+	}
+}
+
+impl fmt::Display for Error {
+	fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Error::Usage(message) | Error::Failed(message) => write!(formatter, "{message}"),
+		}
+	}
+}
+
+impl std::error::Error for Error {}
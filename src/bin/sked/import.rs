@@ -0,0 +1,155 @@
+//! The `import` command: reading spaces' hours from a schedule PDF into a
+//! config file.
+
+use core::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+use chrono::{FixedOffset, Local};
+use sked::pdf::import::{Hours, Import};
+use sked::pdf::table::Table;
+use sked::pdf::Pdf;
+
+use super::config::Config;
+use super::error::Error;
+
+/// The time zone given by `--offset`, e.g. `-05:00`, or the local one.
+pub fn offset(offset: Option<&str>) -> Result<FixedOffset, Error> {
+	match offset {
+		None => Ok(*Local::now().offset()),
+		Some(text) => text
+			.parse()
+			.map_err(|_| Error::Usage(format!("--offset {text} isn't an offset, e.g. -05:00"))),
+	}
+}
+
+pub fn load(file: &Path) -> Result<Pdf, Error> {
+	Pdf::load(file)
+		.map_err(|error| Error::Failed(format!("couldn't read {}: {error}", file.display())))
+}
+
+/// The text of `table`, in aligned columns, with each cell in the first
+/// row and column it covers.
+pub fn grid(table: &Table) -> String {
+	let mut rows: Vec<Vec<String>> = vec![vec![String::new(); table.columns()]; table.rows()];
+	for cell in table.cells() {
+		rows[cell.row()][cell.column()] = cell
+			.text()
+			.split_whitespace()
+			.collect::<Vec<&str>>()
+			.join(" ");
+	}
+
+	let widths: Vec<usize> = (0..table.columns())
+		.map(|column| {
+			rows
+				.iter()
+				.map(|row| row[column].chars().count())
+				.max()
+				.unwrap_or(0)
+		})
+		.collect();
+
+	let mut grid = String::new();
+	for row in &rows {
+		let line: Vec<String> = row
+			.iter()
+			.zip(&widths)
+			.map(|(text, width)| format!("{text:width$}"))
+			.collect();
+		let _ = writeln!(grid, "{}", line.join(" | ").trim_end());
+	}
+	grid
+}
+
+fn hours(hours: Option<&Hours>) -> String {
+	match hours {
+		Some(Hours::Closed(word)) => word.clone(),
+		Some(Hours::Open { open, close }) => {
+			format!("{}–{}", open.format("%H:%M"), close.format("%H:%M"))
+		}
+		None => "?".to_string(),
+	}
+}
+
+/// What `--dry-run` prints: each table found, what was read from each of
+/// its entries, and the review.
+fn review(pdf: &Pdf, import: &Import<'_, FixedOffset>, json: bool) -> Result<String, Error> {
+	if json {
+		return Ok(import.review().to_json());
+	}
+
+	let tables: Vec<Table> = pdf
+		.tables()
+		.map_err(|error| Error::Failed(format!("couldn't read the tables: {error}")))?;
+	let mut output = String::new();
+	for table in &tables {
+		let _ = writeln!(
+			output,
+			"Table on page {}, {} rows by {} columns:\n",
+			table
+				.page()
+				.map_or_else(|| "?".to_string(), |page| page.to_string()),
+			table.rows(),
+			table.columns()
+		);
+		let _ = writeln!(output, "{}", grid(table));
+	}
+
+	output.push_str("Hours:\n\n");
+	for item in import.review().items() {
+		let _ = writeln!(
+			output,
+			"{}, {}: {:?} -> {} ({:.2})",
+			item.space(),
+			item.day(),
+			item.provenance().text(),
+			hours(item.hours()),
+			item.provenance().confidence()
+		);
+	}
+
+	let _ = write!(output, "\n{}", import.review());
+	Ok(output.trim_end().to_string())
+}
+
+/// Import `file`, writing the spaces to `out` or printing them, or with
+/// `dry_run` printing what was read.
+pub fn import(
+	file: &Path,
+	out: Option<&Path>,
+	dry_run: bool,
+	offset: FixedOffset,
+	json: bool,
+) -> Result<String, Error> {
+	let pdf: Pdf = load(file)?;
+	let import: Import<FixedOffset> = Import::from_pdf(&pdf, &offset)
+		.map_err(|error| Error::Failed(format!("couldn't import {}: {error}", file.display())))?;
+	if import.spaces().is_empty() {
+		return Err(Error::Failed(format!(
+			"found no schedule tables in {}",
+			file.display()
+		)));
+	}
+	if dry_run {
+		return review(&pdf, &import, json);
+	}
+
+	let flagged: usize = import.review().flagged().count();
+	let config = Config {
+		spaces: import.into_spaces(),
+	};
+	let text: String = toml::to_string(&config)
+		.map_err(|error| Error::Failed(format!("couldn't write the spaces: {error}")))?;
+
+	let Some(out) = out else {
+		return Ok(text.trim_end().to_string());
+	};
+	fs::write(out, text)
+		.map_err(|error| Error::Failed(format!("couldn't write {}: {error}", out.display())))?;
+	Ok(format!(
+		"Wrote {} spaces to {}; {flagged} entries to check, see --dry-run",
+		config.spaces.len(),
+		out.display()
+	))
+}
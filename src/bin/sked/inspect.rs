@@ -0,0 +1,303 @@
+//! The `pdf` commands, for seeing what's in a PDF when extraction goes
+//! wrong: its operations, text, objects and tables.
+
+use core::fmt::Write;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use lopdf::{Document, Object, ObjectId};
+use serde_json::json;
+use sked::pdf::font::Fonts;
+use sked::pdf::text::TextInterpreter;
+use sked::pdf::{Coordinates, Operation, Page, Pdf, TransformationMatrix};
+
+use super::error::Error;
+use super::import::{grid, load};
+
+/// What to show.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Tool {
+	Ops,
+	Text,
+	Objects,
+	Grid,
+}
+
+impl Tool {
+	pub fn parse(name: &str) -> Option<Self> {
+		match name {
+			"ops" => Some(Self::Ops),
+			"text" => Some(Self::Text),
+			"objects" => Some(Self::Objects),
+			"grid" => Some(Self::Grid),
+			_ => None,
+		}
+	}
+}
+
+fn failed(file: &Path, error: &impl core::fmt::Display) -> Error {
+	Error::Failed(format!("couldn't read {}: {error}", file.display()))
+}
+
+/// The pages numbered `page`, or every page.
+fn pages(pdf: &Pdf, page: Option<u32>) -> Result<Vec<Page<'_>>, Error> {
+	match page {
+		None => Ok(pdf.pages().collect()),
+		Some(number) => pdf
+			.page(number)
+			.map(|page| vec![page])
+			.ok_or_else(|| Error::Failed(format!("there's no page {number}"))),
+	}
+}
+
+/// Each operation of each page as it's written in the content stream,
+/// numbered by its index in it (counting from 0), as errors are.  Within a
+/// text object, each also has where the text position is afterwards on the
+/// page as displayed, as `pdf text` gives the runs.
+fn ops(pdf: &Pdf, file: &Path, page: Option<u32>) -> Result<String, Error> {
+	let mut output = String::new();
+	for page in pages(pdf, page)? {
+		let matrix: TransformationMatrix = page
+			.coordinate_space()
+			.map_err(|error| failed(file, &error))?
+			.matrix();
+		let fonts: Fonts = page.fonts().map_err(|error| failed(file, &error))?;
+		let mut interpreter = TextInterpreter::with_matrix(&fonts, matrix);
+		let mut text: bool = false;
+
+		let _ = writeln!(output, "Page {}:", page.number());
+		for (index, operation) in page
+			.operations()
+			.map_err(|error| failed(file, &error))?
+			.enumerate()
+		{
+			let operation: Operation = match operation {
+				Ok(operation) => operation,
+				Err(error) => {
+					let _ = writeln!(output, "{index:>6}  error: {error}");
+					continue;
+				}
+			};
+			interpreter.apply(&operation);
+			match operation {
+				Operation::BeginTextObject => text = true,
+				Operation::EndTextObject => text = false,
+				_ => {}
+			}
+			let _ = if text {
+				let position: Coordinates = interpreter.position();
+				writeln!(
+					output,
+					"{index:>6}  {:>8.2} {:>8.2}  {operation}",
+					position.x(),
+					position.y()
+				)
+			} else {
+				writeln!(output, "{index:>6}  {:>17}  {operation}", "")
+			};
+		}
+	}
+	Ok(output.trim_end().to_string())
+}
+
+/// The positioned text runs of each page.
+fn text(pdf: &Pdf, file: &Path, page: Option<u32>, json: bool) -> Result<String, Error> {
+	let mut pages_json: Vec<serde_json::Value> = Vec::new();
+	let mut output = String::new();
+	for page in pages(pdf, page)? {
+		let runs = page.text().map_err(|error| failed(file, &error))?;
+		if json {
+			let runs: Vec<serde_json::Value> = runs
+				.iter()
+				.map(|run| {
+					json!({
+						"x": run.origin().x(),
+						"y": run.origin().y(),
+						"size": run.font_size(),
+						"mcid": run.mcid(),
+						"text": run.text(),
+					})
+				})
+				.collect();
+			pages_json.push(json!({ "page": page.number(), "runs": runs }));
+			continue;
+		}
+
+		let _ = writeln!(output, "Page {}:", page.number());
+		for run in runs {
+			let _ = writeln!(
+				output,
+				"{:>8.2} {:>8.2} {:>5.1}  {}",
+				run.origin().x(),
+				run.origin().y(),
+				run.font_size(),
+				run.text()
+			);
+		}
+	}
+
+	if json {
+		Ok(serde_json::Value::Array(pages_json).to_string())
+	} else {
+		Ok(output.trim_end().to_string())
+	}
+}
+
+/// The objects `object` refers to directly.
+fn references(object: &Object, found: &mut Vec<ObjectId>) {
+	match object {
+		Object::Reference(id) => found.push(*id),
+		Object::Array(items) => items.iter().for_each(|item| references(item, found)),
+		Object::Dictionary(dictionary) => dictionary
+			.iter()
+			.for_each(|(_, value)| references(value, found)),
+		Object::Stream(stream) => stream
+			.dict
+			.iter()
+			.for_each(|(_, value)| references(value, found)),
+		_ => {}
+	}
+}
+
+/// The objects reachable from the trailer.
+fn reachable(document: &Document) -> BTreeSet<ObjectId> {
+	let mut queue: Vec<ObjectId> = Vec::new();
+	for (_, value) in &document.trailer {
+		references(value, &mut queue);
+	}
+
+	let mut seen: BTreeSet<ObjectId> = BTreeSet::new();
+	while let Some(id) = queue.pop() {
+		if seen.insert(id) {
+			if let Some(object) = document.objects.get(&id) {
+				references(object, &mut queue);
+			}
+		}
+	}
+	seen
+}
+
+/// What sort of object `object` is, when it has no `/Type`.
+fn kind(object: &Object) -> &'static str {
+	match object {
+		Object::Null => "null",
+		Object::Boolean(_) => "boolean",
+		Object::Integer(_) => "integer",
+		Object::Real(_) => "real",
+		Object::Name(_) => "name",
+		Object::String(..) => "string",
+		Object::Array(_) => "array",
+		Object::Dictionary(_) => "dictionary",
+		Object::Stream(_) => "stream",
+		Object::Reference(_) => "reference",
+	}
+}
+
+fn id(id: ObjectId) -> String {
+	format!("{} {}", id.0, id.1)
+}
+
+/// Each object, its type and what it refers to, and the objects nothing
+/// reachable from the trailer refers to.
+fn objects(pdf: &Pdf, json: bool) -> String {
+	let document: &Document = pdf.document();
+	let reachable: BTreeSet<ObjectId> = reachable(document);
+	let unreferenced: Vec<ObjectId> = document
+		.objects
+		.keys()
+		.filter(|id| !reachable.contains(id))
+		.copied()
+		.collect();
+
+	let objects: Vec<(ObjectId, &str, Vec<ObjectId>)> = document
+		.objects
+		.iter()
+		.map(|(id, object)| {
+			let mut found: Vec<ObjectId> = Vec::new();
+			references(object, &mut found);
+			(
+				*id,
+				object.type_name().unwrap_or_else(|_| kind(object)),
+				found,
+			)
+		})
+		.collect();
+
+	if json {
+		let objects: Vec<serde_json::Value> = objects
+			.iter()
+			.map(|(object, kind, found)| {
+				json!({
+					"id": id(*object),
+					"type": kind,
+					"references": found.iter().map(|found| id(*found)).collect::<Vec<String>>(),
+				})
+			})
+			.collect();
+		let unreferenced: Vec<String> = unreferenced.iter().map(|object| id(*object)).collect();
+		return json!({ "objects": objects, "unreferenced": unreferenced }).to_string();
+	}
+
+	let mut output = String::new();
+	for (object, kind, found) in &objects {
+		let _ = write!(output, "{:>9}  {kind}", id(*object));
+		if !found.is_empty() {
+			let found: Vec<String> = found.iter().map(|found| id(*found)).collect();
+			let _ = write!(output, " -> {}", found.join(", "));
+		}
+		output.push('\n');
+	}
+	let unreferenced: Vec<String> = unreferenced.iter().map(|object| id(*object)).collect();
+	let _ = write!(
+		output,
+		"\nUnreferenced: {}",
+		if unreferenced.is_empty() {
+			"none".to_string()
+		} else {
+			unreferenced.join(", ")
+		}
+	);
+	output
+}
+
+/// The tables reconstructed from the structure tree, or from where the
+/// text was drawn if there isn't one.
+fn tables(pdf: &Pdf, file: &Path) -> Result<String, Error> {
+	let tables = pdf.tables().map_err(|error| failed(file, &error))?;
+	if tables.is_empty() {
+		return Err(Error::Failed(format!(
+			"found no tables in {}",
+			file.display()
+		)));
+	}
+
+	let grids: Vec<String> = tables
+		.iter()
+		.map(|table| {
+			format!(
+				"Table on page {}:\n{}",
+				table
+					.page()
+					.map_or_else(|| "?".to_string(), |page| page.to_string()),
+				grid(table)
+			)
+		})
+		.collect();
+	Ok(grids.join("\n").trim_end().to_string())
+}
+
+pub fn inspect(tool: Tool, file: &Path, page: Option<u32>, json: bool) -> Result<String, Error> {
+	if json && matches!(tool, Tool::Ops | Tool::Grid) {
+		return Err(Error::Usage(
+			"only pdf text and pdf objects print JSON".to_string(),
+		));
+	}
+
+	let pdf: Pdf = load(file)?;
+	match tool {
+		Tool::Ops => ops(&pdf, file, page),
+		Tool::Text => text(&pdf, file, page, json),
+		Tool::Objects => Ok(objects(&pdf, json)),
+		Tool::Grid => tables(&pdf, file),
+	}
+}
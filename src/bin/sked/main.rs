@@ -0,0 +1,92 @@
+#![deny(clippy::all)]
+#![warn(clippy::cargo, clippy::pedantic, clippy::cognitive_complexity)]
+
+//! `sked`: when spaces are open, from the hours in a config file, and
+//! importing those hours from schedule PDFs and seeing what's in them.
+//!
+//! Exits with 0 on success, 1 if the command failed, e.g. the config
+//! couldn't be read or has no such space, and 2 if the command line didn't
+//! make sense.
+
+mod args;
+mod config;
+mod error;
+mod import;
+mod inspect;
+mod query;
+
+use std::env;
+use std::io::{self, Write};
+use std::process::ExitCode;
+
+#[cfg(feature = "simple_logger")]
+use simple_logger::SimpleLogger;
+
+use args::{Args, Command, USAGE};
+use config::Config;
+use error::Error;
+
+fn run(args: &Args) -> Result<String, Error> {
+	let config = || Config::load(&Config::path(args.config.as_deref()));
+	match &args.command {
+		Command::Status { space, at } => Ok(query::status(
+			config()?.space(space)?,
+			&query::time(at.as_deref())?,
+			args.json,
+		)),
+		Command::Next { space, at } => Ok(query::next(
+			config()?.space(space)?,
+			&query::time(at.as_deref())?,
+			args.json,
+		)),
+		Command::Week { space, from } => query::week(
+			config()?.space(space)?,
+			query::date(from.as_deref())?,
+			args.json,
+		),
+		Command::OpenNow { at } => Ok(query::open_now(
+			&config()?.spaces,
+			&query::time(at.as_deref())?,
+			args.json,
+		)),
+		Command::Import {
+			file,
+			out,
+			dry_run,
+			offset,
+		} => import::import(
+			file,
+			out.as_deref(),
+			*dry_run,
+			import::offset(offset.as_deref())?,
+			args.json,
+		),
+		Command::Pdf { tool, file, page } => inspect::inspect(*tool, file, *page, args.json),
+		Command::Help => Ok(USAGE.trim_end().to_string()),
+	}
+}
+
+fn main() -> ExitCode {
+	#[cfg(feature = "simple_logger")]
+	let _ = SimpleLogger::new()
+		.with_level(log::LevelFilter::Warn)
+		.env()
+		.init();
+
+	match Args::parse(env::args().skip(1)).and_then(|args| run(&args)) {
+		Ok(output) => {
+			// Output cut short, e.g. by `head`, isn't a failure.
+			if !output.is_empty() {
+				let _ = writeln!(io::stdout(), "{output}");
+			}
+			ExitCode::SUCCESS
+		}
+		Err(error) => {
+			eprintln!("sked: {error}");
+			if let Error::Usage(_) = error {
+				eprintln!("\n{USAGE}");
+			}
+			ExitCode::from(error.code())
+		}
+	}
+}
@@ -0,0 +1,173 @@
+//! The query commands: `status`, `next`, `week` and `open-now`.
+
+use chrono::{
+	DateTime, Duration, FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone,
+};
+use serde_json::{json, Value};
+use sked::{Clock, English, Locale, Period, Space, Status, StatusChange};
+
+use super::error::Error;
+
+/// A day, the periods the space opens for that day, and why it's closed if
+/// it doesn't.
+type Day = (NaiveDate, Vec<Period<FixedOffset>>, Option<String>);
+
+/// `time` where it is now.
+fn local(time: NaiveDateTime) -> Option<DateTime<FixedOffset>> {
+	Local
+		.from_local_datetime(&time)
+		.earliest()
+		.map(|time| time.fixed_offset())
+}
+
+/// The time given by `--at`, or now.
+pub fn time(at: Option<&str>) -> Result<DateTime<FixedOffset>, Error> {
+	let Some(at) = at else {
+		return Ok(Local::now().fixed_offset());
+	};
+	DateTime::parse_from_rfc3339(at)
+		.or_else(|_| DateTime::parse_from_str(at, "%Y-%m-%dT%H:%M%:z"))
+		.ok()
+		.or_else(|| {
+			["%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M:%S"]
+				.iter()
+				.find_map(|format| NaiveDateTime::parse_from_str(at, format).ok())
+				.and_then(local)
+		})
+		.ok_or_else(|| Error::Usage(format!("--at {at} isn't a time, e.g. 2019-11-28T09:00")))
+}
+
+/// The day given by `--from`, or today.
+pub fn date(from: Option<&str>) -> Result<NaiveDate, Error> {
+	match from {
+		None => Ok(Local::now().date_naive()),
+		Some(from) => NaiveDate::parse_from_str(from, "%Y-%m-%d")
+			.map_err(|_| Error::Usage(format!("--from {from} isn't a date, e.g. 2019-11-25"))),
+	}
+}
+
+pub fn status(space: &Space<'_, FixedOffset>, time: &DateTime<FixedOffset>, json: bool) -> String {
+	let status = space.status_at(time);
+
+	if json {
+		let (Status::Open(reason) | Status::Closed(reason)) = &status;
+		json!({
+			"space": space.name(),
+			"at": time.to_rfc3339(),
+			"open": matches!(status, Status::Open(_)),
+			"note": reason.note(),
+		})
+		.to_string()
+	} else {
+		format!("{} — {status}", space.name())
+	}
+}
+
+pub fn next(space: &Space<'_, FixedOffset>, time: &DateTime<FixedOffset>, json: bool) -> String {
+	let change = space.next_status_change_at(time);
+
+	if json {
+		let change: Value = match &change {
+			Some(StatusChange::Opening(at, reason) | StatusChange::Closing(at, reason)) => json!({
+				"opening": matches!(change, Some(StatusChange::Opening(..))),
+				"at": at.to_rfc3339(),
+				"note": reason.note(),
+			}),
+			None => Value::Null,
+		};
+		json!({ "space": space.name(), "change": change }).to_string()
+	} else {
+		match change {
+			Some(change) => {
+				let (StatusChange::Opening(at, _) | StatusChange::Closing(at, _)) = &change;
+				format!("{} — {} ({})", space.name(), change, at.format("%Y-%m-%d"))
+			}
+			None => format!("{} — no change within a year", space.name()),
+		}
+	}
+}
+
+/// The seven days from `first` for `space`.
+fn days(space: &Space<'_, FixedOffset>, first: NaiveDate) -> Result<Vec<Day>, Error> {
+	let midnight = |day: NaiveDate| {
+		local(day.and_time(NaiveTime::MIN))
+			.ok_or_else(|| Error::Failed(format!("{day} has no midnight here")))
+	};
+	let periods = space.periods(&midnight(first)?, &midnight(first + Duration::days(8))?);
+
+	(0..7)
+		.map(|offset| {
+			let day = first + Duration::days(offset);
+			let (start, end) = (midnight(day)?, midnight(day + Duration::days(1))?);
+			let open: Vec<Period<FixedOffset>> = space.openings(day, &start.timezone());
+			let note: Option<String> = periods
+				.iter()
+				.filter(|period| !period.is_open() && period.start() < &end && &start < period.end())
+				.find_map(|period| period.note().map(ToString::to_string));
+			Ok((day, open, note))
+		})
+		.collect()
+}
+
+pub fn week(space: &Space<'_, FixedOffset>, first: NaiveDate, json: bool) -> Result<String, Error> {
+	let days = days(space, first)?;
+
+	if json {
+		let days: Vec<Value> = days
+			.iter()
+			.map(|(day, open, note)| {
+				let hours: Vec<Value> = open
+					.iter()
+					.map(|period| {
+						json!({
+							"open": period.start().to_rfc3339(),
+							"close": period.end().to_rfc3339(),
+							"note": period.note(),
+						})
+					})
+					.collect();
+				json!({ "date": day.to_string(), "hours": hours, "note": note })
+			})
+			.collect();
+		return Ok(json!({ "space": space.name(), "days": days }).to_string());
+	}
+
+	let mut lines: Vec<String> = vec![space.name().to_string()];
+	for (day, open, note) in &days {
+		let hours: String = if open.is_empty() {
+			English.status(false, note.as_deref())
+		} else {
+			let hours: Vec<String> = open
+				.iter()
+				.map(|period| {
+					English.hours(
+						period.start().time(),
+						period.end().time(),
+						Clock::TwelveHour,
+					)
+				})
+				.collect();
+			English.list(&hours)
+		};
+		lines.push(format!("{}  {hours}", day.format("%a %b %e")));
+	}
+	Ok(lines.join("\n"))
+}
+
+pub fn open_now(
+	spaces: &[Space<'_, FixedOffset>],
+	time: &DateTime<FixedOffset>,
+	json: bool,
+) -> String {
+	let open: Vec<&str> = spaces
+		.iter()
+		.filter(|space| matches!(space.status_at(time), Status::Open(_)))
+		.map(Space::name)
+		.collect();
+
+	if json {
+		json!(open).to_string()
+	} else {
+		open.join("\n")
+	}
+}
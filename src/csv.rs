@@ -0,0 +1,618 @@
+//! Hours as CSV, for editing in a spreadsheet.
+//!
+//! Regular hours are read and written one part to a row, as
+//!
+//! ```text
+//! space,weekday,open,close,valid_from,valid_to,note
+//! Library,Mon-Fri,07:00,23:00,2019-08-26,2019-12-20,
+//! Library,Sat,9:00 AM,5:00 PM,,,Front desk only
+//! ```
+//!
+//! where `weekday` is a day, a range of days or `daily`; hours closing at or
+//! before they open close the next day; and rows of a space which are valid
+//! over the same dates make up one schedule.  The first row names the
+//! columns, in any order; `valid_from`, `valid_to` and `note` may be left
+//! out.
+//!
+//! What's open and closed over a range of dates is written as
+//! `date,space,open,close,status,reason`; see `CsvTimeline`.
+
+use core::fmt;
+
+use chrono::{Duration, NaiveDate, NaiveTime, TimeZone, Weekday};
+
+use super::specifier::{midnight, time_of_day, write_time};
+use super::{Part, Period, Schedule, Space, Specifier};
+
+/// A row of a CSV file which couldn't be read.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CsvError {
+	row: usize,
+	message: String,
+}
+
+impl CsvError {
+	fn new(row: usize, message: &str) -> Self {
+		Self {
+			row,
+			message: message.to_string(),
+		}
+	}
+
+	/// The row the problem is on, counting the header as row 1.
+	#[must_use]
+	pub fn row(&self) -> usize {
+		self.row
+	}
+
+	#[must_use]
+	pub fn message(&self) -> &str {
+		&self.message
+	}
+}
+
+impl fmt::Display for CsvError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "row {}: {}", self.row, self.message)
+	}
+}
+
+impl std::error::Error for CsvError {}
+
+const COLUMNS: [&str; 7] = [
+	"space",
+	"weekday",
+	"open",
+	"close",
+	"valid_from",
+	"valid_to",
+	"note",
+];
+
+/// Split RFC 4180 text into records, with the row each starts on.  Quoted
+/// fields may hold commas, doubled quotes and line breaks.
+fn records(text: &str) -> Result<Vec<(usize, Vec<String>)>, CsvError> {
+	let mut records: Vec<(usize, Vec<String>)> = Vec::new();
+	let mut fields: Vec<String> = Vec::new();
+	let mut field: String = String::new();
+	let mut quoted: bool = false;
+	let (mut row, mut line): (usize, usize) = (1, 1);
+	let mut chars = text.chars().peekable();
+
+	while let Some(c) = chars.next() {
+		match (quoted, c) {
+			(true, '"') if chars.peek() == Some(&'"') => {
+				chars.next();
+				field.push('"');
+			}
+			(true, '"') => quoted = false,
+			(true, c) => {
+				if c == '\n' {
+					line += 1;
+				}
+				field.push(c);
+			}
+			(false, '"') if field.is_empty() => quoted = true,
+			(false, ',') => fields.push(core::mem::take(&mut field)),
+			(false, '\r') if chars.peek() == Some(&'\n') => {}
+			(false, '\n') => {
+				fields.push(core::mem::take(&mut field));
+				records.push((row, core::mem::take(&mut fields)));
+				line += 1;
+				row = line;
+			}
+			(false, c) => field.push(c),
+		}
+	}
+	if quoted {
+		return Err(CsvError::new(row, "unterminated quotes"));
+	}
+	if !field.is_empty() || !fields.is_empty() {
+		fields.push(field);
+		records.push((row, fields));
+	}
+
+	// Blank lines are skipped.
+	records.retain(|(_, fields)| fields.iter().any(|field| !field.trim().is_empty()));
+	Ok(records)
+}
+
+fn field(text: &str) -> String {
+	if text.contains([',', '"', '\r', '\n']) {
+		format!("\"{}\"", text.replace('"', "\"\""))
+	} else {
+		text.to_string()
+	}
+}
+
+/// A day, a range of days which may wrap past Sunday, or `daily`.
+fn weekdays(text: &str) -> Option<Vec<Weekday>> {
+	if text.eq_ignore_ascii_case("daily") {
+		return weekdays("Mon-Sun");
+	}
+	let (first, last) = text.split_once('-').unwrap_or((text, text));
+	let mut day: Weekday = first.trim().parse().ok()?;
+	let last: Weekday = last.trim().parse().ok()?;
+	let mut days: Vec<Weekday> = vec![day];
+	while day != last {
+		day = day.succ();
+		days.push(day);
+	}
+	Some(days)
+}
+
+/// `07:00`, `7:00:30` or `7:00 AM`.
+fn time(text: &str) -> Option<NaiveTime> {
+	time_of_day(text)
+		.or_else(|| NaiveTime::parse_from_str(&text.to_uppercase(), "%I:%M %p").ok())
+		.or_else(|| NaiveTime::parse_from_str(&text.to_uppercase(), "%I:%M:%S %p").ok())
+}
+
+/// `2019-08-26` or `8/26/2019`.
+fn date(text: &str) -> Option<NaiveDate> {
+	NaiveDate::parse_from_str(text, "%Y-%m-%d")
+		.or_else(|_| NaiveDate::parse_from_str(text, "%m/%d/%Y"))
+		.ok()
+}
+
+/// A row of regular hours.
+struct Row {
+	space: String,
+	days: Vec<Weekday>,
+	open: NaiveTime,
+	close: NaiveTime,
+	valid: (Option<NaiveDate>, Option<NaiveDate>),
+	note: String,
+}
+
+impl Row {
+	/// Read a record, given which of `COLUMNS` each field is in.
+	fn parse(fields: &[String], columns: &[Option<usize>; 7]) -> Result<Self, String> {
+		let get = |column: usize| -> &str {
+			columns[column]
+				.and_then(|index| fields.get(index))
+				.map_or("", |field| field.trim())
+		};
+		let required = |column: usize| -> Result<&str, String> {
+			match get(column) {
+				"" => Err(format!("missing {}", COLUMNS[column])),
+				value => Ok(value),
+			}
+		};
+		let optional_date = |column: usize| -> Result<Option<NaiveDate>, String> {
+			match get(column) {
+				"" => Ok(None),
+				value => date(value)
+					.map(Some)
+					.ok_or_else(|| format!("expected a date for {}, not `{value}`", COLUMNS[column])),
+			}
+		};
+		let required_time = |column: usize| -> Result<NaiveTime, String> {
+			let value: &str = required(column)?;
+			time(value).ok_or_else(|| format!("expected a time for {}, not `{value}`", COLUMNS[column]))
+		};
+
+		let space: String = required(0)?.to_string();
+		let days: &str = required(1)?;
+		let days: Vec<Weekday> =
+			weekdays(days).ok_or_else(|| format!("expected a weekday, not `{days}`"))?;
+		let (open, close) = (required_time(2)?, required_time(3)?);
+		let valid = (optional_date(4)?, optional_date(5)?);
+		if let (Some(from), Some(to)) = valid {
+			if to < from {
+				return Err("valid_to is before valid_from".to_string());
+			}
+		}
+
+		Ok(Self {
+			space,
+			days,
+			open,
+			close,
+			valid,
+			note: get(6).to_string(),
+		})
+	}
+
+	fn parts<Tz: TimeZone>(&self) -> impl Iterator<Item = Part<Tz>> + '_ {
+		self.days.iter().map(move |day| {
+			let close: Weekday = if self.close <= self.open {
+				day.succ()
+			} else {
+				*day
+			};
+			let part: Part<Tz> = Part::new()
+				.open(Specifier::Weekly {
+					day: day.to_string(),
+					time: write_time(self.open),
+				})
+				.close(Specifier::Weekly {
+					day: close.to_string(),
+					time: write_time(self.close),
+				});
+			if self.note.is_empty() {
+				part
+			} else {
+				part.note(&self.note)
+			}
+		})
+	}
+}
+
+/// Spaces read from, or to be written as, CSV rows of regular hours.
+#[derive(Debug)]
+pub struct Csv<'schedule, Tz: TimeZone> {
+	spaces: Vec<Space<'schedule, Tz>>,
+}
+
+impl<Tz: TimeZone> Default for Csv<'_, Tz> {
+	fn default() -> Self {
+		Self { spaces: Vec::new() }
+	}
+}
+
+impl<'schedule, Tz: TimeZone> Csv<'schedule, Tz> {
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Read rows of regular hours, with dates local to `timezone`.
+	///
+	/// # Errors
+	///
+	/// Returns an error for each row which can't be read, or just one if
+	/// the header or the quoting can't be.
+	pub fn parse(text: &str, timezone: &Tz) -> Result<Self, Vec<CsvError>> {
+		let records: Vec<(usize, Vec<String>)> = records(text).map_err(|error| vec![error])?;
+		let Some(((header_row, header), rows)) = records.split_first() else {
+			return Err(vec![CsvError::new(1, "expected a header row")]);
+		};
+
+		let mut columns: [Option<usize>; 7] = [None; 7];
+		for (index, name) in header.iter().enumerate() {
+			let name: String = name.trim().to_lowercase();
+			let Some(column) = COLUMNS.iter().position(|column| *column == name) else {
+				let message: String = format!("unknown column `{name}`");
+				return Err(vec![CsvError::new(*header_row, &message)]);
+			};
+			columns[column] = Some(index);
+		}
+		if let Some(missing) = (0..4).find(|column| columns[*column].is_none()) {
+			let message: String = format!("missing the {} column", COLUMNS[missing]);
+			return Err(vec![CsvError::new(*header_row, &message)]);
+		}
+
+		let mut errors: Vec<CsvError> = Vec::new();
+		let mut csv: Self = Self::new();
+		let mut valid: Vec<Vec<(Option<NaiveDate>, Option<NaiveDate>)>> = Vec::new();
+		for (row, fields) in rows {
+			let parsed: Result<Row, String> = if fields.len() > header.len() {
+				Err(format!(
+					"expected {} fields, found {}",
+					header.len(),
+					fields.len()
+				))
+			} else {
+				Row::parse(fields, &columns)
+			};
+			match parsed.and_then(|parsed| csv.add(&parsed, &mut valid, timezone)) {
+				Ok(()) => {}
+				Err(message) => errors.push(CsvError::new(*row, &message)),
+			}
+		}
+
+		if errors.is_empty() {
+			Ok(csv)
+		} else {
+			Err(errors)
+		}
+	}
+
+	/// Add a row's parts to its space's schedule for its dates, which are
+	/// kept in `valid`, space by space and schedule by schedule.
+	fn add(
+		&mut self,
+		row: &Row,
+		valid: &mut Vec<Vec<(Option<NaiveDate>, Option<NaiveDate>)>>,
+		timezone: &Tz,
+	) -> Result<(), String> {
+		let start_of =
+			|date: NaiveDate| midnight(timezone, date).ok_or_else(|| format!("{date} has no midnight"));
+
+		let space: usize = if let Some(space) = self
+			.spaces
+			.iter()
+			.position(|space| space.name() == row.space)
+		{
+			space
+		} else {
+			self.spaces.push(Space::new(&row.space));
+			valid.push(Vec::new());
+			self.spaces.len() - 1
+		};
+		let schedules = self.spaces[space].schedules_mut();
+		let schedule: usize =
+			if let Some(schedule) = valid[space].iter().position(|dates| *dates == row.valid) {
+				schedule
+			} else {
+				let mut schedule: Schedule<'schedule, Tz> = Schedule::new();
+				if let Some(from) = row.valid.0 {
+					*schedule.effective_mut() = Some(start_of(from)?);
+				}
+				if let Some(to) = row.valid.1 {
+					*schedule.expires_mut() = Some(start_of(to + Duration::days(1))?);
+				}
+				schedules.push(schedule);
+				valid[space].push(row.valid);
+				schedules.len() - 1
+			};
+		schedules[schedule].parts_mut().extend(row.parts());
+		Ok(())
+	}
+
+	#[must_use]
+	pub fn space(mut self, space: Space<'schedule, Tz>) -> Self {
+		self.spaces.push(space);
+		self
+	}
+
+	#[must_use]
+	pub fn spaces(&self) -> &[Space<'schedule, Tz>] {
+		&self.spaces
+	}
+
+	#[must_use]
+	pub fn into_spaces(self) -> Vec<Space<'schedule, Tz>> {
+		self.spaces
+	}
+}
+
+/// The rows for a weekly part, if it is one.
+fn part_row<Tz: TimeZone>(part: &Part<Tz>) -> Option<(Weekday, NaiveTime, NaiveTime, String)> {
+	match (part.opens()?, part.closes()?) {
+		(Specifier::Weekly { day, time: open }, Specifier::Weekly { time: close, .. }) => Some((
+			day.parse().ok()?,
+			time_of_day(open)?,
+			time_of_day(close)?,
+			part.notes().join("; "),
+		)),
+		_ => None,
+	}
+}
+
+/// Writes the regular hours in the columns `Csv::parse` reads, leaving out
+/// parts which aren't weekly.
+impl<Tz: TimeZone> fmt::Display for Csv<'_, Tz> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		writeln!(f, "{}", COLUMNS.join(","))?;
+		for space in &self.spaces {
+			for schedule in space.schedules() {
+				let valid_from: String = schedule
+					.effective()
+					.as_ref()
+					.map_or_else(String::new, |from| from.naive_local().date().to_string());
+				let valid_to: String = schedule.expires().as_ref().map_or_else(String::new, |to| {
+					(to.naive_local() - Duration::nanoseconds(1))
+						.date()
+						.to_string()
+				});
+				for (day, open, close, note) in schedule.parts().iter().filter_map(part_row) {
+					writeln!(
+						f,
+						"{},{},{},{},{},{},{}",
+						field(space.name()),
+						day,
+						write_time(open),
+						write_time(close),
+						valid_from,
+						valid_to,
+						field(&note)
+					)?;
+				}
+			}
+		}
+		Ok(())
+	}
+}
+
+/// What's open and closed in some spaces each day over a range of dates,
+/// as CSV rows of `date,space,open,close,status,reason`.
+///
+/// Each day's periods are written in order, with `24:00` for the midnight
+/// ending the day; `status` is `open` or `closed`, and `reason` is the note
+/// of the exception responsible, if any.
+#[derive(Debug)]
+pub struct CsvTimeline<'spaces, 'schedule, Tz: TimeZone> {
+	spaces: &'spaces [Space<'schedule, Tz>],
+	first: NaiveDate,
+	last: NaiveDate,
+	timezone: Tz,
+}
+
+impl<'spaces, 'schedule, Tz: TimeZone> CsvTimeline<'spaces, 'schedule, Tz> {
+	/// Each of `spaces` from the start of `first` to the end of `last`, in
+	/// `timezone`.
+	#[must_use]
+	pub fn new(
+		spaces: &'spaces [Space<'schedule, Tz>],
+		first: NaiveDate,
+		last: NaiveDate,
+		timezone: Tz,
+	) -> Self {
+		Self {
+			spaces,
+			first,
+			last,
+			timezone,
+		}
+	}
+}
+
+impl<Tz: TimeZone> fmt::Display for CsvTimeline<'_, '_, Tz> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		writeln!(f, "date,space,open,close,status,reason")?;
+		for date in self.first.iter_days().take_while(|date| *date <= self.last) {
+			let (Some(start), Some(end)) = (
+				midnight(&self.timezone, date),
+				midnight(&self.timezone, date + Duration::days(1)),
+			) else {
+				continue;
+			};
+			for space in self.spaces {
+				let periods: Vec<Period<Tz>> = space.periods(&start, &end);
+				for period in periods {
+					let close: String = if *period.end() == end {
+						"24:00".to_string()
+					} else {
+						write_time(period.end().naive_local().time())
+					};
+					writeln!(
+						f,
+						"{},{},{},{},{},{}",
+						date,
+						field(space.name()),
+						write_time(period.start().naive_local().time()),
+						close,
+						if period.is_open() { "open" } else { "closed" },
+						field(period.note().unwrap_or(""))
+					)?;
+				}
+			}
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{Csv, CsvTimeline};
+	use crate::{Exception, Reason, Space, Specifier, Status};
+	use chrono::{DateTime, FixedOffset, NaiveDate};
+
+	fn zone() -> FixedOffset {
+		FixedOffset::west_opt(5 * 3600).unwrap()
+	}
+
+	#[test]
+	fn import_and_export() {
+		let text = "\
+Space,Weekday,Open,Close,Valid_From,Valid_To,Note
+Library,Mon-Fri,07:00,23:00,2019-08-26,2019-12-20,
+Library,Sat,9:00 AM,5:00 PM,2019-08-26,2019-12-20,\"Desk, front\"
+
+\"Boe \"\"Chapel\"\"\",daily,22:00,2:00,,,
+Library,Sun,12:00,18:00,,,
+";
+		let csv: Csv<FixedOffset> = Csv::parse(text, &zone()).unwrap();
+		let spaces: &[Space<FixedOffset>] = csv.spaces();
+		assert_eq!(spaces.len(), 2);
+		assert_eq!(spaces[0].schedules().len(), 2);
+		assert_eq!(spaces[0].schedules()[0].parts().len(), 6);
+		assert_eq!(
+			spaces[0].schedules()[0].expires(),
+			&Some(DateTime::parse_from_rfc3339("2019-12-21T00:00:00-05:00").unwrap())
+		);
+		assert_eq!(spaces[0].schedules()[0].parts()[5].notes(), ["Desk, front"]);
+		assert_eq!(
+			spaces[1].schedules()[0].parts()[6].closes(),
+			Some(&Specifier::Weekly {
+				day: "Mon".to_string(),
+				time: "02:00".to_string(),
+			})
+		);
+
+		let written: String = csv.to_string();
+		assert!(written.starts_with(
+			"space,weekday,open,close,valid_from,valid_to,note\n\
+			Library,Mon,07:00,23:00,2019-08-26,2019-12-20,\n"
+		));
+		assert!(written.contains("Library,Sat,09:00,17:00,2019-08-26,2019-12-20,\"Desk, front\"\n"));
+		assert!(written.contains("\"Boe \"\"Chapel\"\"\",Sun,22:00,02:00,,,\n"));
+		let reread: Csv<FixedOffset> = Csv::parse(&written, &zone()).unwrap();
+		assert_eq!(reread.to_string(), written);
+	}
+
+	#[test]
+	fn errors() {
+		let text = "\
+space,weekday,open,close
+Library,Mon,07:00,23:00
+,Mon,07:00,23:00
+Library,Funday,07:00,23:00
+Library,Tue,7,23:00
+Library,Wed,07:00,23:00,extra
+Library,\"Thu
+";
+		let errors: Vec<String> = Csv::<FixedOffset>::parse(text, &zone())
+			.unwrap_err()
+			.iter()
+			.map(ToString::to_string)
+			.collect();
+		assert_eq!(errors, ["row 7: unterminated quotes"]);
+
+		let errors: Vec<String> =
+			Csv::<FixedOffset>::parse(&text.replace("\"Thu\n", "Thu,,\n"), &zone())
+				.unwrap_err()
+				.iter()
+				.map(ToString::to_string)
+				.collect();
+		assert_eq!(
+			errors,
+			[
+				"row 3: missing space",
+				"row 4: expected a weekday, not `Funday`",
+				"row 5: expected a time for open, not `7`",
+				"row 6: expected 4 fields, found 5",
+				"row 7: missing open",
+			]
+		);
+
+		for (header, message) in &[
+			("space,weekday,open", "row 1: missing the close column"),
+			("space,day,open,close", "row 1: unknown column `day`"),
+		] {
+			let errors = Csv::<FixedOffset>::parse(header, &zone()).unwrap_err();
+			assert_eq!(errors[0].to_string(), *message);
+		}
+	}
+
+	#[test]
+	fn timeline() {
+		let text = "space,weekday,open,close\nLibrary,Thu,07:00,23:00\nLibrary,Thu,23:30,01:00\n";
+		let mut space: Space<FixedOffset> = Csv::parse(text, &zone()).unwrap().into_spaces().remove(0);
+		let mut lunch = Exception::new()
+			.effective(Specifier::Weekly {
+				day: "Thu".to_string(),
+				time: "12:00".to_string(),
+			})
+			.expires(Specifier::Weekly {
+				day: "Thu".to_string(),
+				time: "13:00".to_string(),
+			});
+		*lunch.effect_mut() = Some(Status::Closed(Reason::Exception(Some("Lunch".to_string()))));
+		space.schedules_mut()[0].exceptions_mut().push(lunch);
+
+		let spaces = [space];
+		let timeline = CsvTimeline::new(
+			&spaces,
+			NaiveDate::from_ymd_opt(2019, 11, 21).unwrap(),
+			NaiveDate::from_ymd_opt(2019, 11, 22).unwrap(),
+			zone(),
+		);
+		assert_eq!(
+			timeline.to_string(),
+			"\
+date,space,open,close,status,reason
+2019-11-21,Library,00:00,07:00,closed,
+2019-11-21,Library,07:00,12:00,open,
+2019-11-21,Library,12:00,13:00,closed,Lunch
+2019-11-21,Library,13:00,23:00,open,
+2019-11-21,Library,23:00,23:30,closed,
+2019-11-21,Library,23:30,24:00,open,
+2019-11-22,Library,00:00,01:00,open,
+2019-11-22,Library,01:00,24:00,closed,
+"
+		);
+	}
+}
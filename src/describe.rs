@@ -0,0 +1,375 @@
+//! Hours in a sentence or two, e.g. "Open Mon–Thu 7 AM–midnight, Fri
+//! 7 AM–8 PM, Sat 10 AM–6 PM; closed Sun. Closed Nov 28–29 for
+//! Thanksgiving."
+//!
+//! The regular hours are those of the week from the start, collapsed into
+//! runs of days with the same hours, and each run of days in the range
+//! whose hours differ from them, because of a dated exception, gets a
+//! sentence of its own.  The words come from a [`Locale`].
+//!
+//! The hours are read from the same timeline as `Space::status_at`, so
+//! hours running past midnight belong to the day they start on, and the
+//! space is open whenever the description says it is.
+
+use core::fmt;
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, TimeZone, Weekday};
+
+use super::listing::recurring;
+use super::specifier::midnight;
+use super::timeline;
+use super::{Clock, English, Locale, Space};
+
+/// The hours a space opens for on a day: when it opens, and when it next
+/// closes.
+type Hours = Vec<(NaiveTime, NaiveTime)>;
+
+/// A description of a space's hours from `start` until `end`.
+#[derive(Debug)]
+pub struct Description<'space, 'schedule, Tz: TimeZone> {
+	space: &'space Space<'schedule, Tz>,
+	start: DateTime<Tz>,
+	end: DateTime<Tz>,
+	clock: Option<Clock>,
+	locale: Box<dyn Locale>,
+}
+
+impl<'space, 'schedule, Tz: TimeZone> Description<'space, 'schedule, Tz> {
+	/// A description of the hours of `space` from `start` until `end`, in
+	/// English.
+	#[must_use]
+	pub fn new(
+		space: &'space Space<'schedule, Tz>,
+		start: &DateTime<Tz>,
+		end: &DateTime<Tz>,
+	) -> Self {
+		Self {
+			space,
+			start: start.clone(),
+			end: end.clone(),
+			clock: None,
+			locale: Box::new(English),
+		}
+	}
+
+	/// Write times on `clock` rather than the locale's usual one.
+	#[must_use]
+	pub fn clock(mut self, clock: Clock) -> Self {
+		self.clock = Some(clock);
+		self
+	}
+
+	#[must_use]
+	pub fn locale(mut self, locale: impl Locale + 'static) -> Self {
+		self.locale = Box::new(locale);
+		self
+	}
+
+	fn hours(&self, hours: &Hours) -> String {
+		let hours: Vec<String> = hours
+			.iter()
+			.map(|(open, close)| {
+				self.locale.hours(
+					*open,
+					*close,
+					self.clock.unwrap_or_else(|| self.locale.clock()),
+				)
+			})
+			.collect();
+		self.locale.list(&hours)
+	}
+
+	/// The sentence for the regular hours of the week from `first`.
+	fn regular(&self, first: NaiveDate) -> Option<String> {
+		let timezone: Tz = self.start.timezone();
+		let start = midnight(&timezone, first - Duration::days(1))?;
+		let end = midnight(&timezone, first + Duration::days(8))?;
+		let periods = timeline::periods(self.space.schedules(), &start, &end, recurring);
+
+		let mut days: Vec<(Weekday, Hours)> = (0..7)
+			.map(|offset| {
+				let day = first + Duration::days(offset);
+				(day.weekday(), timeline::hours(&periods, day))
+			})
+			.collect();
+		days.sort_by_key(|(day, _)| day.num_days_from_monday());
+
+		// Runs of consecutive days with the same hours.
+		let mut runs: Vec<(Weekday, Weekday, Hours)> = Vec::new();
+		for (day, hours) in days {
+			match runs.last_mut() {
+				Some((_, last, run)) if *run == hours => *last = day,
+				_ => runs.push((day, day, hours)),
+			}
+		}
+
+		let mut open: Vec<String> = Vec::new();
+		let mut closed: Vec<String> = Vec::new();
+		for (first, last, hours) in &runs {
+			let days = self.locale.weekdays(*first, *last);
+			if hours.is_empty() {
+				closed.push(days);
+			} else {
+				open.push(format!("{days} {}", self.hours(hours)));
+			}
+		}
+		Some(self.locale.regular(&open, &closed))
+	}
+
+	/// The sentences for the runs of days in the range whose hours differ
+	/// from the regular ones.
+	fn exceptions(&self) -> Option<Vec<String>> {
+		let first = self.start.naive_local().date();
+		let last = (self.end.clone() - Duration::nanoseconds(1))
+			.naive_local()
+			.date();
+		let timezone: Tz = self.start.timezone();
+		let start = midnight(&timezone, first - Duration::days(1))?;
+		let end = midnight(&timezone, last + Duration::days(2))?;
+		let actual = self.space.periods(&start, &end);
+		let regular = timeline::periods(self.space.schedules(), &start, &end, recurring);
+
+		let mut runs: Vec<(NaiveDate, NaiveDate, Hours, Option<String>)> = Vec::new();
+		for day in first.iter_days().take_while(|day| *day <= last) {
+			let hours = timeline::hours(&actual, day);
+			if hours == timeline::hours(&regular, day) {
+				continue;
+			}
+			let (Some(from), Some(until)) = (
+				midnight(&timezone, day),
+				midnight(&timezone, day + Duration::days(1)),
+			) else {
+				continue;
+			};
+			let reason: Option<String> = actual
+				.iter()
+				.filter(|period| period.start() < &until && &from < period.end())
+				.find_map(|period| period.note().map(ToString::to_string));
+
+			match runs.last_mut() {
+				Some((_, end, run, why))
+					if *end + Duration::days(1) == day && *run == hours && *why == reason =>
+				{
+					*end = day;
+				}
+				_ => runs.push((day, day, hours, reason)),
+			}
+		}
+
+		Some(
+			runs
+				.iter()
+				.map(|(first, last, hours, reason)| {
+					let hours = (!hours.is_empty()).then(|| self.hours(hours));
+					self.locale.exception(
+						&self.locale.dates(*first, *last),
+						hours.as_deref(),
+						reason.as_deref(),
+					)
+				})
+				.collect(),
+		)
+	}
+}
+
+impl<Tz: TimeZone> fmt::Display for Description<'_, '_, Tz> {
+	fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+		if self.start >= self.end {
+			return Ok(());
+		}
+		let mut sentences: Vec<String> = Vec::new();
+		sentences.extend(self.regular(self.start.naive_local().date()));
+		sentences.extend(self.exceptions().unwrap_or_default());
+		write!(formatter, "{}", sentences.join(" "))
+	}
+}
+
+impl<Tz: TimeZone> Space<'_, Tz> {
+	/// The hours of the space from `start` until `end` in English, e.g.
+	/// "Open Mon–Fri 7 AM–11 PM; closed Sat–Sun. Closed Nov 28 for
+	/// Thanksgiving."  See [`Description`] for other clocks and locales.
+	#[must_use]
+	pub fn describe(&self, start: &DateTime<Tz>, end: &DateTime<Tz>) -> String {
+		Description::new(self, start, end).to_string()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{Clock, Description};
+	use crate::{Exception, Part, Reason, Schedule, Space, Specifier, Status};
+	use crate::{Norwegian, Spanish};
+	use chrono::{DateTime, FixedOffset};
+
+	fn weekly(day: &str, time: &str) -> Specifier<FixedOffset> {
+		Specifier::Weekly {
+			day: day.to_string(),
+			time: time.to_string(),
+		}
+	}
+
+	fn exact(time: &str) -> Specifier<FixedOffset> {
+		Specifier::Exact(DateTime::parse_from_rfc3339(time).unwrap())
+	}
+
+	fn space() -> Space<'static, FixedOffset> {
+		let mut thanksgiving = Exception::new()
+			.effective(exact("2019-11-28T00:00:00-06:00"))
+			.expires(exact("2019-11-30T00:00:00-06:00"));
+		*thanksgiving.effect_mut() = Some(Status::Closed(Reason::Exception(Some(
+			"Thanksgiving".to_string(),
+		))));
+
+		let mut eve = Exception::new()
+			.effective(exact("2019-11-27T12:00:00-06:00"))
+			.expires(exact("2019-11-28T00:00:00-06:00"));
+		*eve.effect_mut() = Some(Status::Closed(Reason::Exception(None)));
+
+		let schedule = ["Mon", "Tue", "Wed", "Thu"].iter().fold(
+			Schedule::new().exception(thanksgiving).exception(eve),
+			|schedule, day| {
+				let next = match *day {
+					"Mon" => "Tue",
+					"Tue" => "Wed",
+					"Wed" => "Thu",
+					_ => "Fri",
+				};
+				schedule.part(
+					Part::new()
+						.open(weekly(day, "07:00"))
+						.close(weekly(next, "00:00")),
+				)
+			},
+		);
+		let schedule = schedule
+			.part(
+				Part::new()
+					.open(weekly("Fri", "07:00"))
+					.close(weekly("Fri", "20:00")),
+			)
+			.part(
+				Part::new()
+					.open(weekly("Sat", "10:00"))
+					.close(weekly("Sat", "18:00")),
+			);
+
+		Space::new("Library").schedule(schedule)
+	}
+
+	#[test]
+	fn describe() {
+		let space = space();
+		let start = DateTime::parse_from_rfc3339("2019-11-18T00:00:00-06:00").unwrap();
+		let end = DateTime::parse_from_rfc3339("2019-12-02T00:00:00-06:00").unwrap();
+
+		assert_eq!(
+			space.describe(&start, &end),
+			"Open Mon–Thu 7 AM–midnight, Fri 7 AM–8 PM, Sat 10 AM–6 PM; closed Sun. \
+			 Open Nov 27 7 AM–noon. \
+			 Closed Nov 28–29 for Thanksgiving."
+		);
+		assert_eq!(Description::new(&space, &start, &start).to_string(), "");
+	}
+
+	#[test]
+	fn overnight() {
+		let space: Space<FixedOffset> = Space::new("Tostrud Center").schedule(
+			Schedule::new().part(
+				Part::new()
+					.open(weekly("Fri", "22:00"))
+					.close(weekly("Sat", "02:00")),
+			),
+		);
+		let time = |rfc3339: &str| DateTime::parse_from_rfc3339(rfc3339).unwrap();
+
+		assert_eq!(
+			space.describe(
+				&time("2019-11-18T00:00:00-06:00"),
+				&time("2019-11-25T00:00:00-06:00")
+			),
+			"Open Fri 10 PM–2 AM; closed Mon–Thu, Sat–Sun."
+		);
+		for (at, open) in [
+			("2019-11-22T21:59:00-06:00", false),
+			("2019-11-22T22:00:00-06:00", true),
+			("2019-11-23T01:00:00-06:00", true),
+			("2019-11-23T02:00:00-06:00", false),
+		] {
+			assert_eq!(
+				matches!(space.status_at(&time(at)), Status::Open(_)),
+				open,
+				"at {at}"
+			);
+		}
+	}
+
+	#[test]
+	fn across_midnight() {
+		let daily = |open: &str, close: &str| -> Space<FixedOffset> {
+			Space::new("Lab").schedule(
+				Schedule::new().part(
+					Part::new()
+						.open(Specifier::Daily {
+							time: open.to_string(),
+						})
+						.close(Specifier::Daily {
+							time: close.to_string(),
+						}),
+				),
+			)
+		};
+		let time = |rfc3339: &str| DateTime::parse_from_rfc3339(rfc3339).unwrap();
+		let (start, end) = (
+			time("2019-11-18T00:00:00-06:00"),
+			time("2019-11-25T00:00:00-06:00"),
+		);
+
+		assert_eq!(
+			daily("00:00", "00:00").describe(&start, &end),
+			"Open Mon–Sun midnight–midnight."
+		);
+		// The night before the range starts isn't an opening on its first day.
+		assert_eq!(
+			daily("22:00", "02:00").describe(&start, &end),
+			"Open Mon–Sun 10 PM–2 AM."
+		);
+	}
+
+	#[test]
+	fn twenty_four_hour() {
+		let space = space();
+		let start = DateTime::parse_from_rfc3339("2019-11-18T00:00:00-06:00").unwrap();
+		let end = DateTime::parse_from_rfc3339("2019-11-25T00:00:00-06:00").unwrap();
+
+		assert_eq!(
+			Description::new(&space, &start, &end)
+				.clock(Clock::TwentyFourHour)
+				.to_string(),
+			"Open Mon–Thu 07:00–24:00, Fri 07:00–20:00, Sat 10:00–18:00; closed Sun."
+		);
+	}
+
+	#[test]
+	fn localized() {
+		let space = space();
+		let start = DateTime::parse_from_rfc3339("2019-11-25T00:00:00-06:00").unwrap();
+		let end = DateTime::parse_from_rfc3339("2019-12-02T00:00:00-06:00").unwrap();
+
+		assert_eq!(
+			Description::new(&space, &start, &end)
+				.locale(Spanish)
+				.to_string(),
+			"Abierto lun–jue 07:00–24:00, vie 07:00–20:00, sáb 10:00–18:00; cerrado dom. \
+			 Abierto el 27 nov 07:00–12:00. \
+			 Cerrado el 28–29 nov por Thanksgiving."
+		);
+		assert_eq!(
+			Description::new(&space, &start, &end)
+				.locale(Norwegian)
+				.to_string(),
+			"Åpent man–tor 07.00–24.00, fre 07.00–20.00, lør 10.00–18.00; stengt søn. \
+			 Åpent 27. nov. 07.00–12.00. \
+			 Stengt 28.–29. nov. på grunn av Thanksgiving."
+		);
+	}
+}
@@ -0,0 +1,1089 @@
+//! A concise text format for spaces and their schedules, e.g.
+//!
+//! ```text
+//! space "Library"
+//!   effective 2019-08-26
+//!   Mon-Fri 07:00-23:00
+//!   Sat 09:00-17:00 "Front desk only"
+//!   Fri 22:00-02:00
+//!   daily 12:00-13:00 closed "Lunch"
+//!   2019-11-28..2019-11-29 closed "Thanksgiving"
+//!   2019-12-23 09:00-12:00 open
+//!   2019-12-24T17:00..2019-12-26T09:00 closed
+//! ```
+//!
+//! Each line is one of `space "Name"`, `schedule` (which starts another
+//! schedule in the space; the first one needn't say so), `effective` or
+//! `expires` followed by a date or `dateTtime`, or a rule.  A rule says when,
+//! then optionally `open` or `closed`, then any notes in double quotes.
+//! Rules with `open` or `closed` are exceptions, and apply in the order
+//! written; the rest are parts.
+//!
+//! When is one of:
+//!
+//! - weekdays and time ranges, e.g. `Mon-Wed,Fri 07:00-12:00,13:00-17:00`;
+//!   times ending at or before they start, or at `24:00`, end the next day
+//! - `daily` and time ranges
+//! - a date or an inclusive range of dates, e.g. `2019-11-28..2019-11-29`,
+//!   either all day or with time ranges on each date
+//! - a span between two weekday times, e.g. `Fri 22:00..Mon 06:00`, or two
+//!   date times, e.g. `2019-12-24T17:00..2019-12-26T09:00`
+//! - `always`, for an exception without a start or an end
+//!
+//! Anything after a `#` outside quotes is a comment.  Provenance isn't
+//! written, and neither is the note of an exception whose effect is given by
+//! a part.
+
+use core::fmt;
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Weekday};
+
+use super::specifier::{time_of_day, write_time};
+use super::{Exception, Part, Reason, Schedule, Space, Specifier, Status};
+
+/// Something in the text format which couldn't be read.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DslError {
+	line: usize,
+	message: String,
+}
+
+impl DslError {
+	fn new(line: usize, message: &str) -> Self {
+		Self {
+			line,
+			message: message.to_string(),
+		}
+	}
+
+	/// The line the problem is on, counting from 1.
+	#[must_use]
+	pub fn line(&self) -> usize {
+		self.line
+	}
+
+	#[must_use]
+	pub fn message(&self) -> &str {
+		&self.message
+	}
+}
+
+impl fmt::Display for DslError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "line {}: {}", self.line, self.message)
+	}
+}
+
+impl std::error::Error for DslError {}
+
+type Result<T> = core::result::Result<T, DslError>;
+
+/// A problem on the line being read; the parser adds the line number.
+type LineResult<T> = core::result::Result<T, String>;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+	Word(String),
+	Quoted(String),
+}
+
+/// Split a line into words and quoted strings, dropping any comment.  `..`
+/// is a word of its own, wherever it's written.
+fn tokens(text: &str) -> LineResult<Vec<Token>> {
+	let mut tokens: Vec<Token> = Vec::new();
+	let mut chars = text.chars();
+	let mut word: String = String::new();
+	let end_word = |word: &mut String, tokens: &mut Vec<Token>| {
+		for (i, piece) in word.split("..").enumerate() {
+			if i > 0 {
+				tokens.push(Token::Word("..".to_string()));
+			}
+			if !piece.is_empty() {
+				tokens.push(Token::Word(piece.to_string()));
+			}
+		}
+		word.clear();
+	};
+
+	while let Some(c) = chars.next() {
+		match c {
+			'#' => break,
+			'"' => {
+				end_word(&mut word, &mut tokens);
+				let mut quoted: String = String::new();
+				loop {
+					match chars.next() {
+						Some('"') => break,
+						Some('\\') => match chars.next() {
+							Some(c) => quoted.push(c),
+							None => return Err("unterminated quotes".to_string()),
+						},
+						Some(c) => quoted.push(c),
+						None => return Err("unterminated quotes".to_string()),
+					}
+				}
+				tokens.push(Token::Quoted(quoted));
+			}
+			c if c.is_whitespace() => end_word(&mut word, &mut tokens),
+			c => word.push(c),
+		}
+	}
+	end_word(&mut word, &mut tokens);
+
+	Ok(tokens)
+}
+
+fn quoted(text: &str) -> String {
+	format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn time(text: &str) -> LineResult<NaiveTime> {
+	time_of_day(text).ok_or_else(|| format!("expected a time such as 07:00, not `{text}`"))
+}
+
+fn date(text: &str) -> Option<NaiveDate> {
+	NaiveDate::parse_from_str(text, "%Y-%m-%d").ok()
+}
+
+fn weekday(text: &str) -> Option<Weekday> {
+	text.parse().ok()
+}
+
+/// `Mon-Wed,Fri`, in the order written; ranges may wrap past Sunday.
+fn weekdays(text: &str) -> Option<Vec<Weekday>> {
+	let mut days: Vec<Weekday> = Vec::new();
+	for item in text.split(',') {
+		if let Some((first, last)) = item.split_once('-') {
+			let mut day: Weekday = weekday(first)?;
+			let last: Weekday = weekday(last)?;
+			days.push(day);
+			while day != last {
+				day = day.succ();
+				days.push(day);
+			}
+		} else {
+			days.push(weekday(item)?);
+		}
+	}
+	Some(days)
+}
+
+/// A time range; ending at midnight means the midnight after it starts.
+type Range = (NaiveTime, NaiveTime);
+
+/// `07:00-12:00,13:00-17:00`.
+fn ranges(text: &str) -> LineResult<Vec<Range>> {
+	text
+		.split(',')
+		.map(|range| {
+			let (start, end) = range
+				.split_once('-')
+				.ok_or_else(|| format!("expected a time range such as 07:00-17:00, not `{range}`"))?;
+			let start: NaiveTime = time(start)?;
+			let end: NaiveTime = if end == "24:00" {
+				NaiveTime::MIN
+			} else {
+				time(end)?
+			};
+			Ok((start, end))
+		})
+		.collect()
+}
+
+fn write_range((start, end): Range) -> String {
+	let end: String = if end == NaiveTime::MIN {
+		"24:00".to_string()
+	} else {
+		write_time(end)
+	};
+	format!("{}-{}", write_time(start), end)
+}
+
+fn write_date_time(time: NaiveDateTime) -> String {
+	if time.time() == NaiveTime::MIN {
+		time.date().to_string()
+	} else {
+		format!("{}T{}", time.date(), write_time(time.time()))
+	}
+}
+
+/// `2019-08-26`, `2019-08-26T07:00` or `2019-08-26 07:00`.
+fn date_time(words: &[&str]) -> Option<NaiveDateTime> {
+	match words {
+		[date_time] => match date_time.split_once('T') {
+			Some((day, at)) => Some(date(day)?.and_time(time_of_day(at)?)),
+			None => Some(date(date_time)?.and_time(NaiveTime::MIN)),
+		},
+		[day, at] => Some(date(day)?.and_time(time_of_day(at)?)),
+		_ => None,
+	}
+}
+
+/// When a rule applies.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum When {
+	Always,
+	Daily(Range),
+	Weekly(Vec<Weekday>, Range),
+	WeeklySpan((Weekday, NaiveTime), (Weekday, NaiveTime)),
+	/// From the start of the first date to the end of the last, or during
+	/// the range on each of them.
+	Dates(NaiveDate, NaiveDate, Option<Range>),
+	Span(NaiveDateTime, NaiveDateTime),
+}
+
+impl When {
+	/// Read the words before a rule's effect and notes; ranges give one
+	/// `When` each.
+	fn parse(words: &[&str]) -> LineResult<Vec<Self>> {
+		if let Some(dots) = words.iter().position(|word| *word == "..") {
+			return Self::parse_span(&words[..dots], &words[dots + 1..]);
+		}
+
+		Ok(match words {
+			["always"] => vec![Self::Always],
+			["daily", times] => ranges(times)?.into_iter().map(Self::Daily).collect(),
+			[day] => {
+				let Some(day) = date(day) else {
+					return Err(format!("couldn't read `{day}` as a rule"));
+				};
+				vec![Self::Dates(day, day, None)]
+			}
+			[days, times] => {
+				let Some(day) = date(days) else {
+					let days: Vec<Weekday> =
+						weekdays(days).ok_or_else(|| format!("expected weekdays, not `{days}`"))?;
+					return Ok(
+						ranges(times)?
+							.into_iter()
+							.map(|range| Self::Weekly(days.clone(), range))
+							.collect(),
+					);
+				};
+				ranges(times)?
+					.into_iter()
+					.map(|range| Self::Dates(day, day, Some(range)))
+					.collect()
+			}
+			[] => return Err("expected a rule".to_string()),
+			_ => return Err(format!("couldn't read `{}` as a rule", words.join(" "))),
+		})
+	}
+
+	fn parse_span(from: &[&str], to: &[&str]) -> LineResult<Vec<Self>> {
+		let span = || format!("couldn't read `{}..{}`", from.join(" "), to.join(" "));
+
+		if let ([first], [last, rest @ ..]) = (from, to) {
+			if let (Some(first), Some(last)) = (date(first), date(last)) {
+				if last < first {
+					return Err("dates must be in order".to_string());
+				}
+				return Ok(match rest {
+					[] => vec![Self::Dates(first, last, None)],
+					[times] => ranges(times)?
+						.into_iter()
+						.map(|range| Self::Dates(first, last, Some(range)))
+						.collect(),
+					_ => return Err(span()),
+				});
+			}
+		}
+
+		if let (Some(start), Some(end)) = (date_time(from), date_time(to)) {
+			if end <= start {
+				return Err("spans must end after they start".to_string());
+			}
+			return Ok(vec![Self::Span(start, end)]);
+		}
+
+		match (from, to) {
+			([from_day, from_time], [to_day, to_time]) => {
+				let from_day: Weekday = weekday(from_day).ok_or_else(span)?;
+				let to_day: Weekday = weekday(to_day).ok_or_else(span)?;
+				Ok(vec![Self::WeeklySpan(
+					(from_day, time(from_time)?),
+					(to_day, time(to_time)?),
+				)])
+			}
+			_ => Err(span()),
+		}
+	}
+
+	/// When an open and a close say, if it can be written.
+	fn from_specifiers<Tz: TimeZone>(
+		open: Option<&Specifier<Tz>>,
+		close: Option<&Specifier<Tz>>,
+	) -> Option<Self> {
+		Some(match (open, close) {
+			(None, None) => Self::Always,
+			(Some(Specifier::Daily { time: open }), Some(Specifier::Daily { time: close })) => {
+				Self::Daily((time_of_day(open)?, time_of_day(close)?))
+			}
+			(
+				Some(Specifier::Weekly {
+					day: open_day,
+					time: open,
+				}),
+				Some(Specifier::Weekly {
+					day: close_day,
+					time: close,
+				}),
+			) => {
+				let (open_day, open) = (weekday(open_day)?, time_of_day(open)?);
+				let (close_day, close) = (weekday(close_day)?, time_of_day(close)?);
+				if close_day == later(open_day, open, close) {
+					Self::Weekly(vec![open_day], (open, close))
+				} else {
+					Self::WeeklySpan((open_day, open), (close_day, close))
+				}
+			}
+			(Some(Specifier::Exact(open)), Some(Specifier::Exact(close))) => {
+				let (open, close) = (open.naive_local(), close.naive_local());
+				let (day, range) = (open.date(), (open.time(), close.time()));
+				if close <= open {
+					return None;
+				} else if range == (NaiveTime::MIN, NaiveTime::MIN) {
+					Self::Dates(day, close.date() - Duration::days(1), None)
+				} else if close.date() == day + Duration::days(i64::from(range.1 <= range.0)) {
+					Self::Dates(day, day, Some(range))
+				} else {
+					Self::Span(open, close)
+				}
+			}
+			_ => return None,
+		})
+	}
+
+	/// The opens and closes it stands for, localized in `timezone`.
+	#[allow(clippy::type_complexity)]
+	fn specifiers<Tz: TimeZone>(
+		&self,
+		timezone: &Tz,
+	) -> LineResult<Vec<(Option<Specifier<Tz>>, Option<Specifier<Tz>>)>> {
+		let weekly = |day: Weekday, time: NaiveTime| {
+			Some(Specifier::Weekly {
+				day: day.to_string(),
+				time: write_time(time),
+			})
+		};
+		let exact = |time: NaiveDateTime| {
+			timezone
+				.from_local_datetime(&time)
+				.single()
+				.map(|time| Some(Specifier::Exact(time)))
+				.ok_or_else(|| format!("{} doesn't happen exactly once", write_date_time(time)))
+		};
+
+		Ok(match self {
+			Self::Always => vec![(None, None)],
+			Self::Daily((open, close)) => vec![(
+				Some(Specifier::Daily {
+					time: write_time(*open),
+				}),
+				Some(Specifier::Daily {
+					time: write_time(*close),
+				}),
+			)],
+			Self::Weekly(days, (open, close)) => days
+				.iter()
+				.map(|day| {
+					(
+						weekly(*day, *open),
+						weekly(later(*day, *open, *close), *close),
+					)
+				})
+				.collect(),
+			Self::WeeklySpan((open_day, open), (close_day, close)) => {
+				vec![(weekly(*open_day, *open), weekly(*close_day, *close))]
+			}
+			Self::Dates(first, last, None) => vec![(
+				exact(first.and_time(NaiveTime::MIN))?,
+				exact((*last + Duration::days(1)).and_time(NaiveTime::MIN))?,
+			)],
+			Self::Dates(first, last, Some((open, close))) => first
+				.iter_days()
+				.take_while(|day| day <= last)
+				.map(|day| {
+					let closes: NaiveDate = day + Duration::days(i64::from(close <= open));
+					Ok((exact(day.and_time(*open))?, exact(closes.and_time(*close))?))
+				})
+				.collect::<LineResult<_>>()?,
+			Self::Span(open, close) => vec![(exact(*open)?, exact(*close)?)],
+		})
+	}
+}
+
+/// The day a range starting on `day` ends on.
+fn later(day: Weekday, open: NaiveTime, close: NaiveTime) -> Weekday {
+	if close <= open {
+		day.succ()
+	} else {
+		day
+	}
+}
+
+/// `Mon-Wed,Fri`: runs of following days are joined up.
+fn write_weekdays(days: &[Weekday]) -> String {
+	let mut runs: Vec<(Weekday, Weekday)> = Vec::new();
+	for day in days {
+		match runs.last_mut() {
+			Some((_, last)) if last.succ() == *day => *last = *day,
+			_ => runs.push((*day, *day)),
+		}
+	}
+	runs
+		.iter()
+		.map(|(first, last)| {
+			if first == last {
+				first.to_string()
+			} else {
+				format!("{first}-{last}")
+			}
+		})
+		.collect::<Vec<String>>()
+		.join(",")
+}
+
+impl fmt::Display for When {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Always => f.write_str("always"),
+			Self::Daily(range) => write!(f, "daily {}", write_range(*range)),
+			Self::Weekly(days, range) => {
+				write!(f, "{} {}", write_weekdays(days), write_range(*range))
+			}
+			Self::WeeklySpan((open_day, open), (close_day, close)) => write!(
+				f,
+				"{} {}..{} {}",
+				open_day,
+				write_time(*open),
+				close_day,
+				write_time(*close)
+			),
+			Self::Dates(first, last, range) => {
+				write!(f, "{first}")?;
+				if last != first {
+					write!(f, "..{last}")?;
+				}
+				match range {
+					Some(range) => write!(f, " {}", write_range(*range)),
+					None => Ok(()),
+				}
+			}
+			Self::Span(open, close) => {
+				write!(f, "{}..{}", write_date_time(*open), write_date_time(*close))
+			}
+		}
+	}
+}
+
+/// A part, or an exception if it's open or closed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Rule {
+	when: When,
+	open: Option<bool>,
+	notes: Vec<String>,
+}
+
+impl Rule {
+	fn parse(tokens: &[Token]) -> LineResult<Vec<Self>> {
+		let mut words: Vec<&str> = Vec::new();
+		let mut open: Option<bool> = None;
+		let mut notes: Vec<String> = Vec::new();
+		for token in tokens {
+			match token {
+				Token::Quoted(note) => notes.push(note.clone()),
+				Token::Word(word) if !notes.is_empty() || open.is_some() => {
+					return Err(format!("unexpected `{word}`"));
+				}
+				Token::Word(word) if word == "open" => open = Some(true),
+				Token::Word(word) if word == "closed" => open = Some(false),
+				Token::Word(word) => words.push(word),
+			}
+		}
+		if open.is_some() && notes.len() > 1 {
+			return Err("exceptions have at most one note".to_string());
+		}
+
+		Ok(
+			When::parse(&words)?
+				.into_iter()
+				.map(|when| Self {
+					when,
+					open,
+					notes: notes.clone(),
+				})
+				.collect(),
+		)
+	}
+
+	fn from_part<Tz: TimeZone>(part: &Part<Tz>) -> Option<Self> {
+		Some(Self {
+			when: When::from_specifiers(Some(part.opens()?), Some(part.closes()?))?,
+			open: None,
+			notes: part.notes().to_vec(),
+		})
+	}
+
+	fn from_exception<Tz: TimeZone>(exception: &Exception<'_, Tz>) -> Option<Self> {
+		let (open, reason) = match exception.effect().as_ref()? {
+			Status::Open(reason) => (true, reason),
+			Status::Closed(reason) => (false, reason),
+		};
+		let notes: Vec<String> = match reason {
+			Reason::Exception(Some(note)) => vec![note.clone()],
+			_ => Vec::new(),
+		};
+
+		Some(Self {
+			when: When::from_specifiers(exception.effective_from(), exception.expires_at())?,
+			open: Some(open),
+			notes,
+		})
+	}
+
+	/// Add this rule to the end of `schedule`.
+	fn apply<Tz: TimeZone>(&self, schedule: &mut Schedule<'_, Tz>, timezone: &Tz) -> LineResult<()> {
+		for (opens, closes) in self.when.specifiers(timezone)? {
+			if let Some(open) = self.open {
+				let mut exception: Exception<'_, Tz> = Exception::new();
+				if let Some(effective) = opens {
+					exception = exception.effective(effective);
+				}
+				if let Some(expires) = closes {
+					exception = exception.expires(expires);
+				}
+				let reason = Reason::Exception(self.notes.first().cloned());
+				*exception.effect_mut() = Some(if open {
+					Status::Open(reason)
+				} else {
+					Status::Closed(reason)
+				});
+				schedule.exceptions_mut().push(exception);
+			} else {
+				let (Some(opens), Some(closes)) = (opens, closes) else {
+					return Err("`always` needs `open` or `closed`".to_string());
+				};
+				let part: Part<Tz> = self
+					.notes
+					.iter()
+					.fold(Part::new().open(opens).close(closes), |part, note| {
+						part.note(note)
+					});
+				schedule.parts_mut().push(part);
+			}
+		}
+		Ok(())
+	}
+
+	/// Join this rule's weekdays onto `self`'s, if they differ in nothing
+	/// else.
+	fn merge(&mut self, rule: &Self) -> bool {
+		match (&mut self.when, &rule.when) {
+			(When::Weekly(days, range), When::Weekly(more, more_range))
+				if range == more_range
+					&& self.open == rule.open
+					&& self.notes == rule.notes
+					&& more.iter().all(|day| !days.contains(day)) =>
+			{
+				days.extend(more);
+				true
+			}
+			_ => false,
+		}
+	}
+}
+
+impl fmt::Display for Rule {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{}", self.when)?;
+		match self.open {
+			Some(true) => f.write_str(" open")?,
+			Some(false) => f.write_str(" closed")?,
+			None => {}
+		}
+		for note in &self.notes {
+			write!(f, " {}", quoted(note))?;
+		}
+		Ok(())
+	}
+}
+
+/// Write a schedule's lines, each after `indent`.
+fn write_schedule<Tz: TimeZone>(
+	f: &mut fmt::Formatter<'_>,
+	schedule: &Schedule<'_, Tz>,
+	indent: &str,
+) -> fmt::Result {
+	if let Some(effective) = schedule.effective() {
+		writeln!(
+			f,
+			"{indent}effective {}",
+			write_date_time(effective.naive_local())
+		)?;
+	}
+	if let Some(expires) = schedule.expires() {
+		writeln!(
+			f,
+			"{indent}expires {}",
+			write_date_time(expires.naive_local())
+		)?;
+	}
+
+	let parts = schedule
+		.parts()
+		.iter()
+		.map(|part| Rule::from_part(part).ok_or("a part which can't be written"));
+	let exceptions = schedule
+		.exceptions()
+		.iter()
+		.map(|exception| Rule::from_exception(exception).ok_or("an exception which can't be written"));
+	let mut rules: Vec<core::result::Result<Rule, &str>> = Vec::new();
+	for rule in parts.chain(exceptions) {
+		if let (Some(Ok(last)), Ok(rule)) = (rules.last_mut(), &rule) {
+			if last.merge(rule) {
+				continue;
+			}
+		}
+		rules.push(rule);
+	}
+
+	for rule in rules {
+		match rule {
+			Ok(rule) => writeln!(f, "{indent}{rule}")?,
+			Err(skipped) => writeln!(f, "{indent}# skipped {skipped}")?,
+		}
+	}
+	Ok(())
+}
+
+fn is_empty<Tz: TimeZone>(schedule: &Schedule<'_, Tz>) -> bool {
+	schedule.effective().is_none()
+		&& schedule.expires().is_none()
+		&& schedule.parts().is_empty()
+		&& schedule.exceptions().is_empty()
+}
+
+fn write_space<Tz: TimeZone>(f: &mut fmt::Formatter<'_>, space: &Space<'_, Tz>) -> fmt::Result {
+	writeln!(f, "space {}", quoted(space.name()))?;
+	for (i, schedule) in space.schedules().iter().enumerate() {
+		// The first schedule is started by any line that needs one.
+		if i > 0 || is_empty(schedule) {
+			writeln!(f, "  schedule")?;
+		}
+		write_schedule(f, schedule, "  ")?;
+	}
+	Ok(())
+}
+
+/// Spaces read from, or to be written in, the text format.
+#[derive(Debug)]
+pub struct Dsl<'schedule, Tz: TimeZone> {
+	spaces: Vec<Space<'schedule, Tz>>,
+}
+
+impl<Tz: TimeZone> Default for Dsl<'_, Tz> {
+	fn default() -> Self {
+		Self { spaces: Vec::new() }
+	}
+}
+
+impl<'schedule, Tz: TimeZone> Dsl<'schedule, Tz> {
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Read spaces, with dates and times local to `timezone`.
+	///
+	/// # Errors
+	///
+	/// Returns an error naming the first line which can't be read.
+	pub fn parse(text: &str, timezone: &Tz) -> Result<Self> {
+		let mut spaces: Vec<Space<'schedule, Tz>> = Vec::new();
+		for (line, tokens) in lines(text)? {
+			match tokens.as_slice() {
+				[Token::Word(keyword), Token::Quoted(name)] if keyword == "space" => {
+					spaces.push(Space::new(name));
+				}
+				[Token::Word(keyword), ..] if keyword == "space" => {
+					return Err(DslError::new(line, "expected `space \"Name\"`"));
+				}
+				[Token::Word(keyword)] if keyword == "schedule" => {
+					last_space(&mut spaces, line)?
+						.schedules_mut()
+						.push(Schedule::new());
+				}
+				tokens => {
+					let schedules = last_space(&mut spaces, line)?.schedules_mut();
+					if schedules.is_empty() {
+						schedules.push(Schedule::new());
+					}
+					let last: usize = schedules.len() - 1;
+					schedule_line(&mut schedules[last], tokens, timezone)
+						.map_err(|message| DslError::new(line, &message))?;
+				}
+			}
+		}
+
+		Ok(Self { spaces })
+	}
+
+	#[must_use]
+	pub fn space(mut self, space: Space<'schedule, Tz>) -> Self {
+		self.spaces.push(space);
+		self
+	}
+
+	#[must_use]
+	pub fn spaces(&self) -> &[Space<'schedule, Tz>] {
+		&self.spaces
+	}
+
+	#[must_use]
+	pub fn into_spaces(self) -> Vec<Space<'schedule, Tz>> {
+		self.spaces
+	}
+}
+
+fn last_space<'spaces, 'schedule, Tz: TimeZone>(
+	spaces: &'spaces mut [Space<'schedule, Tz>],
+	line: usize,
+) -> Result<&'spaces mut Space<'schedule, Tz>> {
+	spaces
+		.last_mut()
+		.ok_or_else(|| DslError::new(line, "expected `space \"Name\"` before this line"))
+}
+
+/// The numbered lines which say something, as tokens.
+fn lines(text: &str) -> Result<Vec<(usize, Vec<Token>)>> {
+	let mut lines: Vec<(usize, Vec<Token>)> = Vec::new();
+	for (i, text) in text.lines().enumerate() {
+		let tokens: Vec<Token> = tokens(text).map_err(|message| DslError::new(i + 1, &message))?;
+		if !tokens.is_empty() {
+			lines.push((i + 1, tokens));
+		}
+	}
+	Ok(lines)
+}
+
+/// Read a line belonging to `schedule`: its effective or expiry time, or a
+/// rule.
+fn schedule_line<Tz: TimeZone>(
+	schedule: &mut Schedule<'_, Tz>,
+	tokens: &[Token],
+	timezone: &Tz,
+) -> LineResult<()> {
+	let words: Vec<&str> = tokens
+		.iter()
+		.map(|token| match token {
+			Token::Word(word) => Some(word.as_str()),
+			Token::Quoted(_) => None,
+		})
+		.collect::<Option<Vec<&str>>>()
+		.unwrap_or_default();
+
+	match words.split_first() {
+		Some((&keyword, time)) if keyword == "effective" || keyword == "expires" => {
+			let time: NaiveDateTime = date_time(time)
+				.ok_or_else(|| format!("expected `{keyword}` and a date or date and time"))?;
+			let time: DateTime<Tz> = timezone
+				.from_local_datetime(&time)
+				.single()
+				.ok_or_else(|| format!("{} doesn't happen exactly once", write_date_time(time)))?;
+			if keyword == "effective" {
+				*schedule.effective_mut() = Some(time);
+			} else {
+				*schedule.expires_mut() = Some(time);
+			}
+			Ok(())
+		}
+		_ => {
+			for rule in Rule::parse(tokens)? {
+				rule.apply(schedule, timezone)?;
+			}
+			Ok(())
+		}
+	}
+}
+
+impl<Tz: TimeZone> fmt::Display for Dsl<'_, Tz> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		for (i, space) in self.spaces.iter().enumerate() {
+			if i > 0 {
+				writeln!(f)?;
+			}
+			write_space(f, space)?;
+		}
+		Ok(())
+	}
+}
+
+/// Writes a schedule's lines, as they'd appear under a space.
+struct ScheduleLines<'a, 'schedule, Tz: TimeZone>(&'a Schedule<'schedule, Tz>);
+
+impl<Tz: TimeZone> fmt::Display for ScheduleLines<'_, '_, Tz> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write_schedule(f, self.0, "")
+	}
+}
+
+/// Writes a space's lines.
+struct SpaceLines<'a, 'schedule, Tz: TimeZone>(&'a Space<'schedule, Tz>);
+
+impl<Tz: TimeZone> fmt::Display for SpaceLines<'_, '_, Tz> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write_space(f, self.0)
+	}
+}
+
+impl<Tz: TimeZone> Schedule<'_, Tz> {
+	/// Read a schedule's lines in the text format, without a `space` line.
+	///
+	/// # Errors
+	///
+	/// Returns an error naming the first line which can't be read, or which
+	/// starts a space or another schedule.
+	pub fn from_dsl(text: &str, timezone: &Tz) -> Result<Self> {
+		let mut schedule: Self = Self::new();
+		for (line, tokens) in lines(text)? {
+			if let Some(Token::Word(keyword)) = tokens.first() {
+				if keyword == "space" || keyword == "schedule" {
+					return Err(DslError::new(
+						line,
+						&format!("expected only one schedule, not `{keyword}`"),
+					));
+				}
+			}
+			schedule_line(&mut schedule, &tokens, timezone)
+				.map_err(|message| DslError::new(line, &message))?;
+		}
+		Ok(schedule)
+	}
+
+	/// The schedule's lines in the text format; see `Schedule::from_dsl`.
+	#[must_use]
+	pub fn to_dsl(&self) -> String {
+		ScheduleLines(self).to_string()
+	}
+}
+
+impl<Tz: TimeZone> Space<'_, Tz> {
+	/// The space in the text format; see `Dsl`.
+	#[must_use]
+	pub fn to_dsl(&self) -> String {
+		SpaceLines(self).to_string()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::Dsl;
+	use crate::{Reason, Schedule, Space, Specifier, Status};
+	use chrono::{DateTime, FixedOffset};
+
+	fn zone() -> FixedOffset {
+		FixedOffset::west_opt(5 * 3600).unwrap()
+	}
+
+	fn time(rfc3339: &str) -> DateTime<FixedOffset> {
+		DateTime::parse_from_rfc3339(rfc3339).unwrap()
+	}
+
+	#[test]
+	fn parse_and_write() {
+		let text = "\
+space \"Library\"
+  effective 2019-08-26
+  Mon-Fri 07:00-23:00
+  Sat 09:00-17:00 \"Front desk only\"
+  Fri-Sat 22:00-02:00
+  Sat 10:00..Mon 02:00
+  daily 12:00-13:00 closed \"Lunch\"
+  2019-11-28..2019-11-29 closed \"Thanksgiving\"
+  2019-12-23 09:00-12:00 open
+  2019-12-24T17:00..2019-12-26T09:00 closed
+
+space \"Gym\"
+  schedule
+  schedule
+  always closed \"Renovation \\\"phase\\\" 2\"
+";
+		let dsl: Dsl<FixedOffset> = Dsl::parse(text, &zone()).unwrap();
+		assert_eq!(dsl.to_string(), text);
+
+		let library: &Space<FixedOffset> = &dsl.spaces()[0];
+		let schedule: &Schedule<FixedOffset> = &library.schedules()[0];
+		assert_eq!(
+			schedule.effective(),
+			&Some(time("2019-08-26T00:00:00-05:00"))
+		);
+		assert_eq!(schedule.parts().len(), 9);
+		assert_eq!(
+			schedule.parts()[6].closes(),
+			Some(&Specifier::Weekly {
+				day: "Sat".to_string(),
+				time: "02:00".to_string(),
+			})
+		);
+		assert_eq!(schedule.exceptions().len(), 4);
+		assert_eq!(
+			schedule.exceptions()[1].expires_at(),
+			Some(&Specifier::Exact(time("2019-11-30T00:00:00-05:00")))
+		);
+		assert_eq!(
+			schedule.exceptions()[1].effect(),
+			&Some(Status::Closed(Reason::Exception(Some(
+				"Thanksgiving".to_string()
+			))))
+		);
+
+		let gym: &Space<FixedOffset> = &dsl.spaces()[1];
+		assert_eq!(gym.schedules().len(), 2);
+		assert_eq!(gym.schedules()[1].exceptions()[0].effective_from(), None);
+	}
+
+	#[test]
+	fn normalized() {
+		let text = "\
+# Comments and blank lines are dropped.
+
+space \"Hall\"   # so is this
+\tmonday-wednesday 7:00-24:00
+\tThu 08:00-12:00,13:00-17:00 \"Desk\" \"Phones\"
+\tFri 08:00-12:00
+\tFri 08:00-12:00
+\t2020-01-01 00:00..2020-01-02 00:00 closed
+";
+		// The same day twice isn't joined up.
+		let written = "\
+space \"Hall\"
+  Mon-Wed 07:00-24:00
+  Thu 08:00-12:00 \"Desk\" \"Phones\"
+  Thu 13:00-17:00 \"Desk\" \"Phones\"
+  Fri 08:00-12:00
+  Fri 08:00-12:00
+  2020-01-01 closed
+";
+		let dsl: Dsl<FixedOffset> = Dsl::parse(text, &zone()).unwrap();
+		assert_eq!(dsl.to_string(), written);
+		assert_eq!(Dsl::parse(written, &zone()).unwrap().to_string(), written);
+	}
+
+	#[test]
+	fn schedules() {
+		let schedule: Schedule<FixedOffset> =
+			Schedule::from_dsl("expires 2020-05-01T12:30\nSat-Sun 10:00-16:00\n", &zone()).unwrap();
+		assert_eq!(schedule.expires(), &Some(time("2020-05-01T12:30:00-05:00")));
+		assert_eq!(
+			schedule.to_dsl(),
+			"expires 2020-05-01T12:30\nSat-Sun 10:00-16:00\n"
+		);
+		assert_eq!(
+			Space::new("Pool").schedule(schedule).to_dsl(),
+			"space \"Pool\"\n  expires 2020-05-01T12:30\n  Sat-Sun 10:00-16:00\n"
+		);
+
+		assert_eq!(
+			Schedule::<FixedOffset>::from_dsl("Mon 07:00-08:00\nschedule\n", &zone())
+				.unwrap_err()
+				.line(),
+			2
+		);
+	}
+
+	#[test]
+	fn errors() {
+		for (text, line, message) in &[
+			(
+				"Mon 07:00-17:00",
+				1,
+				"expected `space \"Name\"` before this line",
+			),
+			("space Library", 1, "expected `space \"Name\"`"),
+			(
+				"space \"Library\"\nMon 07:00-17:00,",
+				2,
+				"expected a time range such as 07:00-17:00, not ``",
+			),
+			(
+				"space \"Library\"\n\nMonday 7-17",
+				3,
+				"expected a time such as 07:00, not `7`",
+			),
+			(
+				"space \"Library\"\nFunday 07:00-17:00",
+				2,
+				"expected weekdays, not `Funday`",
+			),
+			(
+				"space \"Library\"\nMon 07:00-17:00 \"Desk",
+				2,
+				"unterminated quotes",
+			),
+			(
+				"space \"Library\"\nMon 07:00-17:00 closed \"A\" \"B\"",
+				2,
+				"exceptions have at most one note",
+			),
+			(
+				"space \"Library\"\nMon 07:00-17:00 \"Desk\" open",
+				2,
+				"unexpected `open`",
+			),
+			(
+				"space \"Library\"\n2019-11-29..2019-11-28 closed",
+				2,
+				"dates must be in order",
+			),
+			(
+				"space \"Library\"\nMon-Fun 07:00-17:00",
+				2,
+				"expected weekdays, not `Mon-Fun`",
+			),
+			(
+				"space \"Library\"\nSat 10:00..Mun 02:00",
+				2,
+				"couldn't read `Sat 10:00..Mun 02:00`",
+			),
+			(
+				"space \"Library\"\n2019-11-29T09:00..2019-11-28T09:00",
+				2,
+				"spans must end after they start",
+			),
+			(
+				"space \"Library\"\n2019-11-29..2019-11-28 09:00-12:00",
+				2,
+				"dates must be in order",
+			),
+			(
+				"space \"Library\"\nMon 07:00",
+				2,
+				"expected a time range such as 07:00-17:00, not `07:00`",
+			),
+			(
+				"space \"Library\"\n2019-11-29 07:00-5pm",
+				2,
+				"expected a time such as 07:00, not `5pm`",
+			),
+			(
+				"space \"Library\"\n2019-02-30 closed",
+				2,
+				"couldn't read `2019-02-30` as a rule",
+			),
+			(
+				"space \"Library\"\nalways",
+				2,
+				"`always` needs `open` or `closed`",
+			),
+			(
+				"space \"Library\"\neffective tomorrow",
+				2,
+				"expected `effective` and a date or date and time",
+			),
+		] {
+			let error = Dsl::<FixedOffset>::parse(text, &zone()).unwrap_err();
+			assert_eq!((error.line(), error.message()), (*line, *message), "{text}");
+		}
+	}
+}
@@ -1,4 +1,4 @@
-use super::{Specifier, Status};
+use super::{timeline, Provenance, Specifier, Status};
 use chrono::{DateTime, TimeZone};
 
 #[allow(dead_code)]
@@ -7,14 +7,16 @@ pub struct Exception<'schedule, Tz: TimeZone> {
 	effect: Option<Status<'schedule, Tz>>,
 	effective: Option<Specifier<Tz>>,
 	expires: Option<Specifier<Tz>>,
+	provenance: Option<Provenance>,
 }
 
-impl<'schedule, Tz: TimeZone> Default for Exception<'schedule, Tz> {
+impl<Tz: TimeZone> Default for Exception<'_, Tz> {
 	fn default() -> Self {
 		Self {
 			effect: None,
 			effective: None,
 			expires: None,
+			provenance: None,
 		}
 	}
 }
@@ -33,6 +35,28 @@ impl<'schedule, Tz: TimeZone> Exception<'schedule, Tz> {
 		&self.effect
 	}
 
+	/// When the exception takes effect, if it says.
+	#[must_use]
+	pub fn effective_from(&self) -> Option<&Specifier<Tz>> {
+		self.effective.as_ref()
+	}
+
+	/// When the exception stops being in effect, if it says.
+	#[must_use]
+	pub fn expires_at(&self) -> Option<&Specifier<Tz>> {
+		self.expires.as_ref()
+	}
+
+	/// Where the exception was imported from, if it was.
+	#[must_use]
+	pub fn provenance(&self) -> Option<&Provenance> {
+		self.provenance.as_ref()
+	}
+
+	pub fn provenance_mut(&mut self) -> &mut Option<Provenance> {
+		&mut self.provenance
+	}
+
 	#[must_use]
 	pub fn effective(mut self, effective: Specifier<Tz>) -> Self {
 		self.effective = Some(effective);
@@ -45,15 +69,86 @@ impl<'schedule, Tz: TimeZone> Exception<'schedule, Tz> {
 		self
 	}
 
+	/// Whether the exception is in effect at the given time, as it would be
+	/// in `Space::periods`.
 	pub fn applies_at(&self, time: &DateTime<Tz>) -> bool {
-		match (self.effective.as_ref(), self.expires.as_ref()) {
-			(Some(open), Some(close)) => {
-				let open = open.instances(time).next().unwrap();
-				let close = close.instances(time).next().unwrap();
-
-				(open..close).contains(time)
-			}
-			(_, _) => true,
+		timeline::in_effect(self.effective.as_ref(), self.expires.as_ref(), time)
+	}
+}
+
+/// The editable form of an exception: `effect` is `"open"` or `"closed"` with
+/// an optional `note`, rather than the nested `Status` and `Reason`.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(bound(serialize = "", deserialize = "DateTime<Tz>: serde::Deserialize<'de>"))]
+struct Editable<Tz: TimeZone> {
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	effect: Option<Effect>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	note: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	effective: Option<Specifier<Tz>>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	expires: Option<Specifier<Tz>>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	provenance: Option<Provenance>,
+}
+
+#[cfg(feature = "serde")]
+#[derive(Clone, Copy, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Effect {
+	Open,
+	Closed,
+}
+
+#[cfg(feature = "serde")]
+impl<Tz: TimeZone> serde::Serialize for Exception<'_, Tz> {
+	fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		use super::Reason;
+
+		let (effect, note) = match &self.effect {
+			Some(Status::Open(reason)) => (Some(Effect::Open), reason),
+			Some(Status::Closed(reason)) => (Some(Effect::Closed), reason),
+			None => (None, &Reason::Exception(None)),
+		};
+		// An exception's own reason is its note; a part can't be written back.
+		let note = match note {
+			Reason::Exception(note) => note.clone(),
+			Reason::Part(_) => None,
+		};
+
+		Editable {
+			effect,
+			note,
+			effective: self.effective.clone(),
+			expires: self.expires.clone(),
+			provenance: self.provenance.clone(),
 		}
+		.serialize(serializer)
+	}
+}
+
+#[cfg(feature = "serde")]
+impl<'de, Tz: TimeZone> serde::Deserialize<'de> for Exception<'_, Tz>
+where
+	DateTime<Tz>: serde::Deserialize<'de>,
+{
+	fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		use super::Reason;
+
+		let editable = Editable::<Tz>::deserialize(deserializer)?;
+		let reason = Reason::Exception(editable.note);
+		let effect = editable.effect.map(|effect| match effect {
+			Effect::Open => Status::Open(reason),
+			Effect::Closed => Status::Closed(reason),
+		});
+
+		Ok(Self {
+			effect,
+			effective: editable.effective,
+			expires: editable.expires,
+			provenance: editable.provenance,
+		})
 	}
 }
@@ -0,0 +1,720 @@
+//! Exporting hours as an RFC 5545 iCalendar, so that they can be subscribed
+//! to.
+//!
+//! Each part becomes a recurring event.  Exceptions are resolved against the
+//! parts: an occurrence they close entirely is excluded with `EXDATE`, one
+//! whose hours they change is overridden by an event with a `RECURRENCE-ID`,
+//! and any time they open the space outside its parts gets an event of its
+//! own.
+
+mod import;
+
+pub use import::*;
+
+use core::fmt;
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeZone};
+
+use super::timeline::{self, Period};
+use super::{Part, Schedule, Space, Specifier};
+
+/// Recurring specifiers are expanded at most this many times.
+const MAX_INSTANCES: usize = 10_000;
+
+/// Without an exact end, exceptions are resolved for this long.
+const HORIZON_WEEKS: i64 = 52;
+
+/// How the calendar identifies itself, as `PRODID`.
+const PRODUCT: &str = "-//sked//sked//EN";
+
+/// A range of time, from the first until the second.
+type Span<Tz> = (DateTime<Tz>, DateTime<Tz>);
+
+/// Escape `text` for a property value.
+fn escape(text: &str) -> String {
+	text
+		.replace('\\', "\\\\")
+		.replace(';', "\\;")
+		.replace(',', "\\,")
+		.replace('\n', "\\n")
+}
+
+/// Push `line`, folded so that no line is longer than 75 octets.
+fn push(lines: &mut Vec<String>, line: &str) {
+	let mut rest: &str = line;
+	let mut limit: usize = 75;
+	while rest.len() > limit {
+		let mut split: usize = limit;
+		while !rest.is_char_boundary(split) {
+			split -= 1;
+		}
+		let (head, tail) = rest.split_at(split);
+		lines.push(if limit == 75 {
+			head.to_string()
+		} else {
+			format!(" {head}")
+		});
+		rest = tail;
+		// Continuation lines start with a space.
+		limit = 74;
+	}
+	lines.push(if limit == 75 {
+		rest.to_string()
+	} else {
+		format!(" {rest}")
+	});
+}
+
+/// Lowercase letters and digits, with anything else as a dash, for UIDs.
+fn slug(name: &str) -> String {
+	name
+		.chars()
+		.map(|c| {
+			if c.is_ascii_alphanumeric() {
+				c.to_ascii_lowercase()
+			} else {
+				'-'
+			}
+		})
+		.collect()
+}
+
+fn local<Tz: TimeZone>(time: &DateTime<Tz>) -> String {
+	time.naive_local().format("%Y%m%dT%H%M%S").to_string()
+}
+
+fn utc<Tz: TimeZone>(time: &DateTime<Tz>) -> String {
+	time.naive_utc().format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// An offset from UTC as `TZOFFSETFROM` and `TZOFFSETTO` write it.
+fn offset(seconds: i32) -> String {
+	let sign: char = if seconds < 0 { '-' } else { '+' };
+	let seconds: i32 = seconds.abs();
+	format!("{sign}{:02}{:02}", seconds / 3600, seconds / 60 % 60)
+}
+
+/// The `BYDAY` of the weekday of `date` within its month, e.g. `2SU`, or
+/// `-1SU` for the last one.
+fn nth_weekday(date: NaiveDate) -> String {
+	let weekday: String = date.weekday().to_string()[..2].to_uppercase();
+	let last: bool = (date + Duration::weeks(1)).month() != date.month();
+	if last {
+		format!("-1{weekday}")
+	} else {
+		format!("{}{weekday}", (date.day() - 1) / 7 + 1)
+	}
+}
+
+/// A `VTIMEZONE` describing `timezone` by the changes in its offset during
+/// `year`, which are assumed to recur on the same weekday every year.
+fn vtimezone<Tz: TimeZone>(lines: &mut Vec<String>, tzid: &str, timezone: &Tz, year: i32) {
+	let seconds = |time: &NaiveDateTime| -> i32 {
+		timezone
+			.offset_from_utc_datetime(time)
+			.fix()
+			.local_minus_utc()
+	};
+	let start: NaiveDateTime = NaiveDate::from_ymd_opt(year, 1, 1)
+		.unwrap_or_default()
+		.and_time(NaiveTime::MIN);
+
+	// Offsets change on the minute, so look hour by hour, then minute by
+	// minute within the hour.
+	let mut transitions: Vec<(NaiveDateTime, i32, i32)> = Vec::new();
+	let mut hour: NaiveDateTime = start;
+	while hour.year() == year {
+		let next: NaiveDateTime = hour + Duration::hours(1);
+		let (before, after): (i32, i32) = (seconds(&hour), seconds(&next));
+		if before != after {
+			let minute: NaiveDateTime = (1..=60)
+				.map(|minutes| hour + Duration::minutes(minutes))
+				.find(|minute| seconds(minute) != before)
+				.unwrap_or(next);
+			transitions.push((minute, before, after));
+		}
+		hour = next;
+	}
+
+	push(lines, "BEGIN:VTIMEZONE");
+	push(lines, &format!("TZID:{}", escape(tzid)));
+	if transitions.is_empty() {
+		let seconds: i32 = seconds(&start);
+		push(lines, "BEGIN:STANDARD");
+		push(lines, "DTSTART:19700101T000000");
+		push(lines, &format!("TZOFFSETFROM:{}", offset(seconds)));
+		push(lines, &format!("TZOFFSETTO:{}", offset(seconds)));
+		push(lines, "END:STANDARD");
+	}
+	for (instant, from, to) in transitions {
+		let kind: &str = if to < from { "STANDARD" } else { "DAYLIGHT" };
+		// Observances start at the wall-clock time before the change.
+		let wall: NaiveDateTime = instant + Duration::seconds(i64::from(from));
+		push(lines, &format!("BEGIN:{kind}"));
+		push(lines, &format!("DTSTART:{}", wall.format("%Y%m%dT%H%M%S")));
+		push(
+			lines,
+			&format!(
+				"RRULE:FREQ=YEARLY;BYMONTH={};BYDAY={}",
+				wall.month(),
+				nth_weekday(wall.date())
+			),
+		);
+		push(lines, &format!("TZOFFSETFROM:{}", offset(from)));
+		push(lines, &format!("TZOFFSETTO:{}", offset(to)));
+		push(lines, &format!("END:{kind}"));
+	}
+	push(lines, "END:VTIMEZONE");
+}
+
+/// The open parts of `periods`.
+fn open<Tz: TimeZone>(periods: Vec<Period<Tz>>) -> Vec<Period<Tz>> {
+	periods.into_iter().filter(Period::is_open).collect()
+}
+
+/// The parts of `span` within `periods`.
+fn within<Tz: TimeZone>(periods: &[Period<Tz>], span: &Span<Tz>) -> Vec<Span<Tz>> {
+	periods
+		.iter()
+		.filter(|period| period.start() < &span.1 && &span.0 < period.end())
+		.map(|period| {
+			(
+				period.start().clone().max(span.0.clone()),
+				period.end().clone().min(span.1.clone()),
+			)
+		})
+		.collect()
+}
+
+/// The parts of `periods` outside `others`.
+fn outside<Tz: TimeZone>(periods: &[Period<Tz>], others: &[Period<Tz>]) -> Vec<Span<Tz>> {
+	let mut spans: Vec<Span<Tz>> = Vec::new();
+	for period in periods {
+		let mut from: DateTime<Tz> = period.start().clone();
+		for other in others {
+			if other.end() <= &from || other.start() >= period.end() {
+				continue;
+			}
+			if other.start() > &from {
+				spans.push((from.clone(), other.start().clone()));
+			}
+			from = other.end().clone();
+		}
+		if &from < period.end() {
+			spans.push((from, period.end().clone()));
+		}
+	}
+	spans
+}
+
+/// Hours as an iCalendar, for one or more spaces or schedules.
+#[derive(Debug)]
+pub struct Calendar<'calendar, 'schedule, Tz: TimeZone> {
+	basis: DateTime<Tz>,
+	tzid: Option<String>,
+	name: Option<String>,
+	entries: Vec<(String, &'calendar [Schedule<'schedule, Tz>])>,
+}
+
+impl<'calendar, 'schedule, Tz: TimeZone> Calendar<'calendar, 'schedule, Tz> {
+	/// A calendar of hours from `basis` onwards, in the time zone of `basis`.
+	///
+	/// `basis` is also used as every event's `DTSTAMP`, so that exporting the
+	/// same hours again gives the same calendar.
+	#[must_use]
+	pub fn new(basis: &DateTime<Tz>) -> Self {
+		Self {
+			basis: basis.clone(),
+			tzid: None,
+			name: None,
+			entries: Vec::new(),
+		}
+	}
+
+	/// Name the time zone, e.g. `America/Chicago`, so that calendar apps can
+	/// recognise it.  Otherwise it's named after its offset at the basis.
+	#[must_use]
+	pub fn tzid(mut self, tzid: &str) -> Self {
+		self.tzid = Some(tzid.to_string());
+		self
+	}
+
+	/// The name calendar apps show for the calendar.
+	#[must_use]
+	pub fn name(mut self, name: &str) -> Self {
+		self.name = Some(name.to_string());
+		self
+	}
+
+	#[must_use]
+	pub fn space(mut self, space: &'calendar Space<'schedule, Tz>) -> Self {
+		self
+			.entries
+			.push((space.name().to_string(), space.schedules()));
+		self
+	}
+
+	/// Add `schedule` on its own, with its events named after `name`.
+	#[must_use]
+	pub fn schedule(mut self, name: &str, schedule: &'calendar Schedule<'schedule, Tz>) -> Self {
+		self
+			.entries
+			.push((name.to_string(), core::slice::from_ref(schedule)));
+		self
+	}
+
+	fn timezone_id(&self) -> String {
+		self.tzid.clone().unwrap_or_else(|| {
+			let seconds: i32 = self.basis.offset().fix().local_minus_utc();
+			format!("UTC{}", offset(seconds))
+		})
+	}
+
+	/// When exceptions stop being resolved: the end of the last exact one, or
+	/// a year after the basis if any of them recur.
+	fn horizon(&self, schedules: &[Schedule<'schedule, Tz>]) -> DateTime<Tz> {
+		let mut horizon: DateTime<Tz> = self.basis.clone();
+		for exception in schedules.iter().flat_map(Schedule::exceptions) {
+			for specifier in [exception.effective_from(), exception.expires_at()] {
+				let time: DateTime<Tz> = match specifier {
+					Some(Specifier::Exact(time)) => time.clone(),
+					_ => self.basis.clone() + Duration::weeks(HORIZON_WEEKS),
+				};
+				horizon = horizon.max(time);
+			}
+		}
+		horizon
+	}
+
+	/// Write the events for `schedules`, named after `name`.
+	fn events(&self, lines: &mut Vec<String>, name: &str, schedules: &[Schedule<'schedule, Tz>]) {
+		let horizon: Span<Tz> = (self.basis.clone(), self.horizon(schedules));
+		// Occurrences which start before the horizon are compared with the
+		// timeline whole, so it runs on for as long as one can last.
+		let end: DateTime<Tz> = horizon.1.clone() + Duration::weeks(1);
+		let events: Events<Tz> = Events {
+			tzid: escape(&self.timezone_id()),
+			stamp: utc(&self.basis),
+			summary: format!("SUMMARY:{} open", escape(name)),
+			slug: slug(name),
+			actual: open(timeline::periods(schedules, &horizon.0, &end, |_| true)),
+			horizon,
+		};
+
+		for (index, schedule) in schedules.iter().enumerate() {
+			let start: DateTime<Tz> = match schedule.effective() {
+				Some(effective) if effective > &self.basis => effective.clone(),
+				_ => self.basis.clone(),
+			};
+			let until: Option<&DateTime<Tz>> = schedule.expires().as_ref();
+			if until.is_some_and(|until| until <= &start) {
+				continue;
+			}
+			for (number, part) in schedule.parts().iter().enumerate() {
+				let uid: String = format!("{}-{index}-{number}@sked", events.slug);
+				events.part(lines, &uid, part, &start, until);
+			}
+		}
+
+		// Times exceptions open the space which none of its parts do.
+		let usual: Vec<Period<Tz>> = open(timeline::periods(
+			schedules,
+			&events.horizon.0,
+			&end,
+			|_| false,
+		));
+		for span in outside(&events.actual, &usual)
+			.into_iter()
+			.take_while(|span| span.0 < events.horizon.1)
+		{
+			let uid: String = format!("{}-{}@sked", events.slug, utc(&span.0));
+			let note: Option<String> = events
+				.actual
+				.iter()
+				.find(|period| period.start() <= &span.0 && &span.0 < period.end())
+				.and_then(Period::note)
+				.map(|note| format!("DESCRIPTION:{}", escape(note)));
+			events.event(lines, &uid, None, &span, note.as_slice());
+		}
+	}
+}
+
+/// What's needed to write the events of one space or schedule.
+struct Events<Tz: TimeZone> {
+	tzid: String,
+	stamp: String,
+	summary: String,
+	slug: String,
+	/// When exceptions are resolved.
+	horizon: Span<Tz>,
+	/// When the space is open until a week after the horizon, exceptions and
+	/// all.
+	actual: Vec<Period<Tz>>,
+}
+
+impl<Tz: TimeZone> Events<Tz> {
+	fn event(
+		&self,
+		lines: &mut Vec<String>,
+		uid: &str,
+		recurrence: Option<&DateTime<Tz>>,
+		span: &Span<Tz>,
+		extra: &[String],
+	) {
+		let tzid: &str = &self.tzid;
+		push(lines, "BEGIN:VEVENT");
+		push(lines, &format!("UID:{uid}"));
+		push(lines, &format!("DTSTAMP:{}", self.stamp));
+		if let Some(recurrence) = recurrence {
+			push(
+				lines,
+				&format!("RECURRENCE-ID;TZID={tzid}:{}", local(recurrence)),
+			);
+		}
+		push(lines, &format!("DTSTART;TZID={tzid}:{}", local(&span.0)));
+		push(lines, &format!("DTEND;TZID={tzid}:{}", local(&span.1)));
+		push(lines, &self.summary);
+		for line in extra {
+			push(lines, line);
+		}
+		push(lines, "END:VEVENT");
+	}
+
+	/// Write the event for `part` from `start` until `until`, with the
+	/// changes exceptions make to it.
+	fn part(
+		&self,
+		lines: &mut Vec<String>,
+		uid: &str,
+		part: &Part<Tz>,
+		start: &DateTime<Tz>,
+		until: Option<&DateTime<Tz>>,
+	) {
+		let (Some(opens), Some(closes)) = (part.opens(), part.closes()) else {
+			return;
+		};
+
+		// The first occurrence, and any more before the horizon.
+		let mut occurrences: Vec<Span<Tz>> = Vec::new();
+		for from in opens
+			.instances(start)
+			.take(MAX_INSTANCES)
+			.take_while(|from| until.is_none_or(|until| from < until))
+		{
+			if !occurrences.is_empty() && from >= self.horizon.1 {
+				break;
+			}
+			if let Some(to) = closes
+				.instances(&from)
+				.take(MAX_INSTANCES)
+				.find(|to| *to > from)
+			{
+				occurrences.push((from, to));
+			}
+		}
+		let Some(first) = occurrences.first() else {
+			return;
+		};
+		if first.1 <= *start {
+			return;
+		}
+
+		let mut extra: Vec<String> = Vec::new();
+		let rule: Option<String> = match opens {
+			Specifier::Weekly { .. } => Some(format!(
+				"FREQ=WEEKLY;BYDAY={}",
+				first.0.weekday().to_string()[..2].to_uppercase()
+			)),
+			Specifier::Daily { .. } => Some("FREQ=DAILY".to_string()),
+			Specifier::Exact(_) => None,
+		};
+		if let Some(rule) = &rule {
+			extra.push(match until {
+				Some(until) => format!("RRULE:{rule};UNTIL={}", utc(until)),
+				None => format!("RRULE:{rule}"),
+			});
+		}
+		if !part.notes().is_empty() {
+			extra.push(format!("DESCRIPTION:{}", escape(&part.notes().join("\n"))));
+		}
+
+		// How exceptions change the occurrences they overlap.
+		let mut overrides: Vec<(&DateTime<Tz>, Vec<Span<Tz>>)> = Vec::new();
+		for occurrence in occurrences
+			.iter()
+			.take_while(|(from, _)| from < &self.horizon.1)
+		{
+			let hours: Vec<Span<Tz>> = within(&self.actual, occurrence);
+			if hours.len() == 1 && hours[0] == *occurrence {
+				continue;
+			}
+			if hours.is_empty() {
+				extra.push(format!(
+					"EXDATE;TZID={}:{}",
+					self.tzid,
+					local(&occurrence.0)
+				));
+			} else {
+				overrides.push((&occurrence.0, hours));
+			}
+		}
+
+		self.event(lines, uid, None, first, &extra);
+		for (recurrence, hours) in overrides {
+			// A changed occurrence keeps the first of its new hours, and any more
+			// get events of their own.
+			if rule.is_some() {
+				self.event(lines, uid, Some(recurrence), &hours[0], &[]);
+			}
+			for span in hours.iter().skip(usize::from(rule.is_some())) {
+				let uid: String = format!("{}-{}@sked", self.slug, utc(&span.0));
+				self.event(lines, &uid, None, span, &[]);
+			}
+		}
+	}
+}
+
+/// The calendar as iCalendar text, with CRLF line endings.
+///
+/// # Panics
+///
+/// Panics if a weekly or daily specifier doesn't name a valid day or time.
+impl<Tz: TimeZone> fmt::Display for Calendar<'_, '_, Tz> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let mut lines: Vec<String> = Vec::new();
+		push(&mut lines, "BEGIN:VCALENDAR");
+		push(&mut lines, "VERSION:2.0");
+		push(&mut lines, &format!("PRODID:{PRODUCT}"));
+		push(&mut lines, "CALSCALE:GREGORIAN");
+		if let Some(name) = &self.name {
+			push(&mut lines, &format!("X-WR-CALNAME:{}", escape(name)));
+		}
+		push(
+			&mut lines,
+			&format!("X-WR-TIMEZONE:{}", escape(&self.timezone_id())),
+		);
+		vtimezone(
+			&mut lines,
+			&self.timezone_id(),
+			&self.basis.timezone(),
+			self.basis.naive_local().year(),
+		);
+		for (name, schedules) in &self.entries {
+			self.events(&mut lines, name, schedules);
+		}
+		push(&mut lines, "END:VCALENDAR");
+
+		for line in lines {
+			write!(f, "{line}\r\n")?;
+		}
+		Ok(())
+	}
+}
+
+impl<Tz: TimeZone> Space<'_, Tz> {
+	/// The space's hours from `basis` onwards as an iCalendar; see `Calendar`
+	/// for more control.
+	#[must_use]
+	pub fn to_ical(&self, basis: &DateTime<Tz>) -> String {
+		Calendar::new(basis)
+			.name(self.name())
+			.space(self)
+			.to_string()
+	}
+}
+
+impl<Tz: TimeZone> Schedule<'_, Tz> {
+	/// The schedule's hours from `basis` onwards as an iCalendar, with its
+	/// events named after `name`.
+	#[must_use]
+	pub fn to_ical(&self, name: &str, basis: &DateTime<Tz>) -> String {
+		Calendar::new(basis).schedule(name, self).to_string()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{push, Ics};
+	use crate::{Exception, Part, Reason, Schedule, Space, Specifier, Status};
+	use chrono::{DateTime, FixedOffset};
+
+	fn time(rfc3339: &str) -> DateTime<FixedOffset> {
+		DateTime::parse_from_rfc3339(rfc3339).unwrap()
+	}
+
+	#[test]
+	fn folding() {
+		let mut lines: Vec<String> = Vec::new();
+		push(&mut lines, &format!("DESCRIPTION:{}", "é".repeat(80)));
+		assert!(lines.len() > 1);
+		assert!(lines.iter().all(|line| line.len() <= 75));
+		assert!(lines[1..].iter().all(|line| line.starts_with(' ')));
+		assert_eq!(
+			lines
+				.iter()
+				.enumerate()
+				.map(|(index, line)| if index == 0 { &line[..] } else { &line[1..] })
+				.collect::<String>(),
+			format!("DESCRIPTION:{}", "é".repeat(80))
+		);
+	}
+
+	#[test]
+	fn parts_and_exceptions() {
+		let weekly = |day: &str, time: &str| Specifier::Weekly {
+			day: day.to_string(),
+			time: time.to_string(),
+		};
+		let exception = |from: &str, to: &str, status: Status<'static, FixedOffset>| {
+			let mut exception = Exception::new()
+				.effective(Specifier::Exact(time(from)))
+				.expires(Specifier::Exact(time(to)));
+			*exception.effect_mut() = Some(status);
+			exception
+		};
+		let closed = || Status::Closed(Reason::Exception(Some("LOCKED".to_string())));
+		let opened = || Status::Open(Reason::Exception(Some("Break hours".to_string())));
+
+		let space: Space<FixedOffset> = Space::new("Boe Chapel").schedule(
+			Schedule::new()
+				.part(
+					Part::new()
+						.open(weekly("Mon", "07:00"))
+						.close(weekly("Mon", "17:00"))
+						.note("Side doors only"),
+				)
+				// Shorter hours one Monday, closed the next, and open one Saturday.
+				.exception(exception(
+					"2020-01-20T10:00:00-06:00",
+					"2020-01-20T12:00:00-06:00",
+					opened(),
+				))
+				.exception(exception(
+					"2020-01-20T00:00:00-06:00",
+					"2020-01-21T00:00:00-06:00",
+					closed(),
+				))
+				.exception(exception(
+					"2020-01-27T00:00:00-06:00",
+					"2020-01-28T00:00:00-06:00",
+					closed(),
+				))
+				.exception(exception(
+					"2020-02-01T09:00:00-06:00",
+					"2020-02-01T13:00:00-06:00",
+					opened(),
+				)),
+		);
+
+		let ics = space.to_ical(&time("2020-01-13T00:00:00-06:00"));
+		let lines: Vec<&str> = ics.split("\r\n").collect();
+		assert_eq!(lines.first(), Some(&"BEGIN:VCALENDAR"));
+		assert_eq!(lines[lines.len() - 2], "END:VCALENDAR");
+
+		let expected = [
+			"X-WR-CALNAME:Boe Chapel",
+			"TZID:UTC-0600",
+			"TZOFFSETTO:-0600",
+			"UID:boe-chapel-0-0@sked",
+			"DTSTART;TZID=UTC-0600:20200113T070000",
+			"DTEND;TZID=UTC-0600:20200113T170000",
+			"RRULE:FREQ=WEEKLY;BYDAY=MO",
+			"DESCRIPTION:Side doors only",
+			"EXDATE;TZID=UTC-0600:20200127T070000",
+			"RECURRENCE-ID;TZID=UTC-0600:20200120T070000",
+			"DTSTART;TZID=UTC-0600:20200120T100000",
+			"DTEND;TZID=UTC-0600:20200120T120000",
+			"UID:boe-chapel-20200201T150000Z@sked",
+			"DTSTART;TZID=UTC-0600:20200201T090000",
+			"DESCRIPTION:Break hours",
+		];
+		for line in &expected {
+			assert!(lines.contains(line), "missing {} in\n{}", line, ics);
+		}
+		assert_eq!(
+			lines.iter().filter(|line| **line == "BEGIN:VEVENT").count(),
+			3
+		);
+		assert!(!ics.contains("EXDATE;TZID=UTC-0600:20200120"));
+	}
+
+	#[test]
+	fn partly_closed_at_the_horizon() {
+		let weekly = |day: &str, time: &str| Specifier::Weekly {
+			day: day.to_string(),
+			time: time.to_string(),
+		};
+		let mut closed = Exception::new()
+			.effective(Specifier::Exact(time("2019-12-24T17:00:00-06:00")))
+			.expires(Specifier::Exact(time("2019-12-26T09:00:00-06:00")));
+		*closed.effect_mut() = Some(Status::Closed(Reason::Exception(None)));
+		let schedule = ["Mon", "Tue", "Wed", "Thu", "Fri"].iter().fold(
+			Schedule::new().exception(closed),
+			|schedule, day| {
+				schedule.part(
+					Part::new()
+						.open(weekly(day, "07:00"))
+						.close(weekly(day, "23:00")),
+				)
+			},
+		);
+		let space: Space<FixedOffset> = Space::new("Library").schedule(schedule);
+
+		let ics = space.to_ical(&time("2019-12-23T00:00:00-06:00"));
+		let lines: Vec<&str> = ics.split("\r\n").collect();
+		for line in &[
+			"RECURRENCE-ID;TZID=UTC-0600:20191224T070000",
+			"DTEND;TZID=UTC-0600:20191224T170000",
+			"EXDATE;TZID=UTC-0600:20191225T070000",
+			// The last day the exception touches keeps the rest of its hours.
+			"RECURRENCE-ID;TZID=UTC-0600:20191226T070000",
+			"DTSTART;TZID=UTC-0600:20191226T090000",
+			"DTEND;TZID=UTC-0600:20191226T230000",
+		] {
+			assert!(lines.contains(line), "missing {} in\n{}", line, ics);
+		}
+		assert!(!ics.contains("EXDATE;TZID=UTC-0600:20191226"));
+	}
+
+	#[test]
+	fn round_trip() {
+		let weekly = |day: &str, time: &str| Specifier::Weekly {
+			day: day.to_string(),
+			time: time.to_string(),
+		};
+		let mut closed = Exception::new()
+			.effective(Specifier::Exact(time("2019-12-24T17:00:00-06:00")))
+			.expires(Specifier::Exact(time("2019-12-26T09:00:00-06:00")));
+		*closed.effect_mut() = Some(Status::Closed(Reason::Exception(None)));
+		let mut schedule = ["Mon", "Tue", "Wed", "Thu", "Fri"].iter().fold(
+			Schedule::new().exception(closed),
+			|schedule, day| {
+				schedule.part(
+					Part::new()
+						.open(weekly(day, "07:00"))
+						.close(weekly(day, "23:00")),
+				)
+			},
+		);
+		*schedule.expires_mut() = Some(time("2020-01-11T00:00:00-06:00"));
+		let space: Space<FixedOffset> = Space::new("Library").schedule(schedule);
+
+		let basis = time("2019-12-23T00:00:00-06:00");
+		let end = time("2020-01-20T00:00:00-06:00");
+		let schedules =
+			Ics::parse(&space.to_ical(&basis))
+				.unwrap()
+				.hours(Schedule::new(), &basis, &end);
+		// The weekdays come back as parts, without the summary as their note.
+		assert_eq!(schedules.len(), 2);
+		assert_eq!(schedules[1].parts().len(), 5);
+		assert!(schedules[1]
+			.parts()
+			.iter()
+			.all(|part| part.notes().is_empty()));
+		let imported = schedules
+			.into_iter()
+			.fold(Space::new("Library"), Space::schedule);
+		assert_eq!(space.periods(&basis, &end), imported.periods(&basis, &end));
+	}
+}
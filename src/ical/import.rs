@@ -0,0 +1,1632 @@
+//! Reading RFC 5545 iCalendar files into schedules.
+//!
+//! Events are read as either closures or opening hours.  Recurring events
+//! are expanded within a range, except that hours which simply repeat every
+//! day or week become `Part`s, in a schedule of their own if they begin later
+//! or stop; cancelled, excluded and moved occurrences of those become
+//! `Exception`s.
+
+use core::convert::TryFrom;
+use core::fmt;
+
+use chrono::{
+	DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Weekday,
+};
+
+use crate::{Exception, Part, Reason, Schedule, Specifier, Status};
+
+/// Rules are expanded over at most this many days, weeks, months or years.
+const MAX_PERIODS: i64 = 10_000;
+
+/// Something in an iCalendar file which couldn't be read.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IcsError {
+	line: usize,
+	message: String,
+}
+
+impl IcsError {
+	fn new(line: usize, message: &str) -> Self {
+		Self {
+			line,
+			message: message.to_string(),
+		}
+	}
+
+	/// The line the problem is on, counting from 1.
+	#[must_use]
+	pub fn line(&self) -> usize {
+		self.line
+	}
+
+	#[must_use]
+	pub fn message(&self) -> &str {
+		&self.message
+	}
+}
+
+impl fmt::Display for IcsError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "line {}: {}", self.line, self.message)
+	}
+}
+
+impl std::error::Error for IcsError {}
+
+type Result<T> = core::result::Result<T, IcsError>;
+
+/// A content line: `NAME;PARAM=VALUE:value`.
+#[derive(Debug)]
+struct Property {
+	name: String,
+	params: Vec<(String, String)>,
+	value: String,
+	line: usize,
+}
+
+impl Property {
+	fn param(&self, name: &str) -> Option<&str> {
+		self
+			.params
+			.iter()
+			.find(|(key, _)| key == name)
+			.map(|(_, value)| value.as_str())
+	}
+
+	/// Split a content line into its name, parameters and value.
+	fn parse(text: &str, line: usize) -> Result<Self> {
+		let mut quoted: bool = false;
+		let colon: usize = text
+			.char_indices()
+			.find(|(_, c)| {
+				if *c == '"' {
+					quoted = !quoted;
+				}
+				*c == ':' && !quoted
+			})
+			.map(|(index, _)| index)
+			.ok_or_else(|| IcsError::new(line, "expected `:` after the property name"))?;
+		let (head, value) = (&text[..colon], &text[colon + 1..]);
+
+		let mut parts = head.split(';');
+		let name: String = parts.next().unwrap_or_default().to_uppercase();
+		let params: Vec<(String, String)> = parts
+			.filter_map(|param| {
+				let (key, value) = param.split_once('=')?;
+				Some((key.to_uppercase(), value.trim_matches('"').to_string()))
+			})
+			.collect();
+
+		Ok(Self {
+			name,
+			params,
+			value: value.to_string(),
+			line,
+		})
+	}
+
+	/// The value as text, unescaped.
+	fn text(&self) -> String {
+		let mut text: String = String::with_capacity(self.value.len());
+		let mut chars = self.value.chars();
+		while let Some(c) = chars.next() {
+			if c == '\\' {
+				match chars.next() {
+					Some('n' | 'N') => text.push('\n'),
+					Some(c) => text.push(c),
+					None => {}
+				}
+			} else {
+				text.push(c);
+			}
+		}
+		text
+	}
+}
+
+/// `BEGIN:NAME` … `END:NAME`.
+#[derive(Debug, Default)]
+struct Component {
+	name: String,
+	properties: Vec<Property>,
+	components: Vec<Component>,
+	line: usize,
+}
+
+impl Component {
+	fn property(&self, name: &str) -> Option<&Property> {
+		self
+			.properties
+			.iter()
+			.find(|property| property.name == name)
+	}
+
+	fn all<'component>(
+		&'component self,
+		name: &'component str,
+	) -> impl Iterator<Item = &'component Property> {
+		self
+			.properties
+			.iter()
+			.filter(move |property| property.name == name)
+	}
+}
+
+/// Unfold `text` into content lines, each with the line it started on.
+fn unfold(text: &str) -> Vec<(usize, String)> {
+	let mut lines: Vec<(usize, String)> = Vec::new();
+	for (index, line) in text.lines().enumerate() {
+		let line: &str = line.strip_suffix('\r').unwrap_or(line);
+		match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+			(Some(rest), Some((_, last))) => last.push_str(rest),
+			_ if line.trim().is_empty() => {}
+			_ => lines.push((index + 1, line.to_string())),
+		}
+	}
+	lines
+}
+
+fn components(text: &str) -> Result<Vec<Component>> {
+	let mut stack: Vec<Component> = vec![Component::default()];
+	for (line, text) in unfold(text) {
+		let property: Property = Property::parse(&text, line)?;
+		match property.name.as_str() {
+			"BEGIN" => stack.push(Component {
+				name: property.value.to_uppercase(),
+				line,
+				..Component::default()
+			}),
+			"END" => {
+				let component: Component = stack
+					.pop()
+					.filter(|component| component.name == property.value.to_uppercase())
+					.ok_or_else(|| IcsError::new(line, &format!("unexpected END:{}", property.value)))?;
+				match stack.last_mut() {
+					Some(parent) => parent.components.push(component),
+					None => return Err(IcsError::new(line, "END without BEGIN")),
+				}
+			}
+			_ if stack.len() < 2 => return Err(IcsError::new(line, "property outside of any component")),
+			_ => {
+				if let Some(component) = stack.last_mut() {
+					component.properties.push(property);
+				}
+			}
+		}
+	}
+	match stack.pop() {
+		Some(root) if stack.is_empty() => Ok(root.components),
+		Some(unclosed) => Err(IcsError::new(
+			unclosed.line,
+			&format!("BEGIN:{} is never ended", unclosed.name),
+		)),
+		None => Ok(Vec::new()),
+	}
+}
+
+/// The time zone a date-time is written in.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Zone {
+	Utc,
+	/// In whatever time zone the schedule is in.
+	Floating,
+	Named(String),
+}
+
+/// A `DATE` or `DATE-TIME` value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Stamp {
+	time: NaiveDateTime,
+	zone: Zone,
+	date: bool,
+}
+
+impl Stamp {
+	/// Read the value of `property`, or one of its comma-separated values.
+	fn parse(value: &str, property: &Property) -> Result<Self> {
+		let error = || {
+			IcsError::new(
+				property.line,
+				&format!("`{value}` isn't a date or date-time"),
+			)
+		};
+		let value: &str = value.trim();
+		if value.len() == 8 || property.param("VALUE") == Some("DATE") {
+			let date: NaiveDate = NaiveDate::parse_from_str(value, "%Y%m%d").map_err(|_| error())?;
+			return Ok(Self {
+				time: date.and_time(NaiveTime::MIN),
+				zone: Zone::Floating,
+				date: true,
+			});
+		}
+		let (value, zone): (&str, Zone) = match value.strip_suffix(['Z', 'z']) {
+			Some(value) => (value, Zone::Utc),
+			None => (
+				value,
+				property
+					.param("TZID")
+					.map_or(Zone::Floating, |tzid| Zone::Named(tzid.to_string())),
+			),
+		};
+		let time: NaiveDateTime =
+			NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").map_err(|_| error())?;
+		Ok(Self {
+			time,
+			zone,
+			date: false,
+		})
+	}
+
+	fn list(property: &Property) -> Result<Vec<Self>> {
+		property
+			.value
+			.split(',')
+			.map(|value| Self::parse(value, property))
+			.collect()
+	}
+}
+
+/// A `DURATION` value, e.g. `PT1H30M` or `P1D`.
+fn duration(property: &Property) -> Result<Duration> {
+	let error = || {
+		IcsError::new(
+			property.line,
+			&format!("`{}` isn't a duration", property.value),
+		)
+	};
+	let value: &str = property.value.trim();
+	let (negative, value): (bool, &str) = match value.strip_prefix('-') {
+		Some(value) => (true, value),
+		None => (false, value.strip_prefix('+').unwrap_or(value)),
+	};
+	let value: &str = value.strip_prefix('P').ok_or_else(error)?;
+
+	let mut total: Duration = Duration::zero();
+	let mut number: String = String::new();
+	for c in value.chars() {
+		match c {
+			'0'..='9' => number.push(c),
+			'T' => {}
+			'W' | 'D' | 'H' | 'M' | 'S' => {
+				let amount: i64 = number.parse().map_err(|_| error())?;
+				number.clear();
+				total = total
+					+ match c {
+						'W' => Duration::weeks(amount),
+						'D' => Duration::days(amount),
+						'H' => Duration::hours(amount),
+						'M' => Duration::minutes(amount),
+						_ => Duration::seconds(amount),
+					};
+			}
+			_ => return Err(error()),
+		}
+	}
+	if !number.is_empty() {
+		return Err(error());
+	}
+	Ok(if negative { -total } else { total })
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Frequency {
+	Daily,
+	Weekly,
+	Monthly,
+	Yearly,
+}
+
+/// An `RRULE`, as far as it's supported.
+#[derive(Clone, Debug)]
+struct Rule {
+	frequency: Frequency,
+	interval: u32,
+	count: Option<usize>,
+	until: Option<Stamp>,
+	/// Weekdays, each with the occurrence within the month or year it must
+	/// be, if any, e.g. `-1` for the last.
+	by_day: Vec<(Option<i32>, Weekday)>,
+	by_month_day: Vec<i32>,
+	by_month: Vec<u32>,
+}
+
+fn weekday(text: &str) -> Option<Weekday> {
+	Some(match text {
+		"MO" => Weekday::Mon,
+		"TU" => Weekday::Tue,
+		"WE" => Weekday::Wed,
+		"TH" => Weekday::Thu,
+		"FR" => Weekday::Fri,
+		"SA" => Weekday::Sat,
+		"SU" => Weekday::Sun,
+		_ => return None,
+	})
+}
+
+impl Rule {
+	fn parse(property: &Property) -> Result<Self> {
+		let error = |message: &str| IcsError::new(property.line, message);
+		let mut rule: Self = Self {
+			frequency: Frequency::Daily,
+			interval: 1,
+			count: None,
+			until: None,
+			by_day: Vec::new(),
+			by_month_day: Vec::new(),
+			by_month: Vec::new(),
+		};
+		let mut frequency: Option<Frequency> = None;
+
+		for part in property.value.split(';').filter(|part| !part.is_empty()) {
+			let (key, value) = part
+				.split_once('=')
+				.ok_or_else(|| error(&format!("`{part}` isn't a rule part")))?;
+			let number = |value: &str| -> Result<i32> {
+				value
+					.parse()
+					.map_err(|_| error(&format!("`{value}` isn't a number in {key}")))
+			};
+			match key.to_uppercase().as_str() {
+				"FREQ" => {
+					frequency = Some(match value.to_uppercase().as_str() {
+						"DAILY" => Frequency::Daily,
+						"WEEKLY" => Frequency::Weekly,
+						"MONTHLY" => Frequency::Monthly,
+						"YEARLY" => Frequency::Yearly,
+						other => return Err(error(&format!("FREQ={other} isn't supported"))),
+					});
+				}
+				"INTERVAL" => {
+					rule.interval = u32::try_from(number(value)?)
+						.ok()
+						.filter(|interval| *interval > 0)
+						.ok_or_else(|| error("INTERVAL must be positive"))?;
+				}
+				"COUNT" => {
+					rule.count =
+						Some(usize::try_from(number(value)?).map_err(|_| error("COUNT can't be negative"))?);
+				}
+				"UNTIL" => rule.until = Some(Stamp::parse(value, property)?),
+				"BYDAY" => {
+					for day in value.split(',') {
+						let day: String = day.trim().to_uppercase();
+						let (ordinal, name) = day.split_at(day.len().saturating_sub(2));
+						let weekday: Weekday =
+							weekday(name).ok_or_else(|| error(&format!("`{day}` isn't a weekday")))?;
+						let ordinal: Option<i32> = if ordinal.is_empty() {
+							None
+						} else {
+							Some(number(ordinal)?)
+						};
+						rule.by_day.push((ordinal, weekday));
+					}
+				}
+				"BYMONTHDAY" => {
+					for day in value.split(',') {
+						rule.by_month_day.push(number(day)?);
+					}
+				}
+				"BYMONTH" => {
+					for month in value.split(',') {
+						rule.by_month.push(
+							u32::try_from(number(month)?)
+								.ok()
+								.filter(|month| (1..=12).contains(month))
+								.ok_or_else(|| error(&format!("`{month}` isn't a month")))?,
+						);
+					}
+				}
+				// Weeks are taken to start on Monday, which is the default.
+				"WKST" => {}
+				other => return Err(error(&format!("{other} isn't supported"))),
+			}
+		}
+
+		rule.frequency = frequency.ok_or_else(|| error("RRULE needs a FREQ"))?;
+		Ok(rule)
+	}
+
+	/// The days of `dates` matching `by_day`, in order.
+	fn by_day(&self, dates: &[NaiveDate]) -> Vec<NaiveDate> {
+		let mut chosen: Vec<NaiveDate> = Vec::new();
+		for (ordinal, weekday) in &self.by_day {
+			let matching: Vec<NaiveDate> = dates
+				.iter()
+				.copied()
+				.filter(|date| date.weekday() == *weekday)
+				.collect();
+			match ordinal {
+				None => chosen.extend(matching),
+				Some(ordinal) => {
+					let index: Option<usize> = if *ordinal > 0 {
+						usize::try_from(ordinal - 1).ok()
+					} else {
+						usize::try_from(-ordinal)
+							.ok()
+							.and_then(|back| matching.len().checked_sub(back))
+					};
+					chosen.extend(index.and_then(|index| matching.get(index)));
+				}
+			}
+		}
+		chosen.sort_unstable();
+		chosen.dedup();
+		chosen
+	}
+
+	/// The days of `month` in `year` which match the rule, given it started
+	/// on `start`.
+	fn month(&self, year: i32, month: u32, start: NaiveDate) -> Vec<NaiveDate> {
+		let Some(first) = NaiveDate::from_ymd_opt(year, month, 1) else {
+			return Vec::new();
+		};
+		let days: Vec<NaiveDate> = first
+			.iter_days()
+			.take_while(|date| date.month() == month)
+			.collect();
+
+		if !self.by_month_day.is_empty() {
+			let mut chosen: Vec<NaiveDate> = self
+				.by_month_day
+				.iter()
+				.filter_map(|day| {
+					let index: usize = if *day > 0 {
+						usize::try_from(day - 1).ok()?
+					} else {
+						days.len().checked_sub(usize::try_from(-day).ok()?)?
+					};
+					days.get(index).copied()
+				})
+				.filter(|date| {
+					self.by_day.is_empty() || self.by_day.iter().any(|(_, day)| *day == date.weekday())
+				})
+				.collect();
+			chosen.sort_unstable();
+			chosen.dedup();
+			chosen
+		} else if !self.by_day.is_empty() {
+			self.by_day(&days)
+		} else {
+			days
+				.get(usize::try_from(start.day0()).unwrap_or_default())
+				.copied()
+				.into_iter()
+				.collect()
+		}
+	}
+
+	/// The days in the `period`th day, week, month or year from `start`.
+	fn period(&self, start: NaiveDate, period: i64) -> Vec<NaiveDate> {
+		let step: i64 = period * i64::from(self.interval);
+		let in_months =
+			|date: &NaiveDate| self.by_month.is_empty() || self.by_month.contains(&date.month());
+
+		let mut dates: Vec<NaiveDate> = match self.frequency {
+			Frequency::Daily => vec![start + Duration::days(step)]
+				.into_iter()
+				.filter(|date| {
+					(self.by_day.is_empty() || self.by_day.iter().any(|(_, day)| *day == date.weekday()))
+						&& (self.by_month_day.is_empty()
+							|| self
+								.by_month_day
+								.contains(&i32::try_from(date.day()).unwrap_or_default()))
+				})
+				.collect(),
+			Frequency::Weekly => {
+				let monday: NaiveDate = start
+					- Duration::days(i64::from(start.weekday().num_days_from_monday()))
+					+ Duration::weeks(step);
+				let week: Vec<NaiveDate> = monday.iter_days().take(7).collect();
+				if self.by_day.is_empty() {
+					week
+						.into_iter()
+						.filter(|date| date.weekday() == start.weekday())
+						.collect()
+				} else {
+					self.by_day(&week)
+				}
+			}
+			Frequency::Monthly => {
+				let months: i64 = i64::from(start.year()) * 12 + i64::from(start.month0()) + step;
+				let (Ok(year), Ok(month)) = (i32::try_from(months / 12), u32::try_from(months % 12 + 1))
+				else {
+					return Vec::new();
+				};
+				self.month(year, month, start)
+			}
+			Frequency::Yearly => {
+				let Ok(year) = i32::try_from(i64::from(start.year()) + step) else {
+					return Vec::new();
+				};
+				if self.by_month.is_empty() && !self.by_day.is_empty() && self.by_month_day.is_empty() {
+					// Weekdays within the whole year.
+					let days: Vec<NaiveDate> = NaiveDate::from_ymd_opt(year, 1, 1)
+						.into_iter()
+						.flat_map(|date| date.iter_days())
+						.take_while(|date| date.year() == year)
+						.collect();
+					self.by_day(&days)
+				} else if self.by_month.is_empty() {
+					self.month(year, start.month(), start)
+				} else {
+					self
+						.by_month
+						.iter()
+						.flat_map(|month| self.month(year, *month, start))
+						.collect()
+				}
+			}
+		};
+		dates.retain(in_months);
+		dates.sort_unstable();
+		dates
+	}
+
+	/// The starts of the occurrences from `start` on, in order, up to `COUNT`
+	/// if there is one, but without regard to `UNTIL`.
+	fn expand(&self, start: NaiveDateTime) -> impl Iterator<Item = NaiveDateTime> + '_ {
+		(0..MAX_PERIODS)
+			.flat_map(move |period| self.period(start.date(), period))
+			.map(move |date| date.and_time(start.time()))
+			.filter(move |time| *time >= start)
+			.take(self.count.unwrap_or(usize::MAX))
+	}
+}
+
+/// A `STANDARD` or `DAYLIGHT` observance of a `VTIMEZONE`.
+#[derive(Debug)]
+struct Observance {
+	start: NaiveDateTime,
+	from: i32,
+	to: i32,
+	rule: Option<Rule>,
+}
+
+impl Observance {
+	fn parse(component: &Component) -> Result<Self> {
+		let offset = |name: &str| -> Result<i32> {
+			let property: &Property = component.property(name).ok_or_else(|| {
+				IcsError::new(component.line, &format!("{} needs {name}", component.name))
+			})?;
+			let value: &str = property.value.trim();
+			let error = || IcsError::new(property.line, &format!("`{value}` isn't a UTC offset"));
+			let sign: i32 = match value.get(..1) {
+				Some("+") => 1,
+				Some("-") => -1,
+				_ => return Err(error()),
+			};
+			let digit = |range: core::ops::Range<usize>| -> Result<i32> {
+				value
+					.get(range)
+					.map_or(Ok(0), |digits| digits.parse().map_err(|_| error()))
+			};
+			if !matches!(value.len(), 5 | 7) {
+				return Err(error());
+			}
+			Ok(sign * (digit(1..3)? * 3600 + digit(3..5)? * 60 + digit(5..7)?))
+		};
+		let start: &Property = component
+			.property("DTSTART")
+			.ok_or_else(|| IcsError::new(component.line, &format!("{} needs DTSTART", component.name)))?;
+
+		Ok(Self {
+			start: Stamp::parse(&start.value, start)?.time,
+			from: offset("TZOFFSETFROM")?,
+			to: offset("TZOFFSETTO")?,
+			rule: component.property("RRULE").map(Rule::parse).transpose()?,
+		})
+	}
+
+	/// When the observance last began at or before `time`, if it has.
+	fn onset(&self, time: NaiveDateTime) -> Option<NaiveDateTime> {
+		match &self.rule {
+			Some(rule) => rule
+				.expand(self.start)
+				.take_while(|onset| *onset <= time)
+				.last(),
+			None => Some(self.start).filter(|start| *start <= time),
+		}
+	}
+}
+
+/// A `VEVENT`, as far as it matters for hours.
+#[derive(Debug)]
+struct Event {
+	uid: Option<String>,
+	summary: Option<String>,
+	description: Option<String>,
+	start: Stamp,
+	end: Option<Stamp>,
+	duration: Option<Duration>,
+	rule: Option<Rule>,
+	dates: Vec<Stamp>,
+	excluded: Vec<Stamp>,
+	recurrence: Option<Stamp>,
+	cancelled: bool,
+}
+
+impl Event {
+	fn parse(component: &Component) -> Result<Self> {
+		let start: &Property = component
+			.property("DTSTART")
+			.ok_or_else(|| IcsError::new(component.line, "VEVENT needs DTSTART"))?;
+		let stamps = |name: &str| -> Result<Vec<Stamp>> {
+			let mut stamps: Vec<Stamp> = Vec::new();
+			for property in component.all(name) {
+				stamps.extend(Stamp::list(property)?);
+			}
+			Ok(stamps)
+		};
+
+		Ok(Self {
+			uid: component.property("UID").map(Property::text),
+			summary: component.property("SUMMARY").map(Property::text),
+			description: component.property("DESCRIPTION").map(Property::text),
+			start: Stamp::parse(&start.value, start)?,
+			end: component
+				.property("DTEND")
+				.map(|end| Stamp::parse(&end.value, end))
+				.transpose()?,
+			duration: component.property("DURATION").map(duration).transpose()?,
+			rule: component.property("RRULE").map(Rule::parse).transpose()?,
+			dates: stamps("RDATE")?,
+			excluded: stamps("EXDATE")?,
+			recurrence: component
+				.property("RECURRENCE-ID")
+				.map(|recurrence| Stamp::parse(&recurrence.value, recurrence))
+				.transpose()?,
+			cancelled: component
+				.property("STATUS")
+				.is_some_and(|status| status.value.eq_ignore_ascii_case("CANCELLED")),
+		})
+	}
+}
+
+/// A range of time, from the first until the second.
+type Span<Tz> = (DateTime<Tz>, DateTime<Tz>);
+
+/// When the parts of an event can come into effect and expire without
+/// changing its hours: from the end of the occurrence the event would have
+/// had before its first until the start of its first, and from the end of
+/// its last until the start of the one after; `None` for always.
+struct Bounds<Tz: TimeZone> {
+	from: Option<Span<Tz>>,
+	until: Option<Span<Tz>>,
+}
+
+impl<Tz: TimeZone> Bounds<Tz> {
+	/// The bounds which suit both these and `other`, if any do.
+	fn join(&self, other: &Self) -> Option<Self> {
+		let join = |a: &Option<Span<Tz>>, b: &Option<Span<Tz>>| match (a, b) {
+			(None, None) => Some(None),
+			(Some(a), Some(b)) => {
+				let span: Span<Tz> = (a.0.clone().max(b.0.clone()), a.1.clone().min(b.1.clone()));
+				(span.0 <= span.1).then_some(Some(span))
+			}
+			_ => None,
+		};
+		Some(Self {
+			from: join(&self.from, &other.from)?,
+			until: join(&self.until, &other.until)?,
+		})
+	}
+
+	/// A schedule of `parts`, in effect for as long as both these bounds and
+	/// `within` allow.
+	fn schedule<'schedule>(
+		self,
+		within: &Schedule<'schedule, Tz>,
+		parts: Vec<Part<Tz>>,
+	) -> Schedule<'schedule, Tz> {
+		let mut schedule: Schedule<'schedule, Tz> = Schedule::new();
+		*schedule.effective_mut() = within
+			.effective()
+			.clone()
+			.into_iter()
+			.chain(self.from.map(|(_, first)| first))
+			.max();
+		*schedule.expires_mut() = within
+			.expires()
+			.clone()
+			.into_iter()
+			.chain(self.until.map(|(last, _)| last))
+			.min();
+		parts.into_iter().fold(schedule, Schedule::part)
+	}
+}
+
+/// An occurrence of an event, in the schedule's time zone.
+struct Occurrence<Tz: TimeZone> {
+	start: DateTime<Tz>,
+	end: DateTime<Tz>,
+	summary: Option<String>,
+	description: Option<String>,
+}
+
+/// The events and time zones of an iCalendar file.
+#[derive(Debug)]
+pub struct Ics {
+	timezones: Vec<(String, Vec<Observance>)>,
+	events: Vec<Event>,
+}
+
+impl Ics {
+	/// Read the `VEVENT`s and `VTIMEZONE`s of every `VCALENDAR` in `text`.
+	///
+	/// # Errors
+	///
+	/// Returns an error, with its line, if `text` isn't well-formed, or if an
+	/// event or time zone uses something that isn't supported, such as an
+	/// hourly `RRULE`.
+	pub fn parse(text: &str) -> Result<Self> {
+		let mut ics: Self = Self {
+			timezones: Vec::new(),
+			events: Vec::new(),
+		};
+		let calendars: Vec<Component> = components(text)?;
+		if calendars.is_empty() {
+			return Err(IcsError::new(1, "no VCALENDAR"));
+		}
+		for calendar in &calendars {
+			if calendar.name != "VCALENDAR" {
+				return Err(IcsError::new(
+					calendar.line,
+					&format!("expected VCALENDAR, found {}", calendar.name),
+				));
+			}
+			for component in &calendar.components {
+				match component.name.as_str() {
+					"VEVENT" => ics.events.push(Event::parse(component)?),
+					"VTIMEZONE" => {
+						let tzid: String = component
+							.property("TZID")
+							.map(Property::text)
+							.ok_or_else(|| IcsError::new(component.line, "VTIMEZONE needs TZID"))?;
+						let observances: Vec<Observance> = component
+							.components
+							.iter()
+							.filter(|observance| matches!(observance.name.as_str(), "STANDARD" | "DAYLIGHT"))
+							.map(Observance::parse)
+							.collect::<Result<_>>()?;
+						ics.timezones.push((tzid, observances));
+					}
+					_ => {}
+				}
+			}
+		}
+		Ok(ics)
+	}
+
+	/// How many events there are, counting each moved occurrence as one.
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.events.len()
+	}
+
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.events.is_empty()
+	}
+
+	/// `stamp` in the time zone `timezone`.
+	///
+	/// Times in a zone the file doesn't describe are taken to be in
+	/// `timezone`, like floating times.
+	fn resolve<Tz: TimeZone>(&self, stamp: &Stamp, timezone: &Tz) -> Option<DateTime<Tz>> {
+		let observances: Option<&Vec<Observance>> = match &stamp.zone {
+			Zone::Utc => return Some(timezone.from_utc_datetime(&stamp.time)),
+			Zone::Floating => None,
+			Zone::Named(tzid) => self
+				.timezones
+				.iter()
+				.find(|(name, _)| name == tzid)
+				.map(|(_, observances)| observances),
+		};
+		let Some(observances) = observances else {
+			return timezone.from_local_datetime(&stamp.time).earliest();
+		};
+
+		let offset: i32 = observances
+			.iter()
+			.filter_map(|observance| {
+				observance
+					.onset(stamp.time)
+					.map(|onset| (onset, observance.to))
+			})
+			.max_by_key(|(onset, _)| *onset)
+			.map(|(_, offset)| offset)
+			.or_else(|| {
+				observances
+					.iter()
+					.min_by_key(|observance| observance.start)
+					.map(|observance| observance.from)
+			})?;
+		Some(timezone.from_utc_datetime(&(stamp.time - Duration::seconds(i64::from(offset)))))
+	}
+
+	/// How long each occurrence of `event` lasts.
+	fn length(event: &Event) -> Duration {
+		match (&event.end, event.duration) {
+			(Some(end), _) => end.time - event.start.time,
+			(None, Some(duration)) => duration,
+			(None, None) if event.start.date => Duration::days(1),
+			(None, None) => Duration::zero(),
+		}
+	}
+
+	/// The last time `rule` can start an occurrence, if it stops.
+	fn until<Tz: TimeZone>(&self, rule: &Rule, timezone: &Tz) -> Option<DateTime<Tz>> {
+		rule.until.as_ref().and_then(|until| {
+			let until: Stamp = if until.date {
+				// A date includes the whole of that day.
+				Stamp {
+					time: until.time + Duration::days(1) - Duration::seconds(1),
+					..until.clone()
+				}
+			} else {
+				until.clone()
+			};
+			self.resolve(&until, timezone)
+		})
+	}
+
+	/// The starts of the occurrences of `event` before `end`, without its
+	/// exclusions.
+	fn starts<Tz: TimeZone>(
+		&self,
+		event: &Event,
+		timezone: &Tz,
+		end: &DateTime<Tz>,
+	) -> Vec<DateTime<Tz>> {
+		let mut starts: Vec<DateTime<Tz>> = match &event.rule {
+			None => self.resolve(&event.start, timezone).into_iter().collect(),
+			Some(rule) => {
+				let until: Option<DateTime<Tz>> = self.until(rule, timezone);
+				rule
+					.expand(event.start.time)
+					.map(|time| {
+						self.resolve(
+							&Stamp {
+								time,
+								..event.start.clone()
+							},
+							timezone,
+						)
+					})
+					.take_while(|time| {
+						time
+							.as_ref()
+							.is_some_and(|time| time < end && until.as_ref().is_none_or(|until| time <= until))
+					})
+					.flatten()
+					.collect()
+			}
+		};
+		starts.extend(
+			event
+				.dates
+				.iter()
+				.filter_map(|date| self.resolve(date, timezone))
+				.filter(|date| date < end),
+		);
+		starts.sort();
+		starts.dedup();
+		starts
+	}
+
+	/// The moved or cancelled occurrences of `event`, by where they were.
+	fn overrides<'ics>(&'ics self, event: &Event) -> impl Iterator<Item = &'ics Event> + 'ics {
+		let uid: Option<String> = event.uid.clone();
+		self
+			.events
+			.iter()
+			.filter(move |other| other.recurrence.is_some() && uid.is_some() && other.uid == uid)
+	}
+
+	/// Every occurrence of every event overlapping `start` to `end`, after
+	/// exclusions, cancellations and moves, in order.
+	fn occurrences<Tz: TimeZone>(
+		&self,
+		start: &DateTime<Tz>,
+		end: &DateTime<Tz>,
+	) -> Vec<Occurrence<Tz>> {
+		let timezone: Tz = start.timezone();
+		let mut occurrences: Vec<Occurrence<Tz>> = Vec::new();
+		for event in self
+			.events
+			.iter()
+			.filter(|event| event.recurrence.is_none())
+		{
+			let excluded: Vec<DateTime<Tz>> = event
+				.excluded
+				.iter()
+				.chain(
+					self
+						.overrides(event)
+						.filter_map(|other| other.recurrence.as_ref()),
+				)
+				.filter_map(|stamp| self.resolve(stamp, &timezone))
+				.collect();
+			if !event.cancelled {
+				occurrences.extend(
+					self
+						.starts(event, &timezone, end)
+						.into_iter()
+						.filter(|from| !excluded.contains(from))
+						.map(|from| Occurrence {
+							end: from.clone() + Self::length(event),
+							start: from,
+							summary: event.summary.clone(),
+							description: event.description.clone(),
+						}),
+				);
+			}
+			for other in self.overrides(event).filter(|other| !other.cancelled) {
+				if let Some(from) = self.resolve(&other.start, &timezone) {
+					occurrences.push(Occurrence {
+						end: from.clone() + Self::length(other),
+						start: from,
+						summary: other.summary.clone().or_else(|| event.summary.clone()),
+						description: other
+							.description
+							.clone()
+							.or_else(|| event.description.clone()),
+					});
+				}
+			}
+		}
+		occurrences.retain(|occurrence| {
+			&occurrence.start < end && &occurrence.end > start && occurrence.start < occurrence.end
+		});
+		occurrences.sort_by(|a, b| a.start.cmp(&b.start));
+		occurrences
+	}
+
+	/// Add the events to `schedule` as closures from `start` until `end`,
+	/// each an `Exception` named after its summary.
+	#[must_use]
+	pub fn closures<'schedule, Tz: TimeZone>(
+		&self,
+		schedule: Schedule<'schedule, Tz>,
+		start: &DateTime<Tz>,
+		end: &DateTime<Tz>,
+	) -> Schedule<'schedule, Tz> {
+		self
+			.occurrences(start, end)
+			.into_iter()
+			.fold(schedule, |schedule, occurrence| {
+				schedule.exception(exception(
+					&occurrence,
+					Status::Closed(Reason::Exception(occurrence.summary.clone())),
+				))
+			})
+	}
+
+	/// Add the events to `schedule` as opening hours from `start` until
+	/// `end`.
+	///
+	/// Events which repeat every day or every week become `Part`s, with their
+	/// excluded and moved occurrences as `Exception`s; any others become an
+	/// `Exception` for each occurrence.  The parts of events which begin after
+	/// `start`, or stop, are in effect from their first occurrence until the
+	/// end of their last, so they go in schedules of their own, after
+	/// `schedule`.  An event's description, not its summary, is the note of
+	/// its parts and the reason for its exceptions.
+	#[must_use]
+	pub fn hours<'schedule, Tz: TimeZone>(
+		&self,
+		schedule: Schedule<'schedule, Tz>,
+		start: &DateTime<Tz>,
+		end: &DateTime<Tz>,
+	) -> Vec<Schedule<'schedule, Tz>> {
+		let timezone: Tz = start.timezone();
+		let mut schedule: Schedule<'schedule, Tz> = schedule;
+		// The parts of events which aren't always in effect, with when.
+		let mut bounded: Vec<(Bounds<Tz>, Vec<Part<Tz>>)> = Vec::new();
+		let mut exceptions: Vec<Exception<'schedule, Tz>> = Vec::new();
+		// Occurrences of events which became parts, which are covered by them.
+		let mut covered: Vec<DateTime<Tz>> = Vec::new();
+		let open = |description: &Option<String>| Status::Open(Reason::Exception(description.clone()));
+		let closed = || Status::Closed(Reason::Exception(None));
+
+		for event in self
+			.events
+			.iter()
+			.filter(|event| event.recurrence.is_none())
+		{
+			let Some((parts, bounds)) = self.parts(event, &timezone, start) else {
+				continue;
+			};
+			if bounds.from.is_none() && bounds.until.is_none() {
+				schedule.parts_mut().extend(parts);
+			} else if let Some((other, joined, others)) = bounded
+				.iter_mut()
+				.find_map(|(other, others)| other.join(&bounds).map(|joined| (other, joined, others)))
+			{
+				*other = joined;
+				others.extend(parts);
+			} else {
+				bounded.push((bounds, parts));
+			}
+
+			// Occurrences which were excluded, moved or cancelled.
+			let length: Duration = Self::length(event);
+			let span = |from: DateTime<Tz>| Occurrence {
+				end: from.clone() + length,
+				start: from,
+				summary: None,
+				description: None,
+			};
+			let starts: Vec<DateTime<Tz>> = self.starts(event, &timezone, end);
+			covered.extend(starts.iter().cloned());
+			covered.extend(
+				self
+					.overrides(event)
+					.filter_map(|other| self.resolve(&other.start, &timezone)),
+			);
+			let within = |from: &DateTime<Tz>| from < end && &(from.clone() + length) > start;
+			for excluded in &event.excluded {
+				if let Some(from) = self.resolve(excluded, &timezone).filter(within) {
+					exceptions.push(exception(&span(from), closed()));
+				}
+			}
+			for other in self.overrides(event) {
+				if !other.cancelled {
+					if let Some(from) = self.resolve(&other.start, &timezone) {
+						exceptions.push(exception(
+							&Occurrence {
+								end: from.clone() + Self::length(other),
+								start: from,
+								summary: None,
+								description: None,
+							},
+							open(
+								&other
+									.description
+									.clone()
+									.or_else(|| event.description.clone()),
+							),
+						));
+					}
+				}
+				if let Some(from) = other
+					.recurrence
+					.as_ref()
+					.and_then(|recurrence| self.resolve(recurrence, &timezone))
+					.filter(|from| starts.contains(from) || within(from))
+				{
+					exceptions.push(exception(&span(from), closed()));
+				}
+			}
+		}
+
+		for occurrence in self
+			.occurrences(start, end)
+			.into_iter()
+			.filter(|occurrence| !covered.contains(&occurrence.start))
+		{
+			exceptions.push(exception(&occurrence, open(&occurrence.description)));
+		}
+
+		let bounded: Vec<Schedule<'schedule, Tz>> = bounded
+			.into_iter()
+			.map(|(bounds, parts)| bounds.schedule(&schedule, parts))
+			.collect();
+		let schedule: Schedule<'schedule, Tz> =
+			exceptions.into_iter().fold(schedule, Schedule::exception);
+		core::iter::once(schedule).chain(bounded).collect()
+	}
+
+	/// `event` as parts, if it repeats every day or week, with when they can
+	/// be in effect.
+	fn parts<Tz: TimeZone>(
+		&self,
+		event: &Event,
+		timezone: &Tz,
+		start: &DateTime<Tz>,
+	) -> Option<(Vec<Part<Tz>>, Bounds<Tz>)> {
+		let rule: &Rule = event.rule.as_ref()?;
+		let length: Duration = Self::length(event);
+		let first: DateTime<Tz> = self.resolve(&event.start, timezone)?;
+		let simple: bool = rule.interval == 1
+			&& rule.count.is_none()
+			&& rule.by_month.is_empty()
+			&& rule.by_month_day.is_empty()
+			&& rule.by_day.iter().all(|(ordinal, _)| ordinal.is_none())
+			&& event.dates.is_empty()
+			&& !event.cancelled
+			&& length > Duration::zero()
+			&& length < Duration::weeks(1);
+		if !simple {
+			return None;
+		}
+
+		let opens: NaiveDateTime = first.naive_local();
+		let closes: NaiveDateTime = (first.clone() + length).naive_local();
+		let days: Vec<Weekday> = match (rule.frequency, rule.by_day.is_empty()) {
+			(Frequency::Daily, true) => vec![
+				Weekday::Mon,
+				Weekday::Tue,
+				Weekday::Wed,
+				Weekday::Thu,
+				Weekday::Fri,
+				Weekday::Sat,
+				Weekday::Sun,
+			],
+			(_, true) => vec![opens.weekday()],
+			(_, false) => rule.by_day.iter().map(|(_, day)| *day).collect(),
+		};
+		// The occurrence the rule would have just before or after `time`'s.
+		let beside = |time: &DateTime<Tz>, later: i64| -> DateTime<Tz> {
+			let offset: Duration = (1..=7)
+				.map(|count| Duration::days(count * later))
+				.find(|offset| days.contains(&(time.naive_local() + *offset).weekday()))
+				.unwrap_or_else(|| Duration::weeks(later));
+			timezone
+				.from_local_datetime(&(time.naive_local() + offset))
+				.earliest()
+				.unwrap_or_else(|| time.clone() + offset)
+		};
+		let from: Option<Span<Tz>> =
+			(first > *start).then(|| (beside(&first, -1) + length, first.clone()));
+		let until: Option<Span<Tz>> = match rule.until {
+			Some(_) => {
+				let until: DateTime<Tz> = self.until(rule, timezone)?;
+				let last: DateTime<Tz> = self
+					.starts(event, timezone, &(until + Duration::seconds(1)))
+					.pop()?;
+				Some((last.clone() + length, beside(&last, 1)))
+			}
+			None => None,
+		};
+
+		let time = |time: NaiveDateTime| time.format("%H:%M:%S").to_string();
+		let note = |part: Part<Tz>| match &event.description {
+			Some(description) => description.lines().fold(part, Part::note),
+			None => part,
+		};
+		let parts: Vec<Part<Tz>> = match (rule.frequency, rule.by_day.is_empty()) {
+			(Frequency::Daily, true) if length <= Duration::days(1) => vec![note(
+				Part::new()
+					.open(Specifier::Daily { time: time(opens) })
+					.close(Specifier::Daily { time: time(closes) }),
+			)],
+			(Frequency::Weekly, _) | (Frequency::Daily, false) => {
+				// How many days after opening it closes.
+				let later: i64 = (closes.date() - opens.date()).num_days();
+				days
+					.iter()
+					.map(|day| {
+						let close: Weekday = (0..later).fold(*day, |day, _| day.succ());
+						note(
+							Part::new()
+								.open(Specifier::Weekly {
+									day: day.to_string(),
+									time: time(opens),
+								})
+								.close(Specifier::Weekly {
+									day: close.to_string(),
+									time: time(closes),
+								}),
+						)
+					})
+					.collect()
+			}
+			_ => return None,
+		};
+		Some((parts, Bounds { from, until }))
+	}
+}
+
+fn exception<'schedule, Tz: TimeZone>(
+	occurrence: &Occurrence<Tz>,
+	status: Status<'schedule, Tz>,
+) -> Exception<'schedule, Tz> {
+	let mut exception: Exception<'schedule, Tz> = Exception::new()
+		.effective(Specifier::Exact(occurrence.start.clone()))
+		.expires(Specifier::Exact(occurrence.end.clone()));
+	*exception.effect_mut() = Some(status);
+	exception
+}
+
+#[cfg(test)]
+mod tests {
+	use super::Ics;
+	use crate::{Part, Reason, Schedule, Space, Specifier, Status};
+	use chrono::{DateTime, FixedOffset};
+
+	const CALENDAR: &str = "BEGIN:VCALENDAR\r
+VERSION:2.0\r
+PRODID:-//Facilities//Closures//EN\r
+BEGIN:VTIMEZONE\r
+TZID:America/Chicago\r
+BEGIN:DAYLIGHT\r
+DTSTART:19700308T020000\r
+RRULE:FREQ=YEARLY;BYMONTH=3;BYDAY=2SU\r
+TZOFFSETFROM:-0600\r
+TZOFFSETTO:-0500\r
+END:DAYLIGHT\r
+BEGIN:STANDARD\r
+DTSTART:19701101T020000\r
+RRULE:FREQ=YEARLY;BYMONTH=11;BYDAY=1SU\r
+TZOFFSETFROM:-0500\r
+TZOFFSETTO:-0600\r
+END:STANDARD\r
+END:VTIMEZONE\r
+BEGIN:VEVENT\r
+UID:weekdays@example.edu\r
+DTSTART;TZID=America/Chicago:20200106T070000\r
+DTEND;TZID=America/Chicago:20200106T170000\r
+RRULE:FREQ=WEEKLY;BYDAY=MO,TU,WE,TH,FR\r
+EXDATE;TZID=America/Chicago:20200120T070000\r
+SUMMARY:Open\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:weekdays@example.edu\r
+RECURRENCE-ID;TZID=America/Chicago:20200124T070000\r
+DTSTART;TZID=America/Chicago:20200124T090000\r
+DTEND;TZID=America/Chicago:20200124T120000\r
+SUMMARY:Open\r
+DESCRIPTION:Open late\\, closing early\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:saturdays@example.edu\r
+DTSTART:20200118T150000Z\r
+DURATION:PT4H\r
+RRULE:FREQ=WEEKLY;COUNT=2\r
+SUMMARY:Saturday hours\r
+END:VEVENT\r
+END:VCALENDAR\r
+";
+
+	fn time(rfc3339: &str) -> DateTime<FixedOffset> {
+		DateTime::parse_from_rfc3339(rfc3339).unwrap()
+	}
+
+	fn is_open(space: &Space<FixedOffset>, rfc3339: &str) -> bool {
+		matches!(space.status_at(&time(rfc3339)), Status::Open(_))
+	}
+
+	/// When each of the closures read from `events` starts and ends.
+	fn closed(events: &str) -> Vec<(String, String)> {
+		let ics = Ics::parse(&format!("BEGIN:VCALENDAR\n{events}END:VCALENDAR\n")).unwrap();
+		ics
+			.closures(
+				Schedule::new(),
+				&time("2020-01-01T00:00:00-06:00"),
+				&time("2020-12-31T00:00:00-06:00"),
+			)
+			.exceptions()
+			.iter()
+			.map(
+				|exception| match (exception.effective_from(), exception.expires_at()) {
+					(Some(Specifier::Exact(from)), Some(Specifier::Exact(until))) => {
+						(from.to_rfc3339(), until.to_rfc3339())
+					}
+					other => panic!("{:?}", other),
+				},
+			)
+			.collect()
+	}
+
+	#[test]
+	fn hours() {
+		let ics = Ics::parse(CALENDAR).unwrap();
+		assert_eq!(ics.len(), 3);
+
+		let mut schedules = ics.hours(
+			Schedule::new(),
+			&time("2020-01-13T00:00:00-06:00"),
+			&time("2020-02-01T00:00:00-06:00"),
+		);
+		// A part for each weekday; the Saturdays stop after two weeks.
+		assert_eq!(schedules.len(), 1);
+		assert_eq!(schedules[0].parts().len(), 5);
+		let space = Space::new("Buntrock Commons").schedule(schedules.remove(0));
+
+		assert!(is_open(&space, "2020-01-13T08:00:00-06:00"));
+		assert!(!is_open(&space, "2020-01-13T18:00:00-06:00"));
+		assert!(!is_open(&space, "2020-01-20T08:00:00-06:00"));
+		assert!(!is_open(&space, "2020-01-24T08:00:00-06:00"));
+		assert!(is_open(&space, "2020-01-24T10:00:00-06:00"));
+		assert!(!is_open(&space, "2020-01-24T13:00:00-06:00"));
+		assert!(is_open(&space, "2020-01-18T10:00:00-06:00"));
+		assert!(is_open(&space, "2020-01-25T10:00:00-06:00"));
+		assert!(!is_open(&space, "2020-02-01T10:00:00-06:00"));
+		assert_eq!(
+			space.status_at(&time("2020-01-24T10:00:00-06:00")),
+			Status::Open(Reason::Exception(Some(
+				"Open late, closing early".to_string()
+			)))
+		);
+		// Summaries name events, so they aren't taken for notes.
+		assert_eq!(
+			space.status_at(&time("2020-01-18T10:00:00-06:00")),
+			Status::Open(Reason::Exception(None))
+		);
+		assert!(matches!(
+			space.status_at(&time("2020-01-13T08:00:00-06:00")),
+			Status::Open(Reason::Part(Some(part))) if part.notes().is_empty()
+		));
+	}
+
+	#[test]
+	fn bounded_hours() {
+		// Weekdays for a fortnight, as exported with the schedule expiring.
+		let ics = Ics::parse(
+			"BEGIN:VCALENDAR\n\
+			 BEGIN:VEVENT\n\
+			 UID:term@example.edu\n\
+			 DTSTART:20200113T130000Z\n\
+			 DTEND:20200113T230000Z\n\
+			 RRULE:FREQ=WEEKLY;BYDAY=MO,TU,WE,TH,FR;UNTIL=20200125T060000Z\n\
+			 SUMMARY:Library open\n\
+			 DESCRIPTION:Term hours\n\
+			 END:VEVENT\n\
+			 END:VCALENDAR\n",
+		)
+		.unwrap();
+		let schedules = ics.hours(
+			Schedule::new(),
+			&time("2020-01-01T00:00:00-06:00"),
+			&time("2020-03-01T00:00:00-06:00"),
+		);
+		assert_eq!(schedules.len(), 2);
+		assert!(schedules[0].parts().is_empty());
+		assert!(schedules[0].exceptions().is_empty());
+		assert_eq!(
+			schedules[1].effective(),
+			&Some(time("2020-01-13T07:00:00-06:00"))
+		);
+		assert_eq!(
+			schedules[1].expires(),
+			&Some(time("2020-01-24T17:00:00-06:00"))
+		);
+		assert_eq!(schedules[1].parts().len(), 5);
+		assert!(schedules[1]
+			.parts()
+			.iter()
+			.all(|part| part.notes() == ["Term hours"]));
+
+		let space = schedules
+			.into_iter()
+			.fold(Space::new("Rolvaag Library"), Space::schedule);
+		assert!(!is_open(&space, "2020-01-10T08:00:00-06:00"));
+		assert!(is_open(&space, "2020-01-13T08:00:00-06:00"));
+		assert!(is_open(&space, "2020-01-24T16:00:00-06:00"));
+		assert!(!is_open(&space, "2020-01-27T08:00:00-06:00"));
+	}
+
+	#[test]
+	fn closures() {
+		let ics = Ics::parse(
+			"BEGIN:VCALENDAR\n\
+			 BEGIN:VEVENT\n\
+			 UID:snow@example.edu\n\
+			 DTSTART;VALUE=DATE:20200115\n\
+			 SUMMARY:Snow day\n\
+			 END:VEVENT\n\
+			 BEGIN:VEVENT\n\
+			 UID:meetings@example.edu\n\
+			 DTSTART:20200101T120000\n\
+			 DTEND:20200101T130000\n\
+			 RRULE:FREQ=MONTHLY;BYDAY=-1WE;UNTIL=20200331\n\
+			 SUMMARY:All-staff\n\
+			 \x20 meeting\n\
+			 END:VEVENT\n\
+			 END:VCALENDAR\n",
+		)
+		.unwrap();
+		let schedule = ics.closures(
+			Schedule::new(),
+			&time("2020-01-01T00:00:00-06:00"),
+			&time("2020-12-31T00:00:00-06:00"),
+		);
+		let closures: Vec<(String, Option<String>)> = schedule
+			.exceptions()
+			.iter()
+			.map(|exception| match exception.effect() {
+				Some(Status::Closed(Reason::Exception(note))) => (
+					format!("{:?}", exception.effective_from().unwrap()),
+					note.clone(),
+				),
+				effect => panic!("{:?}", effect),
+			})
+			.collect();
+
+		assert_eq!(closures.len(), 4);
+		assert!(closures[0].0.contains("2020-01-15T00:00:00-06:00"));
+		assert_eq!(closures[0].1.as_deref(), Some("Snow day"));
+		assert!(closures[1].0.contains("2020-01-29T12:00:00-06:00"));
+		assert_eq!(closures[1].1.as_deref(), Some("All-staff meeting"));
+		assert!(closures[3].0.contains("2020-03-25T12:00:00-06:00"));
+	}
+
+	#[test]
+	fn rules_and_exclusions() {
+		let closed = closed(
+			"BEGIN:VEVENT\n\
+			 UID:every-other-day@example.edu\n\
+			 DTSTART:20200101T120000\n\
+			 DTEND:20200101T130000\n\
+			 RRULE:FREQ=DAILY;INTERVAL=2;COUNT=3\n\
+			 EXDATE:20200103T120000,20200102T120000\n\
+			 END:VEVENT\n\
+			 BEGIN:VEVENT\n\
+			 UID:wednesdays@example.edu\n\
+			 DTSTART:20200108T090000\n\
+			 DURATION:PT1H\n\
+			 RRULE:FREQ=WEEKLY;UNTIL=20200122\n\
+			 END:VEVENT\n\
+			 BEGIN:VEVENT\n\
+			 UID:month-end@example.edu\n\
+			 DTSTART:20200131T170000\n\
+			 DURATION:PT1H\n\
+			 RRULE:FREQ=MONTHLY;BYMONTHDAY=-1;COUNT=2\n\
+			 END:VEVENT\n",
+		);
+		let starts: Vec<&str> = closed.iter().map(|(from, _)| from.as_str()).collect();
+
+		// The exclusion which isn't an occurrence changes nothing, and UNTIL
+		// includes the whole of its day.
+		assert_eq!(
+			starts,
+			vec![
+				"2020-01-01T12:00:00-06:00",
+				"2020-01-05T12:00:00-06:00",
+				"2020-01-08T09:00:00-06:00",
+				"2020-01-15T09:00:00-06:00",
+				"2020-01-22T09:00:00-06:00",
+				"2020-01-31T17:00:00-06:00",
+				"2020-02-29T17:00:00-06:00",
+			]
+		);
+		assert_eq!(closed[2].1, "2020-01-08T10:00:00-06:00");
+	}
+
+	#[test]
+	fn all_day_events() {
+		let closed = closed(
+			"BEGIN:VEVENT\n\
+			 UID:break@example.edu\n\
+			 DTSTART;VALUE=DATE:20200120\n\
+			 DTEND;VALUE=DATE:20200122\n\
+			 END:VEVENT\n\
+			 BEGIN:VEVENT\n\
+			 UID:holiday@example.edu\n\
+			 DTSTART;VALUE=DATE:20200217\n\
+			 END:VEVENT\n",
+		);
+
+		assert_eq!(
+			closed,
+			vec![
+				(
+					"2020-01-20T00:00:00-06:00".to_string(),
+					"2020-01-22T00:00:00-06:00".to_string()
+				),
+				(
+					"2020-02-17T00:00:00-06:00".to_string(),
+					"2020-02-18T00:00:00-06:00".to_string()
+				),
+			]
+		);
+	}
+
+	#[test]
+	fn time_zones() {
+		let closed = closed(
+			"BEGIN:VTIMEZONE\n\
+			 TZID:America/Chicago\n\
+			 BEGIN:DAYLIGHT\n\
+			 DTSTART:19700308T020000\n\
+			 RRULE:FREQ=YEARLY;BYMONTH=3;BYDAY=2SU\n\
+			 TZOFFSETFROM:-0600\n\
+			 TZOFFSETTO:-0500\n\
+			 END:DAYLIGHT\n\
+			 BEGIN:STANDARD\n\
+			 DTSTART:19701101T020000\n\
+			 RRULE:FREQ=YEARLY;BYMONTH=11;BYDAY=1SU\n\
+			 TZOFFSETFROM:-0500\n\
+			 TZOFFSETTO:-0600\n\
+			 END:STANDARD\n\
+			 END:VTIMEZONE\n\
+			 BEGIN:VEVENT\n\
+			 UID:summer@example.edu\n\
+			 DTSTART;TZID=America/Chicago:20200701T090000\n\
+			 DTEND;TZID=America/Chicago:20200701T100000\n\
+			 END:VEVENT\n\
+			 BEGIN:VEVENT\n\
+			 UID:unknown@example.edu\n\
+			 DTSTART;TZID=Mars/Olympus_Mons:20200702T090000\n\
+			 DTEND;TZID=Mars/Olympus_Mons:20200702T100000\n\
+			 END:VEVENT\n\
+			 BEGIN:VEVENT\n\
+			 UID:utc@example.edu\n\
+			 DTSTART:20200703T150000Z\n\
+			 DTEND:20200703T160000Z\n\
+			 END:VEVENT\n",
+		);
+		let starts: Vec<&str> = closed.iter().map(|(from, _)| from.as_str()).collect();
+
+		// Daylight time is an hour ahead; an unknown zone is taken as local.
+		assert_eq!(
+			starts,
+			vec![
+				"2020-07-01T08:00:00-06:00",
+				"2020-07-02T09:00:00-06:00",
+				"2020-07-03T09:00:00-06:00",
+			]
+		);
+	}
+
+	#[test]
+	fn exact_boundaries() {
+		let part: Part<FixedOffset> = Part::new()
+			.open(Specifier::Daily {
+				time: "07:00".to_string(),
+			})
+			.close(Specifier::Daily {
+				time: "23:00".to_string(),
+			});
+		let ics = Ics::parse(
+			"BEGIN:VCALENDAR\n\
+			 BEGIN:VEVENT\n\
+			 DTSTART:20200114T090000\n\
+			 DTEND:20200114T120000\n\
+			 SUMMARY:Exams\n\
+			 END:VEVENT\n\
+			 BEGIN:VEVENT\n\
+			 DTSTART;VALUE=DATE:20200115\n\
+			 SUMMARY:Snow day\n\
+			 END:VEVENT\n\
+			 END:VCALENDAR\n",
+		)
+		.unwrap();
+		let space = Space::new("Rolvaag Library").schedule(ics.closures(
+			Schedule::new().part(part.clone()),
+			&time("2020-01-01T00:00:00-06:00"),
+			&time("2020-02-01T00:00:00-06:00"),
+		));
+		let closed = |note: &str| Status::Closed(Reason::Exception(Some(note.to_string())));
+
+		// Closures start at their first instant and end just before their last.
+		assert_eq!(
+			space.status_at(&time("2020-01-14T09:00:00-06:00")),
+			closed("Exams")
+		);
+		assert_eq!(
+			space.status_at(&time("2020-01-14T12:00:00-06:00")),
+			Status::Open(Reason::Part(Some(&part)))
+		);
+		assert_eq!(
+			space.status_at(&time("2020-01-15T00:00:00-06:00")),
+			closed("Snow day")
+		);
+		assert_eq!(
+			space.status_at(&time("2020-01-16T00:00:00-06:00")),
+			Status::Closed(Reason::Part(None))
+		);
+		assert!(is_open(&space, "2020-01-16T07:00:00-06:00"));
+	}
+
+	#[test]
+	fn errors() {
+		let error =
+			Ics::parse("BEGIN:VCALENDAR\nBEGIN:VEVENT\nDTSTART:2020\nEND:VEVENT\nEND:VCALENDAR\n")
+				.unwrap_err();
+		assert_eq!(error.line(), 3);
+		assert_eq!(
+			error.to_string(),
+			"line 3: `2020` isn't a date or date-time"
+		);
+
+		let error = Ics::parse(
+			"BEGIN:VCALENDAR\nBEGIN:VEVENT\nDTSTART:20200101T000000\nRRULE:FREQ=HOURLY\nEND:VEVENT\nEND:VCALENDAR\n",
+		)
+		.unwrap_err();
+		assert_eq!(error.line(), 4);
+
+		assert_eq!(Ics::parse("BEGIN:VCALENDAR\n").unwrap_err().line(), 1);
+	}
+}
@@ -0,0 +1,195 @@
+//! Just enough JSON to write reports and exports without pulling in a
+//! serializer.
+
+use core::fmt::{self, Write};
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Value {
+	Null,
+	Bool(bool),
+	Number(f64),
+	String(String),
+	Array(Vec<Value>),
+	/// Members in the order they should be written.
+	Object(Vec<(String, Value)>),
+}
+
+impl Value {
+	pub(crate) fn object<'key, I>(members: I) -> Self
+	where
+		I: IntoIterator<Item = (&'key str, Value)>,
+	{
+		Self::Object(
+			members
+				.into_iter()
+				.map(|(key, value)| (key.to_string(), value))
+				.collect(),
+		)
+	}
+
+	/// The value, indented by tabs, one member or element to a line.
+	pub(crate) fn pretty(&self) -> String {
+		let mut string: String = String::new();
+		// Writing to a `String` can't fail.
+		let _ = self.write(&mut string, Some(0));
+		string
+	}
+
+	fn write<W: Write>(&self, out: &mut W, indent: Option<usize>) -> fmt::Result {
+		let newline = |out: &mut W, depth: usize| -> fmt::Result {
+			if indent.is_some() {
+				out.write_char('\n')?;
+				for _ in 0..depth {
+					out.write_char('\t')?;
+				}
+			}
+			Ok(())
+		};
+		let depth: usize = indent.unwrap_or(0);
+		let inner: Option<usize> = indent.map(|depth| depth + 1);
+
+		match self {
+			Self::Null => out.write_str("null"),
+			Self::Bool(value) => write!(out, "{value}"),
+			Self::Number(value) if !value.is_finite() => out.write_str("null"),
+			#[allow(clippy::cast_possible_truncation)]
+			Self::Number(value) if value.fract() == 0.0 && value.abs() < 1e15 => {
+				write!(out, "{}", *value as i64)
+			}
+			Self::Number(value) => write!(out, "{value}"),
+			Self::String(value) => string(out, value),
+			Self::Array(values) if values.is_empty() => out.write_str("[]"),
+			Self::Array(values) => {
+				out.write_char('[')?;
+				for (index, value) in values.iter().enumerate() {
+					if index > 0 {
+						out.write_char(',')?;
+					}
+					newline(out, depth + 1)?;
+					value.write(out, inner)?;
+				}
+				newline(out, depth)?;
+				out.write_char(']')
+			}
+			Self::Object(members) if members.is_empty() => out.write_str("{}"),
+			Self::Object(members) => {
+				out.write_char('{')?;
+				for (index, (key, value)) in members.iter().enumerate() {
+					if index > 0 {
+						out.write_char(',')?;
+					}
+					newline(out, depth + 1)?;
+					string(out, key)?;
+					out.write_str(if indent.is_some() { ": " } else { ":" })?;
+					value.write(out, inner)?;
+				}
+				newline(out, depth)?;
+				out.write_char('}')
+			}
+		}
+	}
+}
+
+/// Write `value` as a JSON string, escaping what RFC 8259 requires.
+fn string<W: Write>(out: &mut W, value: &str) -> fmt::Result {
+	out.write_char('"')?;
+	for c in value.chars() {
+		match c {
+			'"' => out.write_str("\\\"")?,
+			'\\' => out.write_str("\\\\")?,
+			'\n' => out.write_str("\\n")?,
+			'\r' => out.write_str("\\r")?,
+			'\t' => out.write_str("\\t")?,
+			c if c < ' ' => write!(out, "\\u{:04x}", u32::from(c))?,
+			c => out.write_char(c)?,
+		}
+	}
+	out.write_char('"')
+}
+
+/// The value on one line, without any whitespace.
+impl fmt::Display for Value {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		self.write(f, None)
+	}
+}
+
+impl From<bool> for Value {
+	fn from(value: bool) -> Self {
+		Self::Bool(value)
+	}
+}
+
+impl From<f64> for Value {
+	fn from(value: f64) -> Self {
+		Self::Number(value)
+	}
+}
+
+impl From<f32> for Value {
+	/// Converts by way of the shortest decimal which reads back as `value`,
+	/// so that `0.9_f32` is written as `0.9` and not `0.8999999761581421`.
+	fn from(value: f32) -> Self {
+		Self::Number(value.to_string().parse().unwrap_or(f64::NAN))
+	}
+}
+
+impl From<u32> for Value {
+	fn from(value: u32) -> Self {
+		Self::Number(f64::from(value))
+	}
+}
+
+impl From<usize> for Value {
+	#[allow(clippy::cast_precision_loss)]
+	fn from(value: usize) -> Self {
+		Self::Number(value as f64)
+	}
+}
+
+impl From<&str> for Value {
+	fn from(value: &str) -> Self {
+		Self::String(value.to_string())
+	}
+}
+
+impl From<String> for Value {
+	fn from(value: String) -> Self {
+		Self::String(value)
+	}
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+	fn from(value: Option<T>) -> Self {
+		value.map_or(Self::Null, Into::into)
+	}
+}
+
+impl<T: Into<Value>> From<Vec<T>> for Value {
+	fn from(values: Vec<T>) -> Self {
+		Self::Array(values.into_iter().map(Into::into).collect())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::Value;
+
+	#[test]
+	fn compact_and_pretty() {
+		let value = Value::object(vec![
+			("name", "Boe \"Chapel\"\n".into()),
+			("page", 1_u32.into()),
+			("confidence", 0.5_f64.into()),
+			("bbox", Value::Null),
+			("issues", Vec::<String>::new().into()),
+			("days", vec!["Mon", "Tue"].into()),
+		]);
+
+		assert_eq!(
+			value.to_string(),
+			r#"{"name":"Boe \"Chapel\"\n","page":1,"confidence":0.5,"bbox":null,"issues":[],"days":["Mon","Tue"]}"#
+		);
+		assert_eq!(Value::from(vec![1_u32, 2]).pretty(), "[\n\t1,\n\t2\n]");
+	}
+}
@@ -0,0 +1,424 @@
+//! schema.org structured data for a space's hours, as JSON-LD.
+//!
+//! Weekly and daily parts become `openingHoursSpecification`s, one for each
+//! set of hours with the days they're kept on, valid while their schedule
+//! is.  Dates with exact parts or exceptions, such as holidays, become
+//! `specialOpeningHoursSpecification`s giving what's actually open on each
+//! of them; a date that's closed all day opens and closes at `00:00`, as
+//! schema.org suggests.  Weekly and daily exceptions aren't written, as
+//! schema.org has no way to say them.
+
+use core::fmt;
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, TimeZone, Weekday};
+
+use super::json::Value;
+use super::specifier::{day_name, midnight, time_of_day, write_time};
+use super::{Period, Schedule, Space, Specifier};
+
+/// Hours which are the same on each of `days`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Hours {
+	days: Vec<Weekday>,
+	opens: NaiveTime,
+	/// `None` for the end of the day.
+	closes: Option<NaiveTime>,
+	valid_from: Option<NaiveDate>,
+	valid_through: Option<NaiveDate>,
+	name: Option<String>,
+}
+
+impl Hours {
+	fn to_value(&self) -> Value {
+		let mut members: Vec<(&str, Value)> = vec![("@type", "OpeningHoursSpecification".into())];
+		if let Some(name) = &self.name {
+			members.push(("name", name.as_str().into()));
+		}
+		if !self.days.is_empty() {
+			let days: Vec<&str> = self.days.iter().map(|day| day_name(*day)).collect();
+			members.push(("dayOfWeek", days.into()));
+		}
+		members.push(("opens", write_time(self.opens).into()));
+		members.push((
+			"closes",
+			self
+				.closes
+				.map_or_else(|| "23:59".to_string(), write_time)
+				.into(),
+		));
+		if let Some(valid_from) = self.valid_from {
+			members.push(("validFrom", valid_from.to_string().into()));
+		}
+		if let Some(valid_through) = self.valid_through {
+			members.push(("validThrough", valid_through.to_string().into()));
+		}
+		Value::object(members)
+	}
+}
+
+/// The days a weekly part is open on, and when on each; hours past the
+/// following midnight are split onto the days they fall on.
+fn weekly(
+	(open_day, opens): (Weekday, NaiveTime),
+	(close_day, closes): (Weekday, NaiveTime),
+) -> Vec<(Weekday, NaiveTime, Option<NaiveTime>)> {
+	let mut days: u32 = (7 + close_day.num_days_from_monday() - open_day.num_days_from_monday()) % 7;
+	if days == 0 && closes <= opens {
+		days = 7;
+	}
+	if days == 0 || (days == 1 && closes <= opens) {
+		let closes: Option<NaiveTime> = Some(closes).filter(|closes| *closes != NaiveTime::MIN);
+		return vec![(open_day, opens, closes)];
+	}
+
+	let mut hours = vec![(open_day, opens, None)];
+	let mut day: Weekday = open_day.succ();
+	for _ in 1..days {
+		hours.push((day, NaiveTime::MIN, None));
+		day = day.succ();
+	}
+	if closes > NaiveTime::MIN {
+		hours.push((close_day, NaiveTime::MIN, Some(closes)));
+	}
+	hours
+}
+
+/// The last day of a schedule which expires at `expires`.
+fn through<Tz: TimeZone>(expires: &DateTime<Tz>) -> NaiveDate {
+	let expires = expires.naive_local();
+	if expires.time() == NaiveTime::MIN {
+		expires.date() - Duration::days(1)
+	} else {
+		expires.date()
+	}
+}
+
+/// A schedule's weekly and daily parts, joined up where only their days
+/// differ.
+fn regular<Tz: TimeZone>(schedule: &Schedule<'_, Tz>) -> Vec<Hours> {
+	let valid_from: Option<NaiveDate> = schedule
+		.effective()
+		.as_ref()
+		.map(|effective| effective.naive_local().date());
+	let valid_through: Option<NaiveDate> = schedule.expires().as_ref().map(through);
+
+	let mut hours: Vec<Hours> = Vec::new();
+	for part in schedule.parts() {
+		let days: Vec<(Weekday, NaiveTime, Option<NaiveTime>)> = match (part.opens(), part.closes()) {
+			(Some(Specifier::Daily { time: opens }), Some(Specifier::Daily { time: closes })) => {
+				match (time_of_day(opens), time_of_day(closes)) {
+					(Some(opens), Some(closes)) => {
+						let closes = Some(closes).filter(|closes| *closes != NaiveTime::MIN);
+						(0..7)
+							.scan(Weekday::Mon, |day, _| {
+								let today: Weekday = *day;
+								*day = day.succ();
+								Some((today, opens, closes))
+							})
+							.collect()
+					}
+					_ => continue,
+				}
+			}
+			(
+				Some(Specifier::Weekly {
+					day: open_day,
+					time: opens,
+				}),
+				Some(Specifier::Weekly {
+					day: close_day,
+					time: closes,
+				}),
+			) => match (
+				open_day.parse(),
+				time_of_day(opens),
+				close_day.parse(),
+				time_of_day(closes),
+			) {
+				(Ok(open_day), Some(opens), Ok(close_day), Some(closes)) => {
+					weekly((open_day, opens), (close_day, closes))
+				}
+				_ => continue,
+			},
+			_ => continue,
+		};
+
+		for (day, opens, closes) in days {
+			match hours
+				.iter_mut()
+				.find(|hours| hours.opens == opens && hours.closes == closes)
+			{
+				Some(hours) if !hours.days.contains(&day) => hours.days.push(day),
+				Some(_) => {}
+				None => hours.push(Hours {
+					days: vec![day],
+					opens,
+					closes,
+					valid_from,
+					valid_through,
+					name: None,
+				}),
+			}
+		}
+	}
+
+	for hours in &mut hours {
+		hours.days.sort_by_key(Weekday::num_days_from_monday);
+	}
+	hours
+}
+
+/// The dates covered by `from` until `until`.
+fn covered<Tz: TimeZone>(from: &DateTime<Tz>, until: &DateTime<Tz>) -> Vec<NaiveDate> {
+	let last: NaiveDate = through(until);
+	from
+		.naive_local()
+		.date()
+		.iter_days()
+		.take_while(|date| *date <= last)
+		.collect()
+}
+
+/// What's open on `date`, with any note from an exception.
+fn special<Tz: TimeZone>(space: &Space<'_, Tz>, date: NaiveDate, timezone: &Tz) -> Vec<Hours> {
+	let (Some(start), Some(end)) = (
+		midnight(timezone, date),
+		midnight(timezone, date + Duration::days(1)),
+	) else {
+		return Vec::new();
+	};
+	let periods: Vec<Period<Tz>> = space.periods(&start, &end);
+	let name: Option<String> = periods
+		.iter()
+		.find_map(|period| period.note().map(str::to_string));
+	let hours = |opens: NaiveTime, closes: Option<NaiveTime>| Hours {
+		days: Vec::new(),
+		opens,
+		closes,
+		valid_from: Some(date),
+		valid_through: Some(date),
+		name: name.clone(),
+	};
+
+	let open: Vec<Hours> = periods
+		.iter()
+		.filter(|period| period.is_open())
+		.map(|period| {
+			let closes: Option<NaiveTime> = Some(period.end())
+				.filter(|closes| **closes < end)
+				.map(|closes| closes.naive_local().time());
+			hours(period.start().naive_local().time(), closes)
+		})
+		.collect();
+	if open.is_empty() {
+		vec![hours(NaiveTime::MIN, Some(NaiveTime::MIN))]
+	} else {
+		open
+	}
+}
+
+/// A space's hours as schema.org JSON-LD.
+#[derive(Debug)]
+pub struct JsonLd<'space, 'schedule, Tz: TimeZone> {
+	space: &'space Space<'schedule, Tz>,
+	kind: String,
+}
+
+impl<'space, 'schedule, Tz: TimeZone> JsonLd<'space, 'schedule, Tz> {
+	/// Describe `space` as a schema.org `Place`.
+	#[must_use]
+	pub fn new(space: &'space Space<'schedule, Tz>) -> Self {
+		Self {
+			space,
+			kind: "Place".to_string(),
+		}
+	}
+
+	/// Describe the space as some more specific type of `Place`, e.g.
+	/// `Library`.
+	#[must_use]
+	pub fn kind(mut self, kind: &str) -> Self {
+		self.kind = kind.to_string();
+		self
+	}
+
+	fn special_dates(&self) -> Vec<(NaiveDate, Tz)> {
+		let mut dates: Vec<(NaiveDate, Tz)> = Vec::new();
+		let exact = |from: Option<&Specifier<Tz>>, until: Option<&Specifier<Tz>>| match (from, until) {
+			(Some(Specifier::Exact(from)), Some(Specifier::Exact(until))) => covered(from, until)
+				.into_iter()
+				.map(|date| (date, from.timezone()))
+				.collect(),
+			_ => Vec::new(),
+		};
+		for schedule in self.space.schedules() {
+			for part in schedule.parts() {
+				dates.extend(exact(part.opens(), part.closes()));
+			}
+			for exception in schedule.exceptions() {
+				dates.extend(exact(exception.effective_from(), exception.expires_at()));
+			}
+		}
+		dates.sort_by_key(|(date, _)| *date);
+		dates.dedup_by_key(|(date, _)| *date);
+		dates
+	}
+
+	fn to_value(&self) -> Value {
+		let regular: Vec<Value> = self
+			.space
+			.schedules()
+			.iter()
+			.flat_map(regular)
+			.map(|hours| hours.to_value())
+			.collect();
+		let special: Vec<Value> = self
+			.special_dates()
+			.iter()
+			.flat_map(|(date, timezone)| special(self.space, *date, timezone))
+			.map(|hours| hours.to_value())
+			.collect();
+
+		let mut members: Vec<(&str, Value)> = vec![
+			("@context", "https://schema.org".into()),
+			("@type", self.kind.as_str().into()),
+			("name", self.space.name().into()),
+			("openingHoursSpecification", Value::Array(regular)),
+		];
+		if !special.is_empty() {
+			members.push(("specialOpeningHoursSpecification", Value::Array(special)));
+		}
+		Value::object(members)
+	}
+}
+
+/// The JSON-LD, indented, as it would go in a
+/// `<script type="application/ld+json">` element.
+impl<Tz: TimeZone> fmt::Display for JsonLd<'_, '_, Tz> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(&self.to_value().pretty())
+	}
+}
+
+impl<Tz: TimeZone> Space<'_, Tz> {
+	/// The space's hours as schema.org JSON-LD; see `JsonLd`.
+	#[must_use]
+	pub fn to_json_ld(&self) -> String {
+		JsonLd::new(self).to_string()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::JsonLd;
+	use crate::{Exception, Part, Reason, Schedule, Space, Specifier, Status};
+	use chrono::{DateTime, FixedOffset};
+
+	fn time(rfc3339: &str) -> DateTime<FixedOffset> {
+		DateTime::parse_from_rfc3339(rfc3339).unwrap()
+	}
+
+	fn weekly(day: &str, time: &str) -> Specifier<FixedOffset> {
+		Specifier::Weekly {
+			day: day.to_string(),
+			time: time.to_string(),
+		}
+	}
+
+	#[test]
+	fn regular_and_special() {
+		let mut thanksgiving = Exception::new()
+			.effective(Specifier::Exact(time("2019-11-28T00:00:00-05:00")))
+			.expires(Specifier::Exact(time("2019-11-29T00:00:00-05:00")));
+		*thanksgiving.effect_mut() = Some(Status::Closed(Reason::Exception(Some(
+			"Thanksgiving".to_string(),
+		))));
+		let mut short = Exception::new()
+			.effective(Specifier::Exact(time("2019-11-29T12:00:00-05:00")))
+			.expires(Specifier::Exact(time("2019-11-30T00:00:00-05:00")));
+		*short.effect_mut() = Some(Status::Closed(Reason::Exception(None)));
+
+		let mut schedule = ["Monday", "Tuesday", "Wednesday", "Thursday", "Friday"]
+			.iter()
+			.fold(Schedule::new(), |schedule, day| {
+				schedule.part(
+					Part::new()
+						.open(weekly(day, "07:00"))
+						.close(weekly(day, "23:00")),
+				)
+			})
+			.part(
+				Part::new()
+					.open(weekly("Sat", "22:00"))
+					.close(weekly("Sun", "02:00")),
+			)
+			.part(
+				Part::new()
+					.open(weekly("Sun", "12:00"))
+					.close(weekly("Mon", "00:00")),
+			)
+			.exception(thanksgiving)
+			.exception(short);
+		*schedule.effective_mut() = Some(time("2019-08-26T00:00:00-05:00"));
+		*schedule.expires_mut() = Some(time("2019-12-21T00:00:00-05:00"));
+		let space = Space::new("Library").schedule(schedule);
+
+		let json_ld: String = JsonLd::new(&space).kind("Library").to_string();
+		let compact: String = json_ld.replace(['\n', '\t'], "").replace(": ", ":");
+		assert_eq!(
+			compact,
+			concat!(
+				r#"{"@context":"https://schema.org","@type":"Library","name":"Library","#,
+				r#""openingHoursSpecification":["#,
+				r#"{"@type":"OpeningHoursSpecification","#,
+				r#""dayOfWeek":["Monday","Tuesday","Wednesday","Thursday","Friday"],"#,
+				r#""opens":"07:00","closes":"23:00","validFrom":"2019-08-26","validThrough":"2019-12-20"},"#,
+				r#"{"@type":"OpeningHoursSpecification","dayOfWeek":["Saturday"],"#,
+				r#""opens":"22:00","closes":"02:00","validFrom":"2019-08-26","validThrough":"2019-12-20"},"#,
+				r#"{"@type":"OpeningHoursSpecification","dayOfWeek":["Sunday"],"#,
+				r#""opens":"12:00","closes":"23:59","validFrom":"2019-08-26","validThrough":"2019-12-20"}],"#,
+				r#""specialOpeningHoursSpecification":["#,
+				r#"{"@type":"OpeningHoursSpecification","name":"Thanksgiving","#,
+				r#""opens":"00:00","closes":"00:00","validFrom":"2019-11-28","validThrough":"2019-11-28"},"#,
+				r#"{"@type":"OpeningHoursSpecification","#,
+				r#""opens":"07:00","closes":"12:00","validFrom":"2019-11-29","validThrough":"2019-11-29"}]}"#,
+			)
+		);
+		assert!(space.to_json_ld().contains("\"@type\": \"Place\""));
+	}
+
+	#[test]
+	fn long_parts_are_split() {
+		let space = Space::new("Lab").schedule(
+			Schedule::new()
+				.part(
+					Part::new()
+						.open(weekly("Friday", "18:00"))
+						.close(weekly("Monday", "06:00")),
+				)
+				.part(
+					Part::new()
+						.open(Specifier::Daily {
+							time: "09:00".to_string(),
+						})
+						.close(Specifier::Daily {
+							time: "10:00".to_string(),
+						}),
+				),
+		);
+		let json_ld: String = space
+			.to_json_ld()
+			.replace(['\n', '\t'], "")
+			.replace(": ", ":");
+		assert!(json_ld.contains(r#""dayOfWeek":["Friday"],"opens":"18:00","closes":"23:59""#));
+		assert!(
+			json_ld.contains(r#""dayOfWeek":["Saturday","Sunday"],"opens":"00:00","closes":"23:59""#)
+		);
+		assert!(json_ld.contains(r#""dayOfWeek":["Monday"],"opens":"00:00","closes":"06:00""#));
+		assert!(json_ld.contains(concat!(
+			r#""dayOfWeek":["Monday","Tuesday","Wednesday","Thursday","Friday","Saturday","Sunday"],"#,
+			r#""opens":"09:00","closes":"10:00""#
+		)));
+		assert!(!json_ld.contains("specialOpeningHoursSpecification"));
+	}
+}
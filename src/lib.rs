@@ -1,21 +1,48 @@
 #![deny(clippy::all)]
 #![warn(clippy::cargo, clippy::pedantic, clippy::cognitive_complexity)]
 
+mod csv;
+mod describe;
+mod dsl;
 mod exception;
+mod ical;
+mod json;
+mod json_ld;
+mod listing;
+mod locale;
+mod opening_hours;
 mod part;
 pub mod pdf;
+mod provenance;
+mod render;
 mod schedule;
+mod schema;
 mod space;
 mod specifier;
 mod status;
+mod tables;
+mod timeline;
 
+pub use csv::*;
+pub use describe::*;
+pub use dsl::*;
 pub use exception::*;
+pub use ical::*;
+pub use json_ld::*;
+pub use listing::*;
+pub use locale::*;
+pub use opening_hours::*;
 pub use part::*;
 pub use pdf::*;
+pub use provenance::*;
+pub use render::*;
 pub use schedule::*;
+pub use schema::*;
 pub use space::*;
 pub use specifier::*;
 pub use status::*;
+pub use tables::*;
+pub use timeline::*;
 
 #[cfg(test)]
 mod tests {}
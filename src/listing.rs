@@ -0,0 +1,371 @@
+//! Hours for listing services' bulk uploads, as JSON.
+//!
+//! The format follows the hours of a Google Business Profile location, which
+//! other listing services also take:
+//!
+//! ```json
+//! {
+//!   "locations": [
+//!     {
+//!       "name": "Library",
+//!       "regularHours": {
+//!         "periods": [
+//!           { "openDay": "MONDAY", "openTime": "07:00", "closeDay": "MONDAY", "closeTime": "23:00" }
+//!         ]
+//!       },
+//!       "specialHours": {
+//!         "specialHourPeriods": [
+//!           { "startDate": { "year": 2019, "month": 11, "day": 28 }, "closed": true },
+//!           {
+//!             "startDate": { "year": 2019, "month": 11, "day": 29 },
+//!             "openTime": "07:00",
+//!             "endDate": { "year": 2019, "month": 11, "day": 29 },
+//!             "closeTime": "12:00",
+//!             "closed": false
+//!           }
+//!         ]
+//!       }
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! Regular hours are the week from the first day, with parts and weekly or
+//! daily exceptions; a period ending at midnight closes at `24:00` the day
+//! before.  Special hours are given for each of the following days whose
+//! hours, with all of the exceptions, differ from the regular ones, split
+//! at midnight.
+
+use core::fmt;
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, TimeZone, Weekday};
+
+use super::json::Value;
+use super::specifier::{day_name, midnight, write_time};
+use super::timeline;
+use super::{Exception, Period, Space, Specifier};
+
+/// Whether an exception is part of the regular hours, rather than dated.
+pub(crate) fn recurring<Tz: TimeZone>(exception: &Exception<'_, Tz>) -> bool {
+	let exact = |specifier: Option<&Specifier<Tz>>| matches!(specifier, Some(Specifier::Exact(_)));
+	!exact(exception.effective_from()) && !exact(exception.expires_at())
+}
+
+fn date(date: NaiveDate) -> Value {
+	Value::object(vec![
+		("year", f64::from(date.year()).into()),
+		("month", date.month().into()),
+		("day", date.day().into()),
+	])
+}
+
+/// When a period closes: the day, and the time, `24:00` at midnight.
+fn closes<Tz: TimeZone>(end: &DateTime<Tz>) -> (Weekday, String) {
+	let end = end.naive_local();
+	if end.time() == NaiveTime::MIN {
+		(
+			end.date().pred_opt().unwrap_or(end.date()).weekday(),
+			"24:00".to_string(),
+		)
+	} else {
+		(end.weekday(), write_time(end.time()))
+	}
+}
+
+/// The regular or special hours of some spaces for listing services.
+#[derive(Debug)]
+pub struct Listings<'spaces, 'schedule, Tz: TimeZone> {
+	spaces: &'spaces [Space<'schedule, Tz>],
+	first: NaiveDate,
+	days: u32,
+	timezone: Tz,
+}
+
+impl<'spaces, 'schedule, Tz: TimeZone> Listings<'spaces, 'schedule, Tz> {
+	/// Each of `spaces`' regular hours for the week from `first`, and special
+	/// hours for `days` days from it, in `timezone`.
+	#[must_use]
+	pub fn new(
+		spaces: &'spaces [Space<'schedule, Tz>],
+		first: NaiveDate,
+		days: u32,
+		timezone: Tz,
+	) -> Self {
+		Self {
+			spaces,
+			first,
+			days,
+			timezone,
+		}
+	}
+
+	/// The open periods of `space` from the start of `first` for `days`
+	/// days, with the exceptions `exceptions` picks out.
+	fn open(
+		&self,
+		space: &Space<'schedule, Tz>,
+		first: NaiveDate,
+		days: i64,
+		exceptions: impl Fn(&Exception<'_, Tz>) -> bool,
+	) -> Option<Vec<Period<Tz>>> {
+		let start: DateTime<Tz> = midnight(&self.timezone, first)?;
+		let end: DateTime<Tz> = midnight(&self.timezone, first + Duration::days(days))?;
+		let mut periods = timeline::periods(space.schedules(), &start, &end, exceptions);
+		periods.retain(Period::is_open);
+		Some(periods)
+	}
+
+	fn regular(&self, space: &Space<'schedule, Tz>) -> Vec<Value> {
+		// A day more than a week, so that periods can run past the end of it.
+		let (Some(periods), Some(start), Some(end)) = (
+			self.open(space, self.first, 8, recurring),
+			midnight(&self.timezone, self.first),
+			midnight(&self.timezone, self.first + Duration::days(7)),
+		) else {
+			return Vec::new();
+		};
+		let mut periods: Vec<Period<Tz>> = periods
+			.into_iter()
+			.filter(|period| *period.start() < end)
+			.collect();
+
+		let period = |day: Weekday, opens: NaiveTime, (close_day, closes): (Weekday, String)| {
+			Value::object(vec![
+				("openDay", day_name(day).to_uppercase().into()),
+				("openTime", write_time(opens).into()),
+				("closeDay", day_name(close_day).to_uppercase().into()),
+				("closeTime", closes.into()),
+			])
+		};
+		if let [always] = periods.as_slice() {
+			if *always.start() == start && *always.end() >= end {
+				return self
+					.first
+					.iter_days()
+					.take(7)
+					.map(|date| {
+						period(
+							date.weekday(),
+							NaiveTime::MIN,
+							(date.weekday(), "24:00".to_string()),
+						)
+					})
+					.collect();
+			}
+		}
+		// What's open from the first midnight carries on from the last day.
+		if periods.len() > 1
+			&& *periods[0].start() == start
+			&& periods.last().is_some_and(|last| *last.end() >= end)
+		{
+			periods.remove(0);
+		}
+
+		periods
+			.iter()
+			.map(|open| {
+				let opens = open.start().naive_local();
+				period(opens.weekday(), opens.time(), closes(open.end()))
+			})
+			.collect()
+	}
+
+	fn special(&self, space: &Space<'schedule, Tz>) -> Vec<Value> {
+		let hours = |periods: &[Period<Tz>], end: &DateTime<Tz>| -> Vec<(NaiveTime, String)> {
+			periods
+				.iter()
+				.map(|period| {
+					let close: String = if period.end() == end {
+						"24:00".to_string()
+					} else {
+						write_time(period.end().naive_local().time())
+					};
+					(period.start().naive_local().time(), close)
+				})
+				.collect()
+		};
+
+		let mut special: Vec<Value> = Vec::new();
+		for day in self.first.iter_days().take(self.days as usize) {
+			let (Some(actual), Some(usual), Some(end)) = (
+				self.open(space, day, 1, |_| true),
+				self.open(space, day, 1, recurring),
+				midnight(&self.timezone, day + Duration::days(1)),
+			) else {
+				continue;
+			};
+			let actual = hours(&actual, &end);
+			if actual == hours(&usual, &end) {
+				continue;
+			}
+			if actual.is_empty() {
+				special.push(Value::object(vec![
+					("startDate", date(day)),
+					("closed", true.into()),
+				]));
+			}
+			for (opens, closes) in actual {
+				special.push(Value::object(vec![
+					("startDate", date(day)),
+					("openTime", write_time(opens).into()),
+					("endDate", date(day)),
+					("closeTime", closes.into()),
+					("closed", false.into()),
+				]));
+			}
+		}
+		special
+	}
+
+	fn to_value(&self) -> Value {
+		let locations: Vec<Value> = self
+			.spaces
+			.iter()
+			.map(|space| {
+				Value::object(vec![
+					("name", space.name().into()),
+					(
+						"regularHours",
+						Value::object(vec![("periods", Value::Array(self.regular(space)))]),
+					),
+					(
+						"specialHours",
+						Value::object(vec![(
+							"specialHourPeriods",
+							Value::Array(self.special(space)),
+						)]),
+					),
+				])
+			})
+			.collect();
+		Value::object(vec![("locations", Value::Array(locations))])
+	}
+}
+
+impl<Tz: TimeZone> fmt::Display for Listings<'_, '_, Tz> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(&self.to_value().pretty())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::Listings;
+	use crate::{Exception, Part, Reason, Schedule, Space, Specifier, Status};
+	use chrono::{DateTime, FixedOffset, NaiveDate};
+
+	fn zone() -> FixedOffset {
+		FixedOffset::west_opt(5 * 3600).unwrap()
+	}
+
+	fn weekly(day: &str, time: &str) -> Specifier<FixedOffset> {
+		Specifier::Weekly {
+			day: day.to_string(),
+			time: time.to_string(),
+		}
+	}
+
+	fn exception(
+		from: Specifier<FixedOffset>,
+		to: Specifier<FixedOffset>,
+		note: &str,
+	) -> Exception<'static, FixedOffset> {
+		let mut exception = Exception::new().effective(from).expires(to);
+		*exception.effect_mut() = Some(Status::Closed(Reason::Exception(Some(note.to_string()))));
+		exception
+	}
+
+	fn exact(rfc3339: &str) -> Specifier<FixedOffset> {
+		Specifier::Exact(DateTime::parse_from_rfc3339(rfc3339).unwrap())
+	}
+
+	fn compact(json: &str) -> String {
+		json.replace(['\n', '\t'], "").replace(": ", ":")
+	}
+
+	fn library() -> Space<'static, FixedOffset> {
+		let schedule = ["Mon", "Tue", "Wed", "Thu", "Fri"]
+			.iter()
+			.fold(Schedule::new(), |schedule, day| {
+				schedule.part(
+					Part::new()
+						.open(weekly(day, "07:00"))
+						.close(weekly(day, "23:00")),
+				)
+			})
+			.part(
+				Part::new()
+					.open(weekly("Sat", "22:00"))
+					.close(weekly("Sun", "02:00")),
+			)
+			.exception(exception(
+				weekly("Thu", "12:00"),
+				weekly("Thu", "13:00"),
+				"Lunch",
+			))
+			.exception(exception(
+				exact("2019-11-28T00:00:00-05:00"),
+				exact("2019-11-29T00:00:00-05:00"),
+				"Thanksgiving",
+			))
+			.exception(exception(
+				exact("2019-11-29T12:00:00-05:00"),
+				exact("2019-11-30T00:00:00-05:00"),
+				"Closing early",
+			));
+		Space::new("Library").schedule(schedule)
+	}
+
+	#[test]
+	fn regular_and_special() {
+		let spaces = [library()];
+		let monday = NaiveDate::from_ymd_opt(2019, 11, 25).unwrap();
+		let json: String = compact(&Listings::new(&spaces, monday, 14, zone()).to_string());
+
+		let regular = concat!(
+			r#"{"locations":[{"name":"Library","regularHours":{"periods":["#,
+			r#"{"openDay":"MONDAY","openTime":"07:00","closeDay":"MONDAY","closeTime":"23:00"},"#,
+			r#"{"openDay":"TUESDAY","openTime":"07:00","closeDay":"TUESDAY","closeTime":"23:00"},"#,
+			r#"{"openDay":"WEDNESDAY","openTime":"07:00","closeDay":"WEDNESDAY","closeTime":"23:00"},"#,
+			r#"{"openDay":"THURSDAY","openTime":"07:00","closeDay":"THURSDAY","closeTime":"12:00"},"#,
+			r#"{"openDay":"THURSDAY","openTime":"13:00","closeDay":"THURSDAY","closeTime":"23:00"},"#,
+			r#"{"openDay":"FRIDAY","openTime":"07:00","closeDay":"FRIDAY","closeTime":"23:00"},"#,
+			r#"{"openDay":"SATURDAY","openTime":"22:00","closeDay":"SUNDAY","closeTime":"02:00"}]},"#,
+		);
+		let special = concat!(
+			r#""specialHours":{"specialHourPeriods":["#,
+			r#"{"startDate":{"year":2019,"month":11,"day":28},"closed":true},"#,
+			r#"{"startDate":{"year":2019,"month":11,"day":29},"openTime":"07:00","#,
+			r#""endDate":{"year":2019,"month":11,"day":29},"closeTime":"12:00","closed":false}]}}]}"#,
+		);
+		assert_eq!(json, format!("{regular}{special}"));
+
+		// Starting on Sunday, the night from Saturday is still one period.
+		let sunday = NaiveDate::from_ymd_opt(2019, 11, 24).unwrap();
+		let json: String = compact(&Listings::new(&spaces, sunday, 3, zone()).to_string());
+		assert_eq!(json.matches("openDay").count(), 7);
+		assert!(json.contains(r#""openDay":"SATURDAY","openTime":"22:00","closeDay":"SUNDAY""#));
+		assert!(json.contains(r#""specialHourPeriods":[]"#));
+	}
+
+	#[test]
+	fn always_open() {
+		let spaces = [Space::new("Lobby").schedule(
+			Schedule::new().part(
+				Part::new()
+					.open(Specifier::Daily {
+						time: "00:00".to_string(),
+					})
+					.close(Specifier::Daily {
+						time: "00:00".to_string(),
+					}),
+			),
+		)];
+		let monday = NaiveDate::from_ymd_opt(2019, 11, 25).unwrap();
+		let json: String = compact(&Listings::new(&spaces, monday, 1, zone()).to_string());
+		assert_eq!(json.matches(r#""openTime":"00:00","#).count(), 7);
+		assert!(json.contains(
+			r#"{"openDay":"SUNDAY","openTime":"00:00","closeDay":"SUNDAY","closeTime":"24:00"}"#
+		));
+	}
+}
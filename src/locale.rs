@@ -0,0 +1,519 @@
+//! The words hours are written in: names of days and months, 12- or 24-hour
+//! times, words for noon and midnight, which way round dates go, and the
+//! sentences of descriptions and statuses.
+//!
+//! [`English`], [`Spanish`] and [`Norwegian`] are provided; other languages
+//! can implement [`Locale`].
+
+use core::fmt;
+
+use chrono::{
+	DateTime, Datelike, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Timelike, Weekday,
+};
+
+use super::{Reason, Status, StatusChange};
+
+/// How times of day are written.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Clock {
+	/// E.g. `7 AM` and `11:30 PM`.
+	#[default]
+	TwelveHour,
+
+	/// E.g. `07:00` and `23:30`.
+	TwentyFourHour,
+}
+
+/// The words hours are written in.
+pub trait Locale: fmt::Debug {
+	/// A day of the week, e.g. `Mon`.
+	fn weekday(&self, day: Weekday) -> String;
+
+	/// A month, counting from 1, e.g. `Nov`.
+	fn month(&self, month: u32) -> String;
+
+	/// A time of day, e.g. `7 AM` or `noon`.  A `closing` time at midnight
+	/// is the end of the day, e.g. `24:00` rather than `00:00`.
+	fn time(&self, time: NaiveTime, clock: Clock, closing: bool) -> String;
+
+	/// Several things together, e.g. `7 AM–noon and 1 PM–5 PM`.
+	fn list(&self, items: &[String]) -> String;
+
+	/// The regular hours, from the days the space is `open`, e.g.
+	/// `Mon–Thu 7 AM–midnight`, and the days it's `closed`, e.g. `Sun`.
+	fn regular(&self, open: &[String], closed: &[String]) -> String;
+
+	/// Some `dates` with different `hours`, or closed without them, and the
+	/// `reason` if there is one.
+	fn exception(&self, dates: &str, hours: Option<&str>, reason: Option<&str>) -> String;
+
+	/// Whether a space is `open`, and why if there's a `reason`.
+	fn status(&self, open: bool, reason: Option<&str>) -> String;
+
+	/// That a space will open, or close unless `opening`, `when`, and why if
+	/// there's a `reason`.
+	fn change(&self, opening: bool, when: &str, reason: Option<&str>) -> String;
+
+	/// The clock the language usually writes times with.
+	fn clock(&self) -> Clock {
+		Clock::TwelveHour
+	}
+
+	/// A date, e.g. `Nov 28`.
+	fn date(&self, date: NaiveDate) -> String {
+		format!("{} {}", self.month(date.month()), date.day())
+	}
+
+	/// A run of dates, e.g. `Nov 28–29`.
+	fn dates(&self, first: NaiveDate, last: NaiveDate) -> String {
+		if first == last {
+			self.date(first)
+		} else if first.month() == last.month() {
+			format!("{}–{}", self.date(first), last.day())
+		} else {
+			format!("{}–{}", self.date(first), self.date(last))
+		}
+	}
+
+	/// A run of days of the week, e.g. `Mon–Thu`.
+	fn weekdays(&self, first: Weekday, last: Weekday) -> String {
+		if first == last {
+			self.weekday(first)
+		} else {
+			format!("{}–{}", self.weekday(first), self.weekday(last))
+		}
+	}
+
+	/// When a space opens and closes, e.g. `7 AM–midnight`.
+	fn hours(&self, open: NaiveTime, close: NaiveTime, clock: Clock) -> String {
+		format!(
+			"{}–{}",
+			self.time(open, clock, false),
+			self.time(close, clock, true)
+		)
+	}
+
+	/// A day of the week and time, e.g. `Mon 7 AM`.
+	fn when(&self, time: NaiveDateTime, clock: Clock) -> String {
+		format!(
+			"{} {}",
+			self.weekday(time.weekday()),
+			self.time(time.time(), clock, false)
+		)
+	}
+}
+
+/// `items` joined with commas, and `and` before the last.
+fn join(items: &[String], and: &str) -> String {
+	match items {
+		[] => String::new(),
+		[item] => item.clone(),
+		[items @ .., last] => format!("{} {and} {last}", items.join(", ")),
+	}
+}
+
+/// `sentence`, with `reason` after `joiner` if there is one.
+fn because(sentence: String, joiner: &str, reason: Option<&str>) -> String {
+	match reason {
+		Some(reason) => format!("{sentence}{joiner}{reason}"),
+		None => sentence,
+	}
+}
+
+/// A 24-hour time with `separator` between the hours and minutes, with
+/// midnight as `24` hours when `closing`.
+fn twenty_four(time: NaiveTime, separator: char, closing: bool) -> String {
+	if closing && time == NaiveTime::MIN {
+		format!("24{separator}00")
+	} else {
+		format!("{:02}{separator}{:02}", time.hour(), time.minute())
+	}
+}
+
+/// A 12-hour time with `am` and `pm`, and the words for `noon` and
+/// `midnight`.
+fn twelve(time: NaiveTime, am: &str, pm: &str, noon: &str, midnight: &str) -> String {
+	let (hour, minute) = (time.hour(), time.minute());
+	if minute == 0 && hour == 0 {
+		return midnight.to_string();
+	}
+	if minute == 0 && hour == 12 {
+		return noon.to_string();
+	}
+	let meridiem: &str = if hour < 12 { am } else { pm };
+	let hour: u32 = match hour % 12 {
+		0 => 12,
+		hour => hour,
+	};
+	if minute == 0 {
+		format!("{hour} {meridiem}")
+	} else {
+		format!("{hour}:{minute:02} {meridiem}")
+	}
+}
+
+/// A 12-hour time which always has its minutes, with `am` or `pm`, the way
+/// signs and posters write it, e.g. `7:00 AM` or `7:00 a.m.`
+pub(crate) fn twelve_with_minutes(time: NaiveTime, am: &str, pm: &str) -> String {
+	let meridiem: &str = if time.hour() < 12 { am } else { pm };
+	let hour: u32 = match time.hour() % 12 {
+		0 => 12,
+		hour => hour,
+	};
+	format!("{hour}:{:02} {meridiem}", time.minute())
+}
+
+/// English, with a 12-hour clock and months before days, e.g. `Nov 28`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct English;
+
+impl Locale for English {
+	fn weekday(&self, day: Weekday) -> String {
+		day.to_string()
+	}
+
+	fn month(&self, month: u32) -> String {
+		const MONTHS: [&str; 12] = [
+			"Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+		];
+		MONTHS[(month as usize + 11) % 12].to_string()
+	}
+
+	fn time(&self, time: NaiveTime, clock: Clock, closing: bool) -> String {
+		match clock {
+			Clock::TwelveHour => twelve(time, "AM", "PM", "noon", "midnight"),
+			Clock::TwentyFourHour => twenty_four(time, ':', closing),
+		}
+	}
+
+	fn list(&self, items: &[String]) -> String {
+		join(items, "and")
+	}
+
+	fn regular(&self, open: &[String], closed: &[String]) -> String {
+		if open.is_empty() {
+			"Closed.".to_string()
+		} else if closed.is_empty() {
+			format!("Open {}.", open.join(", "))
+		} else {
+			format!("Open {}; closed {}.", open.join(", "), closed.join(", "))
+		}
+	}
+
+	fn exception(&self, dates: &str, hours: Option<&str>, reason: Option<&str>) -> String {
+		let sentence: String = match hours {
+			Some(hours) => format!("Open {dates} {hours}"),
+			None => format!("Closed {dates}"),
+		};
+		because(sentence, " for ", reason) + "."
+	}
+
+	fn status(&self, open: bool, reason: Option<&str>) -> String {
+		because(
+			if open { "Open" } else { "Closed" }.to_string(),
+			": ",
+			reason,
+		)
+	}
+
+	fn change(&self, opening: bool, when: &str, reason: Option<&str>) -> String {
+		let verb: &str = if opening { "Opens" } else { "Closes" };
+		because(format!("{verb} {when}"), ": ", reason)
+	}
+}
+
+/// Spanish, with a 24-hour clock and days before months, e.g. `28 nov`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Spanish;
+
+impl Locale for Spanish {
+	fn weekday(&self, day: Weekday) -> String {
+		const DAYS: [&str; 7] = ["lun", "mar", "mié", "jue", "vie", "sáb", "dom"];
+		DAYS[day.num_days_from_monday() as usize].to_string()
+	}
+
+	fn month(&self, month: u32) -> String {
+		const MONTHS: [&str; 12] = [
+			"ene", "feb", "mar", "abr", "may", "jun", "jul", "ago", "sept", "oct", "nov", "dic",
+		];
+		MONTHS[(month as usize + 11) % 12].to_string()
+	}
+
+	fn time(&self, time: NaiveTime, clock: Clock, closing: bool) -> String {
+		match clock {
+			Clock::TwelveHour => twelve(time, "a. m.", "p. m.", "mediodía", "medianoche"),
+			Clock::TwentyFourHour => twenty_four(time, ':', closing),
+		}
+	}
+
+	fn list(&self, items: &[String]) -> String {
+		join(items, "y")
+	}
+
+	fn regular(&self, open: &[String], closed: &[String]) -> String {
+		if open.is_empty() {
+			"Cerrado.".to_string()
+		} else if closed.is_empty() {
+			format!("Abierto {}.", open.join(", "))
+		} else {
+			format!(
+				"Abierto {}; cerrado {}.",
+				open.join(", "),
+				closed.join(", ")
+			)
+		}
+	}
+
+	fn exception(&self, dates: &str, hours: Option<&str>, reason: Option<&str>) -> String {
+		let sentence: String = match hours {
+			Some(hours) => format!("Abierto el {dates} {hours}"),
+			None => format!("Cerrado el {dates}"),
+		};
+		because(sentence, " por ", reason) + "."
+	}
+
+	fn status(&self, open: bool, reason: Option<&str>) -> String {
+		because(
+			if open { "Abierto" } else { "Cerrado" }.to_string(),
+			": ",
+			reason,
+		)
+	}
+
+	fn change(&self, opening: bool, when: &str, reason: Option<&str>) -> String {
+		let verb: &str = if opening { "Abre" } else { "Cierra" };
+		because(format!("{verb} el {when}"), ": ", reason)
+	}
+
+	fn clock(&self) -> Clock {
+		Clock::TwentyFourHour
+	}
+
+	fn date(&self, date: NaiveDate) -> String {
+		format!("{} {}", date.day(), self.month(date.month()))
+	}
+
+	fn dates(&self, first: NaiveDate, last: NaiveDate) -> String {
+		if first == last {
+			self.date(first)
+		} else if first.month() == last.month() {
+			format!("{}–{}", first.day(), self.date(last))
+		} else {
+			format!("{}–{}", self.date(first), self.date(last))
+		}
+	}
+}
+
+/// Norwegian (Bokmål), with a 24-hour clock written with a full stop and
+/// days before months, e.g. `28. nov.`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Norwegian;
+
+impl Locale for Norwegian {
+	fn weekday(&self, day: Weekday) -> String {
+		const DAYS: [&str; 7] = ["man", "tir", "ons", "tor", "fre", "lør", "søn"];
+		DAYS[day.num_days_from_monday() as usize].to_string()
+	}
+
+	fn month(&self, month: u32) -> String {
+		const MONTHS: [&str; 12] = [
+			"jan.", "feb.", "mar.", "apr.", "mai", "jun.", "jul.", "aug.", "sep.", "okt.", "nov.", "des.",
+		];
+		MONTHS[(month as usize + 11) % 12].to_string()
+	}
+
+	fn time(&self, time: NaiveTime, clock: Clock, closing: bool) -> String {
+		match clock {
+			Clock::TwelveHour => twelve(time, "a.m.", "p.m.", "middag", "midnatt"),
+			Clock::TwentyFourHour => twenty_four(time, '.', closing),
+		}
+	}
+
+	fn list(&self, items: &[String]) -> String {
+		join(items, "og")
+	}
+
+	fn regular(&self, open: &[String], closed: &[String]) -> String {
+		if open.is_empty() {
+			"Stengt.".to_string()
+		} else if closed.is_empty() {
+			format!("Åpent {}.", open.join(", "))
+		} else {
+			format!("Åpent {}; stengt {}.", open.join(", "), closed.join(", "))
+		}
+	}
+
+	fn exception(&self, dates: &str, hours: Option<&str>, reason: Option<&str>) -> String {
+		let sentence: String = match hours {
+			Some(hours) => format!("Åpent {dates} {hours}"),
+			None => format!("Stengt {dates}"),
+		};
+		because(sentence, " på grunn av ", reason) + "."
+	}
+
+	fn status(&self, open: bool, reason: Option<&str>) -> String {
+		because(
+			if open { "Åpent" } else { "Stengt" }.to_string(),
+			": ",
+			reason,
+		)
+	}
+
+	fn change(&self, opening: bool, when: &str, reason: Option<&str>) -> String {
+		let verb: &str = if opening { "Åpner" } else { "Stenger" };
+		because(format!("{verb} {when}"), ": ", reason)
+	}
+
+	fn clock(&self) -> Clock {
+		Clock::TwentyFourHour
+	}
+
+	fn date(&self, date: NaiveDate) -> String {
+		format!("{}. {}", date.day(), self.month(date.month()))
+	}
+
+	fn dates(&self, first: NaiveDate, last: NaiveDate) -> String {
+		if first == last {
+			self.date(first)
+		} else if first.month() == last.month() {
+			format!("{}.–{}", first.day(), self.date(last))
+		} else {
+			format!("{}–{}", self.date(first), self.date(last))
+		}
+	}
+
+	fn when(&self, time: NaiveDateTime, clock: Clock) -> String {
+		format!(
+			"{} kl. {}",
+			self.weekday(time.weekday()),
+			self.time(time.time(), clock, false)
+		)
+	}
+}
+
+impl<Tz: TimeZone> Reason<'_, Tz> {
+	/// The note the reason gives, if any: an exception's note or a part's
+	/// notes.
+	#[must_use]
+	pub fn note(&self) -> Option<String> {
+		match self {
+			Reason::Exception(note) => note.clone(),
+			Reason::Part(Some(part)) if !part.notes().is_empty() => Some(part.notes().join("; ")),
+			Reason::Part(_) => None,
+		}
+	}
+}
+
+impl<Tz: TimeZone> Status<'_, Tz> {
+	/// The status in `locale`'s words, e.g. `Closed: Thanksgiving`.
+	#[must_use]
+	pub fn describe(&self, locale: &dyn Locale) -> String {
+		match self {
+			Status::Open(reason) => locale.status(true, reason.note().as_deref()),
+			Status::Closed(reason) => locale.status(false, reason.note().as_deref()),
+		}
+	}
+}
+
+impl<Tz: TimeZone> fmt::Display for Status<'_, Tz> {
+	fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(formatter, "{}", self.describe(&English))
+	}
+}
+
+impl<Tz: TimeZone> StatusChange<'_, Tz> {
+	/// The change in `locale`'s words, with times on `clock`, e.g.
+	/// `Opens Mon 7 AM`.
+	#[must_use]
+	pub fn describe(&self, locale: &dyn Locale, clock: Clock) -> String {
+		let (opening, time, reason): (bool, &DateTime<Tz>, &Reason<'_, Tz>) = match self {
+			StatusChange::Opening(time, reason) => (true, time, reason),
+			StatusChange::Closing(time, reason) => (false, time, reason),
+		};
+		locale.change(
+			opening,
+			&locale.when(time.naive_local(), clock),
+			reason.note().as_deref(),
+		)
+	}
+}
+
+impl<Tz: TimeZone> fmt::Display for StatusChange<'_, Tz> {
+	fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(formatter, "{}", self.describe(&English, English.clock()))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{twelve_with_minutes, Clock, English, Locale, Norwegian, Spanish};
+	use crate::{Part, Reason, Status, StatusChange};
+	use chrono::{DateTime, FixedOffset, NaiveDate, NaiveTime};
+
+	#[test]
+	fn times_and_dates() {
+		let time = |hour, minute| NaiveTime::from_hms_opt(hour, minute, 0).unwrap();
+		let date = |month, day| NaiveDate::from_ymd_opt(2019, month, day).unwrap();
+
+		assert_eq!(
+			English.hours(time(7, 30), time(0, 0), Clock::TwelveHour),
+			"7:30 AM–midnight"
+		);
+		assert_eq!(
+			English.hours(time(12, 0), time(0, 0), Clock::TwentyFourHour),
+			"12:00–24:00"
+		);
+		assert_eq!(
+			Spanish.hours(time(7, 0), time(12, 0), Clock::TwelveHour),
+			"7 a. m.–mediodía"
+		);
+		assert_eq!(
+			Norwegian.hours(time(7, 0), time(23, 30), Norwegian.clock()),
+			"07.00–23.30"
+		);
+		assert_eq!(twelve_with_minutes(time(0, 0), "AM", "PM"), "12:00 AM");
+		assert_eq!(
+			twelve_with_minutes(time(19, 5), "a.m.", "p.m."),
+			"7:05 p.m."
+		);
+
+		assert_eq!(English.dates(date(11, 28), date(11, 29)), "Nov 28–29");
+		assert_eq!(Spanish.dates(date(11, 28), date(11, 29)), "28–29 nov");
+		assert_eq!(Norwegian.dates(date(11, 28), date(11, 29)), "28.–29. nov.");
+		assert_eq!(
+			Norwegian.dates(date(11, 30), date(12, 1)),
+			"30. nov.–1. des."
+		);
+		assert_eq!(
+			Spanish.list(&["a".to_string(), "b".to_string(), "c".to_string()]),
+			"a, b y c"
+		);
+	}
+
+	#[test]
+	fn statuses() {
+		let part: Part<FixedOffset> = Part::new().note("Front desk only");
+		let open = Status::Open(Reason::Part(Some(&part)));
+		let closed: Status<FixedOffset> =
+			Status::Closed(Reason::Exception(Some("Thanksgiving".to_string())));
+
+		assert_eq!(open.to_string(), "Open: Front desk only");
+		assert_eq!(closed.describe(&Spanish), "Cerrado: Thanksgiving");
+		assert_eq!(
+			Status::<FixedOffset>::Closed(Reason::Part(None)).describe(&Norwegian),
+			"Stengt"
+		);
+
+		let time = DateTime::parse_from_rfc3339("2019-11-25T07:00:00-06:00").unwrap();
+		let change = StatusChange::Opening(time, Reason::Part(None));
+		assert_eq!(change.to_string(), "Opens Mon 7 AM");
+		assert_eq!(
+			change.describe(&Spanish, Clock::TwentyFourHour),
+			"Abre el lun 07:00"
+		);
+		assert_eq!(
+			StatusChange::Closing(time, Reason::Part(None)).describe(&Norwegian, Clock::TwentyFourHour),
+			"Stenger man kl. 07.00"
+		);
+	}
+}
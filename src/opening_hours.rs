@@ -0,0 +1,1130 @@
+//! OpenStreetMap's `opening_hours` syntax, e.g.
+//! `Mo-Fr 07:00-23:00; Sa 09:00-17:00; PH off`.
+//!
+//! What's supported is a common subset: weekday ranges, lists of time
+//! ranges (past midnight too), month and date selectors with optional
+//! years, public and school holidays, `open`/`off`/`closed`, comments,
+//! `24/7`, and rules separated by `;` or added to with `,`.  Rules apply in
+//! order, and each later rule replaces the hours of the days it selects,
+//! unless it's added with `,`.
+
+use core::convert::TryFrom;
+use core::fmt;
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, TimeZone, Timelike, Weekday};
+
+use super::specifier::{midnight, time_of_day};
+use super::timeline;
+use super::{Exception, Part, Reason, Schedule, Specifier, Status};
+
+const MINUTES_PER_DAY: u32 = 24 * 60;
+
+const WEEKDAYS: [(&str, Weekday); 7] = [
+	("Mo", Weekday::Mon),
+	("Tu", Weekday::Tue),
+	("We", Weekday::Wed),
+	("Th", Weekday::Thu),
+	("Fr", Weekday::Fri),
+	("Sa", Weekday::Sat),
+	("Su", Weekday::Sun),
+];
+
+const MONTHS: [&str; 12] = [
+	"Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Something in an `opening_hours` value which couldn't be read.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OpeningHoursError {
+	position: usize,
+	message: String,
+}
+
+impl OpeningHoursError {
+	fn new(position: usize, message: &str) -> Self {
+		Self {
+			position,
+			message: message.to_string(),
+		}
+	}
+
+	/// Where the problem is, in characters from the start, counting from 1.
+	#[must_use]
+	pub fn position(&self) -> usize {
+		self.position
+	}
+
+	#[must_use]
+	pub fn message(&self) -> &str {
+		&self.message
+	}
+}
+
+impl fmt::Display for OpeningHoursError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "at {}: {}", self.position, self.message)
+	}
+}
+
+impl std::error::Error for OpeningHoursError {}
+
+type Result<T> = core::result::Result<T, OpeningHoursError>;
+
+/// Public and school holidays, for the `PH` and `SH` selectors.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Holidays {
+	public: Vec<NaiveDate>,
+	school: Vec<(NaiveDate, NaiveDate)>,
+}
+
+impl Holidays {
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Add a public holiday.
+	#[must_use]
+	pub fn public(mut self, date: NaiveDate) -> Self {
+		self.public.push(date);
+		self
+	}
+
+	/// Add school holidays from `first` until `last`, inclusive.
+	#[must_use]
+	pub fn school(mut self, first: NaiveDate, last: NaiveDate) -> Self {
+		self.school.push((first, last));
+		self
+	}
+
+	#[must_use]
+	pub fn is_public(&self, date: NaiveDate) -> bool {
+		self.public.contains(&date)
+	}
+
+	#[must_use]
+	pub fn is_school(&self, date: NaiveDate) -> bool {
+		self
+			.school
+			.iter()
+			.any(|(first, last)| (*first..=*last).contains(&date))
+	}
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+	Word(String),
+	Number(u32),
+	/// Minutes since midnight.
+	Time(u32),
+	Dash,
+	Comma,
+	Semicolon,
+	Comment(String),
+	AlwaysOpen,
+}
+
+fn tokens(text: &str) -> Result<Vec<(usize, Token)>> {
+	let chars: Vec<char> = text.chars().collect();
+	let mut tokens: Vec<(usize, Token)> = Vec::new();
+	let mut index: usize = 0;
+	while let Some(c) = chars.get(index).copied() {
+		let position: usize = index + 1;
+		let run = |index: usize, test: fn(&char) -> bool| -> String {
+			chars[index..].iter().take_while(|c| test(c)).collect()
+		};
+		match c {
+			c if c.is_whitespace() => index += 1,
+			'-' => {
+				tokens.push((position, Token::Dash));
+				index += 1;
+			}
+			',' => {
+				tokens.push((position, Token::Comma));
+				index += 1;
+			}
+			';' => {
+				tokens.push((position, Token::Semicolon));
+				index += 1;
+			}
+			'"' => {
+				let comment: String = run(index + 1, |c| *c != '"');
+				if chars.get(index + 1 + comment.chars().count()) != Some(&'"') {
+					return Err(OpeningHoursError::new(position, "comment is never closed"));
+				}
+				index += comment.chars().count() + 2;
+				tokens.push((position, Token::Comment(comment)));
+			}
+			'|' => {
+				return Err(OpeningHoursError::new(
+					position,
+					"fallback rules (`||`) aren't supported",
+				))
+			}
+			c if c.is_ascii_digit() => {
+				let digits: String = run(index, char::is_ascii_digit);
+				index += digits.len();
+				let number: u32 = digits
+					.parse()
+					.map_err(|_| OpeningHoursError::new(position, "number is too large"))?;
+				if chars.get(index) == Some(&':') {
+					let minutes: String = run(index + 1, char::is_ascii_digit);
+					if minutes.len() != 2 || digits.len() > 2 {
+						return Err(OpeningHoursError::new(position, "times are written HH:MM"));
+					}
+					index += 3;
+					let minutes: u32 = minutes.parse().unwrap_or_default();
+					if minutes >= 60 || number > 48 {
+						return Err(OpeningHoursError::new(position, "not a time"));
+					}
+					tokens.push((position, Token::Time(number * 60 + minutes)));
+				} else if number == 24
+					&& chars.get(index) == Some(&'/')
+					&& chars.get(index + 1) == Some(&'7')
+				{
+					index += 2;
+					tokens.push((position, Token::AlwaysOpen));
+				} else {
+					tokens.push((position, Token::Number(number)));
+				}
+			}
+			c if c.is_alphabetic() => {
+				let word: String = run(index, |c| c.is_alphabetic());
+				index += word.chars().count();
+				tokens.push((position, Token::Word(word)));
+			}
+			c => {
+				return Err(OpeningHoursError::new(
+					position,
+					&format!("unexpected `{c}`"),
+				))
+			}
+		}
+	}
+	Ok(tokens)
+}
+
+/// A day of a month, optionally of a particular year.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct MonthDay {
+	year: Option<i32>,
+	month: u32,
+	/// `None` for the whole month.
+	day: Option<u32>,
+}
+
+impl MonthDay {
+	fn matches_from(self, date: NaiveDate) -> bool {
+		match self.year {
+			Some(year) if year != date.year() => year < date.year(),
+			_ => (self.month, self.day.unwrap_or(1)) <= (date.month(), date.day()),
+		}
+	}
+
+	fn matches_until(self, date: NaiveDate) -> bool {
+		match self.year {
+			Some(year) if year != date.year() => year > date.year(),
+			_ => (self.month, self.day.unwrap_or(31)) >= (date.month(), date.day()),
+		}
+	}
+}
+
+impl fmt::Display for MonthDay {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		if let Some(year) = self.year {
+			write!(f, "{year} ")?;
+		}
+		f.write_str(MONTHS[usize::try_from(self.month - 1).unwrap_or_default()])?;
+		if let Some(day) = self.day {
+			write!(f, " {day:02}")?;
+		}
+		Ok(())
+	}
+}
+
+/// A range of days, e.g. `Dec 24-26` or `Jun-Aug`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Dates {
+	first: MonthDay,
+	last: MonthDay,
+}
+
+impl Dates {
+	fn contains(self, date: NaiveDate) -> bool {
+		let (from, until) = (self.first.matches_from(date), self.last.matches_until(date));
+		let wraps: bool = self.first.year.is_none()
+			&& self.last.year.is_none()
+			&& (self.first.month, self.first.day) > (self.last.month, self.last.day);
+		if wraps {
+			from || until
+		} else {
+			from && until
+		}
+	}
+}
+
+impl fmt::Display for Dates {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{}", self.first)?;
+		if self.first == self.last {
+			return Ok(());
+		}
+		match (
+			self.first.year == self.last.year,
+			self.first.month == self.last.month,
+			self.last.day,
+		) {
+			(true, true, Some(day)) => write!(f, "-{day:02}"),
+			(true, _, _) => write!(
+				f,
+				"-{}",
+				MonthDay {
+					year: None,
+					..self.last
+				}
+			),
+			_ => write!(f, "-{}", self.last),
+		}
+	}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Days {
+	Weekdays(Weekday, Weekday),
+	PublicHolidays,
+	SchoolHolidays,
+}
+
+impl Days {
+	fn matches(self, weekday: Weekday, date: Option<NaiveDate>, holidays: &Holidays) -> bool {
+		match self {
+			Self::Weekdays(first, last) => {
+				let (first, last, day) = (
+					first.num_days_from_monday(),
+					last.num_days_from_monday(),
+					weekday.num_days_from_monday(),
+				);
+				if first <= last {
+					(first..=last).contains(&day)
+				} else {
+					day >= first || day <= last
+				}
+			}
+			Self::PublicHolidays => date.is_some_and(|date| holidays.is_public(date)),
+			Self::SchoolHolidays => date.is_some_and(|date| holidays.is_school(date)),
+		}
+	}
+}
+
+fn weekday_name(weekday: Weekday) -> &'static str {
+	WEEKDAYS
+		.iter()
+		.find(|(_, day)| *day == weekday)
+		.map_or("", |(name, _)| name)
+}
+
+impl fmt::Display for Days {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Weekdays(first, last) if first == last => f.write_str(weekday_name(*first)),
+			Self::Weekdays(first, last) => {
+				write!(f, "{}-{}", weekday_name(*first), weekday_name(*last))
+			}
+			Self::PublicHolidays => f.write_str("PH"),
+			Self::SchoolHolidays => f.write_str("SH"),
+		}
+	}
+}
+
+/// A time range, in minutes since midnight; it ends after it starts, and
+/// may end after midnight.
+type Times = (u32, u32);
+
+fn time(minutes: u32) -> String {
+	format!("{:02}:{:02}", minutes / 60, minutes % 60)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Rule {
+	/// Whether the rule adds to the hours before it rather than replacing
+	/// them.
+	additional: bool,
+	dates: Vec<Dates>,
+	/// Groups of days, each of which must match one of its days, so that
+	/// `SH Mo-Fr` means weekdays during school holidays.
+	days: Vec<Vec<Days>>,
+	times: Vec<Times>,
+	open: bool,
+	comment: Option<String>,
+}
+
+impl Rule {
+	/// Whether the rule applies on `weekday`, and on `date` if given; without
+	/// a date, rules for particular dates and holidays never apply.
+	fn matches(&self, weekday: Weekday, date: Option<NaiveDate>, holidays: &Holidays) -> bool {
+		let dates: bool = self.dates.is_empty()
+			|| date.is_some_and(|date| self.dates.iter().any(|dates| dates.contains(date)));
+		let days: bool = self.days.iter().all(|group| {
+			group
+				.iter()
+				.any(|days| days.matches(weekday, date, holidays))
+		});
+		dates && days
+	}
+}
+
+impl fmt::Display for Rule {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let mut words: Vec<String> = Vec::new();
+		let list = |items: Vec<String>| items.join(",");
+		if !self.dates.is_empty() {
+			words.push(list(self.dates.iter().map(ToString::to_string).collect()));
+		}
+		for group in &self.days {
+			words.push(list(group.iter().map(ToString::to_string).collect()));
+		}
+		match (self.open, self.times.as_slice()) {
+			(true, [(0, MINUTES_PER_DAY)]) if words.is_empty() => words.push("24/7".to_string()),
+			(true, [] | [(0, MINUTES_PER_DAY)]) => words.push("open".to_string()),
+			(true, times) => words.push(list(
+				times
+					.iter()
+					.map(|(start, end)| {
+						// Ranges past midnight are written as they usually are,
+						// e.g. `22:00-02:00`, when that's unambiguous.
+						let end: u32 = if *end > MINUTES_PER_DAY && end - MINUTES_PER_DAY <= *start {
+							end - MINUTES_PER_DAY
+						} else {
+							*end
+						};
+						format!("{}-{}", time(*start), time(end))
+					})
+					.collect(),
+			)),
+			(false, _) => words.push("off".to_string()),
+		}
+		if let Some(comment) = &self.comment {
+			words.push(format!("\"{comment}\""));
+		}
+		f.write_str(&words.join(" "))
+	}
+}
+
+struct Parser {
+	tokens: Vec<(usize, Token)>,
+	index: usize,
+	end: usize,
+}
+
+impl Parser {
+	fn peek(&self) -> Option<&Token> {
+		self.tokens.get(self.index).map(|(_, token)| token)
+	}
+
+	fn peek_at(&self, ahead: usize) -> Option<&Token> {
+		self.tokens.get(self.index + ahead).map(|(_, token)| token)
+	}
+
+	fn position(&self) -> usize {
+		self
+			.tokens
+			.get(self.index)
+			.map_or(self.end, |(position, _)| *position)
+	}
+
+	fn error<T>(&self, message: &str) -> Result<T> {
+		Err(OpeningHoursError::new(self.position(), message))
+	}
+
+	fn word(&self, ahead: usize) -> Option<String> {
+		match self.peek_at(ahead) {
+			Some(Token::Word(word)) => Some(word.to_lowercase()),
+			_ => None,
+		}
+	}
+
+	fn weekday(&self, ahead: usize) -> Option<Weekday> {
+		let word: String = self.word(ahead)?;
+		WEEKDAYS
+			.iter()
+			.find(|(name, _)| name.to_lowercase() == word)
+			.map(|(_, day)| *day)
+	}
+
+	fn month(&self, ahead: usize) -> Option<u32> {
+		let word: String = self.word(ahead)?;
+		MONTHS
+			.iter()
+			.position(|name| name.to_lowercase() == word)
+			.and_then(|index| u32::try_from(index + 1).ok())
+	}
+
+	fn is_days(&self, ahead: usize) -> bool {
+		self.weekday(ahead).is_some() || matches!(self.word(ahead).as_deref(), Some("ph" | "sh"))
+	}
+
+	fn is_dates(&self, ahead: usize) -> bool {
+		self.month(ahead).is_some()
+			|| matches!(self.peek_at(ahead), Some(Token::Number(year)) if *year >= 1000)
+	}
+
+	fn rules(&mut self) -> Result<Vec<Rule>> {
+		let mut rules: Vec<Rule> = Vec::new();
+		let mut additional: bool = false;
+		loop {
+			if self.peek().is_none() {
+				// A trailing `;` is harmless.
+				if rules.is_empty() {
+					return self.error("expected a rule");
+				}
+				return Ok(rules);
+			}
+			let mut rule: Rule = self.rule()?;
+			rule.additional = additional;
+			rules.push(rule);
+			match self.peek() {
+				None => return Ok(rules),
+				Some(Token::Semicolon) => additional = false,
+				Some(Token::Comma) => additional = true,
+				Some(_) => return self.error("expected `;` between rules"),
+			}
+			self.index += 1;
+		}
+	}
+
+	fn rule(&mut self) -> Result<Rule> {
+		let mut rule: Rule = Rule {
+			additional: false,
+			dates: Vec::new(),
+			days: Vec::new(),
+			times: Vec::new(),
+			open: true,
+			comment: None,
+		};
+
+		if self.peek() == Some(&Token::AlwaysOpen) {
+			self.index += 1;
+			rule.times.push((0, MINUTES_PER_DAY));
+		} else {
+			if self.is_dates(0) {
+				loop {
+					rule.dates.push(self.dates()?);
+					if self.peek() == Some(&Token::Comma) && self.is_dates(1) {
+						self.index += 1;
+					} else {
+						break;
+					}
+				}
+			}
+			while self.is_days(0) {
+				let mut group: Vec<Days> = Vec::new();
+				loop {
+					group.push(self.days()?);
+					if self.peek() == Some(&Token::Comma) && self.is_days(1) {
+						self.index += 1;
+					} else {
+						break;
+					}
+				}
+				rule.days.push(group);
+			}
+			if let Some(Token::Time(_)) = self.peek() {
+				loop {
+					rule.times.push(self.times()?);
+					if self.peek() == Some(&Token::Comma) && matches!(self.peek_at(1), Some(Token::Time(_))) {
+						self.index += 1;
+					} else {
+						break;
+					}
+				}
+			}
+		}
+
+		match self.word(0).as_deref() {
+			Some("open") => self.index += 1,
+			Some("off" | "closed") => {
+				self.index += 1;
+				rule.open = false;
+				rule.times.clear();
+			}
+			Some("unknown") => return self.error("`unknown` isn't supported"),
+			_ => {}
+		}
+		if let Some(Token::Comment(comment)) = self.peek() {
+			rule.comment = Some(comment.clone());
+			self.index += 1;
+		}
+
+		let selected: bool = !rule.dates.is_empty() || !rule.days.is_empty();
+		if !selected && rule.times.is_empty() && rule.open && rule.comment.is_none() {
+			return self.error("expected days, dates, times or a state");
+		}
+		if rule.open && rule.times.is_empty() {
+			rule.times.push((0, MINUTES_PER_DAY));
+		}
+		Ok(rule)
+	}
+
+	fn day(&mut self, month: Option<u32>) -> Result<MonthDay> {
+		let year: Option<i32> = match self.peek() {
+			Some(Token::Number(year)) if *year >= 1000 => {
+				let year: i32 = i32::try_from(*year).unwrap_or_default();
+				self.index += 1;
+				Some(year)
+			}
+			_ => None,
+		};
+		let month: u32 = match (self.month(0), month) {
+			(Some(month), _) => {
+				self.index += 1;
+				month
+			}
+			(None, Some(month)) if year.is_none() => month,
+			_ => return self.error("expected a month"),
+		};
+		let day: Option<u32> = match self.peek() {
+			Some(Token::Number(day)) if (1..=31).contains(day) => {
+				let day: u32 = *day;
+				self.index += 1;
+				Some(day)
+			}
+			_ => None,
+		};
+		Ok(MonthDay { year, month, day })
+	}
+
+	fn dates(&mut self) -> Result<Dates> {
+		let first: MonthDay = self.day(None)?;
+		if self.peek() != Some(&Token::Dash) {
+			return Ok(Dates { first, last: first });
+		}
+		self.index += 1;
+		// `Dec 24-26` stays within the month; `Dec 24-Jan 02` doesn't.
+		let mut last: MonthDay =
+			if first.day.is_some() && matches!(self.peek(), Some(Token::Number(day)) if *day <= 31) {
+				self.day(Some(first.month))?
+			} else {
+				self.day(None)?
+			};
+		if last.year.is_none() {
+			last.year = first.year;
+		}
+		if first.day.is_some() != last.day.is_some() {
+			return self.error("a date range needs days at both ends or neither");
+		}
+		Ok(Dates { first, last })
+	}
+
+	fn days(&mut self) -> Result<Days> {
+		match self.word(0).as_deref() {
+			Some("ph") => {
+				self.index += 1;
+				return Ok(Days::PublicHolidays);
+			}
+			Some("sh") => {
+				self.index += 1;
+				return Ok(Days::SchoolHolidays);
+			}
+			_ => {}
+		}
+		let Some(first) = self.weekday(0) else {
+			return self.error("expected a weekday");
+		};
+		self.index += 1;
+		if self.peek() != Some(&Token::Dash) {
+			return Ok(Days::Weekdays(first, first));
+		}
+		self.index += 1;
+		let Some(last) = self.weekday(0) else {
+			return self.error("expected a weekday");
+		};
+		self.index += 1;
+		Ok(Days::Weekdays(first, last))
+	}
+
+	fn times(&mut self) -> Result<Times> {
+		let Some(Token::Time(start)) = self.peek().cloned() else {
+			return self.error("expected a time");
+		};
+		self.index += 1;
+		if self.peek() != Some(&Token::Dash) {
+			return self.error("expected `-` and a closing time");
+		}
+		self.index += 1;
+		let Some(Token::Time(end)) = self.peek().cloned() else {
+			return self.error("expected a closing time");
+		};
+		self.index += 1;
+		if start >= MINUTES_PER_DAY {
+			return self.error("times must start before 24:00");
+		}
+		// Ending at or before the start means ending the next day.
+		let end: u32 = if end <= start {
+			end + MINUTES_PER_DAY
+		} else {
+			end
+		};
+		Ok((start, end))
+	}
+}
+
+/// Hours written in the `opening_hours` syntax.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OpeningHours {
+	rules: Vec<Rule>,
+}
+
+impl OpeningHours {
+	/// Read `text`.
+	///
+	/// # Errors
+	///
+	/// Returns an error, with where it is, if `text` isn't valid or uses
+	/// something that isn't supported, such as fallback rules or `sunrise`.
+	pub fn parse(text: &str) -> Result<Self> {
+		let mut parser: Parser = Parser {
+			tokens: tokens(text)?,
+			index: 0,
+			end: text.chars().count() + 1,
+		};
+		Ok(Self {
+			rules: parser.rules()?,
+		})
+	}
+
+	/// The times open on `weekday`, and on `date` if given, each with its
+	/// comment, and the comment of the last rule that applied.
+	fn hours(
+		&self,
+		weekday: Weekday,
+		date: Option<NaiveDate>,
+		holidays: &Holidays,
+	) -> (Vec<(Times, Option<String>)>, Option<String>) {
+		let mut hours: Vec<(Times, Option<String>)> = Vec::new();
+		let mut comment: Option<String> = None;
+		for rule in &self.rules {
+			if !rule.matches(weekday, date, holidays) {
+				continue;
+			}
+			if !rule.additional {
+				hours.clear();
+			}
+			hours.extend(
+				rule
+					.times
+					.iter()
+					.map(|times| (*times, rule.comment.clone())),
+			);
+			comment.clone_from(&rule.comment);
+		}
+		hours.sort_by_key(|(times, _)| *times);
+		(hours, comment)
+	}
+
+	/// The hours as a schedule, with parts for the hours of each weekday, and
+	/// exceptions for the days from `start` until `end` whose hours differ
+	/// because of their dates or holidays.
+	#[must_use]
+	pub fn schedule<'schedule, Tz: TimeZone>(
+		&self,
+		holidays: &Holidays,
+		start: &DateTime<Tz>,
+		end: &DateTime<Tz>,
+	) -> Schedule<'schedule, Tz> {
+		let weekdays: Vec<Vec<(Times, Option<String>)>> = WEEKDAYS
+			.iter()
+			.map(|(_, weekday)| self.hours(*weekday, None, holidays).0)
+			.collect();
+		let clock = |minutes: u32| time(minutes % MINUTES_PER_DAY);
+		let note = |part: Part<Tz>, comment: &Option<String>| match comment {
+			Some(comment) => part.note(comment),
+			None => part,
+		};
+
+		let mut schedule: Schedule<'schedule, Tz> = Schedule::new();
+		if weekdays.iter().all(|hours| *hours == weekdays[0]) {
+			for ((open, close), comment) in &weekdays[0] {
+				schedule = schedule.part(note(
+					Part::new()
+						.open(Specifier::Daily { time: clock(*open) })
+						.close(Specifier::Daily {
+							time: clock(*close),
+						}),
+					comment,
+				));
+			}
+		} else {
+			for ((_, weekday), hours) in WEEKDAYS.iter().zip(&weekdays) {
+				for ((open, close), comment) in hours {
+					let later: u32 = close / MINUTES_PER_DAY;
+					let closes: Weekday = (0..later).fold(*weekday, |day, _| day.succ());
+					schedule = schedule.part(note(
+						Part::new()
+							.open(Specifier::Weekly {
+								day: weekday.to_string(),
+								time: clock(*open),
+							})
+							.close(Specifier::Weekly {
+								day: closes.to_string(),
+								time: clock(*close),
+							}),
+						comment,
+					));
+				}
+			}
+		}
+
+		// Days which differ from their weekday get exceptions: their hours
+		// open, then the rest of the day closed.
+		let timezone: Tz = start.timezone();
+		let at = |date: NaiveDate, minutes: u32| {
+			timezone
+				.from_local_datetime(
+					&(date.and_time(NaiveTime::MIN) + Duration::minutes(i64::from(minutes))),
+				)
+				.earliest()
+		};
+		let mut opens: Vec<Exception<'schedule, Tz>> = Vec::new();
+		let mut closes: Vec<Exception<'schedule, Tz>> = Vec::new();
+		let exception = |from: DateTime<Tz>, until: DateTime<Tz>, status: Status<'schedule, Tz>| {
+			let mut exception: Exception<'schedule, Tz> = Exception::new()
+				.effective(Specifier::Exact(from))
+				.expires(Specifier::Exact(until));
+			*exception.effect_mut() = Some(status);
+			exception
+		};
+		for date in start
+			.naive_local()
+			.date()
+			.iter_days()
+			.take_while(|date| at(*date, 0).is_some_and(|time| time < *end))
+		{
+			let weekday: Weekday = date.weekday();
+			let (hours, comment) = self.hours(weekday, Some(date), holidays);
+			if hours == weekdays[usize::try_from(weekday.num_days_from_monday()).unwrap_or_default()] {
+				continue;
+			}
+			for ((open, close), comment) in hours {
+				if let (Some(from), Some(until)) = (at(date, open), at(date, close)) {
+					opens.push(exception(
+						from,
+						until,
+						Status::Open(Reason::Exception(comment)),
+					));
+				}
+			}
+			if let (Some(from), Some(until)) = (at(date, 0), at(date, MINUTES_PER_DAY)) {
+				closes.push(exception(
+					from,
+					until,
+					Status::Closed(Reason::Exception(comment)),
+				));
+			}
+		}
+		opens
+			.into_iter()
+			.chain(closes)
+			.fold(schedule, Schedule::exception)
+	}
+
+	/// The hours of `schedule`: its parts as rules for their weekdays, then
+	/// a rule for each day an exact exception changes.
+	///
+	/// Holidays can't be told apart from other dates once they're in a
+	/// schedule, so they're written as dates.
+	#[must_use]
+	pub fn from_schedule<Tz: TimeZone>(schedule: &Schedule<'_, Tz>) -> Self {
+		let mut rules: Vec<Rule> = Vec::new();
+		let minutes =
+			|time: &str| -> Option<u32> { Some(time_of_day(time)?.num_seconds_from_midnight() / 60) };
+		let comment = |part: &Part<Tz>| part.notes().first().cloned();
+
+		// The hours of each weekday, and of every day.
+		let mut weekdays: Vec<Vec<(Times, Option<String>)>> = vec![Vec::new(); 7];
+		let mut daily: Vec<(Times, Option<String>)> = Vec::new();
+		for part in schedule.parts() {
+			match (part.opens(), part.closes()) {
+				(Some(Specifier::Daily { time: open }), Some(Specifier::Daily { time: close })) => {
+					if let (Some(open), Some(close)) = (minutes(open), minutes(close)) {
+						let close: u32 = if close <= open {
+							close + MINUTES_PER_DAY
+						} else {
+							close
+						};
+						daily.push(((open, close), comment(part)));
+					}
+				}
+				(
+					Some(Specifier::Weekly {
+						day: from,
+						time: open,
+					}),
+					Some(Specifier::Weekly {
+						day: until,
+						time: close,
+					}),
+				) => {
+					let (Ok(from), Ok(until)) = (from.parse::<Weekday>(), until.parse::<Weekday>()) else {
+						continue;
+					};
+					if let (Some(open), Some(close)) = (minutes(open), minutes(close)) {
+						let mut later: u32 =
+							(until.num_days_from_monday() + 7 - from.num_days_from_monday()) % 7;
+						if later == 0 && close <= open {
+							later = 7;
+						}
+						let index: usize = usize::try_from(from.num_days_from_monday()).unwrap_or_default();
+						weekdays[index].push(((open, close + later * MINUTES_PER_DAY), comment(part)));
+					}
+				}
+				_ => {}
+			}
+		}
+
+		// Rules for weekdays add to any for every day.
+		let every_day: bool = !daily.is_empty();
+		let mut times = |hours: &mut Vec<(Times, Option<String>)>, days: Vec<Vec<Days>>| {
+			hours.sort_by_key(|(times, _)| *times);
+			// Consecutive ranges with the same comment share a rule.
+			let mut index: usize = 0;
+			while index < hours.len() {
+				let comment: &Option<String> = &hours[index].1;
+				let group: Vec<Times> = hours[index..]
+					.iter()
+					.take_while(|(_, other)| other == comment)
+					.map(|(times, _)| *times)
+					.collect();
+				rules.push(Rule {
+					additional: index > 0 || (every_day && !days.is_empty()),
+					dates: Vec::new(),
+					days: days.clone(),
+					times: group.clone(),
+					open: true,
+					comment: comment.clone(),
+				});
+				index += group.len();
+			}
+		};
+		times(&mut daily, Vec::new());
+		let mut day: usize = 0;
+		while day < 7 {
+			let same: usize = weekdays[day..]
+				.iter()
+				.take_while(|hours| **hours == weekdays[day])
+				.count();
+			if !weekdays[day].is_empty() {
+				let mut hours = weekdays[day].clone();
+				times(
+					&mut hours,
+					vec![vec![Days::Weekdays(
+						WEEKDAYS[day].1,
+						WEEKDAYS[day + same - 1].1,
+					)]],
+				);
+			}
+			day += same;
+		}
+
+		rules.extend(dated_rules(schedule));
+		Self { rules }
+	}
+}
+
+/// A rule for each day an exact exception of `schedule` covers, giving its
+/// hours as they turn out.
+fn dated_rules<Tz: TimeZone>(schedule: &Schedule<'_, Tz>) -> Vec<Rule> {
+	let mut rules: Vec<Rule> = Vec::new();
+	let mut dates: Vec<NaiveDate> = Vec::new();
+	let mut timezone: Option<Tz> = None;
+	for exception in schedule.exceptions() {
+		if let (Some(Specifier::Exact(from)), Some(Specifier::Exact(until))) =
+			(exception.effective_from(), exception.expires_at())
+		{
+			timezone = Some(from.timezone());
+			let last: NaiveDate = (until.clone() - Duration::seconds(1)).naive_local().date();
+			dates.extend(
+				from
+					.naive_local()
+					.date()
+					.iter_days()
+					.take_while(|date| *date <= last),
+			);
+		}
+	}
+	dates.sort_unstable();
+	dates.dedup();
+
+	for date in dates {
+		let Some(timezone) = &timezone else {
+			break;
+		};
+		let (Some(start), Some(end)) = (
+			midnight(timezone, date),
+			midnight(timezone, date + Duration::days(1)),
+		) else {
+			continue;
+		};
+		let periods = timeline::periods(core::slice::from_ref(schedule), &start, &end, |_| true);
+		let since = |time: &DateTime<Tz>| -> u32 {
+			u32::try_from((time.clone() - start.clone()).num_minutes()).unwrap_or_default()
+		};
+		let open: Vec<Times> = periods
+			.iter()
+			.filter(|period| period.is_open())
+			.map(|period| (since(period.start()), since(period.end())))
+			.collect();
+		let comment: Option<String> = periods
+			.iter()
+			.find_map(|period| period.note().map(ToString::to_string));
+		let day: MonthDay = MonthDay {
+			year: Some(date.year()),
+			month: date.month(),
+			day: Some(date.day()),
+		};
+		rules.push(Rule {
+			additional: false,
+			dates: vec![Dates {
+				first: day,
+				last: day,
+			}],
+			days: Vec::new(),
+			open: !open.is_empty(),
+			times: open,
+			comment,
+		});
+	}
+
+	rules
+}
+
+impl fmt::Display for OpeningHours {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		for (index, rule) in self.rules.iter().enumerate() {
+			if index > 0 {
+				f.write_str(if rule.additional { ", " } else { "; " })?;
+			}
+			write!(f, "{rule}")?;
+		}
+		Ok(())
+	}
+}
+
+impl<Tz: TimeZone> Schedule<'_, Tz> {
+	/// Read hours in the `opening_hours` syntax; see `OpeningHours::schedule`.
+	///
+	/// # Errors
+	///
+	/// Returns an error if `text` can't be read; see `OpeningHours::parse`.
+	pub fn from_opening_hours(
+		text: &str,
+		holidays: &Holidays,
+		start: &DateTime<Tz>,
+		end: &DateTime<Tz>,
+	) -> Result<Self> {
+		Ok(OpeningHours::parse(text)?.schedule(holidays, start, end))
+	}
+
+	/// The hours in the `opening_hours` syntax; see
+	/// `OpeningHours::from_schedule`.
+	#[must_use]
+	pub fn to_opening_hours(&self) -> String {
+		OpeningHours::from_schedule(self).to_string()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{Holidays, OpeningHours};
+	use crate::{Reason, Schedule, Space, Status};
+	use chrono::{DateTime, Duration, FixedOffset, NaiveDate};
+
+	fn time(rfc3339: &str) -> DateTime<FixedOffset> {
+		DateTime::parse_from_rfc3339(rfc3339).unwrap()
+	}
+
+	#[test]
+	fn parse_and_write() {
+		for (text, written) in &[
+			("Mo-Fr 07:00-23:00; Sa 09:00-17:00; PH off", None),
+			("24/7", None),
+			("Mo,We 08:00-12:00,13:00-17:00", None),
+			(
+				"mo-fr 22:00-02:00 \"Night owls\"; Dec 24-26 closed; SH Mo-Fr 10:00-14:00",
+				Some("Mo-Fr 22:00-02:00 \"Night owls\"; Dec 24-26 off; SH Mo-Fr 10:00-14:00"),
+			),
+			("Jun-Aug Sa open; 2020 Dec 31-2021 Jan 01 off", None),
+			("10:00-18:00, Sa 20:00-22:00", None),
+		] {
+			assert_eq!(
+				OpeningHours::parse(text).unwrap().to_string(),
+				written.unwrap_or(text),
+				"{text}"
+			);
+		}
+
+		let error = OpeningHours::parse("Mo-Fr 07:00-23:00; Sa 9-17").unwrap_err();
+		assert_eq!(error.position(), 23);
+		assert_eq!(
+			OpeningHours::parse("Mo-Fr 08:00-12:00 || \"by appointment\"")
+				.unwrap_err()
+				.position(),
+			19
+		);
+		assert!(OpeningHours::parse("Mo 07:00").is_err());
+		assert!(OpeningHours::parse("Mo 25:00-26:00").is_err());
+	}
+
+	#[test]
+	fn schedules() {
+		let christmas = NaiveDate::from_ymd_opt(2019, 12, 25).unwrap();
+		let holidays = Holidays::new().public(christmas);
+		let schedule: Schedule<FixedOffset> = Schedule::from_opening_hours(
+			"Mo-Fr 07:00-23:00; Sa 09:00-17:00; Fr 22:00-02:00; Dec 24 10:00-14:00 \"Christmas Eve\"; PH off \"Christmas\"",
+			&holidays,
+			&time("2019-12-16T00:00:00-06:00"),
+			&time("2020-01-01T00:00:00-06:00"),
+		)
+		.unwrap();
+		// Mo-Th, Fr and Sa.
+		assert_eq!(schedule.parts().len(), 6);
+		assert_eq!(schedule.exceptions().len(), 3);
+
+		let space = Space::new("Buntrock Commons").schedule(schedule);
+		let is_open = |rfc3339: &str| {
+			let at = time(rfc3339);
+			let open = space.periods(&at, &(at + Duration::minutes(1)))[0].is_open();
+			assert_eq!(
+				matches!(space.status_at(&at), Status::Open(_)),
+				open,
+				"{rfc3339}"
+			);
+			open
+		};
+		assert!(is_open("2019-12-16T08:00:00-06:00"));
+		assert!(is_open("2019-12-21T01:00:00-06:00"));
+		assert!(!is_open("2019-12-21T08:00:00-06:00"));
+		assert!(!is_open("2019-12-24T08:00:00-06:00"));
+		assert!(is_open("2019-12-24T11:00:00-06:00"));
+		assert!(!is_open("2019-12-25T08:00:00-06:00"));
+		assert!(!is_open("2019-12-22T12:00:00-06:00"));
+
+		assert!(matches!(
+			space.status_at(&time("2019-12-21T01:00:00-06:00")),
+			Status::Open(Reason::Part(Some(_)))
+		));
+		assert_eq!(
+			space.status_at(&time("2019-12-24T11:00:00-06:00")),
+			Status::Open(Reason::Exception(Some("Christmas Eve".to_string())))
+		);
+		assert_eq!(
+			space.status_at(&time("2019-12-25T08:00:00-06:00")),
+			Status::Closed(Reason::Exception(Some("Christmas".to_string())))
+		);
+
+		assert_eq!(
+			space.schedules()[0].to_opening_hours(),
+			"Mo-Th 07:00-23:00; Fr 22:00-02:00; Sa 09:00-17:00; \
+			 2019 Dec 24 10:00-14:00 \"Christmas Eve\"; 2019 Dec 25 off \"Christmas\""
+		);
+	}
+}
@@ -1,12 +1,34 @@
-use super::Specifier;
+use super::{timeline, Provenance, Specifier};
 use chrono::{DateTime, TimeZone};
 
 #[allow(dead_code)]
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+	feature = "serde",
+	serde(bound(serialize = "", deserialize = "DateTime<Tz>: serde::Deserialize<'de>"))
+)]
 pub struct Part<Tz: TimeZone> {
+	#[cfg_attr(
+		feature = "serde",
+		serde(default, skip_serializing_if = "Option::is_none")
+	)]
 	open: Option<Specifier<Tz>>,
+	#[cfg_attr(
+		feature = "serde",
+		serde(default, skip_serializing_if = "Option::is_none")
+	)]
 	close: Option<Specifier<Tz>>,
+	#[cfg_attr(
+		feature = "serde",
+		serde(default, skip_serializing_if = "Vec::is_empty")
+	)]
 	notes: Vec<String>,
+	#[cfg_attr(
+		feature = "serde",
+		serde(default, skip_serializing_if = "Option::is_none")
+	)]
+	provenance: Option<Provenance>,
 }
 
 impl<Tz: TimeZone> Default for Part<Tz> {
@@ -15,6 +37,7 @@ impl<Tz: TimeZone> Default for Part<Tz> {
 			open: None,
 			close: None,
 			notes: Vec::new(),
+			provenance: None,
 		}
 	}
 }
@@ -43,15 +66,40 @@ impl<Tz: TimeZone> Part<Tz> {
 		self
 	}
 
+	/// When the part opens, if it says.
+	#[must_use]
+	pub fn opens(&self) -> Option<&Specifier<Tz>> {
+		self.open.as_ref()
+	}
+
+	/// When the part closes, if it says.
+	#[must_use]
+	pub fn closes(&self) -> Option<&Specifier<Tz>> {
+		self.close.as_ref()
+	}
+
+	#[must_use]
+	pub fn notes(&self) -> &[String] {
+		&self.notes
+	}
+
+	/// Where the part was imported from, if it was.
+	#[must_use]
+	pub fn provenance(&self) -> Option<&Provenance> {
+		self.provenance.as_ref()
+	}
+
+	pub fn provenance_mut(&mut self) -> &mut Option<Provenance> {
+		&mut self.provenance
+	}
+
+	/// Whether the part is in effect at the given time, as it would be in
+	/// `Space::periods`.  A part which doesn't say both when it opens and
+	/// when it closes never is.
 	pub fn applies_at(&self, time: &DateTime<Tz>) -> bool {
 		match (self.open.as_ref(), self.close.as_ref()) {
-			(Some(open), Some(close)) => {
-				let open = open.instances(time).next().unwrap();
-				let close = close.instances(time).next().unwrap();
-
-				(open..close).contains(time)
-			}
-			(_, _) => true,
+			(Some(open), Some(close)) => timeline::in_effect(Some(open), Some(close), time),
+			(_, _) => false,
 		}
 	}
 }
@@ -1,34 +1,227 @@
+use core::convert::TryFrom;
+use core::fmt;
+
 use lopdf::Object;
 
+pub mod cmap;
+pub mod encoding;
 pub mod error;
+pub mod font;
+pub mod headers;
+pub mod import;
+pub mod info;
+pub mod structure;
+pub mod table;
+pub mod text;
 
+/// A single operation in a content stream, with its operands decoded.
+///
+/// The variants cover every operator in Table A.1 of the PDF 1.7 spec, and
+/// are grouped here the same way the operators are grouped in Section 8 and
+/// Section 9.  Variant names are taken from the operators' descriptions.
 #[allow(dead_code)]
 #[derive(Debug, PartialEq)]
 pub enum Operation {
-	BeginMarkedContentSequenceWithPropertyList,
-	EndMarkedContentSequence,
+	// General graphics state (Table 57)
+	SetLineWidth {
+		width: f32,
+	},
+	SetLineCapStyle {
+		style: u32,
+	},
+	SetLineJoinStyle {
+		style: u32,
+	},
+	SetMiterLimit {
+		limit: f32,
+	},
+	SetLineDashPattern {
+		array: Vec<f32>,
+		phase: f32,
+	},
+	SetColorRenderingIntent {
+		intent: Vec<u8>,
+	},
+	SetFlatnessTolerance {
+		flatness: f32,
+	},
+	SetParametersFromGraphicsStateParameterDictionary {
+		name: Vec<u8>,
+	},
 
-	BeginTextObject,
-	EndTextObject,
+	// Special graphics state (Table 57)
+	SaveGraphicsState,
+	RestoreGraphicsState,
+	ModifyCurrentTransformationMatrix {
+		a: f32,
+		b: f32,
+		c: f32,
+		d: f32,
+		e: f32,
+		f: f32,
+	},
+
+	// Path construction (Table 59)
+	BeginNewSubpath {
+		x: f32,
+		y: f32,
+	},
+	AppendStraightLineSegmentToPath {
+		x: f32,
+		y: f32,
+	},
+	AppendCurvedSegmentToPath {
+		x_1: f32,
+		y_1: f32,
+		x_2: f32,
+		y_2: f32,
+		x_3: f32,
+		y_3: f32,
+	},
+	AppendCurvedSegmentToPathWithInitialPointReplicated {
+		x_2: f32,
+		y_2: f32,
+		x_3: f32,
+		y_3: f32,
+	},
+	AppendCurvedSegmentToPathWithFinalPointReplicated {
+		x_1: f32,
+		y_1: f32,
+		x_3: f32,
+		y_3: f32,
+	},
+	CloseSubpath,
+	AppendRectangleToPath {
+		x: f32,
+		y: f32,
+		width: f32,
+		height: f32,
+	},
 
+	// Path painting (Table 60)
+	StrokePath,
+	CloseAndStrokePath,
+	FillPathUsingNonzeroWindingNumberRule,
+	FillPathUsingNonzeroWindingNumberRuleObsolete,
+	FillPathUsingEvenOddRule,
+	FillAndStrokePathUsingNonzeroWindingNumberRule,
+	FillAndStrokePathUsingEvenOddRule,
+	CloseFillAndStrokePathUsingNonzeroWindingNumberRule,
+	CloseFillAndStrokePathUsingEvenOddRule,
+	EndPathWithoutFillingOrStroking,
+
+	// Clipping paths (Table 61)
+	SetClippingPathUsingNonzeroWindingNumberRule,
+	SetClippingPathUsingEvenOddRule,
+
+	// Colour (Table 74)
 	SetColorSpaceForStrokingOperations {
 		name: Vec<u8>,
 	},
 	SetColorSpaceForNonstrokingOperations {
 		name: Vec<u8>,
 	},
-	SetColorForNonstrokingOperations,
+	SetColorForStrokingOperations {
+		components: Vec<f32>,
+	},
+	SetColorForNonstrokingOperations {
+		components: Vec<f32>,
+	},
+	/// `SCN`, which is `SC` but also allows a Pattern colour space, in which
+	/// case the last operand names the pattern.
+	SetColorOrPatternForStrokingOperations {
+		components: Vec<f32>,
+		pattern: Option<Vec<u8>>,
+	},
+	/// `scn`, the nonstroking counterpart of `SCN`.
+	SetColorOrPatternForNonstrokingOperations {
+		components: Vec<f32>,
+		pattern: Option<Vec<u8>>,
+	},
+	SetGrayLevelForStrokingOperations {
+		gray: f32,
+	},
+	SetGrayLevelForNonstrokingOperations {
+		gray: f32,
+	},
+	SetRgbColorForStrokingOperations {
+		r: f32,
+		g: f32,
+		b: f32,
+	},
+	SetRgbColorForNonstrokingOperations {
+		r: f32,
+		g: f32,
+		b: f32,
+	},
+	SetCmykColorForStrokingOperations {
+		c: f32,
+		m: f32,
+		y: f32,
+		k: f32,
+	},
+	SetCmykColorForNonstrokingOperations {
+		c: f32,
+		m: f32,
+		y: f32,
+		k: f32,
+	},
 
-	SetTextFontAndSize {
+	// Shading patterns (Table 77)
+	PaintShading {
+		name: Vec<u8>,
+	},
+
+	// Inline images (Table 92)
+	//
+	// N.B. lopdf does not know that the bytes between `ID` and `EI` are image
+	// data, so at best these will be seen in a stream which failed to decode.
+	BeginInlineImageObject,
+	BeginInlineImageData,
+	EndInlineImageObject,
+
+	// XObjects (Table 87)
+	InvokeNamedXObject {
 		name: Vec<u8>,
-		size: f32,
 	},
+
+	// Text objects (Table 107)
+	BeginTextObject,
+	EndTextObject,
+
+	// Text state (Table 105)
 	SetCharacterSpacing {
 		spacing: f32,
 	},
 	SetWordSpacing {
 		spacing: f32,
 	},
+	SetHorizontalScaling {
+		scale: f32,
+	},
+	SetTextLeading {
+		leading: f32,
+	},
+	SetTextFontAndSize {
+		name: Vec<u8>,
+		size: f32,
+	},
+	SetTextRenderingMode {
+		mode: u32,
+	},
+	SetTextRise {
+		rise: f32,
+	},
+
+	// Text positioning (Table 108)
+	MoveTextPosition {
+		t_x: f32,
+		t_y: f32,
+	},
+	MoveTextPositionAndSetLeading {
+		t_x: f32,
+		t_y: f32,
+	},
 	SetTextMatrixAndTextLineMatrix {
 		a: f32,
 		b: f32,
@@ -37,39 +230,115 @@ pub enum Operation {
 		e: f32,
 		f: f32,
 	},
+	MoveToStartOfNextLine,
+
+	// Text showing (Table 109)
 	ShowText {
-		body: String,
+		body: Vec<u8>,
 	},
 	ShowTextAllowingIndividualGlyphPositioning {
-		body: String,
+		elements: Vec<TextElement>,
+	},
+	MoveToNextLineAndShowText {
+		body: Vec<u8>,
+	},
+	SetSpacingMoveToNextLineAndShowText {
+		word_spacing: f32,
+		character_spacing: f32,
+		body: Vec<u8>,
 	},
 
-	SaveGraphicsState,
-	RestoreGraphicsState,
+	// Type 3 fonts (Table 113)
+	SetGlyphWidthForType3Font {
+		w_x: f32,
+		w_y: f32,
+	},
+	SetGlyphWidthAndBoundingBoxForType3Font {
+		w_x: f32,
+		w_y: f32,
+		ll_x: f32,
+		ll_y: f32,
+		ur_x: f32,
+		ur_y: f32,
+	},
 
-	MoveTextPosition {
-		t_x: f32,
-		t_y: f32,
+	// Marked content (Table 320)
+	DesignateMarkedContentPoint {
+		tag: Vec<u8>,
 	},
-	MoveTextPositionAndSetLeading {
-		t_x: f32,
-		t_y: f32,
+	DesignateMarkedContentPointWithPropertyList {
+		tag: Vec<u8>,
+		properties: PropertyList,
 	},
-	MoveToStartOfNextLine,
+	BeginMarkedContentSequence {
+		tag: Vec<u8>,
+	},
+	BeginMarkedContentSequenceWithPropertyList {
+		tag: Vec<u8>,
+		properties: PropertyList,
+	},
+	EndMarkedContentSequence,
 
-	AppendRectangleToPath {
-		x: f32,
-		y: f32,
-		width: f32,
-		height: f32,
+	// Compatibility (Table 32)
+	BeginCompatibilitySection,
+	EndCompatibilitySection,
+}
+
+/// An element of the array operand to `TJ`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TextElement {
+	/// A string of character codes to show.
+	Text(Vec<u8>),
+
+	/// A position adjustment, in thousandths of a unit of text space, which is
+	/// subtracted from the current horizontal coordinate.  Large negative
+	/// adjustments are how many producers space words apart.
+	Adjustment(f32),
+}
+
+/// The property list operand to `BDC` and `DP` (Section 14.6.2 of the PDF
+/// spec).
+#[derive(Clone, Debug, PartialEq)]
+pub enum PropertyList {
+	/// A property list given inline.  Only the entries this crate uses are
+	/// kept.
+	Inline {
+		/// The marked-content identifier, which ties the content to an element
+		/// of the structure tree.
+		mcid: Option<u32>,
+		actual_text: Option<String>,
 	},
-	FillPathUsingNonzeroWindingNumberRule,
-	FillPathUsingNonzeroWindingNumberRuleObsolete,
-	FillPathUsingEvenOddRule,
-	SetClippingPathUsingNonzeroWindingNumberRule,
-	EndPathWithoutFillingOrStroking,
+
+	/// The name of a property list in the `Properties` subdictionary of the
+	/// page's resources.
+	Named { name: Vec<u8> },
+}
+
+impl PropertyList {
+	#[must_use]
+	pub fn from_dictionary(dictionary: &lopdf::Dictionary) -> Self {
+		Self::Inline {
+			mcid: match dictionary.get(b"MCID") {
+				Ok(Object::Integer(mcid)) => u32::try_from(*mcid).ok(),
+				_ => None,
+			},
+			actual_text: match dictionary.get(b"ActualText") {
+				Ok(Object::String(bytes, _format)) => Some(info::text_string(bytes)),
+				_ => None,
+			},
+		}
+	}
+
+	#[must_use]
+	pub fn mcid(&self) -> Option<u32> {
+		match self {
+			Self::Inline { mcid, .. } => *mcid,
+			Self::Named { .. } => None,
+		}
+	}
 }
 
+#[allow(dead_code)]
 pub struct GraphicsState {
 	current_transformation_matrix: [f64; 6],
 	clipping_path: (),
@@ -88,10 +357,26 @@ pub struct GraphicsState {
 	alpha_constant: f64,
 }
 
+/// The geometry of a page: which part of user space is visible, and how it
+/// is turned and scaled when the page is displayed.
+///
+/// See Section 14.11.2 of the PDF spec for the page boundaries, and Table 30
+/// for `Rotate` and `UserUnit`.
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct CoordinateSpace {
-	origin: [f32; 2],
-	orientation: ([f64; 2], [f64; 2]),
-	axis_length: (f64, f64),
+	media_box: Rectangle,
+	crop_box: Rectangle,
+	rotation: u32,
+	user_unit: f64,
+}
+
+/// A rectangle in user space, normalized so that `lower_left` is below and to
+/// the left of `upper_right`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rectangle {
+	lower_left: Coordinates,
+	upper_right: Coordinates,
 }
 
 /// A Coordinate Transformation Matrix (CTM)
@@ -105,257 +390,1636 @@ pub struct CoordinateSpace {
 /// [  .2   .3  0.0 ]
 /// [  .4   .5  1.0 ]
 /// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct TransformationMatrix((f64, f64), (f64, f64), (f64, f64));
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Coordinates(f64, f64);
 
+impl Default for TransformationMatrix {
+	fn default() -> Self {
+		Self::identity()
+	}
+}
+
+impl TransformationMatrix {
+	#[must_use]
+	#[allow(clippy::many_single_char_names)]
+	pub fn new(a: f64, b: f64, c: f64, d: f64, e: f64, f: f64) -> Self {
+		Self((a, b), (c, d), (e, f))
+	}
+
+	#[must_use]
+	pub fn identity() -> Self {
+		Self::new(1.0, 0.0, 0.0, 1.0, 0.0, 0.0)
+	}
+
+	#[must_use]
+	pub fn translation(t_x: f64, t_y: f64) -> Self {
+		Self::new(1.0, 0.0, 0.0, 1.0, t_x, t_y)
+	}
+
+	#[must_use]
+	pub fn scale(s_x: f64, s_y: f64) -> Self {
+		Self::new(s_x, 0.0, 0.0, s_y, 0.0, 0.0)
+	}
+
+	/// Compute `self × other`, i.e. the transformation which applies `self`
+	/// first and then `other`.
+	#[must_use]
+	#[allow(clippy::many_single_char_names)]
+	pub fn multiply(&self, other: &Self) -> Self {
+		let (Self((a, b), (c, d), (e, f)), Self((a2, b2), (c2, d2), (e2, f2))) = (self, other);
+		Self::new(
+			a * a2 + b * c2,
+			a * b2 + b * d2,
+			c * a2 + d * c2,
+			c * b2 + d * d2,
+			e * a2 + f * c2 + e2,
+			e * b2 + f * d2 + f2,
+		)
+	}
+
+	/// The factor by which the matrix scales vertical distances.
+	#[must_use]
+	pub fn vertical_scale(&self) -> f64 {
+		(self.1 .0).hypot(self.1 .1)
+	}
+}
+
 impl Coordinates {
-	fn transform(&self, ctm: &TransformationMatrix) -> Coordinates {
+	#[must_use]
+	pub fn new(x: f64, y: f64) -> Self {
+		Self(x, y)
+	}
+
+	#[must_use]
+	pub fn x(&self) -> f64 {
+		self.0
+	}
+
+	#[must_use]
+	pub fn y(&self) -> f64 {
+		self.1
+	}
+
+	#[must_use]
+	pub fn transform(&self, ctm: &TransformationMatrix) -> Coordinates {
 		let x_prime: f64 = ctm.0 .0 * self.0 + ctm.1 .0 * self.1 + ctm.2 .0;
 		let y_prime: f64 = ctm.0 .1 * self.0 + ctm.1 .1 * self.1 + ctm.2 .1;
 		Coordinates(x_prime, y_prime)
 	}
 }
 
-impl CoordinateSpace {
+impl Rectangle {
 	#[must_use]
-	pub fn from_page_dictionary(dictionary: &lopdf::Object) -> Self {
-		let dictionary: &lopdf::Dictionary = match dictionary {
-			lopdf::Object::Dictionary(dictionary) => dictionary,
-
-			// According to Section 7.7.3.3 of the PDF spec, the leaves of the page
-			// tree are page objects, each of which is a dictionary specifying the
-			// attributes of a single page of the document.  It is, therefore, an
-			// error (logic or otherwise) to have anything besides a Dictionary object
-			// stored in the leaves of the page tree, and therefore we should not
-			// support that.
-			//
-			// TODO Consider, instead of unwinding via panic, returning a Result?
-			_ => unreachable!(),
-		};
-
-		let media_box: Vec<f32> = dictionary
-			.get(b"MediaBox")
-			.map(|object| match object {
-				Object::Array(array) => array
-					.iter()
-					.map(|element| match element {
-						Object::Real(number) => number,
-						_ => unreachable!(),
-					})
-					.copied()
-					.collect(),
-				_ => unreachable!(),
-			})
-			.ok()
-			.unwrap();
-		let crop_box: Vec<f32> = dictionary
-			.get(b"CropBox")
-			.map(|object| match object {
-				Object::Array(array) => array
-					.iter()
-					.map(|element| match element {
-						Object::Real(number) => number,
-						_ => unreachable!(),
-					})
-					.copied()
-					.collect(),
-				_ => unreachable!(),
-			})
-			.unwrap_or(media_box);
-
-		let origin: [f32; 2] = [crop_box[0], crop_box[1]];
-		// TODO fix?
-		let orientation: ([f64; 2], [f64; 2]) = ([1.0, 0.0], [0.0, 1.0]);
-		let axis_length: (f64, f64) = (1.0, 1.0);
-
+	pub fn new(lower_left: Coordinates, upper_right: Coordinates) -> Self {
 		Self {
-			origin,
-			orientation,
-			axis_length,
+			lower_left: Coordinates::new(
+				lower_left.x().min(upper_right.x()),
+				lower_left.y().min(upper_right.y()),
+			),
+			upper_right: Coordinates::new(
+				lower_left.x().max(upper_right.x()),
+				lower_left.y().max(upper_right.y()),
+			),
 		}
 	}
-}
 
-impl core::convert::TryFrom<lopdf::content::Operation> for Operation {
-	type Error = error::ParseError;
+	/// Read a rectangle from an array of four numbers (Section 7.9.5 of the
+	/// PDF spec), any of which may be indirect.
+	///
+	/// # Errors
+	///
+	/// Returns an error if `object` is not an array of four numbers.
+	pub fn from_object(document: &lopdf::Document, object: &Object) -> error::Result<Self> {
+		let malformed =
+			|| error::ParseError::Malformed("a rectangle should be an array of four numbers".to_string());
 
-	// TODO(rye): This is a very long method.  Can we make it smaller?
-	#[allow(clippy::many_single_char_names)]
-	fn try_from(operation: lopdf::content::Operation) -> error::Result<Operation> {
-		/// Since `Object::as_f64` fails if `Object` is not a `Object::Real`, this
-		/// function can coerce an `Object::Integer` to an `Object::Real`, and will
-		/// unwrap into an `Option<f64>` if either of those variants are given.
-		fn to_f32(object: &Object) -> Option<f32> {
-			match object {
-				Object::Real(x) => Some(*x),
-				Object::Integer(x) => todo!(),
-				_ => None,
-			}
+		let array: &Vec<Object> = match document.dereference(object)?.1 {
+			Object::Array(array) if array.len() == 4 => array,
+			_ => return Err(malformed()),
+		};
+
+		let mut numbers: [f64; 4] = [0.0; 4];
+		for (number, element) in numbers.iter_mut().zip(array) {
+			*number = operands::to_f32(document.dereference(element)?.1)
+				.map(f64::from)
+				.ok_or_else(malformed)?;
 		}
 
-		match (operation.operator.as_str(), operation.operands) {
-			("BDC", _) => Ok(Self::BeginMarkedContentSequenceWithPropertyList),
-			("EMC", _) => Ok(Self::EndMarkedContentSequence),
+		let [x_1, y_1, x_2, y_2] = numbers;
+		Ok(Self::new(
+			Coordinates::new(x_1, y_1),
+			Coordinates::new(x_2, y_2),
+		))
+	}
 
-			("BT", _) => Ok(Self::BeginTextObject),
-			("ET", _) => Ok(Self::EndTextObject),
+	#[must_use]
+	pub fn lower_left(&self) -> Coordinates {
+		self.lower_left
+	}
 
-			("CS", opds) => match opds.first() {
-				Some(Object::Name(name)) => {
-					Ok(Self::SetColorSpaceForStrokingOperations { name: name.clone() })
-				}
-				_ => todo!(),
-			},
-			("cs", opds) => match opds.first() {
-				Some(Object::Name(name)) => {
-					Ok(Self::SetColorSpaceForNonstrokingOperations { name: name.clone() })
-				}
-				_ => todo!(),
-			},
-			("scn", _) => {
-				// Same as SCN, but for Nonstroking operations.
-				//
-				// SCN: Operands are the same as SC, but also supports Pattern,
-				// Separation, DeviceN, and ICCBased colour spaces.
-				//
-				// SC: Set the color to use for stroking operations in a device,
-				// CIE-based (other than ICCBased), or Indexed colour space.  The number
-				// of operands required and their interpretation depends on the current
-				// stroking colour space.
-				//
-				// - For DeviceGray, CalGray, and Indexed colour spaces, one operand
-				//   shall be required.
-				//
-				// - For DeviceRGB, CalRGB, and Lab colour spaces, three operands shall
-				//   be required.
-				//
-				// - For DeviceCMYK, four operands shall be required.
-				//
-				// TODO implement
-				Ok(Self::SetColorForNonstrokingOperations)
-			}
-
-			#[allow(clippy::get_first)]
-			("Tf", opds) => match (opds.get(0), opds.get(1).and_then(to_f32)) {
-				(Some(Object::Name(name)), Some(size)) => Ok(Self::SetTextFontAndSize {
-					name: name.clone(),
-					size,
-				}),
-				_ => todo!(),
-			},
-			("Tc", opds) => match opds.first().and_then(to_f32) {
-				Some(spacing) => Ok(Self::SetCharacterSpacing { spacing }),
-				_ => Err(error::ParseError::OperandType),
-			},
-			("Tw", opds) => match opds.first().and_then(to_f32) {
-				Some(spacing) => Ok(Self::SetWordSpacing { spacing }),
-				_ => Err(error::ParseError::OperandType),
-			},
-			("Tm", opds) => match (
-				#[allow(clippy::get_first)]
-				opds.get(0).and_then(to_f32),
-				opds.get(1).and_then(to_f32),
-				opds.get(2).and_then(to_f32),
-				opds.get(3).and_then(to_f32),
-				opds.get(4).and_then(to_f32),
-				opds.get(5).and_then(to_f32),
-			) {
-				// N.B. In the PDF spec these use the names a, b, c, d, e, and f; these
-				// are used as generic parameters in the 3x3 transformation matrices,
-				// filling the first two columns column-wise.
-				(Some(a), Some(b), Some(c), Some(d), Some(e), Some(f)) => {
-					Ok(Self::SetTextMatrixAndTextLineMatrix { a, b, c, d, e, f })
-				}
-				_ => Err(error::ParseError::OperandType),
-			},
-			("TJ", opds) => match opds.first() {
-				Some(Object::Array(array)) => {
-					let body: error::Result<String> = array
-						.iter()
-						.map(|element: &Object| -> error::Result<String> {
-							match element {
-								Object::String(bytes, _format) => {
-									String::from_utf8(bytes.clone()).map_err(Into::into)
-								}
-								Object::Real(_f) => Ok("".to_string()),
-								Object::Integer(_f) => Ok("".to_string()),
-								_ => Err(error::ParseError::OperandType),
-							}
-						})
-						.collect::<error::Result<Vec<String>>>()
-						.map(|strings| strings.concat());
-
-					body.map(|body: String| Self::ShowTextAllowingIndividualGlyphPositioning { body })
-				}
-				None => Ok(Self::ShowTextAllowingIndividualGlyphPositioning {
-					body: "".to_string(),
-				}),
-				_ => Err(error::ParseError::OperandType),
-			},
-			("Tj", opds) => match opds.first() {
-				Some(Object::String(bytes, _format)) => {
-					let body = String::from_utf8(bytes.clone()).map_err(Into::into);
-					body.map(|body: String| Self::ShowText { body })
-				}
-				_ => Err(error::ParseError::OperandType),
-			},
+	#[must_use]
+	pub fn upper_right(&self) -> Coordinates {
+		self.upper_right
+	}
 
-			("q", _) => Ok(Self::SaveGraphicsState),
-			("Q", _) => Ok(Self::RestoreGraphicsState),
+	#[must_use]
+	pub fn width(&self) -> f64 {
+		self.upper_right.x() - self.lower_left.x()
+	}
 
-			#[allow(clippy::get_first)]
-			("Td", opds) => match (opds.get(0).and_then(to_f32), opds.get(1).and_then(to_f32)) {
-				(Some(t_x), Some(t_y)) => Ok(Self::MoveTextPosition { t_x, t_y }),
-				_ => todo!(),
-			},
-			#[allow(clippy::get_first)]
-			("TD", opds) => match (opds.get(0).and_then(to_f32), opds.get(1).and_then(to_f32)) {
-				(Some(t_x), Some(t_y)) => Ok(Self::MoveTextPositionAndSetLeading { t_x, t_y }),
-				_ => todo!(),
-			},
-			("T*", _) => Ok(Self::MoveToStartOfNextLine),
-
-			#[allow(clippy::get_first)]
-			("re", opds) => match (
-				opds.get(0).and_then(to_f32),
-				opds.get(1).and_then(to_f32),
-				opds.get(2).and_then(to_f32),
-				opds.get(3).and_then(to_f32),
-			) {
-				(Some(x), Some(y), Some(width), Some(height)) => Ok(Self::AppendRectangleToPath {
-					x,
-					y,
-					width,
-					height,
-				}),
-				_ => Err(error::ParseError::OperandType),
-			},
+	#[must_use]
+	pub fn height(&self) -> f64 {
+		self.upper_right.y() - self.lower_left.y()
+	}
 
-			("f", _) => Ok(Self::FillPathUsingNonzeroWindingNumberRule),
-			("F", _) => Ok(Self::FillPathUsingNonzeroWindingNumberRuleObsolete),
-			("f*", _) => Ok(Self::FillPathUsingEvenOddRule),
-			("W", _) => Ok(Self::SetClippingPathUsingNonzeroWindingNumberRule),
-			("n", _) => Ok(Self::EndPathWithoutFillingOrStroking),
+	/// The smallest rectangle containing both rectangles.
+	#[must_use]
+	pub fn union(&self, other: &Self) -> Self {
+		Self {
+			lower_left: Coordinates::new(
+				self.lower_left.x().min(other.lower_left.x()),
+				self.lower_left.y().min(other.lower_left.y()),
+			),
+			upper_right: Coordinates::new(
+				self.upper_right.x().max(other.upper_right.x()),
+				self.upper_right.y().max(other.upper_right.y()),
+			),
+		}
+	}
 
-			(op, _) => Err(error::ParseError::UnknownOperator(op.to_string())),
+	/// The overlap of two rectangles, or `None` if they don't overlap.
+	#[must_use]
+	pub fn intersection(&self, other: &Self) -> Option<Self> {
+		let lower_left = Coordinates::new(
+			self.lower_left.x().max(other.lower_left.x()),
+			self.lower_left.y().max(other.lower_left.y()),
+		);
+		let upper_right = Coordinates::new(
+			self.upper_right.x().min(other.upper_right.x()),
+			self.upper_right.y().min(other.upper_right.y()),
+		);
+
+		if lower_left.x() <= upper_right.x() && lower_left.y() <= upper_right.y() {
+			Some(Self {
+				lower_left,
+				upper_right,
+			})
+		} else {
+			None
 		}
 	}
-}
 
-#[derive(Debug, Default)]
-pub struct Pdf {
-	version: Option<String>,
+	#[must_use]
+	pub fn contains(&self, point: Coordinates) -> bool {
+		(self.lower_left.x()..=self.upper_right.x()).contains(&point.x())
+			&& (self.lower_left.y()..=self.upper_right.y()).contains(&point.y())
+	}
 }
 
-impl Pdf {
-	#[must_use]
-	pub fn new() -> Self {
-		Self::default()
+impl CoordinateSpace {
+	/// Compute the geometry of the page `page_id`, looking up `MediaBox`,
+	/// `CropBox` and `Rotate` in the ancestors of the page when the page
+	/// itself doesn't have them.
+	///
+	/// # Errors
+	///
+	/// Returns an error if the page or one of its ancestors is not a dictionary,
+	/// if no `MediaBox` can be found, or if any of the attributes are malformed.
+	pub fn from_page(document: &lopdf::Document, page_id: lopdf::ObjectId) -> error::Result<Self> {
+		Self::from_page_dictionary(document, document.get_dictionary(page_id)?)
 	}
 
-	#[must_use]
-	pub fn version(mut self, version: &str) -> Self {
-		self.version = Some(version.to_string());
-		self
+	/// Like `from_page`, but for a page dictionary which has already been
+	/// looked up.
+	///
+	/// # Errors
+	///
+	/// See `from_page`.
+	pub fn from_page_dictionary(
+		document: &lopdf::Document,
+		dictionary: &lopdf::Dictionary,
+	) -> error::Result<Self> {
+		let media_box: Rectangle = match inherited(document, dictionary, b"MediaBox")? {
+			Some(object) => Rectangle::from_object(document, object)?,
+			None => {
+				return Err(error::ParseError::Malformed(
+					"the page has no MediaBox".to_string(),
+				))
+			}
+		};
+
+		// N.B. Section 14.11.2.1 of the PDF spec says that a crop box which
+		// extends past the media box is to be clipped to it.
+		let crop_box: Rectangle = match inherited(document, dictionary, b"CropBox")? {
+			Some(object) => Rectangle::from_object(document, object)?
+				.intersection(&media_box)
+				.unwrap_or(media_box),
+			None => media_box,
+		};
+
+		let rotation: u32 = match inherited(document, dictionary, b"Rotate")? {
+			Some(Object::Integer(degrees)) => match degrees.rem_euclid(360) {
+				0 => 0,
+				90 => 90,
+				180 => 180,
+				270 => 270,
+				_ => {
+					return Err(error::ParseError::Malformed(format!(
+						"Rotate should be a multiple of 90, but is {degrees}"
+					)))
+				}
+			},
+			Some(object) => {
+				return Err(error::ParseError::Malformed(format!(
+					"Rotate should be an integer, but is {}",
+					error::object_type(object)
+				)))
+			}
+			None => 0,
+		};
+
+		// UserUnit, unlike the others, is not inheritable.
+		let user_unit: f64 = match dictionary.get(b"UserUnit").ok() {
+			Some(object) => operands::to_f32(document.dereference(object)?.1)
+				.map(f64::from)
+				.filter(|unit| *unit > 0.0)
+				.ok_or_else(|| {
+					error::ParseError::Malformed("UserUnit should be a positive number".to_string())
+				})?,
+			None => 1.0,
+		};
+
+		Ok(Self {
+			media_box,
+			crop_box,
+			rotation,
+			user_unit,
+		})
+	}
+
+	#[must_use]
+	pub fn media_box(&self) -> Rectangle {
+		self.media_box
+	}
+
+	/// The visible region of the page, in user space.
+	#[must_use]
+	pub fn crop_box(&self) -> Rectangle {
+		self.crop_box
+	}
+
+	/// How far the page is turned clockwise when displayed: 0, 90, 180 or 270.
+	#[must_use]
+	pub fn rotation(&self) -> u32 {
+		self.rotation
+	}
+
+	/// The size of a unit of user space, in multiples of 1/72 inch.
+	#[must_use]
+	pub fn user_unit(&self) -> f64 {
+		self.user_unit
+	}
+
+	/// The width of the page as displayed, in points.
+	#[must_use]
+	pub fn width(&self) -> f64 {
+		self.user_unit
+			* match self.rotation {
+				90 | 270 => self.crop_box.height(),
+				_ => self.crop_box.width(),
+			}
+	}
+
+	/// The height of the page as displayed, in points.
+	#[must_use]
+	pub fn height(&self) -> f64 {
+		self.user_unit
+			* match self.rotation {
+				90 | 270 => self.crop_box.width(),
+				_ => self.crop_box.height(),
+			}
+	}
+
+	/// The matrix which takes user space to the page as displayed: the origin
+	/// at the lower-left corner of the turned crop box, `x` increasing to the
+	/// right, `y` increasing upwards, and units of one point.
+	#[must_use]
+	pub fn matrix(&self) -> TransformationMatrix {
+		let origin: Coordinates = self.crop_box.lower_left();
+		let (width, height) = (self.crop_box.width(), self.crop_box.height());
+
+		let rotation: TransformationMatrix = match self.rotation {
+			90 => TransformationMatrix::new(0.0, -1.0, 1.0, 0.0, 0.0, width),
+			180 => TransformationMatrix::new(-1.0, 0.0, 0.0, -1.0, width, height),
+			270 => TransformationMatrix::new(0.0, 1.0, -1.0, 0.0, height, 0.0),
+			_ => TransformationMatrix::identity(),
+		};
+
+		TransformationMatrix::translation(-origin.x(), -origin.y())
+			.multiply(&rotation)
+			.multiply(&TransformationMatrix::scale(self.user_unit, self.user_unit))
+	}
+}
+
+/// Look up an inheritable page attribute (Section 7.7.3.4 of the PDF spec),
+/// walking up the `Parent` chain from `dictionary` until it is found.
+fn inherited<'document>(
+	document: &'document lopdf::Document,
+	dictionary: &'document lopdf::Dictionary,
+	key: &[u8],
+) -> error::Result<Option<&'document Object>> {
+	// Guards against cycles in a broken page tree.
+	const MAX_DEPTH: usize = 64;
+
+	let mut dictionary: &lopdf::Dictionary = dictionary;
+	for _ in 0..MAX_DEPTH {
+		if let Ok(object) = dictionary.get(key) {
+			return Ok(Some(document.dereference(object)?.1));
+		}
+
+		dictionary = match dictionary.get(b"Parent") {
+			Ok(parent) => document.dereference(parent)?.1.as_dict()?,
+			Err(_) => return Ok(None),
+		};
+	}
+
+	Err(error::ParseError::Malformed(
+		"the page tree is too deep".to_string(),
+	))
+}
+
+/// Helpers for pulling typed operands out of a `lopdf::content::Operation`.
+///
+/// Each takes the index of the operand, so that a missing operand can be told
+/// apart from one of the wrong type.
+mod operands {
+	use super::error::{object_type, ParseError, Result};
+	use super::{PropertyList, TextElement};
+	use core::convert::TryFrom;
+	use lopdf::Object;
+
+	/// Coerce an `Object::Integer` or `Object::Real` into an `f32`.
+	#[allow(clippy::cast_precision_loss)]
+	pub(super) fn to_f32(object: &Object) -> Option<f32> {
+		match object {
+			Object::Real(x) => Some(*x),
+			Object::Integer(x) => Some(*x as f32),
+			_ => None,
+		}
+	}
+
+	/// The operands of a single operation, along with its operator so that
+	/// errors can say which operation they came from.
+	pub(super) struct Operands<'a> {
+		operator: &'a str,
+		operands: &'a [Object],
+	}
+
+	impl<'a> Operands<'a> {
+		pub(super) fn new(operator: &'a str, operands: &'a [Object]) -> Self {
+			Self { operator, operands }
+		}
+
+		fn get(&self, index: usize) -> Result<&'a Object> {
+			self
+				.operands
+				.get(index)
+				.ok_or_else(|| ParseError::MissingOperands {
+					operator: self.operator.to_string(),
+					index,
+				})
+		}
+
+		fn mismatch(&self, index: usize, expected: &'static str, found: &Object) -> ParseError {
+			ParseError::OperandType {
+				operator: self.operator.to_string(),
+				index,
+				expected,
+				found: object_type(found),
+			}
+		}
+
+		pub(super) fn number(&self, index: usize) -> Result<f32> {
+			let object: &Object = self.get(index)?;
+			to_f32(object).ok_or_else(|| self.mismatch(index, "a number", object))
+		}
+
+		pub(super) fn numbers<const N: usize>(&self) -> Result<[f32; N]> {
+			let mut numbers: [f32; N] = [0.0; N];
+			for (index, number) in numbers.iter_mut().enumerate() {
+				*number = self.number(index)?;
+			}
+			Ok(numbers)
+		}
+
+		pub(super) fn integer(&self, index: usize) -> Result<u32> {
+			match self.get(index)? {
+				Object::Integer(integer) => u32::try_from(*integer)
+					.map_err(|_| self.mismatch(index, "a non-negative integer", &Object::Integer(*integer))),
+				object => Err(self.mismatch(index, "an integer", object)),
+			}
+		}
+
+		pub(super) fn name(&self, index: usize) -> Result<Vec<u8>> {
+			match self.get(index)? {
+				Object::Name(name) => Ok(name.clone()),
+				object => Err(self.mismatch(index, "a name", object)),
+			}
+		}
+
+		pub(super) fn string(&self, index: usize) -> Result<Vec<u8>> {
+			match self.get(index)? {
+				Object::String(bytes, _format) => Ok(bytes.clone()),
+				object => Err(self.mismatch(index, "a string", object)),
+			}
+		}
+
+		fn array(&self, index: usize) -> Result<&'a [Object]> {
+			match self.get(index)? {
+				Object::Array(array) => Ok(array),
+				object => Err(self.mismatch(index, "an array", object)),
+			}
+		}
+
+		/// An array of numbers, as given to `d`.
+		pub(super) fn number_array(&self, index: usize) -> Result<Vec<f32>> {
+			self
+				.array(index)?
+				.iter()
+				.map(|element| {
+					to_f32(element).ok_or_else(|| self.mismatch(index, "an array of numbers", element))
+				})
+				.collect()
+		}
+
+		/// The array operand to `TJ`, which may be omitted entirely.
+		pub(super) fn text_elements(&self, index: usize) -> Result<Vec<TextElement>> {
+			if self.operands.len() <= index {
+				return Ok(Vec::new());
+			}
+
+			self
+				.array(index)?
+				.iter()
+				.map(|element: &Object| match element {
+					Object::String(bytes, _format) => Ok(TextElement::Text(bytes.clone())),
+					number => to_f32(number)
+						.map(TextElement::Adjustment)
+						.ok_or_else(|| self.mismatch(index, "an array of strings and numbers", element)),
+				})
+				.collect()
+		}
+
+		/// The property list operand of `DP` and `BDC`.
+		pub(super) fn property_list(&self, index: usize) -> Result<PropertyList> {
+			match self.get(index)? {
+				Object::Name(name) => Ok(PropertyList::Named { name: name.clone() }),
+				Object::Dictionary(dictionary) => Ok(PropertyList::from_dictionary(dictionary)),
+				object => Err(self.mismatch(index, "a name or a dictionary", object)),
+			}
+		}
+
+		/// The operands of `SC`, `sc`, `SCN` and `scn`: some number of colour
+		/// components, optionally followed by a pattern name.
+		pub(super) fn color(&self) -> Result<(Vec<f32>, Option<Vec<u8>>)> {
+			let (pattern, components) = match self.operands.split_last() {
+				Some((Object::Name(name), components)) => (Some(name.clone()), components),
+				_ => (None, self.operands),
+			};
+
+			let components: Vec<f32> = (0..components.len())
+				.map(|index| self.number(index))
+				.collect::<Result<_>>()?;
+
+			Ok((components, pattern))
+		}
+	}
+}
+
+impl core::convert::TryFrom<lopdf::content::Operation> for Operation {
+	type Error = error::ParseError;
+
+	#[allow(clippy::many_single_char_names, clippy::too_many_lines)]
+	fn try_from(operation: lopdf::content::Operation) -> error::Result<Operation> {
+		let opds = operands::Operands::new(&operation.operator, &operation.operands);
+
+		match operation.operator.as_str() {
+			"w" => Ok(Self::SetLineWidth {
+				width: opds.number(0)?,
+			}),
+			"J" => Ok(Self::SetLineCapStyle {
+				style: opds.integer(0)?,
+			}),
+			"j" => Ok(Self::SetLineJoinStyle {
+				style: opds.integer(0)?,
+			}),
+			"M" => Ok(Self::SetMiterLimit {
+				limit: opds.number(0)?,
+			}),
+			"d" => Ok(Self::SetLineDashPattern {
+				array: opds.number_array(0)?,
+				phase: opds.number(1)?,
+			}),
+			"ri" => Ok(Self::SetColorRenderingIntent {
+				intent: opds.name(0)?,
+			}),
+			"i" => Ok(Self::SetFlatnessTolerance {
+				flatness: opds.number(0)?,
+			}),
+			"gs" => Ok(Self::SetParametersFromGraphicsStateParameterDictionary {
+				name: opds.name(0)?,
+			}),
+
+			"q" => Ok(Self::SaveGraphicsState),
+			"Q" => Ok(Self::RestoreGraphicsState),
+			"cm" => {
+				// N.B. In the PDF spec these use the names a, b, c, d, e, and f; these
+				// are used as generic parameters in the 3x3 transformation matrices,
+				// filling the first two columns column-wise.
+				let [a, b, c, d, e, f] = opds.numbers()?;
+				Ok(Self::ModifyCurrentTransformationMatrix { a, b, c, d, e, f })
+			}
+
+			"m" => {
+				let [x, y] = opds.numbers()?;
+				Ok(Self::BeginNewSubpath { x, y })
+			}
+			"l" => {
+				let [x, y] = opds.numbers()?;
+				Ok(Self::AppendStraightLineSegmentToPath { x, y })
+			}
+			"c" => {
+				let [x_1, y_1, x_2, y_2, x_3, y_3] = opds.numbers()?;
+				Ok(Self::AppendCurvedSegmentToPath {
+					x_1,
+					y_1,
+					x_2,
+					y_2,
+					x_3,
+					y_3,
+				})
+			}
+			"v" => {
+				let [x_2, y_2, x_3, y_3] = opds.numbers()?;
+				Ok(Self::AppendCurvedSegmentToPathWithInitialPointReplicated { x_2, y_2, x_3, y_3 })
+			}
+			"y" => {
+				let [x_1, y_1, x_3, y_3] = opds.numbers()?;
+				Ok(Self::AppendCurvedSegmentToPathWithFinalPointReplicated { x_1, y_1, x_3, y_3 })
+			}
+			"h" => Ok(Self::CloseSubpath),
+			"re" => {
+				let [x, y, width, height] = opds.numbers()?;
+				Ok(Self::AppendRectangleToPath {
+					x,
+					y,
+					width,
+					height,
+				})
+			}
+
+			"S" => Ok(Self::StrokePath),
+			"s" => Ok(Self::CloseAndStrokePath),
+			"f" => Ok(Self::FillPathUsingNonzeroWindingNumberRule),
+			"F" => Ok(Self::FillPathUsingNonzeroWindingNumberRuleObsolete),
+			"f*" => Ok(Self::FillPathUsingEvenOddRule),
+			"B" => Ok(Self::FillAndStrokePathUsingNonzeroWindingNumberRule),
+			"B*" => Ok(Self::FillAndStrokePathUsingEvenOddRule),
+			"b" => Ok(Self::CloseFillAndStrokePathUsingNonzeroWindingNumberRule),
+			"b*" => Ok(Self::CloseFillAndStrokePathUsingEvenOddRule),
+			"n" => Ok(Self::EndPathWithoutFillingOrStroking),
+
+			"W" => Ok(Self::SetClippingPathUsingNonzeroWindingNumberRule),
+			"W*" => Ok(Self::SetClippingPathUsingEvenOddRule),
+
+			"CS" => Ok(Self::SetColorSpaceForStrokingOperations {
+				name: opds.name(0)?,
+			}),
+			"cs" => Ok(Self::SetColorSpaceForNonstrokingOperations {
+				name: opds.name(0)?,
+			}),
+			"SC" => Ok(Self::SetColorForStrokingOperations {
+				components: opds.color()?.0,
+			}),
+			"sc" => Ok(Self::SetColorForNonstrokingOperations {
+				components: opds.color()?.0,
+			}),
+			"SCN" => {
+				// SCN: Operands are the same as SC, but also supports Pattern,
+				// Separation, DeviceN, and ICCBased colour spaces.
+				let (components, pattern) = opds.color()?;
+				Ok(Self::SetColorOrPatternForStrokingOperations {
+					components,
+					pattern,
+				})
+			}
+			"scn" => {
+				let (components, pattern) = opds.color()?;
+				Ok(Self::SetColorOrPatternForNonstrokingOperations {
+					components,
+					pattern,
+				})
+			}
+			"G" => Ok(Self::SetGrayLevelForStrokingOperations {
+				gray: opds.number(0)?,
+			}),
+			"g" => Ok(Self::SetGrayLevelForNonstrokingOperations {
+				gray: opds.number(0)?,
+			}),
+			"RG" => {
+				let [r, g, b] = opds.numbers()?;
+				Ok(Self::SetRgbColorForStrokingOperations { r, g, b })
+			}
+			"rg" => {
+				let [r, g, b] = opds.numbers()?;
+				Ok(Self::SetRgbColorForNonstrokingOperations { r, g, b })
+			}
+			"K" => {
+				let [c, m, y, k] = opds.numbers()?;
+				Ok(Self::SetCmykColorForStrokingOperations { c, m, y, k })
+			}
+			"k" => {
+				let [c, m, y, k] = opds.numbers()?;
+				Ok(Self::SetCmykColorForNonstrokingOperations { c, m, y, k })
+			}
+
+			"sh" => Ok(Self::PaintShading {
+				name: opds.name(0)?,
+			}),
+
+			"BI" => Ok(Self::BeginInlineImageObject),
+			"ID" => Ok(Self::BeginInlineImageData),
+			"EI" => Ok(Self::EndInlineImageObject),
+
+			"Do" => Ok(Self::InvokeNamedXObject {
+				name: opds.name(0)?,
+			}),
+
+			"BT" => Ok(Self::BeginTextObject),
+			"ET" => Ok(Self::EndTextObject),
+
+			"Tc" => Ok(Self::SetCharacterSpacing {
+				spacing: opds.number(0)?,
+			}),
+			"Tw" => Ok(Self::SetWordSpacing {
+				spacing: opds.number(0)?,
+			}),
+			"Tz" => Ok(Self::SetHorizontalScaling {
+				scale: opds.number(0)?,
+			}),
+			"TL" => Ok(Self::SetTextLeading {
+				leading: opds.number(0)?,
+			}),
+			"Tf" => Ok(Self::SetTextFontAndSize {
+				name: opds.name(0)?,
+				size: opds.number(1)?,
+			}),
+			"Tr" => Ok(Self::SetTextRenderingMode {
+				mode: opds.integer(0)?,
+			}),
+			"Ts" => Ok(Self::SetTextRise {
+				rise: opds.number(0)?,
+			}),
+
+			"Td" => {
+				let [t_x, t_y] = opds.numbers()?;
+				Ok(Self::MoveTextPosition { t_x, t_y })
+			}
+			"TD" => {
+				let [t_x, t_y] = opds.numbers()?;
+				Ok(Self::MoveTextPositionAndSetLeading { t_x, t_y })
+			}
+			"Tm" => {
+				let [a, b, c, d, e, f] = opds.numbers()?;
+				Ok(Self::SetTextMatrixAndTextLineMatrix { a, b, c, d, e, f })
+			}
+			"T*" => Ok(Self::MoveToStartOfNextLine),
+
+			"Tj" => Ok(Self::ShowText {
+				body: opds.string(0)?,
+			}),
+			// The strings are character codes in the current font, so they are kept
+			// as-is here and decoded later by a `text::TextInterpreter`.
+			"TJ" => Ok(Self::ShowTextAllowingIndividualGlyphPositioning {
+				elements: opds.text_elements(0)?,
+			}),
+			"'" => Ok(Self::MoveToNextLineAndShowText {
+				body: opds.string(0)?,
+			}),
+			"\"" => Ok(Self::SetSpacingMoveToNextLineAndShowText {
+				word_spacing: opds.number(0)?,
+				character_spacing: opds.number(1)?,
+				body: opds.string(2)?,
+			}),
+
+			"d0" => {
+				let [w_x, w_y] = opds.numbers()?;
+				Ok(Self::SetGlyphWidthForType3Font { w_x, w_y })
+			}
+			"d1" => {
+				let [w_x, w_y, ll_x, ll_y, ur_x, ur_y] = opds.numbers()?;
+				Ok(Self::SetGlyphWidthAndBoundingBoxForType3Font {
+					w_x,
+					w_y,
+					ll_x,
+					ll_y,
+					ur_x,
+					ur_y,
+				})
+			}
+
+			"MP" => Ok(Self::DesignateMarkedContentPoint { tag: opds.name(0)? }),
+			"DP" => Ok(Self::DesignateMarkedContentPointWithPropertyList {
+				tag: opds.name(0)?,
+				properties: opds.property_list(1)?,
+			}),
+			"BMC" => Ok(Self::BeginMarkedContentSequence { tag: opds.name(0)? }),
+			"BDC" => Ok(Self::BeginMarkedContentSequenceWithPropertyList {
+				tag: opds.name(0)?,
+				properties: opds.property_list(1)?,
+			}),
+			"EMC" => Ok(Self::EndMarkedContentSequence),
+
+			"BX" => Ok(Self::BeginCompatibilitySection),
+			"EX" => Ok(Self::EndCompatibilitySection),
+
+			operator => Err(error::ParseError::UnknownOperator {
+				operator: operator.to_string(),
+			}),
+		}
+	}
+}
+
+/// An operand, for writing operations out as they appear in a content
+/// stream.
+enum Operand<'a> {
+	Number(f32),
+	Integer(u32),
+	Name(&'a [u8]),
+	String(&'a [u8]),
+	Numbers(&'a [f32]),
+	Elements(&'a [TextElement]),
+	Properties(&'a PropertyList),
+}
+
+/// Write `name` as a PDF name, e.g. `/TT0`, with anything a name can't hold
+/// as `#xx` (Section 7.3.5).
+fn write_name(f: &mut fmt::Formatter<'_>, name: &[u8]) -> fmt::Result {
+	f.write_str("/")?;
+	for byte in name {
+		match byte {
+			b'!'..=b'~' if !b"#%()/<>[]{}".contains(byte) => write!(f, "{}", char::from(*byte))?,
+			_ => write!(f, "#{byte:02X}")?,
+		}
+	}
+	Ok(())
+}
+
+/// Write `bytes` as a literal string, e.g. `(Open)`, with anything
+/// unprintable as an octal escape (Section 7.3.4.2).
+fn write_string(f: &mut fmt::Formatter<'_>, bytes: &[u8]) -> fmt::Result {
+	f.write_str("(")?;
+	for byte in bytes {
+		match byte {
+			b'(' | b')' | b'\\' => write!(f, "\\{}", char::from(*byte))?,
+			b' '..=b'~' => write!(f, "{}", char::from(*byte))?,
+			_ => write!(f, "\\{byte:03o}")?,
+		}
+	}
+	f.write_str(")")
+}
+
+impl fmt::Display for Operand<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Number(number) => write!(f, "{number}"),
+			Self::Integer(integer) => write!(f, "{integer}"),
+			Self::Name(name) => write_name(f, name),
+			Self::String(bytes) => write_string(f, bytes),
+			Self::Numbers(numbers) => {
+				let numbers: Vec<String> = numbers.iter().map(ToString::to_string).collect();
+				write!(f, "[{}]", numbers.join(" "))
+			}
+			Self::Elements(elements) => {
+				f.write_str("[")?;
+				for (index, element) in elements.iter().enumerate() {
+					if index > 0 {
+						f.write_str(" ")?;
+					}
+					match element {
+						TextElement::Text(bytes) => write_string(f, bytes)?,
+						TextElement::Adjustment(adjustment) => write!(f, "{adjustment}")?,
+					}
+				}
+				f.write_str("]")
+			}
+			Self::Properties(PropertyList::Named { name }) => write_name(f, name),
+			Self::Properties(PropertyList::Inline { mcid, actual_text }) => {
+				f.write_str("<<")?;
+				if let Some(mcid) = mcid {
+					write!(f, " /MCID {mcid}")?;
+				}
+				if let Some(text) = actual_text {
+					let text: String = text
+						.replace('\\', "\\\\")
+						.replace('(', "\\(")
+						.replace(')', "\\)");
+					write!(f, " /ActualText ({text})")?;
+				}
+				f.write_str(" >>")
+			}
+		}
+	}
+}
+
+impl Operation {
+	/// The operator and its operands, in the order they're written.
+	#[allow(clippy::too_many_lines)]
+	fn operands(&self) -> (&'static str, Vec<Operand<'_>>) {
+		use Operand::{Elements, Integer, Name, Number, Numbers, Properties, String};
+
+		match self {
+			Self::SetLineWidth { width } => ("w", vec![Number(*width)]),
+			Self::SetLineCapStyle { style } => ("J", vec![Integer(*style)]),
+			Self::SetLineJoinStyle { style } => ("j", vec![Integer(*style)]),
+			Self::SetMiterLimit { limit } => ("M", vec![Number(*limit)]),
+			Self::SetLineDashPattern { array, phase } => ("d", vec![Numbers(array), Number(*phase)]),
+			Self::SetColorRenderingIntent { intent } => ("ri", vec![Name(intent)]),
+			Self::SetFlatnessTolerance { flatness } => ("i", vec![Number(*flatness)]),
+			Self::SetParametersFromGraphicsStateParameterDictionary { name } => ("gs", vec![Name(name)]),
+
+			Self::SaveGraphicsState => ("q", Vec::new()),
+			Self::RestoreGraphicsState => ("Q", Vec::new()),
+			Self::ModifyCurrentTransformationMatrix { a, b, c, d, e, f } => (
+				"cm",
+				[a, b, c, d, e, f].iter().map(|x| Number(**x)).collect(),
+			),
+
+			Self::BeginNewSubpath { x, y } => ("m", vec![Number(*x), Number(*y)]),
+			Self::AppendStraightLineSegmentToPath { x, y } => ("l", vec![Number(*x), Number(*y)]),
+			Self::AppendCurvedSegmentToPath {
+				x_1,
+				y_1,
+				x_2,
+				y_2,
+				x_3,
+				y_3,
+			} => (
+				"c",
+				[x_1, y_1, x_2, y_2, x_3, y_3]
+					.iter()
+					.map(|x| Number(**x))
+					.collect(),
+			),
+			Self::AppendCurvedSegmentToPathWithInitialPointReplicated { x_2, y_2, x_3, y_3 } => (
+				"v",
+				[x_2, y_2, x_3, y_3].iter().map(|x| Number(**x)).collect(),
+			),
+			Self::AppendCurvedSegmentToPathWithFinalPointReplicated { x_1, y_1, x_3, y_3 } => (
+				"y",
+				[x_1, y_1, x_3, y_3].iter().map(|x| Number(**x)).collect(),
+			),
+			Self::CloseSubpath => ("h", Vec::new()),
+			Self::AppendRectangleToPath {
+				x,
+				y,
+				width,
+				height,
+			} => (
+				"re",
+				[x, y, width, height].iter().map(|x| Number(**x)).collect(),
+			),
+
+			Self::StrokePath => ("S", Vec::new()),
+			Self::CloseAndStrokePath => ("s", Vec::new()),
+			Self::FillPathUsingNonzeroWindingNumberRule => ("f", Vec::new()),
+			Self::FillPathUsingNonzeroWindingNumberRuleObsolete => ("F", Vec::new()),
+			Self::FillPathUsingEvenOddRule => ("f*", Vec::new()),
+			Self::FillAndStrokePathUsingNonzeroWindingNumberRule => ("B", Vec::new()),
+			Self::FillAndStrokePathUsingEvenOddRule => ("B*", Vec::new()),
+			Self::CloseFillAndStrokePathUsingNonzeroWindingNumberRule => ("b", Vec::new()),
+			Self::CloseFillAndStrokePathUsingEvenOddRule => ("b*", Vec::new()),
+			Self::EndPathWithoutFillingOrStroking => ("n", Vec::new()),
+
+			Self::SetClippingPathUsingNonzeroWindingNumberRule => ("W", Vec::new()),
+			Self::SetClippingPathUsingEvenOddRule => ("W*", Vec::new()),
+
+			Self::SetColorSpaceForStrokingOperations { name } => ("CS", vec![Name(name)]),
+			Self::SetColorSpaceForNonstrokingOperations { name } => ("cs", vec![Name(name)]),
+			Self::SetColorForStrokingOperations { components } => {
+				("SC", components.iter().map(|x| Number(*x)).collect())
+			}
+			Self::SetColorForNonstrokingOperations { components } => {
+				("sc", components.iter().map(|x| Number(*x)).collect())
+			}
+			Self::SetColorOrPatternForStrokingOperations {
+				components,
+				pattern,
+			} => (
+				"SCN",
+				components
+					.iter()
+					.map(|x| Number(*x))
+					.chain(pattern.iter().map(|pattern| Name(pattern)))
+					.collect(),
+			),
+			Self::SetColorOrPatternForNonstrokingOperations {
+				components,
+				pattern,
+			} => (
+				"scn",
+				components
+					.iter()
+					.map(|x| Number(*x))
+					.chain(pattern.iter().map(|pattern| Name(pattern)))
+					.collect(),
+			),
+			Self::SetGrayLevelForStrokingOperations { gray } => ("G", vec![Number(*gray)]),
+			Self::SetGrayLevelForNonstrokingOperations { gray } => ("g", vec![Number(*gray)]),
+			Self::SetRgbColorForStrokingOperations { r, g, b } => {
+				("RG", vec![Number(*r), Number(*g), Number(*b)])
+			}
+			Self::SetRgbColorForNonstrokingOperations { r, g, b } => {
+				("rg", vec![Number(*r), Number(*g), Number(*b)])
+			}
+			Self::SetCmykColorForStrokingOperations { c, m, y, k } => {
+				("K", vec![Number(*c), Number(*m), Number(*y), Number(*k)])
+			}
+			Self::SetCmykColorForNonstrokingOperations { c, m, y, k } => {
+				("k", vec![Number(*c), Number(*m), Number(*y), Number(*k)])
+			}
+
+			Self::PaintShading { name } => ("sh", vec![Name(name)]),
+
+			Self::BeginInlineImageObject => ("BI", Vec::new()),
+			Self::BeginInlineImageData => ("ID", Vec::new()),
+			Self::EndInlineImageObject => ("EI", Vec::new()),
+
+			Self::InvokeNamedXObject { name } => ("Do", vec![Name(name)]),
+
+			Self::BeginTextObject => ("BT", Vec::new()),
+			Self::EndTextObject => ("ET", Vec::new()),
+
+			Self::SetCharacterSpacing { spacing } => ("Tc", vec![Number(*spacing)]),
+			Self::SetWordSpacing { spacing } => ("Tw", vec![Number(*spacing)]),
+			Self::SetHorizontalScaling { scale } => ("Tz", vec![Number(*scale)]),
+			Self::SetTextLeading { leading } => ("TL", vec![Number(*leading)]),
+			Self::SetTextFontAndSize { name, size } => ("Tf", vec![Name(name), Number(*size)]),
+			Self::SetTextRenderingMode { mode } => ("Tr", vec![Integer(*mode)]),
+			Self::SetTextRise { rise } => ("Ts", vec![Number(*rise)]),
+
+			Self::MoveTextPosition { t_x, t_y } => ("Td", vec![Number(*t_x), Number(*t_y)]),
+			Self::MoveTextPositionAndSetLeading { t_x, t_y } => ("TD", vec![Number(*t_x), Number(*t_y)]),
+			Self::SetTextMatrixAndTextLineMatrix { a, b, c, d, e, f } => (
+				"Tm",
+				[a, b, c, d, e, f].iter().map(|x| Number(**x)).collect(),
+			),
+			Self::MoveToStartOfNextLine => ("T*", Vec::new()),
+
+			Self::ShowText { body } => ("Tj", vec![String(body)]),
+			Self::ShowTextAllowingIndividualGlyphPositioning { elements } => {
+				("TJ", vec![Elements(elements)])
+			}
+			Self::MoveToNextLineAndShowText { body } => ("'", vec![String(body)]),
+			Self::SetSpacingMoveToNextLineAndShowText {
+				word_spacing,
+				character_spacing,
+				body,
+			} => (
+				"\"",
+				vec![
+					Number(*word_spacing),
+					Number(*character_spacing),
+					String(body),
+				],
+			),
+
+			Self::SetGlyphWidthForType3Font { w_x, w_y } => ("d0", vec![Number(*w_x), Number(*w_y)]),
+			Self::SetGlyphWidthAndBoundingBoxForType3Font {
+				w_x,
+				w_y,
+				ll_x,
+				ll_y,
+				ur_x,
+				ur_y,
+			} => (
+				"d1",
+				[w_x, w_y, ll_x, ll_y, ur_x, ur_y]
+					.iter()
+					.map(|x| Number(**x))
+					.collect(),
+			),
+
+			Self::DesignateMarkedContentPoint { tag } => ("MP", vec![Name(tag)]),
+			Self::DesignateMarkedContentPointWithPropertyList { tag, properties } => {
+				("DP", vec![Name(tag), Properties(properties)])
+			}
+			Self::BeginMarkedContentSequence { tag } => ("BMC", vec![Name(tag)]),
+			Self::BeginMarkedContentSequenceWithPropertyList { tag, properties } => {
+				("BDC", vec![Name(tag), Properties(properties)])
+			}
+			Self::EndMarkedContentSequence => ("EMC", Vec::new()),
+
+			Self::BeginCompatibilitySection => ("BX", Vec::new()),
+			Self::EndCompatibilitySection => ("EX", Vec::new()),
+		}
+	}
+}
+
+/// The operation as it would be written in a content stream, e.g.
+/// `/TT0 1 Tf`.
+impl fmt::Display for Operation {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let (operator, operands) = self.operands();
+		for operand in operands {
+			write!(f, "{operand} ")?;
+		}
+		f.write_str(operator)
+	}
+}
+
+/// A loaded PDF document.
+///
+/// This is the entry point for reading schedules out of PDFs: it wraps a
+/// `lopdf::Document` and hands out its `Page`s and `Info`.
+#[derive(Debug)]
+pub struct Pdf {
+	document: lopdf::Document,
+	source: Option<String>,
+}
+
+impl Pdf {
+	/// Load a document from a file.
+	///
+	/// # Errors
+	///
+	/// Returns an error if the file cannot be read or is not a PDF.
+	pub fn load<P: AsRef<std::path::Path>>(path: P) -> error::Result<Self> {
+		Ok(Self {
+			document: lopdf::Document::load(&path)?,
+			source: Some(path.as_ref().display().to_string()),
+		})
+	}
+
+	/// Load a document from the bytes of a PDF file.
+	///
+	/// # Errors
+	///
+	/// Returns an error if `bytes` are not a PDF.
+	pub fn from_bytes(bytes: &[u8]) -> error::Result<Self> {
+		Ok(Self::from(lopdf::Document::load_mem(bytes)?))
+	}
+
+	/// The version from the file header, e.g. `"1.5"`.
+	#[must_use]
+	pub fn version(&self) -> &str {
+		&self.document.version
+	}
+
+	/// The path the document was loaded from, if it came from a file.
+	#[must_use]
+	pub fn source(&self) -> Option<&str> {
+		self.source.as_deref()
+	}
+
+	#[must_use]
+	pub fn document(&self) -> &lopdf::Document {
+		&self.document
+	}
+
+	#[must_use]
+	pub fn info(&self) -> info::Info {
+		info::Info::from_document(&self.document)
+	}
+
+	/// The pages of the document, in order.
+	pub fn pages(&self) -> impl Iterator<Item = Page<'_>> + '_ {
+		self
+			.document
+			.get_pages()
+			.into_iter()
+			.map(move |(number, id)| Page::new(&self.document, number, id))
+	}
+
+	/// The document's structure tree, or `None` if it isn't tagged.
+	///
+	/// # Errors
+	///
+	/// Returns an error if the structure tree is malformed.
+	pub fn structure(&self) -> error::Result<Option<structure::StructureTree>> {
+		structure::StructureTree::from_document(&self.document)
+	}
+
+	/// The tables in the document.
+	///
+	/// These come from the structure tree.  An untagged document has none, so
+	/// each page with text on it is read as one table instead, laid out by
+	/// where its text was drawn (see `Table::from_runs`).
+	///
+	/// # Errors
+	///
+	/// Returns an error if the structure tree or the text of any page cannot
+	/// be read.
+	pub fn tables(&self) -> error::Result<Vec<table::Table>> {
+		let mut pages: table::PageRuns = table::PageRuns::new();
+		for page in self.pages() {
+			pages.insert(page.id(), (page.number(), page.text()?));
+		}
+
+		let Some(tree) = self.structure()? else {
+			let mut tables: Vec<table::Table> = pages
+				.values()
+				.filter(|(_, runs)| !runs.is_empty())
+				.map(|(number, runs)| table::Table::from_runs(*number, runs))
+				.collect();
+			tables.sort_by_key(table::Table::page);
+			return Ok(tables);
+		};
+
+		Ok(
+			tree
+				.find("Table")
+				.into_iter()
+				.map(|element| table::Table::from_structure(element, &pages))
+				.collect(),
+		)
+	}
+
+	/// The hours of each space on each day, read from every table.
+	///
+	/// Dates without a year are put in the year nearest when the document was
+	/// made, or today if it doesn't say.
+	///
+	/// # Errors
+	///
+	/// Returns an error if the tables cannot be read.
+	pub fn entries(&self) -> error::Result<Vec<headers::Entry>> {
+		let info = self.info();
+		let reference: chrono::NaiveDate = info
+			.creation_date()
+			.or_else(|| info.modification_date())
+			.map_or_else(
+				|| chrono::Local::now().date_naive(),
+				chrono::DateTime::date_naive,
+			);
+
+		Ok(
+			self
+				.tables()?
+				.iter()
+				.flat_map(|table| headers::entries(table, reference))
+				.collect(),
+		)
+	}
+
+	/// The page numbered `number`, counting from 1.
+	#[must_use]
+	pub fn page(&self, number: u32) -> Option<Page<'_>> {
+		self
+			.document
+			.get_pages()
+			.get(&number)
+			.map(|id| Page::new(&self.document, number, *id))
+	}
+}
+
+impl From<lopdf::Document> for Pdf {
+	fn from(document: lopdf::Document) -> Self {
+		Self {
+			document,
+			source: None,
+		}
+	}
+}
+
+/// A single page of a `Pdf`.
+#[derive(Clone, Copy, Debug)]
+pub struct Page<'pdf> {
+	document: &'pdf lopdf::Document,
+	number: u32,
+	id: lopdf::ObjectId,
+}
+
+impl<'pdf> Page<'pdf> {
+	fn new(document: &'pdf lopdf::Document, number: u32, id: lopdf::ObjectId) -> Self {
+		Self {
+			document,
+			number,
+			id,
+		}
+	}
+
+	/// The page's number, counting from 1.
+	#[must_use]
+	pub fn number(&self) -> u32 {
+		self.number
+	}
+
+	#[must_use]
+	pub fn id(&self) -> lopdf::ObjectId {
+		self.id
+	}
+
+	/// # Errors
+	///
+	/// Returns an error if the page object is not a dictionary.
+	pub fn dictionary(&self) -> error::Result<&'pdf lopdf::Dictionary> {
+		self
+			.document
+			.get_dictionary(self.id)
+			.map_err(|e| error::ParseError::from(e).on_page(self.number))
+	}
+
+	/// # Errors
+	///
+	/// Returns an error if the page's boundaries or rotation are missing or
+	/// malformed.
+	pub fn coordinate_space(&self) -> error::Result<CoordinateSpace> {
+		CoordinateSpace::from_page(self.document, self.id).map_err(|e| e.on_page(self.number))
+	}
+
+	/// The fonts in the page's resources, by resource name.
+	///
+	/// # Errors
+	///
+	/// Returns an error if a font dictionary or its streams cannot be read.
+	pub fn fonts(&self) -> error::Result<font::Fonts> {
+		font::page_fonts(self.document, self.id).map_err(|e| e.on_page(self.number))
+	}
+
+	/// Decode the page's content stream into an iterator of `Operation`s.
+	///
+	/// # Errors
+	///
+	/// Returns an error if the content stream cannot be read or tokenized; errors
+	/// in individual operations are instead yielded by the iterator.
+	pub fn operations(&self) -> error::Result<Operations> {
+		let content: Vec<u8> = self
+			.document
+			.get_page_content(self.id)
+			.map_err(|e| error::ParseError::from(e).on_page(self.number))?;
+		let content = lopdf::content::Content::decode(&content)
+			.map_err(|e| error::ParseError::from(e).on_page(self.number))?;
+
+		Ok(Operations {
+			page: self.number,
+			operations: content.operations.into_iter().enumerate(),
+		})
+	}
+
+	/// The text shown on the page, as positioned runs.
+	///
+	/// The runs are positioned on the page as displayed (see
+	/// `CoordinateSpace::matrix`), so a page with `/Rotate 90` reads the same
+	/// way as one drawn upright.
+	///
+	/// An operation which can't be decoded is skipped with a warning, so the
+	/// rest of the page still reads; between `BX` and `EX` unknown operators
+	/// are skipped silently, as the spec asks (Section 8.10.3).
+	///
+	/// # Errors
+	///
+	/// Returns an error if the page geometry, the fonts or the content stream
+	/// cannot be decoded.
+	pub fn text(&self) -> error::Result<Vec<text::TextRun>> {
+		let matrix: TransformationMatrix = self.coordinate_space()?.matrix();
+		let fonts: font::Fonts = self.fonts()?;
+
+		let mut interpreter = text::TextInterpreter::with_matrix(&fonts, matrix);
+		let mut compatibility: usize = 0;
+		for operation in self.operations()? {
+			match operation {
+				Ok(operation) => {
+					match operation {
+						Operation::BeginCompatibilitySection => compatibility += 1,
+						Operation::EndCompatibilitySection => {
+							compatibility = compatibility.saturating_sub(1);
+						}
+						_ => {}
+					}
+					interpreter.apply(&operation);
+				}
+				Err(error)
+					if compatibility > 0
+						&& matches!(error.root(), error::ParseError::UnknownOperator { .. }) => {}
+				Err(error) => log::warn!("skipping {error}"),
+			}
+		}
+		Ok(interpreter.into_runs())
+	}
+}
+
+/// The decoded operations of a page's content stream.
+///
+/// Each item is parsed as it is reached, so one bad operation doesn't hide
+/// the rest; errors say which page and operation they came from.
+#[derive(Debug)]
+pub struct Operations {
+	page: u32,
+	operations: core::iter::Enumerate<std::vec::IntoIter<lopdf::content::Operation>>,
+}
+
+impl Iterator for Operations {
+	type Item = error::Result<Operation>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let page: u32 = self.page;
+		self
+			.operations
+			.next()
+			.map(|(index, operation)| Operation::try_from(operation).map_err(|e| e.at(page, index)))
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		self.operations.size_hint()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	mod operation {
+		use super::super::{error::ParseError, Operation, TextElement};
+		use core::convert::TryFrom;
+		use lopdf::{content, Object};
+
+		fn parse(operator: &str, operands: Vec<Object>) -> Result<Operation, ParseError> {
+			Operation::try_from(content::Operation::new(operator, operands))
+		}
+
+		#[test]
+		fn integer_operands() {
+			assert_eq!(
+				parse("Td", vec![0.into(), Object::Real(-1.5)]).unwrap(),
+				Operation::MoveTextPosition {
+					t_x: 0.0,
+					t_y: -1.5
+				}
+			);
+			assert_eq!(
+				parse("Tf", vec![Object::Name(b"F1".to_vec()), 12.into()]).unwrap(),
+				Operation::SetTextFontAndSize {
+					name: b"F1".to_vec(),
+					size: 12.0
+				}
+			);
+			assert_eq!(
+				parse(
+					"TJ",
+					vec![Object::Array(vec![
+						Object::string_literal("A"),
+						(-250).into()
+					])]
+				)
+				.unwrap(),
+				Operation::ShowTextAllowingIndividualGlyphPositioning {
+					elements: vec![
+						TextElement::Text(b"A".to_vec()),
+						TextElement::Adjustment(-250.0)
+					]
+				}
+			);
+		}
+
+		#[test]
+		fn display() {
+			let written =
+				|operator: &str, operands: Vec<Object>| parse(operator, operands).unwrap().to_string();
+			assert_eq!(
+				written("Tf", vec![Object::Name(b"TT0".to_vec()), 12.into()]),
+				"/TT0 12 Tf"
+			);
+			assert_eq!(
+				written(
+					"TJ",
+					vec![Object::Array(vec![
+						Object::string_literal("Mon (1)"),
+						Object::Real(-250.5),
+						Object::String(vec![0x00, 0xE9], lopdf::StringFormat::Hexadecimal),
+					])]
+				),
+				"[(Mon \\(1\\)) -250.5 (\\000\\351)] TJ"
+			);
+			assert_eq!(
+				written(
+					"BDC",
+					vec![
+						Object::Name(b"Span".to_vec()),
+						Object::Dictionary(lopdf::dictionary! { "MCID" => 3 })
+					]
+				),
+				"/Span << /MCID 3 >> BDC"
+			);
+			assert_eq!(
+				written("gs", vec![Object::Name(b"GS 1#".to_vec())]),
+				"/GS#201#23 gs"
+			);
+			assert_eq!(written("BT", Vec::new()), "BT");
+		}
+
+		#[test]
+		fn color_operands() {
+			assert_eq!(
+				parse("scn", vec![Object::Real(0.5), Object::Name(b"P0".to_vec())]).unwrap(),
+				Operation::SetColorOrPatternForNonstrokingOperations {
+					components: vec![0.5],
+					pattern: Some(b"P0".to_vec())
+				}
+			);
+			assert_eq!(
+				parse("rg", vec![1.into(), 0.into(), 0.into()]).unwrap(),
+				Operation::SetRgbColorForNonstrokingOperations {
+					r: 1.0,
+					g: 0.0,
+					b: 0.0
+				}
+			);
+		}
+
+		#[test]
+		fn bad_operands_are_errors() {
+			assert!(matches!(
+				parse("cm", vec![1.into(), 0.into()]),
+				Err(ParseError::MissingOperands { index: 2, .. })
+			));
+			assert_eq!(
+				parse("CS", vec![Object::Real(1.0)])
+					.unwrap_err()
+					.to_string(),
+				"operand 0 of `CS` should be a name, but is real"
+			);
+			assert!(matches!(
+				parse("J", vec![(-1).into()]),
+				Err(ParseError::OperandType { .. })
+			));
+			assert!(matches!(
+				parse("zz", vec![]),
+				Err(ParseError::UnknownOperator { operator }) if operator == "zz"
+			));
+		}
+	}
+
+	mod page {
+		use super::super::{text::TextRun, Pdf};
+		use lopdf::{content::Content, dictionary, Document, Object, ObjectId, Stream};
+
+		/// A one-page document whose content stream is `content`.
+		fn pdf(content: &str) -> Pdf {
+			let mut document = Document::with_version("1.5");
+			let pages: ObjectId = document.new_object_id();
+			let font: ObjectId = document.add_object(dictionary! {
+				"Type" => "Font",
+				"Subtype" => "Type1",
+				"BaseFont" => "Helvetica",
+				"Encoding" => "WinAnsiEncoding",
+			});
+			let content = Content::decode(content.as_bytes()).unwrap();
+			let contents: ObjectId =
+				document.add_object(Stream::new(dictionary! {}, content.encode().unwrap()));
+			let page: ObjectId = document.add_object(dictionary! {
+				"Type" => "Page",
+				"Parent" => pages,
+				"Contents" => contents,
+				"Resources" => dictionary! { "Font" => dictionary! { "F1" => font } },
+				"MediaBox" => vec![0.into(), 0.into(), 612.into(), 792.into()],
+			});
+			document.objects.insert(
+				pages,
+				Object::Dictionary(dictionary! {
+					"Type" => "Pages",
+					"Kids" => vec![page.into()],
+					"Count" => 1,
+				}),
+			);
+			let catalog: ObjectId = document.add_object(dictionary! {
+				"Type" => "Catalog",
+				"Pages" => pages,
+			});
+			document.trailer.set("Root", catalog);
+			Pdf::from(document)
+		}
+
+		fn text(content: &str) -> Vec<String> {
+			pdf(content)
+				.page(1)
+				.unwrap()
+				.text()
+				.unwrap()
+				.iter()
+				.map(TextRun::text)
+				.map(str::to_string)
+				.collect()
+		}
+
+		#[test]
+		fn bad_operations_are_skipped() {
+			assert_eq!(
+				text("BT /F1 12 Tf 72 700 Td (Monday) Tj zz 0 -14 Td (Tuesday) Tj ET"),
+				["Monday", "Tuesday"]
+			);
+			assert_eq!(
+				text("BT /F1 12 Tf 72 700 Td (Monday) Tj /F1 Tc 0 -14 Td (Tuesday) Tj ET"),
+				["Monday", "Tuesday"]
+			);
+		}
+
+		#[test]
+		fn compatibility_sections() {
+			assert_eq!(
+				text("BT /F1 12 Tf 72 700 Td (Monday) Tj BX 1 zz EX 0 -14 Td (Tuesday) Tj ET"),
+				["Monday", "Tuesday"]
+			);
+		}
+	}
+
+	mod coordinate_space {
+		use super::super::{error::ParseError, CoordinateSpace, Coordinates};
+		use lopdf::{dictionary, Document, Object, ObjectId};
+
+		/// A document with one page under a `Pages` node which has the media box.
+		fn document(page: lopdf::Dictionary) -> (Document, ObjectId) {
+			let mut document = Document::with_version("1.5");
+			let parent: ObjectId = document.add_object(dictionary! {
+				"Type" => "Pages",
+				"MediaBox" => vec![0.into(), 0.into(), 612.into(), 792.into()],
+				"Rotate" => 90,
+			});
+
+			let mut page = page;
+			page.set("Type", "Page");
+			page.set("Parent", parent);
+			let page_id: ObjectId = document.add_object(page);
+
+			(document, page_id)
+		}
+
+		fn assert_close(actual: Coordinates, x: f64, y: f64) {
+			assert!(
+				(actual.x() - x).abs() < 1e-9 && (actual.y() - y).abs() < 1e-9,
+				"{:?} != ({}, {})",
+				actual,
+				x,
+				y
+			);
+		}
+
+		#[test]
+		fn inherited_integer_media_box_and_rotation() {
+			let (document, page_id) = document(dictionary! {});
+			let space = CoordinateSpace::from_page(&document, page_id).unwrap();
+
+			assert_eq!(space.rotation(), 90);
+			assert!((space.width() - 792.0).abs() < 1e-9);
+			assert!((space.height() - 612.0).abs() < 1e-9);
+
+			// Turned clockwise, the bottom-left corner ends up at the top-left.
+			let matrix = space.matrix();
+			assert_close(Coordinates::new(0.0, 0.0).transform(&matrix), 0.0, 612.0);
+			assert_close(
+				Coordinates::new(612.0, 792.0).transform(&matrix),
+				792.0,
+				0.0,
+			);
+		}
+
+		#[test]
+		fn own_crop_box_rotation_and_user_unit() {
+			let (document, page_id) = document(dictionary! {
+				"CropBox" => vec![Object::Real(10.0), Object::Real(20.0), 2000.into(), 500.into()],
+				"Rotate" => -180,
+				"UserUnit" => 2,
+			});
+			let space = CoordinateSpace::from_page(&document, page_id).unwrap();
+
+			assert_eq!(space.rotation(), 180);
+			assert_close(space.crop_box().upper_right(), 612.0, 500.0);
+			assert!((space.width() - 2.0 * 602.0).abs() < 1e-9);
+
+			let matrix = space.matrix();
+			assert_close(
+				Coordinates::new(10.0, 20.0).transform(&matrix),
+				1204.0,
+				960.0,
+			);
+			assert_close(Coordinates::new(612.0, 500.0).transform(&matrix), 0.0, 0.0);
+		}
+
+		#[test]
+		fn malformed_attributes() {
+			let (document, page_id) = document(dictionary! { "Rotate" => 45 });
+			assert!(matches!(
+				CoordinateSpace::from_page(&document, page_id),
+				Err(ParseError::Malformed(_))
+			));
+
+			let mut document = Document::with_version("1.5");
+			let page_id: ObjectId = document.add_object(dictionary! { "Type" => "Page" });
+			assert!(matches!(
+				CoordinateSpace::from_page(&document, page_id),
+				Err(ParseError::Malformed(_))
+			));
+		}
 	}
 }
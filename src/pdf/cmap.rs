@@ -0,0 +1,442 @@
+//! A small reader for the `CMap` files embedded in PDFs.
+//!
+//! Only the parts of the `CMap` syntax that matter for text extraction are
+//! interpreted: codespace ranges (to split a string into character codes),
+//! `bfchar`/`bfrange` (the `/ToUnicode` mappings) and `cidchar`/`cidrange`
+//! (for embedded `/Encoding` `CMap`s of composite fonts).  Everything else in the
+//! PostScript program is skipped.
+
+use core::convert::TryFrom;
+use std::collections::BTreeMap;
+
+/// A range of valid input codes of a given byte length.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct CodespaceRange {
+	low: Vec<u8>,
+	high: Vec<u8>,
+}
+
+impl CodespaceRange {
+	fn contains(&self, bytes: &[u8]) -> bool {
+		bytes.len() == self.low.len()
+			&& bytes
+				.iter()
+				.zip(self.low.iter().zip(self.high.iter()))
+				.all(|(byte, (low, high))| low <= byte && byte <= high)
+	}
+}
+
+/// A parsed `CMap`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CMap {
+	codespace: Vec<CodespaceRange>,
+	unicode: BTreeMap<(usize, u32), String>,
+	cids: Vec<(usize, u32, u32, u32)>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Token<'data> {
+	Hex(Vec<u8>),
+	Literal(Vec<u8>),
+	Name(&'data [u8]),
+	Number(i64),
+	ArrayStart,
+	ArrayEnd,
+	Keyword(&'data [u8]),
+}
+
+fn is_delimiter(byte: u8) -> bool {
+	matches!(
+		byte,
+		b'(' | b')' | b'<' | b'>' | b'[' | b']' | b'{' | b'}' | b'/' | b'%'
+	)
+}
+
+fn tokenize(data: &[u8]) -> Vec<Token<'_>> {
+	let mut tokens: Vec<Token> = Vec::new();
+	let mut position: usize = 0;
+
+	while position < data.len() {
+		let byte: u8 = data[position];
+
+		match byte {
+			b'%' => {
+				while position < data.len() && data[position] != b'\n' && data[position] != b'\r' {
+					position += 1;
+				}
+			}
+			b'<' if data.get(position + 1) == Some(&b'<') => position += 2,
+			b'>' if data.get(position + 1) == Some(&b'>') => position += 2,
+			b'<' => {
+				let end: usize = data[position..]
+					.iter()
+					.position(|b| *b == b'>')
+					.map_or(data.len(), |offset| position + offset);
+				let digits: Vec<u8> = data[position + 1..end]
+					.iter()
+					.copied()
+					.filter(u8::is_ascii_hexdigit)
+					.collect();
+				tokens.push(Token::Hex(decode_hex(&digits)));
+				position = end + 1;
+			}
+			b'(' => {
+				let mut depth: usize = 0;
+				let mut literal: Vec<u8> = Vec::new();
+				position += 1;
+				while position < data.len() {
+					match data[position] {
+						b'\\' => {
+							position += 1;
+							if let Some(escaped) = data.get(position) {
+								literal.push(*escaped);
+							}
+						}
+						b'(' => {
+							depth += 1;
+							literal.push(b'(');
+						}
+						b')' if depth == 0 => break,
+						b')' => {
+							depth -= 1;
+							literal.push(b')');
+						}
+						other => literal.push(other),
+					}
+					position += 1;
+				}
+				tokens.push(Token::Literal(literal));
+				position += 1;
+			}
+			b'[' => {
+				tokens.push(Token::ArrayStart);
+				position += 1;
+			}
+			b']' => {
+				tokens.push(Token::ArrayEnd);
+				position += 1;
+			}
+			_ if byte.is_ascii_whitespace() || is_delimiter(byte) && byte != b'/' => position += 1,
+			_ => {
+				let start: usize = position;
+				position += 1;
+				while position < data.len()
+					&& !data[position].is_ascii_whitespace()
+					&& !is_delimiter(data[position])
+				{
+					position += 1;
+				}
+				let word: &[u8] = &data[start..position];
+				if let Some(name) = word.strip_prefix(b"/") {
+					tokens.push(Token::Name(name));
+				} else if let Some(number) = std::str::from_utf8(word)
+					.ok()
+					.and_then(|word| word.parse::<i64>().ok())
+				{
+					tokens.push(Token::Number(number));
+				} else {
+					tokens.push(Token::Keyword(word));
+				}
+			}
+		}
+	}
+
+	tokens
+}
+
+fn decode_hex(digits: &[u8]) -> Vec<u8> {
+	digits
+		.chunks(2)
+		.map(|pair| {
+			let high: u8 = hex_value(pair[0]);
+			let low: u8 = pair.get(1).copied().map_or(0, hex_value);
+			high << 4 | low
+		})
+		.collect()
+}
+
+fn hex_value(digit: u8) -> u8 {
+	match digit {
+		b'0'..=b'9' => digit - b'0',
+		b'a'..=b'f' => digit - b'a' + 10,
+		b'A'..=b'F' => digit - b'A' + 10,
+		_ => 0,
+	}
+}
+
+fn code_of(bytes: &[u8]) -> u32 {
+	bytes
+		.iter()
+		.take(4)
+		.fold(0_u32, |code, byte| code << 8 | u32::from(*byte))
+}
+
+/// Destination strings in a `/ToUnicode` `CMap` are UTF-16BE.
+fn utf16be(bytes: &[u8]) -> String {
+	let units: Vec<u16> = bytes
+		.chunks(2)
+		.map(|pair| u16::from(pair[0]) << 8 | u16::from(pair.get(1).copied().unwrap_or(0)))
+		.collect();
+	String::from_utf16_lossy(&units)
+}
+
+/// Increment the last code unit of a UTF-16BE destination, as `bfrange` does.
+fn increment(destination: &[u8], by: u32) -> Vec<u8> {
+	let mut destination: Vec<u8> = destination.to_vec();
+	if destination.len() >= 2 {
+		let at: usize = destination.len() - 2;
+		let last: u32 = u32::from(destination[at]) << 8 | u32::from(destination[at + 1]);
+		let last: u32 = last.wrapping_add(by);
+		destination[at] = ((last >> 8) & 0xff) as u8;
+		destination[at + 1] = (last & 0xff) as u8;
+	} else if let Some(last) = destination.last_mut() {
+		*last = last.wrapping_add((by & 0xff) as u8);
+	}
+	destination
+}
+
+impl CMap {
+	/// Parse the (decompressed) contents of a `CMap` stream.
+	///
+	/// Parsing is lenient; anything which is not understood is skipped, so the
+	/// result may be empty but is never an error.
+	#[must_use]
+	pub fn parse(data: &[u8]) -> Self {
+		let tokens: Vec<Token> = tokenize(data);
+		let mut cmap: Self = Self::default();
+		let mut index: usize = 0;
+
+		while index < tokens.len() {
+			match &tokens[index] {
+				Token::Keyword(b"begincodespacerange") => {
+					index += 1;
+					while let (Some(Token::Hex(low)), Some(Token::Hex(high))) =
+						(tokens.get(index), tokens.get(index + 1))
+					{
+						cmap.codespace.push(CodespaceRange {
+							low: low.clone(),
+							high: high.clone(),
+						});
+						index += 2;
+					}
+				}
+				Token::Keyword(b"beginbfchar") => {
+					index += 1;
+					while let (Some(Token::Hex(source)), Some(destination)) =
+						(tokens.get(index), tokens.get(index + 1))
+					{
+						let text: Option<String> = match destination {
+							Token::Hex(bytes) => Some(utf16be(bytes)),
+							Token::Name(name) => std::str::from_utf8(name)
+								.ok()
+								.and_then(super::encoding::glyph_name_to_char)
+								.map(String::from),
+							_ => None,
+						};
+						if let Some(text) = text {
+							cmap.unicode.insert((source.len(), code_of(source)), text);
+						}
+						index += 2;
+					}
+				}
+				Token::Keyword(b"beginbfrange") => {
+					index += 1;
+					while let (Some(Token::Hex(low)), Some(Token::Hex(high))) =
+						(tokens.get(index), tokens.get(index + 1))
+					{
+						let length: usize = low.len();
+						let (low, high): (u32, u32) = (code_of(low), code_of(high));
+						index += 2;
+
+						match tokens.get(index) {
+							Some(Token::Hex(destination)) => {
+								for code in low..=high {
+									let text: String = utf16be(&increment(destination, code - low));
+									cmap.unicode.insert((length, code), text);
+								}
+								index += 1;
+							}
+							Some(Token::ArrayStart) => {
+								index += 1;
+								let mut code: u32 = low;
+								while let Some(Token::Hex(destination)) = tokens.get(index) {
+									if code <= high {
+										cmap.unicode.insert((length, code), utf16be(destination));
+									}
+									code += 1;
+									index += 1;
+								}
+								if tokens.get(index) == Some(&Token::ArrayEnd) {
+									index += 1;
+								}
+							}
+							_ => {}
+						}
+					}
+				}
+				Token::Keyword(b"begincidchar") => {
+					index += 1;
+					while let (Some(Token::Hex(source)), Some(Token::Number(cid))) =
+						(tokens.get(index), tokens.get(index + 1))
+					{
+						let code: u32 = code_of(source);
+						let cid: u32 = u32::try_from(*cid).unwrap_or_default();
+						cmap.cids.push((source.len(), code, code, cid));
+						index += 2;
+					}
+				}
+				Token::Keyword(b"begincidrange") => {
+					index += 1;
+					while let (Some(Token::Hex(low)), Some(Token::Hex(high)), Some(Token::Number(cid))) = (
+						tokens.get(index),
+						tokens.get(index + 1),
+						tokens.get(index + 2),
+					) {
+						let cid: u32 = u32::try_from(*cid).unwrap_or_default();
+						cmap
+							.cids
+							.push((low.len(), code_of(low), code_of(high), cid));
+						index += 3;
+					}
+				}
+				_ => index += 1,
+			}
+		}
+
+		cmap
+	}
+
+	/// The predefined `Identity-H`/`Identity-V` `CMap`s: two-byte codes which are
+	/// their own CIDs.
+	#[must_use]
+	pub fn identity() -> Self {
+		Self {
+			codespace: vec![CodespaceRange {
+				low: vec![0x00, 0x00],
+				high: vec![0xff, 0xff],
+			}],
+			unicode: BTreeMap::new(),
+			cids: vec![(2, 0x0000, 0xffff, 0)],
+		}
+	}
+
+	/// Whether the `CMap` declares any codespace ranges of its own.
+	#[must_use]
+	pub fn has_codespace(&self) -> bool {
+		!self.codespace.is_empty()
+	}
+
+	/// Split the next character code off of the front of `bytes`, returning the
+	/// code and the number of bytes it occupied.
+	///
+	/// Codes which match no codespace range consume the shortest declared code
+	/// length (or a single byte), per Section 9.7.6.3 of the PDF spec.
+	#[must_use]
+	pub fn next_code(&self, bytes: &[u8]) -> (u32, usize) {
+		for length in 1..=4 {
+			if bytes.len() < length {
+				break;
+			}
+
+			let candidate: &[u8] = &bytes[..length];
+			if self.codespace.iter().any(|range| range.contains(candidate)) {
+				return (code_of(candidate), length);
+			}
+		}
+
+		let length: usize = self
+			.codespace
+			.iter()
+			.map(|range| range.low.len())
+			.min()
+			.unwrap_or(1)
+			.min(bytes.len())
+			.max(1);
+
+		(code_of(&bytes[..length.min(bytes.len())]), length)
+	}
+
+	/// Look up the Unicode text for a code of the given byte length.
+	#[must_use]
+	pub fn unicode(&self, code: u32, length: usize) -> Option<&str> {
+		self
+			.unicode
+			.get(&(length, code))
+			.or_else(|| {
+				// Some producers write one-byte sources for two-byte fonts (and vice
+				// versa); fall back to any mapping for the same numeric code.
+				self
+					.unicode
+					.iter()
+					.find(|((_, candidate), _)| *candidate == code)
+					.map(|(_, text)| text)
+			})
+			.map(String::as_str)
+	}
+
+	/// Look up the CID selected by a code of the given byte length.
+	#[must_use]
+	pub fn cid(&self, code: u32, length: usize) -> Option<u32> {
+		self
+			.cids
+			.iter()
+			.find(|(range_length, low, high, _)| {
+				*range_length == length && (*low..=*high).contains(&code)
+			})
+			.map(|(_, low, _, cid)| cid + (code - low))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::CMap;
+
+	const TO_UNICODE: &[u8] = b"/CIDInit /ProcSet findresource begin
+12 dict begin
+begincmap
+/CMapName /Adobe-Identity-UCS def
+/CMapType 2 def
+1 begincodespacerange
+<0000> <FFFF>
+endcodespacerange
+2 beginbfchar
+<0003> <0020>
+<0010> <2013>
+endbfchar
+2 beginbfrange
+<0024> <0026> <0061>
+<0030> <0031> [<0066006C> <D83DDE00>]
+endbfrange
+endcmap
+CMapName currentdict /CMap defineresource pop
+end
+end";
+
+	#[test]
+	fn bfchar_and_bfrange() {
+		let cmap = CMap::parse(TO_UNICODE);
+		assert_eq!(cmap.unicode(0x0003, 2), Some(" "));
+		assert_eq!(cmap.unicode(0x0010, 2), Some("–"));
+		assert_eq!(cmap.unicode(0x0025, 2), Some("b"));
+		assert_eq!(cmap.unicode(0x0030, 2), Some("fl"));
+		assert_eq!(cmap.unicode(0x0031, 2), Some("😀"));
+		assert_eq!(cmap.unicode(0x0027, 2), None);
+	}
+
+	#[test]
+	fn codespace_splits_codes() {
+		let cmap = CMap::parse(TO_UNICODE);
+		assert_eq!(cmap.next_code(&[0x00, 0x24, 0x00]), (0x0024, 2));
+
+		let mixed = CMap::parse(b"2 begincodespacerange <00> <80> <8140> <9FFC> endcodespacerange");
+		assert_eq!(mixed.next_code(&[0x41, 0x81, 0x40]), (0x41, 1));
+		assert_eq!(mixed.next_code(&[0x81, 0x40]), (0x8140, 2));
+	}
+
+	#[test]
+	fn cid_ranges() {
+		let cmap = CMap::parse(b"1 begincidrange <0020> <007e> 1 endcidrange");
+		assert_eq!(cmap.cid(0x0021, 2), Some(2));
+		assert_eq!(CMap::identity().cid(0x1234, 2), Some(0x1234));
+	}
+}
@@ -0,0 +1,468 @@
+//! Single-byte base encodings and glyph names used by simple fonts.
+//!
+//! See Annex D of the PDF spec for the character sets and encodings; only the
+//! upper halves of the tables differ from ASCII in interesting ways, so the
+//! tables here cover `0x80..=0xFF` and the handful of lower-half exceptions.
+
+use core::convert::TryFrom;
+
+/// One of the predefined encodings which may be named in a font's `/Encoding`
+/// entry or as the `/BaseEncoding` of an encoding dictionary.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BaseEncoding {
+	Standard,
+	WinAnsi,
+	MacRoman,
+}
+
+impl BaseEncoding {
+	/// Look up a base encoding by its PDF name (e.g. `WinAnsiEncoding`).
+	#[must_use]
+	pub fn from_name(name: &[u8]) -> Option<Self> {
+		match name {
+			b"StandardEncoding" => Some(Self::Standard),
+			b"WinAnsiEncoding" => Some(Self::WinAnsi),
+			b"MacRomanEncoding" => Some(Self::MacRoman),
+			_ => None,
+		}
+	}
+
+	/// Build the full 256-entry code-to-character table for this encoding.
+	#[must_use]
+	pub fn table(self) -> [Option<char>; 256] {
+		let mut table = [None; 256];
+
+		for code in 0x20_u8..0x7f {
+			table[usize::from(code)] = Some(char::from(code));
+		}
+
+		let (upper, offset): (&str, usize) = match self {
+			Self::Standard => {
+				table[0x27] = Some('\u{2019}');
+				table[0x60] = Some('\u{2018}');
+				for (code, ch) in STANDARD_UPPER {
+					table[usize::from(*code)] = Some(*ch);
+				}
+				return table;
+			}
+			Self::WinAnsi => (WIN_ANSI_UPPER, 0x80),
+			Self::MacRoman => (MAC_ROMAN_UPPER, 0x80),
+		};
+
+		for (index, ch) in upper.chars().enumerate() {
+			if ch != UNDEFINED {
+				table[offset + index] = Some(ch);
+			}
+		}
+
+		table
+	}
+}
+
+/// Placeholder used in the tables below for codes the encoding leaves undefined.
+const UNDEFINED: char = '\u{0}';
+
+/// `WinAnsiEncoding` for `0x80..=0xFF`; the upper half is Latin-1 apart from the
+/// Windows-1252 punctuation block at `0x80..=0x9F`.
+const WIN_ANSI_UPPER: &str = "\u{20ac}\u{0}\u{201a}\u{0192}\u{201e}\u{2026}\u{2020}\u{2021}\
+\u{2c6}\u{2030}\u{160}\u{2039}\u{152}\u{0}\u{17d}\u{0}\
+\u{0}\u{2018}\u{2019}\u{201c}\u{201d}\u{2022}\u{2013}\u{2014}\
+\u{2dc}\u{2122}\u{161}\u{203a}\u{153}\u{0}\u{17e}\u{178}\
+\u{20}¡¢£¤¥¦§¨©ª«¬\u{ad}®¯°±²³´µ¶·¸¹º»¼½¾¿\
+ÀÁÂÃÄÅÆÇÈÉÊËÌÍÎÏÐÑÒÓÔÕÖ×ØÙÚÛÜÝÞß\
+àáâãäåæçèéêëìíîïðñòóôõö÷øùúûüýþÿ";
+
+/// `MacRomanEncoding` for `0x80..=0xFF`.
+const MAC_ROMAN_UPPER: &str = "ÄÅÇÉÑÖÜáàâäãåçéèêëíìîïñóòôöõúùûü\
+†°¢£§•¶ß®©™´¨≠ÆØ∞±≤≥¥µ∂∑∏π∫ªºΩæø\
+¿¡¬√ƒ≈∆«»…\u{a0}ÀÃÕŒœ–—“”‘’÷◊ÿŸ⁄¤‹›ﬁﬂ\
+‡·‚„‰ÂÊÁËÈÍÎÏÌÓÔ\u{0}ÒÚÛÙıˆ˜¯˘˙˚¸˝˛ˇ";
+
+/// `StandardEncoding` is sparse in its upper half, so list it by code.
+const STANDARD_UPPER: &[(u8, char)] = &[
+	(0xa1, '¡'),
+	(0xa2, '¢'),
+	(0xa3, '£'),
+	(0xa4, '⁄'),
+	(0xa5, '¥'),
+	(0xa6, 'ƒ'),
+	(0xa7, '§'),
+	(0xa8, '¤'),
+	(0xa9, '\''),
+	(0xaa, '“'),
+	(0xab, '«'),
+	(0xac, '‹'),
+	(0xad, '›'),
+	(0xae, 'ﬁ'),
+	(0xaf, 'ﬂ'),
+	(0xb1, '–'),
+	(0xb2, '†'),
+	(0xb3, '‡'),
+	(0xb4, '·'),
+	(0xb6, '¶'),
+	(0xb7, '•'),
+	(0xb8, '‚'),
+	(0xb9, '„'),
+	(0xba, '”'),
+	(0xbb, '»'),
+	(0xbc, '…'),
+	(0xbd, '‰'),
+	(0xbf, '¿'),
+	(0xc1, '`'),
+	(0xc2, '´'),
+	(0xc3, 'ˆ'),
+	(0xc4, '˜'),
+	(0xc5, '¯'),
+	(0xc6, '˘'),
+	(0xc7, '˙'),
+	(0xc8, '¨'),
+	(0xca, '˚'),
+	(0xcb, '¸'),
+	(0xcd, '˝'),
+	(0xce, '˛'),
+	(0xcf, 'ˇ'),
+	(0xd0, '—'),
+	(0xe1, 'Æ'),
+	(0xe3, 'ª'),
+	(0xe8, 'Ł'),
+	(0xe9, 'Ø'),
+	(0xea, 'Œ'),
+	(0xeb, 'º'),
+	(0xf1, 'æ'),
+	(0xf5, 'ı'),
+	(0xf8, 'ł'),
+	(0xf9, 'ø'),
+	(0xfa, 'œ'),
+	(0xfb, 'ß'),
+];
+
+/// Glyph names for the printable ASCII range, starting at `0x20`.
+const ASCII_NAMES: [&str; 95] = [
+	"space",
+	"exclam",
+	"quotedbl",
+	"numbersign",
+	"dollar",
+	"percent",
+	"ampersand",
+	"quotesingle",
+	"parenleft",
+	"parenright",
+	"asterisk",
+	"plus",
+	"comma",
+	"hyphen",
+	"period",
+	"slash",
+	"zero",
+	"one",
+	"two",
+	"three",
+	"four",
+	"five",
+	"six",
+	"seven",
+	"eight",
+	"nine",
+	"colon",
+	"semicolon",
+	"less",
+	"equal",
+	"greater",
+	"question",
+	"at",
+	"A",
+	"B",
+	"C",
+	"D",
+	"E",
+	"F",
+	"G",
+	"H",
+	"I",
+	"J",
+	"K",
+	"L",
+	"M",
+	"N",
+	"O",
+	"P",
+	"Q",
+	"R",
+	"S",
+	"T",
+	"U",
+	"V",
+	"W",
+	"X",
+	"Y",
+	"Z",
+	"bracketleft",
+	"backslash",
+	"bracketright",
+	"asciicircum",
+	"underscore",
+	"grave",
+	"a",
+	"b",
+	"c",
+	"d",
+	"e",
+	"f",
+	"g",
+	"h",
+	"i",
+	"j",
+	"k",
+	"l",
+	"m",
+	"n",
+	"o",
+	"p",
+	"q",
+	"r",
+	"s",
+	"t",
+	"u",
+	"v",
+	"w",
+	"x",
+	"y",
+	"z",
+	"braceleft",
+	"bar",
+	"braceright",
+	"asciitilde",
+];
+
+/// Glyph names for Latin-1, starting at `0xA1`.
+const LATIN1_NAMES: [&str; 95] = [
+	"exclamdown",
+	"cent",
+	"sterling",
+	"currency",
+	"yen",
+	"brokenbar",
+	"section",
+	"dieresis",
+	"copyright",
+	"ordfeminine",
+	"guillemotleft",
+	"logicalnot",
+	"uni00AD",
+	"registered",
+	"macron",
+	"degree",
+	"plusminus",
+	"twosuperior",
+	"threesuperior",
+	"acute",
+	"mu",
+	"paragraph",
+	"periodcentered",
+	"cedilla",
+	"onesuperior",
+	"ordmasculine",
+	"guillemotright",
+	"onequarter",
+	"onehalf",
+	"threequarters",
+	"questiondown",
+	"Agrave",
+	"Aacute",
+	"Acircumflex",
+	"Atilde",
+	"Adieresis",
+	"Aring",
+	"AE",
+	"Ccedilla",
+	"Egrave",
+	"Eacute",
+	"Ecircumflex",
+	"Edieresis",
+	"Igrave",
+	"Iacute",
+	"Icircumflex",
+	"Idieresis",
+	"Eth",
+	"Ntilde",
+	"Ograve",
+	"Oacute",
+	"Ocircumflex",
+	"Otilde",
+	"Odieresis",
+	"multiply",
+	"Oslash",
+	"Ugrave",
+	"Uacute",
+	"Ucircumflex",
+	"Udieresis",
+	"Yacute",
+	"Thorn",
+	"germandbls",
+	"agrave",
+	"aacute",
+	"acircumflex",
+	"atilde",
+	"adieresis",
+	"aring",
+	"ae",
+	"ccedilla",
+	"egrave",
+	"eacute",
+	"ecircumflex",
+	"edieresis",
+	"igrave",
+	"iacute",
+	"icircumflex",
+	"idieresis",
+	"eth",
+	"ntilde",
+	"ograve",
+	"oacute",
+	"ocircumflex",
+	"otilde",
+	"odieresis",
+	"divide",
+	"oslash",
+	"ugrave",
+	"uacute",
+	"ucircumflex",
+	"udieresis",
+	"yacute",
+	"thorn",
+	"ydieresis",
+];
+
+/// Glyph names outside of ASCII and Latin-1 which turn up in Latin text.
+const OTHER_NAMES: &[(&str, char)] = &[
+	("nbspace", '\u{a0}'),
+	("sfthyphen", '\u{ad}'),
+	("middot", '·'),
+	("endash", '–'),
+	("emdash", '—'),
+	("minus", '−'),
+	("quoteleft", '‘'),
+	("quoteright", '’'),
+	("quotesinglbase", '‚'),
+	("quotedblleft", '“'),
+	("quotedblright", '”'),
+	("quotedblbase", '„'),
+	("guilsinglleft", '‹'),
+	("guilsinglright", '›'),
+	("dagger", '†'),
+	("daggerdbl", '‡'),
+	("bullet", '•'),
+	("ellipsis", '…'),
+	("perthousand", '‰'),
+	("trademark", '™'),
+	("Euro", '€'),
+	("florin", 'ƒ'),
+	("fraction", '⁄'),
+	("circumflex", 'ˆ'),
+	("tilde", '˜'),
+	("breve", '˘'),
+	("dotaccent", '˙'),
+	("ring", '˚'),
+	("ogonek", '˛'),
+	("caron", 'ˇ'),
+	("hungarumlaut", '˝'),
+	("dotlessi", 'ı'),
+	("Lslash", 'Ł'),
+	("lslash", 'ł'),
+	("OE", 'Œ'),
+	("oe", 'œ'),
+	("Scaron", 'Š'),
+	("scaron", 'š'),
+	("Zcaron", 'Ž'),
+	("zcaron", 'ž'),
+	("Ydieresis", 'Ÿ'),
+	("fi", 'ﬁ'),
+	("fl", 'ﬂ'),
+	("ff", 'ﬀ'),
+	("ffi", 'ﬃ'),
+	("ffl", 'ﬄ'),
+	("notequal", '≠'),
+	("lessequal", '≤'),
+	("greaterequal", '≥'),
+	("infinity", '∞'),
+	("arrowright", '→'),
+];
+
+/// Map a glyph name (as used in a `/Differences` array) to the character it
+/// represents.
+///
+/// This covers the names that appear in Latin text as well as the `uniXXXX` and
+/// `uXXXX[XX]` forms from the Adobe Glyph List specification.  Suffixes such as
+/// `.sc` or `.alt` are ignored.
+#[must_use]
+pub fn glyph_name_to_char(name: &str) -> Option<char> {
+	let name: &str = name.split('.').next().unwrap_or(name);
+
+	if let Some(index) = ASCII_NAMES.iter().position(|n| *n == name) {
+		return char::from_u32(0x20 + u32::try_from(index).ok()?);
+	}
+
+	if let Some(index) = LATIN1_NAMES.iter().position(|n| *n == name) {
+		return char::from_u32(0xa1 + u32::try_from(index).ok()?);
+	}
+
+	if let Some((_, ch)) = OTHER_NAMES.iter().find(|(n, _)| *n == name) {
+		return Some(*ch);
+	}
+
+	let hex: &str = name
+		.strip_prefix("uni")
+		.filter(|hex| hex.len() == 4)
+		.or_else(|| {
+			name
+				.strip_prefix('u')
+				.filter(|hex| (4..=6).contains(&hex.len()))
+		})?;
+
+	u32::from_str_radix(hex, 16).ok().and_then(char::from_u32)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn win_ansi_punctuation() {
+		let table = BaseEncoding::WinAnsi.table();
+		assert_eq!(table[0x41], Some('A'));
+		assert_eq!(table[0x96], Some('–'));
+		assert_eq!(table[0x92], Some('’'));
+		assert_eq!(table[0x81], None);
+		assert_eq!(table[0xe9], Some('é'));
+	}
+
+	#[test]
+	fn mac_roman_upper_half() {
+		let table = BaseEncoding::MacRoman.table();
+		assert_eq!(table[0x80], Some('Ä'));
+		assert_eq!(table[0xd0], Some('–'));
+		assert_eq!(table[0xf0], None);
+		assert_eq!(table[0xff], Some('ˇ'));
+	}
+
+	#[test]
+	fn standard_quotes() {
+		let table = BaseEncoding::Standard.table();
+		assert_eq!(table[0x27], Some('’'));
+		assert_eq!(table[0xb1], Some('–'));
+		assert_eq!(table[0xb0], None);
+	}
+
+	#[test]
+	fn glyph_names() {
+		assert_eq!(glyph_name_to_char("a"), Some('a'));
+		assert_eq!(glyph_name_to_char("period"), Some('.'));
+		assert_eq!(glyph_name_to_char("endash"), Some('–'));
+		assert_eq!(glyph_name_to_char("eacute"), Some('é'));
+		assert_eq!(glyph_name_to_char("uni2013"), Some('–'));
+		assert_eq!(glyph_name_to_char("u1F600"), Some('😀'));
+		assert_eq!(glyph_name_to_char("a.sc"), Some('a'));
+		assert_eq!(glyph_name_to_char("g123"), None);
+	}
+}
@@ -1,10 +1,145 @@
+use core::fmt;
+
+use lopdf::Object;
+
 #[derive(Debug)]
 pub enum ParseError {
-	UnknownOperator(String),
-	MissingOperands,
-	OperandType,
-	Lopdf,
+	/// The operator is not one of those in Table A.1 of the PDF spec.
+	UnknownOperator {
+		operator: String,
+	},
+
+	/// The operator needs an operand at `index`, but was given fewer operands.
+	MissingOperands {
+		operator: String,
+		index: usize,
+	},
+
+	/// The operand at `index` is of the wrong type.
+	OperandType {
+		operator: String,
+		index: usize,
+		expected: &'static str,
+		found: &'static str,
+	},
+
+	/// An object in the document is not what the spec says it should be.
+	Malformed(String),
+
+	/// An error from `lopdf`, e.g. while loading or decoding a stream.
+	Lopdf(lopdf::Error),
+
 	Utf8(std::string::FromUtf8Error),
+
+	/// Another error, annotated with where in the document it happened.
+	///
+	/// `page` counts from 1, and `operation`, when known, is the index of the
+	/// operation within the page's content stream, counting from 0; it is not
+	/// a byte offset.
+	Content {
+		page: u32,
+		operation: Option<usize>,
+		error: Box<ParseError>,
+	},
+}
+
+impl ParseError {
+	/// Annotate this error with the page and the index of the operation at
+	/// which it was encountered.
+	#[must_use]
+	pub fn at(self, page: u32, operation: usize) -> Self {
+		Self::Content {
+			page,
+			operation: Some(operation),
+			error: Box::new(self),
+		}
+	}
+
+	/// Annotate this error with the page on which it was encountered.
+	#[must_use]
+	pub fn on_page(self, page: u32) -> Self {
+		Self::Content {
+			page,
+			operation: None,
+			error: Box::new(self),
+		}
+	}
+
+	/// The error without any location annotations.
+	#[must_use]
+	pub fn root(&self) -> &Self {
+		match self {
+			Self::Content { error, .. } => error.root(),
+			error => error,
+		}
+	}
+}
+
+/// A human-readable name for the type of `object`, for use in messages.
+#[must_use]
+pub fn object_type(object: &Object) -> &'static str {
+	match object {
+		Object::Null => "null",
+		Object::Boolean(_) => "boolean",
+		Object::Integer(_) => "integer",
+		Object::Real(_) => "real",
+		Object::Name(_) => "name",
+		Object::String(_, _) => "string",
+		Object::Array(_) => "array",
+		Object::Dictionary(_) => "dictionary",
+		Object::Stream(_) => "stream",
+		Object::Reference(_) => "reference",
+	}
+}
+
+impl fmt::Display for ParseError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::UnknownOperator { operator } => write!(f, "unknown operator `{operator}`"),
+			Self::MissingOperands { operator, index } => {
+				write!(f, "`{operator}` is missing operand {index}")
+			}
+			Self::OperandType {
+				operator,
+				index,
+				expected,
+				found,
+			} => write!(
+				f,
+				"operand {index} of `{operator}` should be {expected}, but is {found}"
+			),
+			Self::Malformed(description) => write!(f, "malformed document: {description}"),
+			Self::Lopdf(error) => write!(f, "couldn't read the document: {error}"),
+			Self::Utf8(error) => write!(f, "invalid UTF-8: {error}"),
+			Self::Content {
+				page,
+				operation: Some(operation),
+				error,
+			} => write!(f, "page {page}, operation {operation}: {error}"),
+			Self::Content {
+				page,
+				operation: None,
+				error,
+			} => write!(f, "page {page}: {error}"),
+		}
+	}
+}
+
+impl std::error::Error for ParseError {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			Self::Lopdf(error) => Some(error),
+			Self::Utf8(error) => Some(error),
+			Self::Content { error, .. } => Some(error.as_ref()),
+			_ => None,
+		}
+	}
+}
+
+impl From<lopdf::Error> for ParseError {
+	fn from(e: lopdf::Error) -> Self {
+		Self::Lopdf(e)
+	}
 }
 
 impl From<std::string::FromUtf8Error> for ParseError {
@@ -14,3 +149,36 @@ impl From<std::string::FromUtf8Error> for ParseError {
 }
 
 pub type Result<T> = core::result::Result<T, ParseError>;
+
+#[cfg(test)]
+mod tests {
+	use super::ParseError;
+
+	#[test]
+	fn display_includes_location_and_types() {
+		let error = ParseError::OperandType {
+			operator: "Tf".to_string(),
+			index: 1,
+			expected: "a number",
+			found: "name",
+		}
+		.at(2, 40);
+
+		assert_eq!(
+			error.to_string(),
+			"page 2, operation 40: operand 1 of `Tf` should be a number, but is name"
+		);
+		assert!(matches!(
+			error,
+			ParseError::Content {
+				page: 2,
+				operation: Some(40),
+				..
+			}
+		));
+		assert!(matches!(
+			error.root(),
+			ParseError::OperandType { index: 1, .. }
+		));
+	}
+}
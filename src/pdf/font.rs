@@ -0,0 +1,495 @@
+//! Fonts, as far as text extraction needs them.
+//!
+//! A string shown with `Tj`/`TJ` is a sequence of character codes whose
+//! meaning depends entirely on the current font: simple fonts use one byte per
+//! code and an `/Encoding` (a base encoding plus `/Differences`), while
+//! composite (`Type0`) fonts use a `CMap` to split the string into codes.  In
+//! either case a `/ToUnicode` `CMap`, when present, is the most reliable way of
+//! getting back to text and takes precedence.
+
+use core::convert::TryFrom;
+use lopdf::{Dictionary, Document, Object, ObjectId};
+use std::collections::BTreeMap;
+
+use super::cmap::CMap;
+use super::encoding::{glyph_name_to_char, BaseEncoding};
+use super::error;
+
+/// The character a code decodes to when no mapping for it is known.
+pub const REPLACEMENT: char = '\u{fffd}';
+
+#[derive(Clone, Debug)]
+enum Encoding {
+	/// A single-byte encoding, fully resolved (base encoding plus differences).
+	Simple(Box<[Option<char>; 256]>),
+
+	/// A `CMap` which splits strings into codes for a composite font.
+	Composite {
+		cmap: CMap,
+
+		/// Whether codes are themselves UTF-16BE (the `Uni*-UCS2-*` and
+		/// `Uni*-UTF16-*` predefined `CMap`s).
+		utf16: bool,
+	},
+}
+
+/// A font from a page's resource dictionary.
+#[derive(Clone, Debug)]
+pub struct Font {
+	base_name: Option<String>,
+	subtype: Option<String>,
+	encoding: Encoding,
+	to_unicode: Option<CMap>,
+
+	/// Glyph widths in thousandths of a unit of text space, keyed by character
+	/// code for simple fonts and by CID for composite fonts.
+	widths: BTreeMap<u32, f32>,
+	default_width: f32,
+}
+
+impl Default for Font {
+	/// A font with no resources behind it, decoding with `StandardEncoding`.
+	fn default() -> Self {
+		Self {
+			base_name: None,
+			subtype: None,
+			encoding: Encoding::Simple(Box::new(BaseEncoding::Standard.table())),
+			to_unicode: None,
+			widths: BTreeMap::new(),
+			default_width: UNKNOWN_WIDTH,
+		}
+	}
+}
+
+/// Get the decoded data of a stream, whether or not it has any filters.
+pub(crate) fn stream_content(stream: &lopdf::Stream) -> error::Result<Vec<u8>> {
+	if stream.filters().map_or(true, |filters| filters.is_empty()) {
+		Ok(stream.content.clone())
+	} else {
+		stream.decompressed_content().map_err(Into::into)
+	}
+}
+
+/// The width assumed for glyphs of fonts which don't say, e.g. the standard 14
+/// fonts: half an em is a reasonable average for proportional Latin fonts.
+const UNKNOWN_WIDTH: f32 = 500.0;
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+fn number(object: &Object) -> Option<f32> {
+	match object {
+		Object::Integer(number) => Some(*number as f32),
+		Object::Real(number) => Some(*number),
+		_ => None,
+	}
+}
+
+fn resolve<'document>(
+	document: &'document Document,
+	object: &'document Object,
+) -> &'document Object {
+	document
+		.dereference(object)
+		.map_or(object, |(_, object)| object)
+}
+
+impl Font {
+	/// Load a font from its font dictionary.
+	///
+	/// # Errors
+	///
+	/// Returns an error if a referenced `/ToUnicode` or `/Encoding` stream cannot
+	/// be decoded.
+	pub fn from_dictionary(document: &Document, dictionary: &Dictionary) -> error::Result<Self> {
+		let name = |key: &[u8]| -> Option<String> {
+			dictionary
+				.get(key)
+				.map(|object| resolve(document, object))
+				.and_then(Object::as_name_str)
+				.ok()
+				.map(ToString::to_string)
+		};
+
+		let base_name: Option<String> = name(b"BaseFont");
+		let subtype: Option<String> = name(b"Subtype");
+
+		let to_unicode: Option<CMap> = match dictionary.get(b"ToUnicode").map(|o| resolve(document, o))
+		{
+			Ok(Object::Stream(stream)) => Some(CMap::parse(&stream_content(stream)?)),
+			_ => None,
+		};
+
+		let (encoding, (widths, default_width)) = if subtype.as_deref() == Some("Type0") {
+			(
+				Self::composite_encoding(document, dictionary)?,
+				Self::composite_widths(document, dictionary),
+			)
+		} else {
+			(
+				Encoding::Simple(Box::new(Self::simple_encoding(document, dictionary))),
+				Self::simple_widths(document, dictionary),
+			)
+		};
+
+		Ok(Self {
+			base_name,
+			subtype,
+			encoding,
+			to_unicode,
+			widths,
+			default_width,
+		})
+	}
+
+	fn simple_widths(document: &Document, dictionary: &Dictionary) -> (BTreeMap<u32, f32>, f32) {
+		let first_char: u32 = dictionary
+			.get(b"FirstChar")
+			.and_then(Object::as_i64)
+			.ok()
+			.and_then(|first| u32::try_from(first).ok())
+			.unwrap_or(0);
+
+		let widths: BTreeMap<u32, f32> = dictionary
+			.get(b"Widths")
+			.map(|object| resolve(document, object))
+			.and_then(Object::as_array)
+			.map(|widths| {
+				(first_char..)
+					.zip(widths.iter())
+					.filter_map(|(code, width)| Some((code, number(resolve(document, width))?)))
+					.collect()
+			})
+			.unwrap_or_default();
+
+		let missing_width: Option<f32> = dictionary
+			.get(b"FontDescriptor")
+			.map(|object| resolve(document, object))
+			.and_then(Object::as_dict)
+			.and_then(|descriptor| descriptor.get(b"MissingWidth"))
+			.ok()
+			.and_then(number);
+
+		let default_width: f32 = match missing_width {
+			Some(width) => width,
+			None if widths.is_empty() => UNKNOWN_WIDTH,
+			None => 0.0,
+		};
+
+		(widths, default_width)
+	}
+
+	fn composite_widths(document: &Document, dictionary: &Dictionary) -> (BTreeMap<u32, f32>, f32) {
+		let descendant: Option<&Dictionary> = dictionary
+			.get(b"DescendantFonts")
+			.map(|object| resolve(document, object))
+			.and_then(Object::as_array)
+			.ok()
+			.and_then(|fonts| fonts.first())
+			.map(|font| resolve(document, font))
+			.and_then(|font| font.as_dict().ok());
+
+		let Some(descendant) = descendant else {
+			return (BTreeMap::new(), 1000.0);
+		};
+
+		let default_width: f32 = descendant
+			.get(b"DW")
+			.ok()
+			.and_then(number)
+			.unwrap_or(1000.0);
+
+		// The `/W` array is a sequence of either `c [w1 w2 ...]`, giving widths
+		// for consecutive CIDs starting at `c`, or `c_first c_last w`.
+		let mut widths: BTreeMap<u32, f32> = BTreeMap::new();
+		let entries: &[Object] = descendant
+			.get(b"W")
+			.map(|object| resolve(document, object))
+			.and_then(Object::as_array)
+			.map_or(&[], Vec::as_slice);
+
+		let cid = |object: &Object| -> Option<u32> { u32::try_from(object.as_i64().ok()?).ok() };
+
+		let mut index: usize = 0;
+		while index < entries.len() {
+			match (
+				entries.get(index).and_then(cid),
+				entries.get(index + 1).map(|o| resolve(document, o)),
+			) {
+				(Some(first), Some(Object::Array(run))) => {
+					for (cid, width) in (first..).zip(run.iter()) {
+						if let Some(width) = number(width) {
+							widths.insert(cid, width);
+						}
+					}
+					index += 2;
+				}
+				(Some(first), Some(last)) => {
+					if let (Some(last), Some(width)) = (cid(last), entries.get(index + 2).and_then(number)) {
+						for cid in first..=last.min(first.saturating_add(0xffff)) {
+							widths.insert(cid, width);
+						}
+					}
+					index += 3;
+				}
+				_ => break,
+			}
+		}
+
+		(widths, default_width)
+	}
+
+	fn simple_encoding(document: &Document, dictionary: &Dictionary) -> [Option<char>; 256] {
+		let symbolic: bool = dictionary
+			.get(b"FontDescriptor")
+			.map(|object| resolve(document, object))
+			.and_then(Object::as_dict)
+			.and_then(|descriptor| descriptor.get(b"Flags"))
+			.and_then(Object::as_i64)
+			.is_ok_and(|flags| flags & 0b100 != 0);
+
+		// Symbolic fonts without an explicit encoding use their built-in encoding,
+		// which in practice is nearly always a direct mapping of codes.
+		let implicit: [Option<char>; 256] = if symbolic {
+			let mut table = [None; 256];
+			for code in 0x20_u8..=0xff {
+				table[usize::from(code)] = Some(char::from(code));
+			}
+			table
+		} else {
+			BaseEncoding::Standard.table()
+		};
+
+		match dictionary.get(b"Encoding").map(|o| resolve(document, o)) {
+			Ok(Object::Name(name)) => BaseEncoding::from_name(name).map_or(implicit, BaseEncoding::table),
+			Ok(Object::Dictionary(encoding)) => {
+				let mut table = encoding
+					.get(b"BaseEncoding")
+					.and_then(Object::as_name)
+					.ok()
+					.and_then(BaseEncoding::from_name)
+					.map_or(implicit, BaseEncoding::table);
+
+				if let Ok(differences) = encoding
+					.get(b"Differences")
+					.map(|o| resolve(document, o))
+					.and_then(Object::as_array)
+				{
+					let mut code: usize = 0;
+					for element in differences {
+						match element {
+							Object::Integer(start) => code = usize::try_from(*start).unwrap_or(256),
+							Object::Name(glyph) => {
+								if code < 256 {
+									table[code] = std::str::from_utf8(glyph).ok().and_then(glyph_name_to_char);
+								}
+								code += 1;
+							}
+							_ => {}
+						}
+					}
+				}
+
+				table
+			}
+			_ => implicit,
+		}
+	}
+
+	fn composite_encoding(document: &Document, dictionary: &Dictionary) -> error::Result<Encoding> {
+		match dictionary.get(b"Encoding").map(|o| resolve(document, o)) {
+			Ok(Object::Stream(stream)) => Ok(Encoding::Composite {
+				cmap: CMap::parse(&stream_content(stream)?),
+				utf16: false,
+			}),
+			Ok(Object::Name(name)) => {
+				let utf16: bool = name.starts_with(b"Uni")
+					&& (name.windows(4).any(|w| w == b"UCS2") || name.windows(5).any(|w| w == b"UTF16"));
+				Ok(Encoding::Composite {
+					cmap: CMap::identity(),
+					utf16,
+				})
+			}
+			_ => Ok(Encoding::Composite {
+				cmap: CMap::identity(),
+				utf16: false,
+			}),
+		}
+	}
+
+	/// The `/BaseFont` name, including any subset tag (e.g. `ABCDEF+Arial`).
+	#[must_use]
+	pub fn base_font(&self) -> Option<&str> {
+		self.base_name.as_deref()
+	}
+
+	/// The `/BaseFont` name with any subset tag removed.
+	#[must_use]
+	pub fn name(&self) -> Option<&str> {
+		self
+			.base_name
+			.as_deref()
+			.map(|name| match name.split_once('+') {
+				Some((tag, rest)) if tag.len() == 6 && tag.bytes().all(|b| b.is_ascii_uppercase()) => rest,
+				_ => name,
+			})
+	}
+
+	/// The font's `/Subtype`, e.g. `TrueType` or `Type0`.
+	#[must_use]
+	pub fn subtype(&self) -> Option<&str> {
+		self.subtype.as_deref()
+	}
+
+	/// Whether the font is a composite (`Type0`) font with multi-byte codes.
+	#[must_use]
+	pub fn is_composite(&self) -> bool {
+		matches!(self.encoding, Encoding::Composite { .. })
+	}
+
+	/// Split a shown string into its character codes, as `(code, length)` pairs.
+	#[must_use]
+	pub fn codes(&self, bytes: &[u8]) -> Vec<(u32, usize)> {
+		let mut codes: Vec<(u32, usize)> = Vec::new();
+		let mut rest: &[u8] = bytes;
+
+		while !rest.is_empty() {
+			let (code, length): (u32, usize) = match &self.encoding {
+				Encoding::Simple(_) => (u32::from(rest[0]), 1),
+				Encoding::Composite { cmap, .. } if cmap.has_codespace() => cmap.next_code(rest),
+				Encoding::Composite { .. } => match self.to_unicode.as_ref() {
+					Some(to_unicode) if to_unicode.has_codespace() => to_unicode.next_code(rest),
+					_ => CMap::identity().next_code(rest),
+				},
+			};
+
+			codes.push((code, length));
+			rest = &rest[length.min(rest.len())..];
+		}
+
+		codes
+	}
+
+	/// The horizontal displacement of a code's glyph, in thousandths of a unit of
+	/// text space.
+	#[must_use]
+	pub fn width(&self, code: u32, length: usize) -> f32 {
+		let key: u32 = match &self.encoding {
+			Encoding::Simple(_) => code,
+			Encoding::Composite { cmap, .. } => cmap.cid(code, length).unwrap_or(code),
+		};
+
+		self.widths.get(&key).copied().unwrap_or(self.default_width)
+	}
+
+	/// The width of the space glyph, falling back to a quarter of an em (a
+	/// typical word space) when the font has no usable space.
+	#[must_use]
+	pub fn space_width(&self) -> f32 {
+		let width: f32 = match &self.encoding {
+			Encoding::Simple(_) => self.width(0x20, 1),
+			Encoding::Composite { .. } => 0.0,
+		};
+
+		if width > 0.0 {
+			width
+		} else {
+			250.0
+		}
+	}
+
+	/// Decode a single character code to text.
+	#[must_use]
+	pub fn decode_code(&self, code: u32, length: usize) -> String {
+		if let Some(text) = self
+			.to_unicode
+			.as_ref()
+			.and_then(|to_unicode| to_unicode.unicode(code, length))
+		{
+			return text.to_string();
+		}
+
+		let decoded: Option<char> = match &self.encoding {
+			Encoding::Simple(table) => usize::try_from(code)
+				.ok()
+				.and_then(|code| table.get(code).copied().flatten()),
+			Encoding::Composite { utf16: true, .. } => char::from_u32(code),
+			Encoding::Composite { .. } => None,
+		};
+
+		decoded.unwrap_or(REPLACEMENT).to_string()
+	}
+
+	/// Decode a shown string to text.
+	#[must_use]
+	pub fn decode(&self, bytes: &[u8]) -> String {
+		self
+			.codes(bytes)
+			.into_iter()
+			.map(|(code, length)| self.decode_code(code, length))
+			.collect()
+	}
+}
+
+/// The fonts available to a page, keyed by their resource names (e.g. `TT0`).
+pub type Fonts = BTreeMap<Vec<u8>, Font>;
+
+/// Load all of the fonts in a page's resources, including inherited ones.
+///
+/// # Errors
+///
+/// Returns an error if any of the fonts fail to load.
+pub fn page_fonts(document: &Document, page_id: ObjectId) -> error::Result<Fonts> {
+	document
+		.get_page_fonts(page_id)
+		.into_iter()
+		.map(|(name, dictionary)| Ok((name, Font::from_dictionary(document, dictionary)?)))
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::Font;
+	use lopdf::{dictionary, Document, Object, Stream};
+
+	#[test]
+	fn win_ansi_with_differences() {
+		let document = Document::with_version("1.5");
+		let font = dictionary! {
+			"Type" => "Font",
+			"Subtype" => "Type1",
+			"BaseFont" => "ABCDEF+Helvetica",
+			"Encoding" => dictionary! {
+				"BaseEncoding" => "WinAnsiEncoding",
+				"Differences" => vec![Object::Integer(1), "endash".into(), "a".into()],
+			},
+		};
+
+		let font = Font::from_dictionary(&document, &font).unwrap();
+		assert_eq!(font.name(), Some("Helvetica"));
+		assert_eq!(font.decode(b"7\x01\x96 a.m.\x02"), "7–– a.m.a");
+	}
+
+	#[test]
+	fn identity_h_with_to_unicode() {
+		let mut document = Document::with_version("1.5");
+		let to_unicode = document.add_object(Stream::new(
+			dictionary! {},
+			b"1 begincodespacerange <0000> <FFFF> endcodespacerange
+			2 beginbfchar <0001> <0061> <0002> <2013> endbfchar"
+				.to_vec(),
+		));
+		let font = dictionary! {
+			"Type" => "Font",
+			"Subtype" => "Type0",
+			"BaseFont" => "Arial",
+			"Encoding" => "Identity-H",
+			"ToUnicode" => to_unicode,
+		};
+
+		let font = Font::from_dictionary(&document, &font).unwrap();
+		assert!(font.is_composite());
+		assert_eq!(
+			font.decode(&[0x00, 0x01, 0x00, 0x02, 0x00, 0x09]),
+			"a–\u{fffd}"
+		);
+	}
+}
@@ -0,0 +1,645 @@
+//! Working out what the rows and columns of a schedule table mean.
+//!
+//! A `Table` only knows which text is in which cell.  Schedules put the days
+//! along the top, in one or more header rows which may span several columns
+//! (`Friday` over `10/11/19` over `Open` and `Close`), and the spaces down the
+//! side.  This module classifies each cell's text, finds the header rows and
+//! the column of names, and turns every other row into `Entry`s of a space, a
+//! day, and the text of its hours that day.
+
+use core::convert::TryFrom;
+use core::fmt;
+
+use chrono::{Datelike, NaiveDate, Weekday};
+
+use super::table::{Cell, Table};
+
+/// Ranges longer than this are taken to be misreadings rather than expanded.
+const MAX_RANGE: usize = 366;
+
+/// The day a column of a schedule is for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Day {
+	/// A particular date, e.g. from `12/23/19` or `Nov. 27`.
+	Date(NaiveDate),
+
+	/// Every week on this day, e.g. from `Monday-Thursday`.
+	Weekday(Weekday),
+}
+
+impl fmt::Display for Day {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Date(date) => write!(f, "{}", date.format("%Y-%m-%d")),
+			Self::Weekday(weekday) => write!(f, "{weekday}"),
+		}
+	}
+}
+
+/// Which half of a pair of columns a cell heads.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Role {
+	Open,
+	Close,
+}
+
+/// What the text of a cell looks like.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Label {
+	Empty,
+
+	/// Weekday names, dates, or ranges of either; dates are preferred when a
+	/// cell has both, as in `Tuesday 11/26/19`.
+	Days(Vec<Day>),
+
+	/// `Open` or `Close`, heading one half of a day.
+	Role(Role),
+
+	/// Times, ranges of times, or words such as `CLOSED` which stand for them.
+	Hours,
+
+	/// Anything else: names, titles and notes.
+	Text,
+}
+
+/// Classify the text of a cell.
+///
+/// Dates without a year are put in whichever year brings them closest to
+/// `reference`, which is usually when the document was made.
+#[must_use]
+pub fn classify(text: &str, reference: NaiveDate) -> Label {
+	let text: &str = text.trim();
+	if text.is_empty() {
+		return Label::Empty;
+	}
+
+	match text.to_lowercase().trim_end_matches(':') {
+		"open" | "opens" => return Label::Role(Role::Open),
+		"close" | "closes" => return Label::Role(Role::Close),
+		_ => {}
+	}
+
+	if is_hours(text) {
+		return Label::Hours;
+	}
+
+	days(text, reference).map_or(Label::Text, Label::Days)
+}
+
+/// Whether every word of `text` belongs in a description of opening hours.
+fn is_hours(text: &str) -> bool {
+	let mut times: usize = 0;
+	let mut words: usize = 0;
+
+	for word in text.split_whitespace() {
+		let word: String = word.to_lowercase();
+		let word: &str = word.trim_end_matches([',', ';', '*']);
+		words += 1;
+
+		match word {
+			"closed" | "locked" | "unlocked" | "open" | "midnight" | "noon" | "24" | "hours" => {
+				times += 1;
+			}
+			"a.m." | "p.m." | "am" | "pm" | "a.m" | "p.m" | "-" | "to" | "until" => {}
+			word if word.split('-').all(is_time) => times += 1,
+			_ => return false,
+		}
+	}
+
+	times > 0 && words > 0
+}
+
+/// Whether `word` is a time, e.g. `7:00`, `0800` or `7:00am`.
+fn is_time(word: &str) -> bool {
+	let word: &str = word
+		.trim_end_matches("a.m.")
+		.trim_end_matches("p.m.")
+		.trim_end_matches("am")
+		.trim_end_matches("pm");
+
+	match word.split_once(':') {
+		Some((hours, minutes)) => {
+			(1..=2).contains(&hours.len())
+				&& minutes.len() == 2
+				&& hours
+					.chars()
+					.chain(minutes.chars())
+					.all(|c| c.is_ascii_digit())
+		}
+		None => (1..=4).contains(&word.len()) && word.chars().all(|c| c.is_ascii_digit()),
+	}
+}
+
+fn weekday(word: &str) -> Option<Weekday> {
+	const WEEKDAYS: [(&str, Weekday); 7] = [
+		("monday", Weekday::Mon),
+		("tuesday", Weekday::Tue),
+		("wednesday", Weekday::Wed),
+		("thursday", Weekday::Thu),
+		("friday", Weekday::Fri),
+		("saturday", Weekday::Sat),
+		("sunday", Weekday::Sun),
+	];
+
+	let word: String = word.trim_end_matches(['.', ',']).to_lowercase();
+	if word.len() < 3 {
+		return None;
+	}
+	WEEKDAYS
+		.iter()
+		.find(|(name, _)| name.starts_with(&word))
+		.map(|(_, weekday)| *weekday)
+}
+
+fn month(word: &str) -> Option<u32> {
+	const MONTHS: [&str; 12] = [
+		"january",
+		"february",
+		"march",
+		"april",
+		"may",
+		"june",
+		"july",
+		"august",
+		"september",
+		"october",
+		"november",
+		"december",
+	];
+
+	let word: String = word.trim_end_matches(['.', ',']).to_lowercase();
+	if word.len() < 3 {
+		return None;
+	}
+	MONTHS
+		.iter()
+		.position(|name| name.starts_with(&word))
+		.and_then(|index| u32::try_from(index + 1).ok())
+}
+
+/// Weekdays from `first` to `last` inclusive, wrapping around the weekend.
+fn weekdays(first: Weekday, last: Weekday) -> Vec<Weekday> {
+	let mut weekdays: Vec<Weekday> = vec![first];
+	let mut weekday: Weekday = first;
+	while weekday != last {
+		weekday = weekday.succ();
+		weekdays.push(weekday);
+	}
+	weekdays
+}
+
+/// Dates from `first` to `last` inclusive.
+fn dates(first: NaiveDate, last: NaiveDate) -> Option<Vec<NaiveDate>> {
+	let length: usize = usize::try_from((last - first).num_days()).ok()?;
+	if length > MAX_RANGE {
+		return None;
+	}
+	Some(first.iter_days().take(length + 1).collect())
+}
+
+/// The date in `year`, or if there isn't one, in the year nearest
+/// `reference`.
+fn resolve(month: u32, day: u32, year: Option<i32>, reference: NaiveDate) -> Option<NaiveDate> {
+	match year {
+		Some(year) if year < 100 => NaiveDate::from_ymd_opt(2000 + year, month, day),
+		Some(year) => NaiveDate::from_ymd_opt(year, month, day),
+		None => [reference.year() - 1, reference.year(), reference.year() + 1]
+			.iter()
+			.filter_map(|year| NaiveDate::from_ymd_opt(*year, month, day))
+			.min_by_key(|date| (*date - reference).num_days().abs()),
+	}
+}
+
+/// The month, day and year of a numeric date such as `12/23` or `02/01/20`.
+fn numeric(text: &str) -> Option<(u32, u32, Option<i32>)> {
+	let mut parts = text.split('/');
+	let month: u32 = parts.next()?.parse().ok()?;
+	let day: u32 = parts.next()?.parse().ok()?;
+	let year: Option<i32> = match parts.next() {
+		Some(year) => Some(year.parse().ok()?),
+		None => None,
+	};
+	if parts.next().is_some() {
+		return None;
+	}
+	Some((month, day, year))
+}
+
+/// The dates of a numeric date or range, e.g. `1/29-1/30/2020` or `12/18-20`.
+///
+/// The first date, when it has no year, is the one nearest the last.
+fn numeric_dates(text: &str, reference: NaiveDate) -> Option<Vec<NaiveDate>> {
+	match text.split_once('-') {
+		None => {
+			let (month, day, year) = numeric(text)?;
+			Some(vec![resolve(month, day, year, reference)?])
+		}
+		Some((first, last)) => {
+			let (first_month, first_day, first_year) = numeric(first)?;
+			let (last_month, last_day, last_year) = match numeric(last) {
+				Some(last) => last,
+				None => (first_month, last.parse().ok()?, first_year),
+			};
+			let last: NaiveDate = resolve(last_month, last_day, last_year, reference)?;
+			let first: NaiveDate = resolve(first_month, first_day, first_year, last)?;
+			dates(first, last)
+		}
+	}
+}
+
+/// The dates of a month name followed by a day or range of days, e.g.
+/// `Nov. 27` or `Nov. 27-29`, with the year if it's in `year`.
+fn named_dates(
+	month: u32,
+	days: &str,
+	year: Option<i32>,
+	reference: NaiveDate,
+) -> Option<Vec<NaiveDate>> {
+	let days: &str = days.trim_end_matches(',');
+	match days.split_once('-') {
+		None => Some(vec![resolve(month, days.parse().ok()?, year, reference)?]),
+		Some((first, last)) => {
+			let first: NaiveDate = resolve(month, first.parse().ok()?, year, reference)?;
+			let last: NaiveDate = resolve(month, last.parse().ok()?, Some(first.year()), first)?;
+			dates(first, last)
+		}
+	}
+}
+
+/// The days named by `text`, if that's all it does.
+fn days(text: &str, reference: NaiveDate) -> Option<Vec<Day>> {
+	let text: String = text
+		.replace(['–', '—'], "-")
+		.replace(" - ", "-")
+		.replace(" through ", "-")
+		.replace(" thru ", "-");
+	let words: Vec<&str> = text.split_whitespace().collect();
+
+	let mut weekdays_found: Vec<Weekday> = Vec::new();
+	let mut dates_found: Vec<NaiveDate> = Vec::new();
+
+	let mut index: usize = 0;
+	while let Some(word) = words.get(index) {
+		index += 1;
+
+		if matches!(*word, "&" | "and" | "," | "-") {
+			continue;
+		}
+
+		if let Some((first, last)) = word.split_once('-') {
+			if let (Some(first), Some(last)) = (weekday(first), weekday(last)) {
+				weekdays_found.extend(weekdays(first, last));
+				continue;
+			}
+		}
+
+		if let Some(weekday) = weekday(word) {
+			weekdays_found.push(weekday);
+			continue;
+		}
+
+		if let Some(month) = month(word) {
+			let days: &str = words.get(index)?;
+			index += 1;
+			let year: Option<i32> = words
+				.get(index)
+				.and_then(|year| year.trim_end_matches(',').parse().ok())
+				.filter(|year| *year > 1900);
+			if year.is_some() {
+				index += 1;
+			}
+			dates_found.extend(named_dates(month, days, year, reference)?);
+			continue;
+		}
+
+		if word.contains('/') {
+			dates_found.extend(numeric_dates(word.trim_end_matches(','), reference)?);
+			continue;
+		}
+
+		return None;
+	}
+
+	if !dates_found.is_empty() {
+		Some(dates_found.into_iter().map(Day::Date).collect())
+	} else if !weekdays_found.is_empty() {
+		Some(weekdays_found.into_iter().map(Day::Weekday).collect())
+	} else {
+		None
+	}
+}
+
+/// The hours of a space on a day, read from a schedule table.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Entry {
+	space: String,
+	written_space: String,
+	day: Day,
+	hours: String,
+	cells: Vec<Cell>,
+}
+
+impl Entry {
+	/// The name of the space, without any footnote markers.
+	#[must_use]
+	pub fn space(&self) -> &str {
+		&self.space
+	}
+
+	/// The name of the space as it was written, with any footnote markers.
+	#[must_use]
+	pub fn written_space(&self) -> &str {
+		&self.written_space
+	}
+
+	#[must_use]
+	pub fn day(&self) -> Day {
+		self.day
+	}
+
+	/// The text of the hours, as written, e.g. `7:00 a.m. - 5:00 p.m.`,
+	/// `0800-1700` or `CLOSED`.
+	#[must_use]
+	pub fn hours(&self) -> &str {
+		&self.hours
+	}
+
+	/// The cells the hours were read from.
+	#[must_use]
+	pub fn cells(&self) -> &[Cell] {
+		&self.cells
+	}
+}
+
+/// What a column of the table is for, from the header rows above.
+#[derive(Clone, Debug, Default)]
+struct Heading {
+	days: Vec<Day>,
+	role: Option<Role>,
+}
+
+/// The column holding the names of the spaces: the one with the most cells of
+/// plain text, or the leftmost of those with the most.
+fn name_column(table: &Table, labels: &[Label]) -> Option<usize> {
+	let mut counts: Vec<usize> = vec![0; table.columns()];
+	for (cell, label) in table.cells().iter().zip(labels) {
+		if cell.col_span() == 1 && *label == Label::Text {
+			counts[cell.column()] += 1;
+		}
+	}
+	counts
+		.iter()
+		.enumerate()
+		.rev()
+		.max_by_key(|(_, count)| **count)
+		.filter(|(_, count)| **count > 0)
+		.map(|(column, _)| column)
+}
+
+/// Read the entries of a schedule table.
+///
+/// Header rows are those whose cells are mostly days or `Open` and `Close`;
+/// they may appear again part way down, starting a new block of the
+/// schedule.  Cells spanning the whole table are titles or notes, and are
+/// skipped, as are rows without any hours.  When a day has separate `Open`
+/// and `Close` columns their text is joined with ` - `.
+#[must_use]
+pub fn entries(table: &Table, reference: NaiveDate) -> Vec<Entry> {
+	let labels: Vec<Label> = table
+		.cells()
+		.iter()
+		.map(|cell| classify(cell.text(), reference))
+		.collect();
+	let Some(names) = name_column(table, &labels) else {
+		return Vec::new();
+	};
+
+	let mut headings: Vec<Heading> = vec![Heading::default(); table.columns()];
+	let mut in_body: bool = false;
+	let mut entries: Vec<Entry> = Vec::new();
+
+	for row in 0..table.rows() {
+		let row_labels: Vec<(&Cell, &Label)> = table
+			.cells()
+			.iter()
+			.zip(&labels)
+			.filter(|(cell, _)| {
+				cell.row() == row && cell.column() != names && cell.col_span() < table.columns()
+			})
+			.collect();
+		let count = |predicate: fn(&Label) -> bool| -> usize {
+			row_labels
+				.iter()
+				.filter(|(_, label)| predicate(label))
+				.count()
+		};
+		let days: usize = count(|label| matches!(label, Label::Days(_)));
+		let roles: usize = count(|label| matches!(label, Label::Role(_)));
+		let hours: usize = count(|label| *label == Label::Hours);
+
+		if days > 0 && days >= hours {
+			if in_body {
+				headings = vec![Heading::default(); table.columns()];
+				in_body = false;
+			}
+			for (cell, label) in &row_labels {
+				if let Label::Days(days) = label {
+					for heading in &mut headings[cell.column()..cell.column() + cell.col_span()] {
+						// A row of dates under a row of weekdays says more.
+						let dated = |days: &[Day]| days.iter().any(|day| matches!(day, Day::Date(_)));
+						if heading.days.is_empty() || dated(days) || !dated(&heading.days) {
+							heading.days.clone_from(days);
+						}
+					}
+				}
+			}
+		} else if roles > 0 && roles >= hours {
+			for (cell, label) in &row_labels {
+				if let Label::Role(role) = label {
+					for heading in &mut headings[cell.column()..cell.column() + cell.col_span()] {
+						heading.role = Some(*role);
+					}
+				}
+			}
+		} else if hours > 0 {
+			in_body = true;
+			entries.extend(body_row(table, row, names, &headings));
+		}
+	}
+
+	entries
+}
+
+/// The entries of one row of the body of a table.
+fn body_row(table: &Table, row: usize, names: usize, headings: &[Heading]) -> Vec<Entry> {
+	let name: &str = table.cell(row, names).map_or("", |cell| cell.text().trim());
+	let space: String = name
+		.trim_end_matches(|c: char| "*@%^#&†‡".contains(c) || c.is_whitespace())
+		.to_string();
+	if space.is_empty() {
+		return Vec::new();
+	}
+
+	let mut entries: Vec<Entry> = Vec::new();
+	let mut column: usize = 0;
+	while column < headings.len() {
+		let days: &[Day] = &headings[column].days;
+		let end: usize = (column..headings.len())
+			.find(|end| *end == names || headings[*end].days != days)
+			.unwrap_or(headings.len());
+		if column == names || days.is_empty() {
+			column = end.max(column + 1);
+			continue;
+		}
+
+		// The cells covering this day's columns, once each.
+		let mut cells: Vec<(Option<Role>, &Cell)> = Vec::new();
+		for (heading_column, heading) in headings.iter().enumerate().take(end).skip(column) {
+			if let Some(cell) = table.cell(row, heading_column) {
+				if !cells.iter().any(|(_, other)| *other == cell) {
+					cells.push((heading.role, cell));
+				}
+			}
+		}
+		// Tables too wide for one page repeat the names on the next, which
+		// can end up under a day when the row is short.
+		cells.retain(|(_, cell)| !cell.text().trim().is_empty() && cell.text().trim() != name);
+
+		let text = |role: Role| -> Option<&str> {
+			cells
+				.iter()
+				.find(|(cell_role, _)| *cell_role == Some(role))
+				.map(|(_, cell)| cell.text().trim())
+		};
+		let hours: String = match (cells.len(), text(Role::Open), text(Role::Close)) {
+			(0, _, _) => String::new(),
+			(1, _, _) => cells[0].1.text().trim().to_string(),
+			(_, Some(open), Some(close)) => format!("{open} - {close}"),
+			(_, _, _) => cells
+				.iter()
+				.map(|(_, cell)| cell.text().trim())
+				.collect::<Vec<&str>>()
+				.join(" - "),
+		};
+
+		if !hours.is_empty() {
+			for day in days {
+				entries.push(Entry {
+					space: space.clone(),
+					written_space: name.to_string(),
+					day: *day,
+					hours: hours.clone(),
+					cells: cells.iter().map(|(_, cell)| (*cell).clone()).collect(),
+				});
+			}
+		}
+
+		column = end;
+	}
+
+	entries
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{classify, entries, Day, Label, Role};
+	use crate::pdf::table::{Cell, Table};
+	use chrono::{NaiveDate, Weekday};
+
+	fn date(year: i32, month: u32, day: u32) -> Day {
+		Day::Date(NaiveDate::from_ymd_opt(year, month, day).unwrap())
+	}
+
+	#[test]
+	fn labels() {
+		let reference = NaiveDate::from_ymd_opt(2019, 12, 12).unwrap();
+
+		assert_eq!(classify("", reference), Label::Empty);
+		assert_eq!(
+			classify("Thurs.", reference),
+			Label::Days(vec![Day::Weekday(Weekday::Thu)])
+		);
+		assert_eq!(
+			classify("Monday-Thursday", reference),
+			Label::Days(
+				[Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu]
+					.iter()
+					.map(|weekday| Day::Weekday(*weekday))
+					.collect()
+			)
+		);
+		assert_eq!(
+			classify("Nov. 27", reference),
+			Label::Days(vec![date(2019, 11, 27)])
+		);
+		// Nearer to December 2019 than January 2019 is.
+		assert_eq!(
+			classify("1/2", reference),
+			Label::Days(vec![date(2020, 1, 2)])
+		);
+		assert_eq!(
+			classify("Tuesday 11/26/19", reference),
+			Label::Days(vec![date(2019, 11, 26)])
+		);
+		assert_eq!(
+			classify("1/29-1/30/2020", reference),
+			Label::Days(vec![date(2020, 1, 29), date(2020, 1, 30)])
+		);
+		assert_eq!(
+			classify("12/30-1/1", reference),
+			Label::Days(vec![
+				date(2019, 12, 30),
+				date(2019, 12, 31),
+				date(2020, 1, 1)
+			])
+		);
+		assert_eq!(classify("Close", reference), Label::Role(Role::Close));
+		assert_eq!(classify("7:00 a.m.", reference), Label::Hours);
+		assert_eq!(classify("0800-1700", reference), Label::Hours);
+		assert_eq!(classify("CLOSED", reference), Label::Hours);
+		assert_eq!(classify("Boe Chapel", reference), Label::Text);
+		assert_eq!(classify("as of 11/20/19", reference), Label::Text);
+	}
+
+	#[test]
+	fn spanned_headers() {
+		let reference = NaiveDate::from_ymd_opt(2019, 10, 10).unwrap();
+		let table = Table::new(
+			Some(1),
+			vec![
+				Cell::new(0, 0, "2019 Fall Break Building Schedule").spans(1, 5),
+				Cell::new(1, 0, "Building").spans(3, 1),
+				Cell::new(1, 1, "Friday").spans(1, 2),
+				Cell::new(1, 3, "Saturday").spans(1, 2),
+				Cell::new(2, 1, "10/11/19").spans(1, 2),
+				Cell::new(2, 3, "10/12/19").spans(1, 2),
+				Cell::new(3, 1, "Open"),
+				Cell::new(3, 2, "Close"),
+				Cell::new(3, 3, "Open"),
+				Cell::new(3, 4, "Close"),
+				Cell::new(4, 0, "Alumni Hall *"),
+				Cell::new(4, 1, "7:00 a.m."),
+				Cell::new(4, 2, "5:00 p.m."),
+				Cell::new(4, 3, "CLOSED").spans(1, 2),
+				Cell::new(5, 0, "Art Barn"),
+				Cell::new(5, 1, "LOCKED").spans(1, 2),
+				Cell::new(5, 3, "LOCKED").spans(1, 2),
+				Cell::new(6, 0, "* as of 10/10/19").spans(1, 5),
+			],
+		);
+
+		let entries = entries(&table, reference);
+		assert_eq!(
+			entries
+				.iter()
+				.map(|entry| (entry.space(), entry.day(), entry.hours()))
+				.collect::<Vec<_>>(),
+			vec![
+				("Alumni Hall", date(2019, 10, 11), "7:00 a.m. - 5:00 p.m."),
+				("Alumni Hall", date(2019, 10, 12), "CLOSED"),
+				("Art Barn", date(2019, 10, 11), "LOCKED"),
+				("Art Barn", date(2019, 10, 12), "LOCKED"),
+			]
+		);
+	}
+}
@@ -0,0 +1,632 @@
+//! Turning the entries of schedule tables into `Space`s.
+//!
+//! Each entry's hours are interpreted, and become a `Part` when they're for a
+//! day of the week or an `Exception` when they're for a date.  Every one
+//! carries its `Provenance`, with a confidence lowered by anything about the
+//! text which made it harder to read, and the `Review` lists those worth a
+//! second look before the import is trusted.
+
+use core::fmt;
+
+use chrono::{Duration, NaiveDate, NaiveTime, TimeZone, Weekday};
+
+use super::error;
+use super::headers::{Day, Entry};
+use super::Pdf;
+use crate::json::Value;
+use crate::{Exception, Part, Provenance, Reason, Schedule, Space, Specifier, Status};
+
+/// Entries less confident than this are listed for review by default.
+pub const DEFAULT_THRESHOLD: f32 = 0.9;
+
+/// Characters which mark text as having a footnote.
+const FOOTNOTE_MARKERS: &str = "*@%^#&†‡";
+
+/// What a cell of hours says.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Hours {
+	/// Closed all day, with the word used, e.g. `CLOSED` or `LOCKED`.
+	Closed(String),
+
+	/// Open from `open` until `close`, which is on the next day if it isn't
+	/// after `open`.
+	Open { open: NaiveTime, close: NaiveTime },
+}
+
+/// The hours read from some text, how sure we are of them, and why not more.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Interpretation {
+	hours: Option<Hours>,
+	confidence: f32,
+	issues: Vec<String>,
+}
+
+impl Interpretation {
+	/// The hours, or `None` if the text couldn't be read as hours at all.
+	#[must_use]
+	pub fn hours(&self) -> Option<&Hours> {
+		self.hours.as_ref()
+	}
+
+	#[must_use]
+	pub fn confidence(&self) -> f32 {
+		self.confidence
+	}
+
+	#[must_use]
+	pub fn issues(&self) -> &[String] {
+		&self.issues
+	}
+
+	fn doubt(&mut self, factor: f32, issue: &str) {
+		self.confidence *= factor;
+		self.issues.push(issue.to_string());
+	}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Clock {
+	Am,
+	Pm,
+	TwentyFour,
+	Unknown,
+}
+
+/// The hour, minute and clock of a time such as `7:00 a.m.`, `1730` or `noon`.
+fn time(text: &str) -> Option<(u32, u32, Clock)> {
+	let text: String = text.to_lowercase().replace(' ', "");
+	match text.as_str() {
+		"noon" => return Some((12, 0, Clock::Pm)),
+		"midnight" => return Some((12, 0, Clock::Am)),
+		_ => {}
+	}
+
+	let (digits, clock) = [
+		("a.m.", Clock::Am),
+		("a.m", Clock::Am),
+		("am", Clock::Am),
+		("a", Clock::Am),
+		("p.m.", Clock::Pm),
+		("p.m", Clock::Pm),
+		("pm", Clock::Pm),
+		("p", Clock::Pm),
+	]
+	.iter()
+	.find_map(|(suffix, clock)| text.strip_suffix(suffix).map(|digits| (digits, *clock)))
+	.unwrap_or((text.as_str(), Clock::Unknown));
+
+	let (hour, minute): (&str, &str) = match digits.split_once(':') {
+		Some((hour, minute)) => (hour, minute),
+		None if (3..=4).contains(&digits.len()) => digits.split_at(digits.len() - 2),
+		None => (digits, "0"),
+	};
+	if hour.is_empty()
+		|| !hour
+			.chars()
+			.chain(minute.chars())
+			.all(|c| c.is_ascii_digit())
+	{
+		return None;
+	}
+	let hour: u32 = hour.parse().ok()?;
+	let minute: u32 = minute.parse().ok()?;
+	if minute >= 60 {
+		return None;
+	}
+
+	let clock: Clock = match clock {
+		Clock::Unknown
+			if (digits.len() == 4 && !digits.contains(':'))
+				|| hour > 12
+				|| hour == 0
+				|| digits.starts_with('0') =>
+		{
+			Clock::TwentyFour
+		}
+		clock => clock,
+	};
+	match clock {
+		Clock::Am | Clock::Pm if !(1..=12).contains(&hour) => None,
+		Clock::TwentyFour if hour > 24 || (hour == 24 && minute > 0) => None,
+		_ => Some((hour, minute, clock)),
+	}
+}
+
+fn naive_time(hour: u32, minute: u32, clock: Clock) -> Option<NaiveTime> {
+	let hour: u32 = match clock {
+		Clock::Am => hour % 12,
+		Clock::Pm => hour % 12 + 12,
+		Clock::TwentyFour | Clock::Unknown => hour % 24,
+	};
+	NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+/// Read hours such as `7:00 a.m. - 5:00 p.m.`, `0800-1700` or `CLOSED`.
+#[must_use]
+pub fn interpret(text: &str) -> Interpretation {
+	let mut interpretation = Interpretation {
+		hours: None,
+		confidence: 1.0,
+		issues: Vec::new(),
+	};
+
+	let trimmed: &str = text.trim();
+	let bare: &str =
+		trimmed.trim_matches(|c: char| FOOTNOTE_MARKERS.contains(c) || c.is_whitespace());
+	if bare != trimmed {
+		interpretation.doubt(0.8, "has a footnote marker; check the notes");
+	}
+	let lower: String = bare.to_lowercase();
+
+	match lower.as_str() {
+		"" => {
+			interpretation.confidence = 0.0;
+			interpretation.issues.push("is empty".to_string());
+			return interpretation;
+		}
+		"closed" | "locked" => {
+			interpretation.hours = Some(Hours::Closed(bare.to_string()));
+			return interpretation;
+		}
+		"24 hours" | "open 24 hours" => {
+			interpretation.hours = Some(Hours::Open {
+				open: NaiveTime::MIN,
+				close: NaiveTime::MIN,
+			});
+			return interpretation;
+		}
+		_ => {}
+	}
+
+	let normalized: String = lower
+		.replace(['–', '—'], "-")
+		.replace(" to ", "-")
+		.replace(" until ", "-");
+	let parts: Vec<&str> = normalized.split('-').collect();
+	let times: Option<Vec<(u32, u32, Clock)>> = match parts.as_slice() {
+		[open, close] => time(open)
+			.zip(time(close))
+			.map(|(open, close)| vec![open, close]),
+		_ => None,
+	};
+	let Some(times) = times else {
+		interpretation.confidence = 0.0;
+		interpretation
+			.issues
+			.push("isn't a time range or CLOSED".to_string());
+		return interpretation;
+	};
+
+	// Guess whether times without a.m. or p.m. are in the morning or evening
+	// from when buildings usually open and close.
+	let (open_hour, open_minute, mut open_clock) = times[0];
+	let (close_hour, close_minute, mut close_clock) = times[1];
+	if open_clock == Clock::Unknown || close_clock == Clock::Unknown {
+		interpretation.doubt(0.7, "has no a.m. or p.m.; guessed");
+	}
+	if open_clock == Clock::Unknown {
+		open_clock = if (5..12).contains(&open_hour) {
+			Clock::Am
+		} else {
+			Clock::Pm
+		};
+	}
+	if close_clock == Clock::Unknown {
+		close_clock = if close_hour == 12 {
+			Clock::Am
+		} else {
+			Clock::Pm
+		};
+	}
+
+	let (Some(open), Some(close)) = (
+		naive_time(open_hour, open_minute, open_clock),
+		naive_time(close_hour, close_minute, close_clock),
+	) else {
+		interpretation.confidence = 0.0;
+		interpretation
+			.issues
+			.push("has an impossible time".to_string());
+		return interpretation;
+	};
+
+	if close <= open && close != NaiveTime::MIN {
+		if close <= NaiveTime::from_hms_opt(4, 0, 0).unwrap_or(NaiveTime::MIN) {
+			interpretation.doubt(0.95, "closes after midnight");
+		} else {
+			interpretation.doubt(0.5, "closes before it opens");
+		}
+	}
+
+	interpretation.hours = Some(Hours::Open { open, close });
+	interpretation
+}
+
+/// One entry of an import, and how it was read.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Item {
+	space: String,
+	day: Day,
+	provenance: Provenance,
+	hours: Option<Hours>,
+	issues: Vec<String>,
+}
+
+impl Item {
+	#[must_use]
+	pub fn space(&self) -> &str {
+		&self.space
+	}
+
+	#[must_use]
+	pub fn day(&self) -> Day {
+		self.day
+	}
+
+	#[must_use]
+	pub fn provenance(&self) -> &Provenance {
+		&self.provenance
+	}
+
+	/// The hours, or `None` if they couldn't be read.
+	#[must_use]
+	pub fn hours(&self) -> Option<&Hours> {
+		self.hours.as_ref()
+	}
+
+	#[must_use]
+	pub fn issues(&self) -> &[String] {
+		&self.issues
+	}
+
+	fn json(&self) -> Value {
+		let provenance: &Provenance = &self.provenance;
+		Value::object(vec![
+			("space", self.space.as_str().into()),
+			("day", self.day.to_string().into()),
+			("text", provenance.text().into()),
+			("page", provenance.page().into()),
+			(
+				"bbox",
+				provenance
+					.bbox()
+					.map(|bbox| {
+						[
+							bbox.lower_left().x(),
+							bbox.lower_left().y(),
+							bbox.upper_right().x(),
+							bbox.upper_right().y(),
+						]
+						.iter()
+						.map(|coordinate| (coordinate * 100.0).round() / 100.0)
+						.collect::<Vec<f64>>()
+					})
+					.into(),
+			),
+			("confidence", provenance.confidence().into()),
+			("parsed", self.hours.is_some().into()),
+			("issues", self.issues.clone().into()),
+		])
+	}
+}
+
+/// The entries of an import which a person should check.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Review {
+	source: Option<String>,
+	threshold: f32,
+	items: Vec<Item>,
+}
+
+impl Review {
+	/// List entries less confident than `threshold`, rather than
+	/// `DEFAULT_THRESHOLD`.
+	#[must_use]
+	pub fn threshold(mut self, threshold: f32) -> Self {
+		self.threshold = threshold;
+		self
+	}
+
+	/// Every entry of the import.
+	#[must_use]
+	pub fn items(&self) -> &[Item] {
+		&self.items
+	}
+
+	/// The entries which couldn't be read, or are below the threshold.
+	pub fn flagged(&self) -> impl Iterator<Item = &Item> + '_ {
+		self
+			.items
+			.iter()
+			.filter(move |item| item.hours.is_none() || item.provenance.confidence() < self.threshold)
+	}
+
+	/// The report as pretty-printed JSON.
+	#[must_use]
+	pub fn to_json(&self) -> String {
+		Value::object(vec![
+			("source", self.source.as_deref().into()),
+			("threshold", self.threshold.into()),
+			("entries", self.items.len().into()),
+			(
+				"flagged",
+				Value::Array(self.flagged().map(Item::json).collect()),
+			),
+		])
+		.pretty()
+	}
+}
+
+/// The report as plain text, one paragraph per flagged entry.
+impl fmt::Display for Review {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let flagged: Vec<&Item> = self.flagged().collect();
+		writeln!(
+			f,
+			"Review of {}: {} entries read, {} to check",
+			self.source.as_deref().unwrap_or("import"),
+			self.items.len(),
+			flagged.len()
+		)?;
+
+		for item in flagged {
+			let provenance: &Provenance = &item.provenance;
+			writeln!(f)?;
+			write!(f, "{}, {}", item.space, item.day)?;
+			if let Some(page) = provenance.page() {
+				write!(f, " (page {page})")?;
+			}
+			writeln!(f, ": confidence {:.2}", provenance.confidence())?;
+			writeln!(f, "  text: {:?}", provenance.text())?;
+			if let Some(bbox) = provenance.bbox() {
+				writeln!(
+					f,
+					"  at: ({:.1}, {:.1}) to ({:.1}, {:.1})",
+					bbox.lower_left().x(),
+					bbox.lower_left().y(),
+					bbox.upper_right().x(),
+					bbox.upper_right().y()
+				)?;
+			}
+			for issue in &item.issues {
+				writeln!(f, "  - {issue}")?;
+			}
+		}
+
+		Ok(())
+	}
+}
+
+/// `Space`s read from schedule tables, and a review of how well they were
+/// read.
+#[derive(Debug)]
+pub struct Import<'schedule, Tz: TimeZone> {
+	spaces: Vec<Space<'schedule, Tz>>,
+	review: Review,
+}
+
+impl<'schedule, Tz: TimeZone> Import<'schedule, Tz> {
+	/// Import the tables of `pdf`, putting dates in `timezone`.
+	///
+	/// # Errors
+	///
+	/// Returns an error if the tables cannot be read.
+	pub fn from_pdf(pdf: &Pdf, timezone: &Tz) -> error::Result<Self> {
+		Ok(Self::from_entries(&pdf.entries()?, pdf.source(), timezone))
+	}
+
+	/// Import `entries`, which were read from `source`, putting dates in
+	/// `timezone`.
+	///
+	/// Each space gets one `Schedule`.  Hours for a day of the week become a
+	/// `Part`, and days of the week on which a space is closed have none.
+	/// Hours for a date become an `Exception` opening the space, followed by
+	/// one closing it for the rest of the day.
+	#[must_use]
+	pub fn from_entries(entries: &[Entry], source: Option<&str>, timezone: &Tz) -> Self {
+		let mut spaces: Vec<Space<'schedule, Tz>> = Vec::new();
+		let mut items: Vec<Item> = Vec::new();
+
+		for entry in entries {
+			let mut interpretation: Interpretation = interpret(entry.hours());
+			if entry.written_space() != entry.space() {
+				interpretation.doubt(0.8, "the space has a footnote marker; check the notes");
+			}
+
+			let mut provenance: Provenance =
+				Provenance::new(entry.hours()).with_confidence(interpretation.confidence);
+			if let Some(source) = source {
+				provenance = provenance.with_source(source);
+			}
+			if let Some(page) = entry
+				.cells()
+				.iter()
+				.find_map(super::table::Cell::page_number)
+			{
+				provenance = provenance.with_page(page);
+			}
+			if let Some(bbox) = entry
+				.cells()
+				.iter()
+				.filter_map(super::table::Cell::text_bbox)
+				.reduce(|a, b| a.union(&b))
+			{
+				provenance = provenance.with_bbox(bbox);
+			}
+
+			let index: usize = spaces
+				.iter()
+				.position(|space| space.name() == entry.space())
+				.unwrap_or_else(|| {
+					spaces.push(Space::new(entry.space()).schedule(Schedule::new()));
+					spaces.len() - 1
+				});
+			if let (Some(hours), Some(schedule)) = (
+				interpretation.hours.as_ref(),
+				spaces[index].schedules_mut().first_mut(),
+			) {
+				if !add(schedule, entry.day(), hours, &provenance, timezone) {
+					interpretation.hours = None;
+					interpretation
+						.issues
+						.push("doesn't exist in the time zone".to_string());
+					provenance = provenance.with_confidence(0.0);
+				}
+			}
+
+			items.push(Item {
+				space: entry.space().to_string(),
+				day: entry.day(),
+				provenance,
+				hours: interpretation.hours,
+				issues: interpretation.issues,
+			});
+		}
+
+		Self {
+			spaces,
+			review: Review {
+				source: source.map(ToString::to_string),
+				threshold: DEFAULT_THRESHOLD,
+				items,
+			},
+		}
+	}
+
+	#[must_use]
+	pub fn spaces(&self) -> &[Space<'schedule, Tz>] {
+		&self.spaces
+	}
+
+	#[must_use]
+	pub fn into_spaces(self) -> Vec<Space<'schedule, Tz>> {
+		self.spaces
+	}
+
+	#[must_use]
+	pub fn review(&self) -> &Review {
+		&self.review
+	}
+}
+
+/// Add `hours` on `day` to `schedule`, or return `false` if they fall in a gap
+/// in `timezone`.
+fn add<Tz: TimeZone>(
+	schedule: &mut Schedule<'_, Tz>,
+	day: Day,
+	hours: &Hours,
+	provenance: &Provenance,
+	timezone: &Tz,
+) -> bool {
+	match (day, hours) {
+		(Day::Weekday(_), Hours::Closed(_)) => true,
+
+		(Day::Weekday(weekday), Hours::Open { open, close }) => {
+			let closes: Weekday = if close <= open {
+				weekday.succ()
+			} else {
+				weekday
+			};
+			let mut part: Part<Tz> = Part::new()
+				.open(Specifier::Weekly {
+					day: weekday.to_string(),
+					time: open.format("%H:%M").to_string(),
+				})
+				.close(Specifier::Weekly {
+					day: closes.to_string(),
+					time: close.format("%H:%M").to_string(),
+				});
+			*part.provenance_mut() = Some(provenance.clone());
+			schedule.parts_mut().push(part);
+			true
+		}
+
+		(Day::Date(date), hours) => {
+			let at = |date: NaiveDate, time: NaiveTime| {
+				timezone
+					.from_local_datetime(&date.and_time(time))
+					.earliest()
+			};
+			let next: NaiveDate = date + Duration::days(1);
+			let (Some(start), Some(end)) = (at(date, NaiveTime::MIN), at(next, NaiveTime::MIN)) else {
+				return false;
+			};
+
+			let mut closed: Exception<'_, Tz> = Exception::new()
+				.effective(Specifier::Exact(start))
+				.expires(Specifier::Exact(end));
+			*closed.provenance_mut() = Some(provenance.clone());
+
+			match hours {
+				Hours::Closed(word) => {
+					*closed.effect_mut() = Some(Status::Closed(Reason::Exception(Some(word.clone()))));
+				}
+				Hours::Open { open, close } => {
+					let until: NaiveDate = if close <= open { next } else { date };
+					let (Some(opens_at), Some(closes_at)) = (at(date, *open), at(until, *close)) else {
+						return false;
+					};
+					let mut opened: Exception<'_, Tz> = Exception::new()
+						.effective(Specifier::Exact(opens_at))
+						.expires(Specifier::Exact(closes_at));
+					*opened.effect_mut() = Some(Status::Open(Reason::Exception(None)));
+					*opened.provenance_mut() = Some(provenance.clone());
+					schedule.exceptions_mut().push(opened);
+
+					*closed.effect_mut() = Some(Status::Closed(Reason::Exception(None)));
+				}
+			}
+			schedule.exceptions_mut().push(closed);
+			true
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{interpret, Hours};
+	use chrono::NaiveTime;
+
+	fn hours(open: (u32, u32), close: (u32, u32)) -> Hours {
+		Hours::Open {
+			open: NaiveTime::from_hms_opt(open.0, open.1, 0).unwrap(),
+			close: NaiveTime::from_hms_opt(close.0, close.1, 0).unwrap(),
+		}
+	}
+
+	#[test]
+	fn interpretations() {
+		let am_pm = interpret("7:00 a.m. - 5:00 p.m.");
+		assert_eq!(am_pm.hours(), Some(&hours((7, 0), (17, 0))));
+		assert!(am_pm.issues().is_empty());
+
+		assert_eq!(
+			interpret("0800-1700").hours(),
+			Some(&hours((8, 0), (17, 0)))
+		);
+		assert_eq!(
+			interpret("7:30 a.m. - 12:00 a.m.").hours(),
+			Some(&hours((7, 30), (0, 0)))
+		);
+		assert_eq!(
+			interpret("LOCKED").hours(),
+			Some(&Hours::Closed("LOCKED".to_string()))
+		);
+
+		let overnight = interpret("7:00 a.m. - 2:00 a.m.");
+		assert_eq!(overnight.hours(), Some(&hours((7, 0), (2, 0))));
+		assert!(overnight.confidence() > 0.9);
+
+		let guessed = interpret("7:00 - 5:00");
+		assert_eq!(guessed.hours(), Some(&hours((7, 0), (17, 0))));
+		assert!(guessed.confidence() < 0.9);
+
+		let footnote = interpret("CLOSED *");
+		assert_eq!(footnote.hours(), Some(&Hours::Closed("CLOSED".to_string())));
+		assert!(footnote.confidence() < 0.9);
+
+		let unreadable = interpret("see note");
+		assert_eq!(unreadable.hours(), None);
+		assert!(unreadable.confidence().abs() < f32::EPSILON);
+	}
+}
@@ -0,0 +1,195 @@
+//! The document information dictionary (Section 14.3.3 of the PDF spec).
+
+use chrono::{DateTime, FixedOffset, NaiveDate, TimeZone};
+use lopdf::{Dictionary, Document, Object};
+
+/// Metadata from a document's `/Info` dictionary.
+///
+/// Every entry is optional in the spec, and many producers leave out most of
+/// them; Excel, for instance, writes an author and dates but no title.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Info {
+	title: Option<String>,
+	author: Option<String>,
+	subject: Option<String>,
+	creator: Option<String>,
+	producer: Option<String>,
+	creation_date: Option<DateTime<FixedOffset>>,
+	modification_date: Option<DateTime<FixedOffset>>,
+}
+
+impl Info {
+	/// Read the `/Info` dictionary referenced from the trailer of `document`,
+	/// or an empty `Info` if it has none.
+	#[must_use]
+	pub fn from_document(document: &Document) -> Self {
+		document
+			.trailer
+			.get(b"Info")
+			.and_then(|info| match info {
+				Object::Reference(id) => document.get_dictionary(*id),
+				object => object.as_dict(),
+			})
+			.map(Self::from_dictionary)
+			.unwrap_or_default()
+	}
+
+	#[must_use]
+	pub fn from_dictionary(dictionary: &Dictionary) -> Self {
+		let text = |key: &[u8]| -> Option<String> {
+			match dictionary.get(key) {
+				Ok(Object::String(bytes, _format)) => Some(text_string(bytes)),
+				_ => None,
+			}
+		};
+		let date = |key: &[u8]| -> Option<DateTime<FixedOffset>> {
+			match dictionary.get(key) {
+				Ok(Object::String(bytes, _format)) => date(&text_string(bytes)),
+				_ => None,
+			}
+		};
+
+		Self {
+			title: text(b"Title"),
+			author: text(b"Author"),
+			subject: text(b"Subject"),
+			creator: text(b"Creator"),
+			producer: text(b"Producer"),
+			creation_date: date(b"CreationDate"),
+			modification_date: date(b"ModDate"),
+		}
+	}
+
+	#[must_use]
+	pub fn title(&self) -> Option<&str> {
+		self.title.as_deref()
+	}
+
+	#[must_use]
+	pub fn author(&self) -> Option<&str> {
+		self.author.as_deref()
+	}
+
+	#[must_use]
+	pub fn subject(&self) -> Option<&str> {
+		self.subject.as_deref()
+	}
+
+	#[must_use]
+	pub fn creator(&self) -> Option<&str> {
+		self.creator.as_deref()
+	}
+
+	#[must_use]
+	pub fn producer(&self) -> Option<&str> {
+		self.producer.as_deref()
+	}
+
+	#[must_use]
+	pub fn creation_date(&self) -> Option<&DateTime<FixedOffset>> {
+		self.creation_date.as_ref()
+	}
+
+	#[must_use]
+	pub fn modification_date(&self) -> Option<&DateTime<FixedOffset>> {
+		self.modification_date.as_ref()
+	}
+}
+
+/// Decode a text string (Section 7.9.2.2 of the PDF spec).
+///
+/// Text strings are either UTF-16BE with a byte order mark, or
+/// `PDFDocEncoding`, which agrees with Latin-1 for every character likely to
+/// turn up in metadata.
+#[must_use]
+pub fn text_string(bytes: &[u8]) -> String {
+	match bytes {
+		[0xfe, 0xff, rest @ ..] => {
+			let units: Vec<u16> = rest
+				.chunks(2)
+				.map(|pair| u16::from_be_bytes([pair[0], *pair.get(1).unwrap_or(&0)]))
+				.collect();
+			String::from_utf16_lossy(&units)
+		}
+		bytes => bytes.iter().copied().map(char::from).collect(),
+	}
+}
+
+/// Parse a date (Section 7.9.4 of the PDF spec), which looks like
+/// `D:YYYYMMDDHHmmSSOHH'mm'`; everything after the year is optional, and a
+/// missing offset is taken to be UTC.
+#[must_use]
+pub fn date(string: &str) -> Option<DateTime<FixedOffset>> {
+	let string: &str = string.trim();
+	let string: &str = string.strip_prefix("D:").unwrap_or(string);
+
+	let digits: usize = string
+		.find(|c: char| !c.is_ascii_digit())
+		.unwrap_or(string.len());
+	let (fields, offset) = string.split_at(digits);
+	if fields.len() < 4 || fields.len() % 2 != 0 {
+		return None;
+	}
+
+	let field = |start: usize, default: u32| -> Option<u32> {
+		fields
+			.get(start..start + 2)
+			.map_or(Some(default), |digits| digits.parse().ok())
+	};
+
+	let year: i32 = fields[..4].parse().ok()?;
+	let date: NaiveDate = NaiveDate::from_ymd_opt(year, field(4, 1)?, field(6, 1)?)?;
+	let time = date.and_hms_opt(field(8, 0)?, field(10, 0)?, field(12, 0)?)?;
+
+	let offset: FixedOffset = match offset.chars().next() {
+		None | Some('Z') => FixedOffset::east_opt(0)?,
+		Some(sign @ ('+' | '-')) => {
+			let mut parts = offset[1..].split('\'').filter(|part| !part.is_empty());
+			let hours: i32 = parts.next()?.parse().ok()?;
+			let minutes: i32 = parts
+				.next()
+				.map_or(Some(0), |minutes| minutes.parse().ok())?;
+			let seconds: i32 = hours * 3600 + minutes * 60;
+			if sign == '+' {
+				FixedOffset::east_opt(seconds)?
+			} else {
+				FixedOffset::west_opt(seconds)?
+			}
+		}
+		Some(_) => return None,
+	};
+
+	offset.from_local_datetime(&time).single()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{date, text_string};
+	use chrono::{FixedOffset, TimeZone};
+
+	#[test]
+	fn dates() {
+		let central = FixedOffset::west_opt(5 * 3600).unwrap();
+		assert_eq!(
+			date("D:20191010103955-05'00'"),
+			central.with_ymd_and_hms(2019, 10, 10, 10, 39, 55).single()
+		);
+
+		let utc = FixedOffset::east_opt(0).unwrap();
+		assert_eq!(
+			date("D:2019"),
+			utc.with_ymd_and_hms(2019, 1, 1, 0, 0, 0).single()
+		);
+		assert_eq!(
+			date("D:201910101039Z"),
+			utc.with_ymd_and_hms(2019, 10, 10, 10, 39, 0).single()
+		);
+		assert_eq!(date("yesterday"), None);
+	}
+
+	#[test]
+	fn text_strings() {
+		assert_eq!(text_string(b"Caf\xe9"), "Café");
+		assert_eq!(text_string(b"\xfe\xff\x00H\x00i"), "Hi");
+	}
+}
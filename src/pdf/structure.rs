@@ -0,0 +1,398 @@
+//! Reading the logical structure of tagged PDFs (Section 14.7 of the PDF
+//! spec).
+//!
+//! Tagged PDFs, which includes anything exported for accessibility from Excel
+//! or Word, carry a tree of structure elements (`Table`, `TR`, `TD`, ...)
+//! whose leaves point at marked-content sequences on the pages by their MCID.
+//! Following those pointers tells us exactly which text is in which cell,
+//! without having to guess from where it was drawn.
+
+use core::convert::TryFrom;
+
+use lopdf::{Dictionary, Document, Object, ObjectId};
+
+use super::error;
+
+/// A reference from the structure tree to a marked-content sequence.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct MarkedContentReference {
+	page: Option<ObjectId>,
+	mcid: u32,
+}
+
+impl MarkedContentReference {
+	/// The page the content is on, if the tree says.
+	#[must_use]
+	pub fn page(&self) -> Option<ObjectId> {
+		self.page
+	}
+
+	#[must_use]
+	pub fn mcid(&self) -> u32 {
+		self.mcid
+	}
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum StructureNode {
+	Element(StructureElement),
+	Content(MarkedContentReference),
+}
+
+/// A structure element, e.g. a table or one of its cells.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StructureElement {
+	kind: String,
+	page: Option<ObjectId>,
+	col_span: u32,
+	row_span: u32,
+	children: Vec<StructureNode>,
+}
+
+impl StructureElement {
+	/// The structure type, after mapping through the `RoleMap`, e.g. `"TD"`.
+	#[must_use]
+	pub fn kind(&self) -> &str {
+		&self.kind
+	}
+
+	/// The page the element is on, if the tree says.
+	#[must_use]
+	pub fn page(&self) -> Option<ObjectId> {
+		self.page
+	}
+
+	/// The `ColSpan` table attribute; 1 if not given.
+	#[must_use]
+	pub fn col_span(&self) -> u32 {
+		self.col_span
+	}
+
+	/// The `RowSpan` table attribute; 1 if not given.
+	#[must_use]
+	pub fn row_span(&self) -> u32 {
+		self.row_span
+	}
+
+	#[must_use]
+	pub fn children(&self) -> &[StructureNode] {
+		&self.children
+	}
+
+	/// The child elements, skipping references to content.
+	pub fn elements(&self) -> impl Iterator<Item = &StructureElement> {
+		self.children.iter().filter_map(|child| match child {
+			StructureNode::Element(element) => Some(element),
+			StructureNode::Content(_) => None,
+		})
+	}
+
+	/// All the marked content in this element and its descendants, in
+	/// structure order.
+	#[must_use]
+	pub fn marked_content(&self) -> Vec<MarkedContentReference> {
+		let mut references: Vec<MarkedContentReference> = Vec::new();
+		collect_marked_content(&self.children, &mut references);
+		references
+	}
+
+	/// This element and its descendants of type `kind`, outermost first,
+	/// without looking inside the ones that match.
+	#[must_use]
+	pub fn find(&self, kind: &str) -> Vec<&StructureElement> {
+		if self.kind == kind {
+			vec![self]
+		} else {
+			self
+				.elements()
+				.flat_map(|element| element.find(kind))
+				.collect()
+		}
+	}
+}
+
+fn collect_marked_content(
+	children: &[StructureNode],
+	references: &mut Vec<MarkedContentReference>,
+) {
+	for child in children {
+		match child {
+			StructureNode::Element(element) => collect_marked_content(&element.children, references),
+			StructureNode::Content(reference) => references.push(*reference),
+		}
+	}
+}
+
+/// The structure tree of a document, from its `StructTreeRoot`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StructureTree {
+	children: Vec<StructureElement>,
+}
+
+/// Guards against cycles in a broken structure tree.
+const MAX_DEPTH: usize = 256;
+
+impl StructureTree {
+	/// Read the structure tree of `document`, or `None` if it isn't tagged.
+	///
+	/// # Errors
+	///
+	/// Returns an error if the catalog cannot be read, or the tree contains
+	/// objects of the wrong type where elements should be.
+	pub fn from_document(document: &Document) -> error::Result<Option<Self>> {
+		let root: &Dictionary = match document.catalog()?.get(b"StructTreeRoot") {
+			Ok(root) => document.dereference(root)?.1.as_dict()?,
+			Err(_) => return Ok(None),
+		};
+
+		let role_map: Option<&Dictionary> = match root.get(b"RoleMap") {
+			Ok(role_map) => Some(document.dereference(role_map)?.1.as_dict()?),
+			Err(_) => None,
+		};
+
+		let reader = Reader { document, role_map };
+		let children: Vec<StructureElement> = match root.get(b"K") {
+			Ok(kids) => reader
+				.kids(kids, None, 0)?
+				.into_iter()
+				.filter_map(|node| match node {
+					StructureNode::Element(element) => Some(element),
+					StructureNode::Content(_) => None,
+				})
+				.collect(),
+			Err(_) => Vec::new(),
+		};
+
+		Ok(Some(Self { children }))
+	}
+
+	#[must_use]
+	pub fn children(&self) -> &[StructureElement] {
+		&self.children
+	}
+
+	/// Every element of type `kind`, outermost first, without looking inside
+	/// the ones that match.
+	#[must_use]
+	pub fn find(&self, kind: &str) -> Vec<&StructureElement> {
+		self
+			.children
+			.iter()
+			.flat_map(|element| element.find(kind))
+			.collect()
+	}
+}
+
+struct Reader<'document> {
+	document: &'document Document,
+	role_map: Option<&'document Dictionary>,
+}
+
+impl Reader<'_> {
+	/// Map a structure type through the `RoleMap` to a standard one.
+	fn role(&self, kind: &[u8]) -> String {
+		let mut kind: &[u8] = kind;
+		if let Some(role_map) = self.role_map {
+			// Role maps may chain, but not forever.
+			for _ in 0..8 {
+				match role_map.get(kind).and_then(Object::as_name) {
+					Ok(mapped) if mapped != kind => kind = mapped,
+					_ => break,
+				}
+			}
+		}
+		String::from_utf8_lossy(kind).into_owned()
+	}
+
+	/// Read the `K` entry of an element, whose page (if known) is `page`.
+	fn kids(
+		&self,
+		kids: &Object,
+		page: Option<ObjectId>,
+		depth: usize,
+	) -> error::Result<Vec<StructureNode>> {
+		if depth > MAX_DEPTH {
+			return Err(error::ParseError::Malformed(
+				"the structure tree is too deep".to_string(),
+			));
+		}
+
+		match self.document.dereference(kids)?.1 {
+			Object::Array(array) => {
+				let mut nodes: Vec<StructureNode> = Vec::new();
+				for kid in array {
+					nodes.extend(self.kids(kid, page, depth + 1)?);
+				}
+				Ok(nodes)
+			}
+
+			Object::Integer(mcid) => Ok(
+				u32::try_from(*mcid)
+					.ok()
+					.map(|mcid| StructureNode::Content(MarkedContentReference { page, mcid }))
+					.into_iter()
+					.collect(),
+			),
+
+			Object::Dictionary(dictionary) => match dictionary.get(b"Type").and_then(Object::as_name) {
+				Ok(b"MCR") => {
+					let page: Option<ObjectId> = dictionary
+						.get(b"Pg")
+						.and_then(Object::as_reference)
+						.ok()
+						.or(page);
+					Ok(match dictionary.get(b"MCID") {
+						Ok(Object::Integer(mcid)) => u32::try_from(*mcid)
+							.ok()
+							.map(|mcid| StructureNode::Content(MarkedContentReference { page, mcid }))
+							.into_iter()
+							.collect(),
+						_ => Vec::new(),
+					})
+				}
+
+				// References to whole objects, such as annotations, have no text.
+				Ok(b"OBJR") => Ok(Vec::new()),
+
+				_ => Ok(vec![StructureNode::Element(
+					self.element(dictionary, page, depth)?,
+				)]),
+			},
+
+			object => Err(error::ParseError::Malformed(format!(
+				"a structure element's kids should be elements or MCIDs, not {}",
+				error::object_type(object)
+			))),
+		}
+	}
+
+	fn element(
+		&self,
+		dictionary: &Dictionary,
+		page: Option<ObjectId>,
+		depth: usize,
+	) -> error::Result<StructureElement> {
+		let kind: String = dictionary
+			.get(b"S")
+			.and_then(Object::as_name)
+			.map(|kind| self.role(kind))
+			.unwrap_or_default();
+		let page: Option<ObjectId> = dictionary
+			.get(b"Pg")
+			.and_then(Object::as_reference)
+			.ok()
+			.or(page);
+
+		let (col_span, row_span) = match dictionary.get(b"A") {
+			Ok(attributes) => self.spans(attributes)?,
+			Err(_) => (1, 1),
+		};
+
+		let children: Vec<StructureNode> = match dictionary.get(b"K") {
+			Ok(kids) => self.kids(kids, page, depth + 1)?,
+			Err(_) => Vec::new(),
+		};
+
+		Ok(StructureElement {
+			kind,
+			page,
+			col_span,
+			row_span,
+			children,
+		})
+	}
+
+	/// Find `ColSpan` and `RowSpan` in an element's attributes, which are
+	/// either a single attribute object or an array of them, possibly with
+	/// revision numbers mixed in (Section 14.7.5.2 of the PDF spec).
+	fn spans(&self, attributes: &Object) -> error::Result<(u32, u32)> {
+		let mut spans: (u32, u32) = (1, 1);
+
+		let attributes: Vec<&Object> = match self.document.dereference(attributes)?.1 {
+			Object::Array(array) => array.iter().collect(),
+			object => vec![object],
+		};
+
+		for attribute in attributes {
+			if let Object::Dictionary(attribute) = self.document.dereference(attribute)?.1 {
+				let span = |key: &[u8]| match attribute.get(key) {
+					Ok(Object::Integer(span)) => u32::try_from(*span).ok().filter(|span| *span > 0),
+					_ => None,
+				};
+				spans = (
+					span(b"ColSpan").unwrap_or(spans.0),
+					span(b"RowSpan").unwrap_or(spans.1),
+				);
+			}
+		}
+
+		Ok(spans)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{MarkedContentReference, StructureTree};
+	use lopdf::{dictionary, Document, Object, ObjectId};
+
+	#[test]
+	fn tables_and_cells() {
+		let mut document = Document::with_version("1.5");
+		let page: ObjectId = document.add_object(dictionary! { "Type" => "Page" });
+		let span: ObjectId = document.add_object(dictionary! { "O" => "Table", "ColSpan" => 2 });
+
+		let cells: Vec<Object> = vec![
+			dictionary! { "S" => "Cell", "K" => 3 }.into(),
+			dictionary! {
+				"S" => "TD",
+				"A" => vec![Object::Reference(span), 0.into()],
+				"K" => dictionary! { "Type" => "MCR", "MCID" => 4 },
+			}
+			.into(),
+		];
+		let row: Object = dictionary! { "S" => "TR", "K" => cells }.into();
+		let table: ObjectId =
+			document.add_object(dictionary! { "S" => "Table", "Pg" => page, "K" => vec![row] });
+		let root: ObjectId = document.add_object(dictionary! {
+			"Type" => "StructTreeRoot",
+			"RoleMap" => dictionary! { "Cell" => "TD" },
+			"K" => dictionary! { "S" => "Document", "K" => vec![Object::Reference(table)] },
+		});
+		let catalog: ObjectId =
+			document.add_object(dictionary! { "Type" => "Catalog", "StructTreeRoot" => root });
+		document.trailer.set("Root", catalog);
+
+		let tree = StructureTree::from_document(&document).unwrap().unwrap();
+		let tables = tree.find("Table");
+		assert_eq!(tables.len(), 1);
+
+		assert_eq!(tables[0].find("TD").len(), 2);
+
+		let rows: Vec<_> = tables[0].elements().collect();
+		let cells: Vec<_> = rows[0].elements().collect();
+		assert_eq!(cells.len(), 2);
+		assert_eq!(cells[0].kind(), "TD");
+		assert_eq!((cells[0].col_span(), cells[1].col_span()), (1, 2));
+
+		let content = tables[0].marked_content();
+		assert_eq!(
+			content
+				.iter()
+				.map(MarkedContentReference::mcid)
+				.collect::<Vec<_>>(),
+			vec![3, 4]
+		);
+		assert!(content
+			.iter()
+			.all(|reference| reference.page() == Some(page)));
+	}
+
+	#[test]
+	fn untagged() {
+		let mut document = Document::with_version("1.5");
+		let catalog: ObjectId = document.add_object(dictionary! { "Type" => "Catalog" });
+		document.trailer.set("Root", catalog);
+
+		assert_eq!(StructureTree::from_document(&document).unwrap(), None);
+	}
+}
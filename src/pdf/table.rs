@@ -0,0 +1,525 @@
+//! Tables of text extracted from PDFs.
+//!
+//! Schedules are almost always laid out as tables, with buildings down the
+//! side and days across the top.  A `Table` here is a grid of `Cell`s, each of
+//! which knows its text, where it was drawn, and how many rows and columns it
+//! spans.
+
+use std::collections::BTreeMap;
+
+use lopdf::ObjectId;
+
+use super::structure::{MarkedContentReference, StructureElement};
+use super::text::TextRun;
+use super::{Coordinates, Rectangle};
+
+/// The text runs of each page, with the page's number, by page object.
+pub type PageRuns = BTreeMap<ObjectId, (u32, Vec<TextRun>)>;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cell {
+	row: usize,
+	column: usize,
+	row_span: usize,
+	col_span: usize,
+	text: String,
+	page: Option<u32>,
+	bbox: Option<Rectangle>,
+	header: bool,
+}
+
+impl Cell {
+	#[must_use]
+	pub fn new(row: usize, column: usize, text: &str) -> Self {
+		Self {
+			row,
+			column,
+			row_span: 1,
+			col_span: 1,
+			text: text.to_string(),
+			page: None,
+			bbox: None,
+			header: false,
+		}
+	}
+
+	#[must_use]
+	pub fn spans(mut self, row_span: usize, col_span: usize) -> Self {
+		self.row_span = row_span.max(1);
+		self.col_span = col_span.max(1);
+		self
+	}
+
+	#[must_use]
+	pub fn page(mut self, page: u32) -> Self {
+		self.page = Some(page);
+		self
+	}
+
+	#[must_use]
+	pub fn bbox(mut self, bbox: Rectangle) -> Self {
+		self.bbox = Some(bbox);
+		self
+	}
+
+	#[must_use]
+	pub fn header(mut self, header: bool) -> Self {
+		self.header = header;
+		self
+	}
+
+	/// The first row the cell is in, counting from 0.
+	#[must_use]
+	pub fn row(&self) -> usize {
+		self.row
+	}
+
+	/// The first column the cell is in, counting from 0.
+	#[must_use]
+	pub fn column(&self) -> usize {
+		self.column
+	}
+
+	#[must_use]
+	pub fn row_span(&self) -> usize {
+		self.row_span
+	}
+
+	#[must_use]
+	pub fn col_span(&self) -> usize {
+		self.col_span
+	}
+
+	#[must_use]
+	pub fn text(&self) -> &str {
+		&self.text
+	}
+
+	/// The number of the page the cell's text is on, counting from 1, if known.
+	#[must_use]
+	pub fn page_number(&self) -> Option<u32> {
+		self.page
+	}
+
+	/// The extent of the cell's text on the page as displayed, if it has any.
+	#[must_use]
+	pub fn text_bbox(&self) -> Option<Rectangle> {
+		self.bbox
+	}
+
+	/// Whether the document marked the cell as a header (`TH`).
+	#[must_use]
+	pub fn is_header(&self) -> bool {
+		self.header
+	}
+
+	/// Whether the cell covers the slot at `row`, `column`.
+	#[must_use]
+	pub fn covers(&self, row: usize, column: usize) -> bool {
+		(self.row..self.row + self.row_span).contains(&row)
+			&& (self.column..self.column + self.col_span).contains(&column)
+	}
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Table {
+	page: Option<u32>,
+	rows: usize,
+	columns: usize,
+	cells: Vec<Cell>,
+}
+
+impl Table {
+	/// Build a table from cells which already know where they are.
+	#[must_use]
+	pub fn new(page: Option<u32>, cells: Vec<Cell>) -> Self {
+		let rows: usize = cells
+			.iter()
+			.map(|cell| cell.row + cell.row_span)
+			.max()
+			.unwrap_or(0);
+		let columns: usize = cells
+			.iter()
+			.map(|cell| cell.column + cell.col_span)
+			.max()
+			.unwrap_or(0);
+		Self {
+			page,
+			rows,
+			columns,
+			cells,
+		}
+	}
+
+	/// Build a table from a `Table` structure element, taking the text of each
+	/// cell from the runs of marked content it refers to.
+	///
+	/// Rows are the `TR` elements (wherever they are under `THead`, `TBody` or
+	/// `TFoot`), and cells are their `TD` and `TH` children, placed into the
+	/// grid the way HTML places cells: each goes in the first column of its row
+	/// not already covered by a cell spanning down from above.
+	#[must_use]
+	pub fn from_structure(table: &StructureElement, pages: &PageRuns) -> Self {
+		let mut cells: Vec<Cell> = Vec::new();
+		let mut occupied: Vec<Vec<bool>> = Vec::new();
+
+		let rows = table
+			.find("TR")
+			.into_iter()
+			.filter(|row| row.elements().next().is_some());
+		for (row, element) in rows.enumerate() {
+			let elements: Vec<(&StructureElement, String, Option<Rectangle>, Option<u32>)> = element
+				.elements()
+				.filter(|cell| matches!(cell.kind(), "TD" | "TH"))
+				.map(|cell| {
+					let (text, bbox, page) = text(cell, pages);
+					(cell, text, bbox, page)
+				})
+				.collect();
+
+			// Producers don't always list the cells of a row from left to right, so
+			// put the ones with text in order of where they were drawn, leaving
+			// empty ones after whichever cell they followed.  A table which is too
+			// wide for one page continues on the next.
+			let mut key: (u32, f64) = (0, f64::NEG_INFINITY);
+			let mut keys: Vec<(u32, f64)> = Vec::with_capacity(elements.len());
+			for (_, _, bbox, page) in &elements {
+				if let Some(bbox) = bbox {
+					key = (page.unwrap_or(0), bbox.lower_left().x());
+				}
+				keys.push(key);
+			}
+			let mut order: Vec<usize> = (0..elements.len()).collect();
+			order.sort_by(|a, b| {
+				keys[*a]
+					.0
+					.cmp(&keys[*b].0)
+					.then(keys[*a].1.total_cmp(&keys[*b].1))
+			});
+
+			let mut column: usize = 0;
+			for (element, text, bbox, page) in order.into_iter().map(|index| &elements[index]) {
+				while occupied
+					.get(row)
+					.and_then(|row| row.get(column))
+					.copied()
+					.unwrap_or(false)
+				{
+					column += 1;
+				}
+
+				let row_span: usize = element.row_span() as usize;
+				let col_span: usize = element.col_span() as usize;
+				for covered in row..row + row_span {
+					if occupied.len() <= covered {
+						occupied.resize(covered + 1, Vec::new());
+					}
+					if occupied[covered].len() < column + col_span {
+						occupied[covered].resize(column + col_span, false);
+					}
+					for slot in &mut occupied[covered][column..column + col_span] {
+						*slot = true;
+					}
+				}
+
+				let mut cell = Cell::new(row, column, text)
+					.spans(row_span, col_span)
+					.header(element.kind() == "TH");
+				cell.page = *page;
+				cell.bbox = *bbox;
+				cells.push(cell);
+
+				column += col_span;
+			}
+		}
+
+		realign(&mut cells);
+
+		let page: Option<u32> = cells.iter().filter_map(|cell| cell.page).min();
+		Self::new(page, cells)
+	}
+
+	/// Build a table from where the text on a page was drawn, for documents
+	/// without a structure tree to say where the cells are.
+	///
+	/// Runs on the same baseline make a row.  The columns are where the text
+	/// of the fullest rows was drawn, and a run drawn across more than one of
+	/// them, like a day heading over its opening and closing times, spans them.
+	#[must_use]
+	pub fn from_runs(page: u32, runs: &[TextRun]) -> Self {
+		// Read top to bottom, then left to right.
+		let mut runs: Vec<&TextRun> = runs
+			.iter()
+			.filter(|run| !run.text().trim().is_empty())
+			.collect();
+		runs.sort_by(|a, b| {
+			b.origin()
+				.y()
+				.total_cmp(&a.origin().y())
+				.then(a.origin().x().total_cmp(&b.origin().x()))
+		});
+
+		let mut rows: Vec<Vec<&TextRun>> = Vec::new();
+		for run in runs {
+			match rows.last_mut() {
+				Some(row) if (row[0].origin().y() - run.origin().y()).abs() < row[0].font_size() / 2.0 => {
+					row.push(run);
+				}
+				_ => rows.push(vec![run]),
+			}
+		}
+		for row in &mut rows {
+			row.sort_by(|a, b| a.origin().x().total_cmp(&b.origin().x()));
+		}
+
+		// The extents of the text in the fullest rows, merged where they overlap.
+		let fullest: usize = rows.iter().map(Vec::len).max().unwrap_or(0);
+		let mut extents: Vec<(f64, f64)> = rows
+			.iter()
+			.filter(|row| row.len() == fullest)
+			.flatten()
+			.map(|run| (run.origin().x(), run.end().x()))
+			.collect();
+		extents.sort_by(|a, b| a.0.total_cmp(&b.0));
+		let mut columns: Vec<(f64, f64)> = Vec::new();
+		for (start, end) in extents {
+			match columns.last_mut() {
+				Some(column) if start <= column.1 => column.1 = column.1.max(end),
+				_ => columns.push((start, end)),
+			}
+		}
+
+		let mut cells: Vec<Cell> = Vec::new();
+		for (row, runs) in rows.iter().enumerate() {
+			for run in runs {
+				let (start, end) = (run.origin().x(), run.end().x());
+				let covered: Vec<usize> = (0..columns.len())
+					.filter(|column| columns[*column].0 <= end && start <= columns[*column].1)
+					.collect();
+				// A run between columns goes in the one nearest it.
+				let middle: f64 = f64::midpoint(start, end);
+				let distance = |column: &usize| -> f64 {
+					(f64::midpoint(columns[*column].0, columns[*column].1) - middle).abs()
+				};
+				let (column, col_span) =
+					if let (Some(first), Some(last)) = (covered.first(), covered.last()) {
+						(*first, last - first + 1)
+					} else {
+						let nearest: usize = (0..columns.len())
+							.min_by(|a, b| distance(a).total_cmp(&distance(b)))
+							.unwrap_or(0);
+						(nearest, 1)
+					};
+
+				let bbox = Rectangle::new(
+					run.origin(),
+					Coordinates::new(end, run.origin().y() + run.font_size()),
+				);
+				match cells
+					.iter_mut()
+					.find(|cell| cell.row == row && cell.column == column)
+				{
+					// Runs drawn in the same column are one cell's text.
+					Some(cell) => {
+						cell.text = format!("{} {}", cell.text, run.text().trim());
+						cell.col_span = cell.col_span.max(col_span);
+						cell.bbox = cell.bbox.map(|other| other.union(&bbox));
+					}
+					None => cells.push(
+						Cell::new(row, column, run.text().trim())
+							.spans(1, col_span)
+							.page(page)
+							.bbox(bbox),
+					),
+				}
+			}
+		}
+
+		Self::new(Some(page), cells)
+	}
+
+	/// The number of the page the table is on, counting from 1, if known.
+	#[must_use]
+	pub fn page(&self) -> Option<u32> {
+		self.page
+	}
+
+	#[must_use]
+	pub fn rows(&self) -> usize {
+		self.rows
+	}
+
+	#[must_use]
+	pub fn columns(&self) -> usize {
+		self.columns
+	}
+
+	#[must_use]
+	pub fn cells(&self) -> &[Cell] {
+		&self.cells
+	}
+
+	/// The cell covering the slot at `row`, `column`, if any.
+	#[must_use]
+	pub fn cell(&self, row: usize, column: usize) -> Option<&Cell> {
+		self.cells.iter().find(|cell| cell.covers(row, column))
+	}
+
+	/// The cells which start in `row`, from left to right.
+	#[must_use]
+	pub fn row(&self, row: usize) -> Vec<&Cell> {
+		let mut cells: Vec<&Cell> = self.cells.iter().filter(|cell| cell.row == row).collect();
+		cells.sort_by_key(|cell| cell.column);
+		cells
+	}
+}
+
+/// Move rows whose cells were drawn in different columns from the ones they
+/// were placed in.
+///
+/// Spreadsheets don't always record when a cell spans more than one row, and
+/// then every cell after it in the rows below is placed one column too far to
+/// the left.  Comparing where each cell was drawn with where the other cells
+/// in each column were drawn finds those rows, which are moved right by
+/// however many columns puts the most cells where they were drawn.
+fn realign(cells: &mut [Cell]) {
+	let columns: usize = cells
+		.iter()
+		.map(|cell| cell.column + cell.col_span)
+		.max()
+		.unwrap_or(0);
+	let rows: usize = cells
+		.iter()
+		.map(|cell| cell.row + cell.row_span)
+		.max()
+		.unwrap_or(0);
+
+	let center = |cell: &Cell| -> Option<(u32, f64)> {
+		cell.bbox.map(|bbox| {
+			(
+				cell.page.unwrap_or(0),
+				f64::midpoint(bbox.lower_left().x(), bbox.upper_right().x()),
+			)
+		})
+	};
+
+	// Where the single-column cells of each column were drawn, on average.
+	let mut sums: BTreeMap<(u32, usize), (f64, f64)> = BTreeMap::new();
+	for cell in cells.iter().filter(|cell| cell.col_span == 1) {
+		if let Some((page, x)) = center(cell) {
+			let (sum, count) = sums.entry((page, cell.column)).or_insert((0.0, 0.0));
+			*sum += x;
+			*count += 1.0;
+		}
+	}
+
+	// The column each single-column cell was drawn in.
+	let drawn = |cell: &Cell| -> Option<usize> {
+		let (page, x) = center(cell)?;
+		sums
+			.range((page, 0)..=(page, usize::MAX))
+			.map(|((_, column), (sum, count))| (*column, (x - sum / count).abs()))
+			.min_by(|a, b| a.1.total_cmp(&b.1))
+			.map(|(column, _)| column)
+	};
+
+	// How many of the cells of `row` would be out of place if moved right by
+	// `shift`, or `None` if they can't be moved that far.
+	let misplaced = |cells: &[Cell], row: usize, shift: usize| -> Option<usize> {
+		let mut misplaced: usize = 0;
+		for cell in cells.iter().filter(|cell| cell.row == row) {
+			let start: usize = cell.column + shift;
+			if start + cell.col_span > columns {
+				return None;
+			}
+			if shift > 0
+				&& cells.iter().any(|other| {
+					other.row < row && (start..start + cell.col_span).any(|column| other.covers(row, column))
+				}) {
+				return None;
+			}
+
+			if cell.col_span == 1 && drawn(cell).is_some_and(|column| column != start) {
+				misplaced += 1;
+			}
+		}
+		Some(misplaced)
+	};
+
+	for row in 0..rows {
+		let Some(current) = misplaced(cells, row, 0) else {
+			continue;
+		};
+		let best = (1..columns)
+			.filter_map(|shift| misplaced(cells, row, shift).map(|misplaced| (shift, misplaced)))
+			.min_by_key(|(_, misplaced)| *misplaced);
+
+		if let Some((shift, misplaced)) = best {
+			if misplaced < current && misplaced * 2 < current {
+				for cell in cells.iter_mut().filter(|cell| cell.row == row) {
+					cell.column += shift;
+				}
+			}
+		}
+	}
+}
+
+/// The text of a structure element, where it was drawn, and the number of the
+/// page it's on.
+fn text(element: &StructureElement, pages: &PageRuns) -> (String, Option<Rectangle>, Option<u32>) {
+	let mut runs: Vec<&TextRun> = Vec::new();
+	let mut number: Option<u32> = None;
+
+	for reference in element.marked_content() {
+		let Some((page_number, page_runs)) = page_of(&reference, element, pages) else {
+			continue;
+		};
+		number = number.or(Some(*page_number));
+		runs.extend(
+			page_runs
+				.iter()
+				.filter(|run| run.mcid() == Some(reference.mcid())),
+		);
+	}
+
+	// Read top to bottom, then left to right.
+	runs.sort_by(|a, b| {
+		b.origin()
+			.y()
+			.total_cmp(&a.origin().y())
+			.then(a.origin().x().total_cmp(&b.origin().x()))
+	});
+
+	let text: String = runs
+		.iter()
+		.map(|run| run.text().trim())
+		.filter(|text| !text.is_empty())
+		.collect::<Vec<&str>>()
+		.join(" ");
+
+	let bbox: Option<Rectangle> = runs
+		.iter()
+		.map(|run| {
+			Rectangle::new(
+				run.origin(),
+				Coordinates::new(run.end().x(), run.origin().y() + run.font_size()),
+			)
+		})
+		.reduce(|a, b| a.union(&b));
+
+	(text, bbox, number)
+}
+
+fn page_of<'pages>(
+	reference: &MarkedContentReference,
+	element: &StructureElement,
+	pages: &'pages PageRuns,
+) -> Option<&'pages (u32, Vec<TextRun>)> {
+	match reference.page().or_else(|| element.page()) {
+		Some(page) => pages.get(&page),
+		// Without a page, the reference can only be resolved in a one-page
+		// document.
+		None if pages.len() == 1 => pages.values().next(),
+		None => None,
+	}
+}
@@ -0,0 +1,476 @@
+//! Turning the text-showing operations of a content stream back into text.
+//!
+//! The interpreter here follows Section 9.4 of the PDF spec closely enough to
+//! know where each glyph lands: it tracks the text state parameters, the text
+//! and line matrices and the glyph widths of the current font.  Knowing where
+//! one string ends and the next begins is what lets it tell kerning (which is
+//! ignored) from a word space (which becomes `' '`) from a jump to another
+//! column of a table (which starts a new `TextRun`).
+
+use super::font::{Font, Fonts};
+use super::{Coordinates, Operation, TextElement, TransformationMatrix};
+
+/// Gaps narrower than this fraction of the width of the space glyph are
+/// treated as kerning rather than as a word space.
+const SPACE_THRESHOLD: f64 = 0.5;
+
+/// Gaps wider than this many ems end the current run; anything wider than a
+/// word space but narrower than this becomes a single space.
+const MAX_WORD_GAP: f64 = 0.6;
+
+/// A stretch of text shown along a single baseline, in user space.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextRun {
+	text: String,
+	origin: Coordinates,
+	end: Coordinates,
+	font_size: f64,
+	mcid: Option<u32>,
+}
+
+impl TextRun {
+	#[must_use]
+	pub fn text(&self) -> &str {
+		&self.text
+	}
+
+	/// Where the baseline of the first glyph starts.
+	#[must_use]
+	pub fn origin(&self) -> Coordinates {
+		self.origin
+	}
+
+	/// Where the baseline of the last glyph ends.
+	#[must_use]
+	pub fn end(&self) -> Coordinates {
+		self.end
+	}
+
+	/// The font size, scaled into user space.
+	#[must_use]
+	pub fn font_size(&self) -> f64 {
+		self.font_size
+	}
+
+	#[must_use]
+	pub fn width(&self) -> f64 {
+		self.end.x() - self.origin.x()
+	}
+
+	/// The identifier of the innermost marked-content sequence with one which
+	/// the text was shown in, for matching it to the structure tree.
+	#[must_use]
+	pub fn mcid(&self) -> Option<u32> {
+		self.mcid
+	}
+}
+
+/// The text state parameters of Section 9.3 of the PDF spec.
+#[derive(Clone, Copy, Debug)]
+struct TextState<'fonts> {
+	font: Option<&'fonts Font>,
+	size: f64,
+	character_spacing: f64,
+	word_spacing: f64,
+	horizontal_scaling: f64,
+	leading: f64,
+	rise: f64,
+}
+
+impl Default for TextState<'_> {
+	fn default() -> Self {
+		Self {
+			font: None,
+			size: 1.0,
+			character_spacing: 0.0,
+			word_spacing: 0.0,
+			horizontal_scaling: 1.0,
+			leading: 0.0,
+			rise: 0.0,
+		}
+	}
+}
+
+/// Walks the operations of a content stream, decoding shown strings with the
+/// page's fonts and collecting them into positioned `TextRun`s.
+#[derive(Debug)]
+pub struct TextInterpreter<'fonts> {
+	fonts: &'fonts Fonts,
+	fallback: Font,
+
+	ctm: TransformationMatrix,
+	state: TextState<'fonts>,
+	saved: Vec<(TransformationMatrix, TextState<'fonts>)>,
+
+	text_matrix: TransformationMatrix,
+	line_matrix: TransformationMatrix,
+
+	/// The MCID, if any, of each open marked-content sequence.
+	marked_content: Vec<Option<u32>>,
+
+	current: Option<TextRun>,
+	runs: Vec<TextRun>,
+}
+
+impl<'fonts> TextInterpreter<'fonts> {
+	#[must_use]
+	pub fn new(fonts: &'fonts Fonts) -> Self {
+		Self::with_matrix(fonts, TransformationMatrix::identity())
+	}
+
+	/// Start with `matrix` as the current transformation matrix, e.g. to map
+	/// user space onto the page as displayed.
+	#[must_use]
+	pub fn with_matrix(fonts: &'fonts Fonts, matrix: TransformationMatrix) -> Self {
+		Self {
+			fonts,
+			fallback: Font::default(),
+			ctm: matrix,
+			state: TextState::default(),
+			saved: Vec::new(),
+			text_matrix: TransformationMatrix::identity(),
+			line_matrix: TransformationMatrix::identity(),
+			marked_content: Vec::new(),
+			current: None,
+			runs: Vec::new(),
+		}
+	}
+
+	/// Run every operation through a fresh interpreter and collect the runs.
+	pub fn interpret<'operations, I>(fonts: &'fonts Fonts, operations: I) -> Vec<TextRun>
+	where
+		I: IntoIterator<Item = &'operations Operation>,
+	{
+		let mut interpreter = Self::new(fonts);
+		for operation in operations {
+			interpreter.apply(operation);
+		}
+		interpreter.into_runs()
+	}
+
+	/// The currently-selected font, if `Tf` has named one that exists.
+	#[must_use]
+	pub fn font(&self) -> Option<&'fonts Font> {
+		self.state.font
+	}
+
+	/// Apply an operation to the interpreter's state.
+	pub fn apply(&mut self, operation: &Operation) {
+		match operation {
+			Operation::SaveGraphicsState => self.saved.push((self.ctm, self.state)),
+			Operation::RestoreGraphicsState => {
+				if let Some((ctm, state)) = self.saved.pop() {
+					self.ctm = ctm;
+					self.state = state;
+				}
+			}
+
+			Operation::ModifyCurrentTransformationMatrix { a, b, c, d, e, f } => {
+				let matrix = TransformationMatrix::new(
+					f64::from(*a),
+					f64::from(*b),
+					f64::from(*c),
+					f64::from(*d),
+					f64::from(*e),
+					f64::from(*f),
+				);
+				self.ctm = matrix.multiply(&self.ctm);
+			}
+
+			Operation::BeginTextObject => {
+				self.text_matrix = TransformationMatrix::identity();
+				self.line_matrix = TransformationMatrix::identity();
+			}
+
+			Operation::SetTextFontAndSize { name, size } => {
+				self.state.font = self.fonts.get(name);
+				self.state.size = f64::from(*size);
+				if self.state.font.is_none() {
+					log::warn!(
+						"font {:?} is not in the page resources",
+						String::from_utf8_lossy(name)
+					);
+				}
+			}
+			Operation::SetCharacterSpacing { spacing } => {
+				self.state.character_spacing = f64::from(*spacing);
+			}
+			Operation::SetWordSpacing { spacing } => self.state.word_spacing = f64::from(*spacing),
+			Operation::SetHorizontalScaling { scale } => {
+				self.state.horizontal_scaling = f64::from(*scale) / 100.0;
+			}
+			Operation::SetTextLeading { leading } => self.state.leading = f64::from(*leading),
+			Operation::SetTextRise { rise } => self.state.rise = f64::from(*rise),
+
+			Operation::SetTextMatrixAndTextLineMatrix { a, b, c, d, e, f } => {
+				self.line_matrix = TransformationMatrix::new(
+					f64::from(*a),
+					f64::from(*b),
+					f64::from(*c),
+					f64::from(*d),
+					f64::from(*e),
+					f64::from(*f),
+				);
+				self.text_matrix = self.line_matrix;
+			}
+			Operation::MoveTextPosition { t_x, t_y } => {
+				self.move_text_position(f64::from(*t_x), f64::from(*t_y));
+			}
+			Operation::MoveTextPositionAndSetLeading { t_x, t_y } => {
+				self.state.leading = -f64::from(*t_y);
+				self.move_text_position(f64::from(*t_x), f64::from(*t_y));
+			}
+			Operation::MoveToStartOfNextLine => self.move_text_position(0.0, -self.state.leading),
+
+			Operation::ShowText { body } => self.show(body),
+			Operation::MoveToNextLineAndShowText { body } => {
+				self.move_text_position(0.0, -self.state.leading);
+				self.show(body);
+			}
+			Operation::SetSpacingMoveToNextLineAndShowText {
+				word_spacing,
+				character_spacing,
+				body,
+			} => {
+				self.state.word_spacing = f64::from(*word_spacing);
+				self.state.character_spacing = f64::from(*character_spacing);
+				self.move_text_position(0.0, -self.state.leading);
+				self.show(body);
+			}
+			Operation::ShowTextAllowingIndividualGlyphPositioning { elements } => {
+				for element in elements {
+					match element {
+						TextElement::Text(body) => self.show(body),
+						TextElement::Adjustment(adjustment) => {
+							let t_x: f64 =
+								-f64::from(*adjustment) / 1000.0 * self.state.size * self.state.horizontal_scaling;
+							self.advance(t_x);
+						}
+					}
+				}
+			}
+
+			Operation::BeginMarkedContentSequence { .. } => self.marked_content.push(None),
+			Operation::BeginMarkedContentSequenceWithPropertyList { properties, .. } => {
+				self.marked_content.push(properties.mcid());
+			}
+			Operation::EndMarkedContentSequence => {
+				self.marked_content.pop();
+			}
+
+			_ => {}
+		}
+	}
+
+	fn mcid(&self) -> Option<u32> {
+		self.marked_content.iter().rev().find_map(|mcid| *mcid)
+	}
+
+	/// The runs collected so far, not including the one still being built.
+	#[must_use]
+	pub fn runs(&self) -> &[TextRun] {
+		&self.runs
+	}
+
+	/// Finish the current run and return all of them.
+	#[must_use]
+	pub fn into_runs(mut self) -> Vec<TextRun> {
+		self.runs.extend(self.current.take());
+		self.runs
+	}
+
+	fn move_text_position(&mut self, t_x: f64, t_y: f64) {
+		self.line_matrix = TransformationMatrix::translation(t_x, t_y).multiply(&self.line_matrix);
+		self.text_matrix = self.line_matrix;
+	}
+
+	fn advance(&mut self, t_x: f64) {
+		self.text_matrix = TransformationMatrix::translation(t_x, 0.0).multiply(&self.text_matrix);
+	}
+
+	/// Where the text matrix currently puts the baseline, in user space, or
+	/// on the page as displayed if the interpreter started with its matrix.
+	#[must_use]
+	pub fn position(&self) -> Coordinates {
+		Coordinates::new(0.0, self.state.rise).transform(&self.text_matrix.multiply(&self.ctm))
+	}
+
+	fn show(&mut self, body: &[u8]) {
+		let font: &Font = self.state.font.unwrap_or(&self.fallback);
+		let scale: f64 = self.text_matrix.multiply(&self.ctm).vertical_scale();
+		let font_size: f64 = self.state.size * scale;
+		let space_width: f64 = f64::from(font.space_width()) / 1000.0 * font_size;
+
+		let start: Coordinates = self.position();
+		let mut text: String = String::new();
+		let mut advance: f64 = 0.0;
+
+		for (code, length) in font.codes(body) {
+			text.push_str(&font.decode_code(code, length));
+
+			let word_spacing: f64 = if length == 1 && code == 0x20 {
+				self.state.word_spacing
+			} else {
+				0.0
+			};
+
+			advance += (f64::from(font.width(code, length)) / 1000.0 * self.state.size
+				+ self.state.character_spacing
+				+ word_spacing)
+				* self.state.horizontal_scaling;
+		}
+
+		self.advance(advance);
+		let end: Coordinates = self.position();
+		self.append(start, end, font_size, space_width, &text);
+	}
+
+	/// Add shown text to the current run if it continues it, or start a new one.
+	fn append(
+		&mut self,
+		start: Coordinates,
+		end: Coordinates,
+		font_size: f64,
+		space_width: f64,
+		text: &str,
+	) {
+		let mcid: Option<u32> = self.mcid();
+
+		// Text from different structure elements never belongs in the same run,
+		// however close together it is.
+		if let Some(run) = self.current.as_mut().filter(|run| run.mcid == mcid) {
+			let gap: f64 = start.x() - run.end.x();
+			let same_line: bool = (start.y() - run.end.y()).abs() < 0.3 * font_size.max(run.font_size);
+
+			if same_line && gap > -0.5 * font_size && gap < MAX_WORD_GAP * font_size {
+				if gap > SPACE_THRESHOLD * space_width && !run.text.ends_with(' ') && !text.starts_with(' ')
+				{
+					run.text.push(' ');
+				}
+				run.text.push_str(text);
+				run.end = end;
+				return;
+			}
+		}
+
+		if text.is_empty() {
+			return;
+		}
+
+		self.runs.extend(self.current.take());
+		self.current = Some(TextRun {
+			text: text.to_string(),
+			origin: start,
+			end,
+			font_size,
+			mcid,
+		});
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{TextInterpreter, TextRun};
+	use crate::pdf::font::{Font, Fonts};
+	use crate::pdf::{Operation, TextElement};
+	use lopdf::{dictionary, Document, Object};
+
+	fn fonts() -> Fonts {
+		let document = Document::with_version("1.5");
+		let mut widths: Vec<Object> = vec![Object::Integer(600); 95];
+		widths[0] = Object::Integer(250);
+		let font = dictionary! {
+			"Type" => "Font",
+			"Subtype" => "TrueType",
+			"BaseFont" => "Arial",
+			"Encoding" => "WinAnsiEncoding",
+			"FirstChar" => 32,
+			"Widths" => widths,
+		};
+
+		let mut fonts = Fonts::new();
+		fonts.insert(
+			b"F1".to_vec(),
+			Font::from_dictionary(&document, &font).unwrap(),
+		);
+		fonts
+	}
+
+	fn texts(runs: &[TextRun]) -> Vec<&str> {
+		runs.iter().map(TextRun::text).collect()
+	}
+
+	#[test]
+	fn adjustments_become_spaces_or_kerning() {
+		let fonts = fonts();
+		let operations = vec![
+			Operation::BeginTextObject,
+			Operation::SetTextFontAndSize {
+				name: b"F1".to_vec(),
+				size: 10.0,
+			},
+			Operation::ShowTextAllowingIndividualGlyphPositioning {
+				elements: vec![
+					TextElement::Text(b"Mon".to_vec()),
+					TextElement::Adjustment(-12.0),
+					TextElement::Text(b"day".to_vec()),
+					TextElement::Adjustment(-400.0),
+					TextElement::Text(b"7:00".to_vec()),
+				],
+			},
+			Operation::EndTextObject,
+		];
+
+		let runs = TextInterpreter::interpret(&fonts, &operations);
+		assert_eq!(texts(&runs), vec!["Monday 7:00"]);
+		assert!((runs[0].width() - (6.0 * 6.0 + 0.12 + 4.0 + 4.0 * 6.0)).abs() < 1e-6);
+	}
+
+	#[test]
+	fn far_moves_start_new_runs() {
+		let fonts = fonts();
+		let operations = vec![
+			Operation::BeginTextObject,
+			Operation::SetTextFontAndSize {
+				name: b"F1".to_vec(),
+				size: 1.0,
+			},
+			Operation::SetTextMatrixAndTextLineMatrix {
+				a: 10.0,
+				b: 0.0,
+				c: 0.0,
+				d: 10.0,
+				e: 100.0,
+				f: 500.0,
+			},
+			Operation::ShowText {
+				body: b"Alumni".to_vec(),
+			},
+			Operation::MoveTextPosition { t_x: 3.9, t_y: 0.0 },
+			Operation::ShowText {
+				body: b"Hall".to_vec(),
+			},
+			Operation::MoveTextPosition {
+				t_x: 12.0,
+				t_y: 0.0,
+			},
+			Operation::ShowText {
+				body: b"7:00 a.m.".to_vec(),
+			},
+			Operation::MoveTextPosition {
+				t_x: -15.9,
+				t_y: -2.0,
+			},
+			Operation::ShowText {
+				body: b"Art Barn".to_vec(),
+			},
+			Operation::EndTextObject,
+		];
+
+		let runs = TextInterpreter::interpret(&fonts, &operations);
+		assert_eq!(texts(&runs), vec!["Alumni Hall", "7:00 a.m.", "Art Barn"]);
+		assert!((runs[1].origin().x() - 259.0).abs() < 1e-3);
+		assert!((runs[2].origin().y() - 480.0).abs() < 1e-3);
+		assert!((runs[2].font_size() - 10.0).abs() < 1e-3);
+	}
+}
@@ -0,0 +1,150 @@
+use super::pdf::Rectangle;
+
+/// Where an imported `Part` or `Exception` came from, and how sure the import
+/// was that it read it right.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "Unchecked"))]
+pub struct Provenance {
+	#[cfg_attr(
+		feature = "serde",
+		serde(default, skip_serializing_if = "Option::is_none")
+	)]
+	source: Option<String>,
+	#[cfg_attr(
+		feature = "serde",
+		serde(default, skip_serializing_if = "Option::is_none")
+	)]
+	page: Option<u32>,
+	#[cfg_attr(
+		feature = "serde",
+		serde(default, skip_serializing_if = "Option::is_none")
+	)]
+	bbox: Option<Rectangle>,
+	text: String,
+	confidence: f32,
+}
+
+// Coordinates and confidences are always finite, so equality is reflexive.
+impl Eq for Provenance {}
+
+/// Whether all of `bbox`'s coordinates are finite.
+fn finite(bbox: &Rectangle) -> bool {
+	[bbox.lower_left(), bbox.upper_right()]
+		.iter()
+		.all(|corner| corner.x().is_finite() && corner.y().is_finite())
+}
+
+/// Provenance as it's deserialized, before its numbers are checked.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct Unchecked {
+	#[serde(default)]
+	source: Option<String>,
+	#[serde(default)]
+	page: Option<u32>,
+	#[serde(default)]
+	bbox: Option<Rectangle>,
+	text: String,
+	confidence: f32,
+}
+
+#[cfg(feature = "serde")]
+impl core::convert::TryFrom<Unchecked> for Provenance {
+	type Error = String;
+
+	fn try_from(unchecked: Unchecked) -> Result<Self, Self::Error> {
+		if !(0.0..=1.0).contains(&unchecked.confidence) {
+			return Err(format!(
+				"confidence must be from 0 to 1, not {}",
+				unchecked.confidence
+			));
+		}
+		if unchecked.bbox.as_ref().is_some_and(|bbox| !finite(bbox)) {
+			return Err("bbox must have finite coordinates".to_string());
+		}
+		Ok(Self {
+			source: unchecked.source,
+			page: unchecked.page,
+			bbox: unchecked.bbox,
+			text: unchecked.text,
+			confidence: unchecked.confidence,
+		})
+	}
+}
+
+impl Provenance {
+	/// Provenance for `text`, with full confidence until told otherwise.
+	#[must_use]
+	pub fn new(text: &str) -> Self {
+		Self {
+			source: None,
+			page: None,
+			bbox: None,
+			text: text.to_string(),
+			confidence: 1.0,
+		}
+	}
+
+	#[must_use]
+	pub fn with_source(mut self, source: &str) -> Self {
+		self.source = Some(source.to_string());
+		self
+	}
+
+	#[must_use]
+	pub fn with_page(mut self, page: u32) -> Self {
+		self.page = Some(page);
+		self
+	}
+
+	/// Set where on the page the text was drawn, unless a coordinate isn't
+	/// finite.
+	#[must_use]
+	pub fn with_bbox(mut self, bbox: Rectangle) -> Self {
+		self.bbox = Some(bbox).filter(finite);
+		self
+	}
+
+	/// Set the confidence, which is clamped to between 0 and 1; anything that
+	/// isn't a number counts as 0.
+	#[must_use]
+	pub fn with_confidence(mut self, confidence: f32) -> Self {
+		self.confidence = if confidence.is_nan() {
+			0.0
+		} else {
+			confidence.clamp(0.0, 1.0)
+		};
+		self
+	}
+
+	/// The file the text was read from, if known.
+	#[must_use]
+	pub fn source(&self) -> Option<&str> {
+		self.source.as_deref()
+	}
+
+	/// The page the text was read from, counting from 1, if known.
+	#[must_use]
+	pub fn page(&self) -> Option<u32> {
+		self.page
+	}
+
+	/// Where on the page the text was drawn, if known.
+	#[must_use]
+	pub fn bbox(&self) -> Option<Rectangle> {
+		self.bbox
+	}
+
+	/// The text as it was read, before being interpreted.
+	#[must_use]
+	pub fn text(&self) -> &str {
+		&self.text
+	}
+
+	/// How likely the text was read and interpreted correctly, from 0 to 1.
+	#[must_use]
+	pub fn confidence(&self) -> f32 {
+		self.confidence
+	}
+}
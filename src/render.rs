@@ -0,0 +1,538 @@
+//! Drawing the hours of spaces as a table, with a row for each space and a
+//! pair of `Open` and `Close` columns for each day, like the posters the
+//! schedules are published as.
+
+use core::convert::TryFrom;
+use core::fmt::Write;
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveTime, TimeZone};
+use lopdf::content::{Content, Operation};
+use lopdf::{dictionary, Document, Object, Stream};
+
+use super::locale::twelve_with_minutes;
+use super::specifier::midnight;
+use super::timeline;
+use super::Space;
+
+/// Sizes, in points.
+const MARGIN: f64 = 18.0;
+const NAME_WIDTH: f64 = 140.0;
+const TIME_WIDTH: f64 = 56.0;
+const ROW_HEIGHT: f64 = 16.0;
+const FONT_SIZE: f64 = 8.0;
+const TITLE_SIZE: f64 = 12.0;
+
+/// The hours of a space on one day, as they'll be written.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DayHours {
+	/// Closed all day, with the reason if there is one, e.g. `LOCKED`.
+	Closed(Option<String>),
+
+	/// The times the space opens and closes that day.
+	Open(Vec<(String, String)>),
+}
+
+/// Write `time` the way the posters do, e.g. `7:00 a.m.`
+fn time(time: NaiveTime) -> String {
+	twelve_with_minutes(time, "a.m.", "p.m.")
+}
+
+/// A box of the table, positioned from the top left.
+#[derive(Clone, Debug, PartialEq)]
+struct Block {
+	x: f64,
+	y: f64,
+	width: f64,
+	height: f64,
+	text: String,
+	size: f64,
+	header: bool,
+	border: bool,
+}
+
+/// A table of the hours of `spaces` from `first` until `last`, inclusive.
+#[derive(Debug)]
+pub struct Grid<'spaces, 'schedule, Tz: TimeZone> {
+	title: Option<String>,
+	spaces: &'spaces [Space<'schedule, Tz>],
+	first: NaiveDate,
+	last: NaiveDate,
+	timezone: Tz,
+}
+
+impl<'spaces, 'schedule, Tz: TimeZone> Grid<'spaces, 'schedule, Tz> {
+	/// A table of the hours of `spaces` on each day from `first` until
+	/// `last`, in `timezone`.
+	#[must_use]
+	pub fn new(
+		spaces: &'spaces [Space<'schedule, Tz>],
+		first: NaiveDate,
+		last: NaiveDate,
+		timezone: Tz,
+	) -> Self {
+		Self {
+			title: None,
+			spaces,
+			first,
+			last,
+			timezone,
+		}
+	}
+
+	#[must_use]
+	pub fn title(mut self, title: &str) -> Self {
+		self.title = Some(title.to_string());
+		self
+	}
+
+	/// The days of the table, in order.
+	#[must_use]
+	pub fn days(&self) -> Vec<NaiveDate> {
+		self
+			.first
+			.iter_days()
+			.take_while(|day| *day <= self.last)
+			.collect()
+	}
+
+	/// The hours of each space on each day, a row for each space.
+	///
+	/// A space's hours on a day are the periods it opens that day, so a
+	/// period which runs past midnight belongs to the day it started, and
+	/// one running through whole days opens again on each of them.
+	#[must_use]
+	pub fn hours(&self) -> Vec<Vec<DayHours>> {
+		let days: Vec<NaiveDate> = self.days();
+		let (Some(start), Some(end)) = (
+			midnight(&self.timezone, self.first - Duration::days(1)),
+			midnight(&self.timezone, self.last + Duration::days(2)),
+		) else {
+			return vec![vec![DayHours::Closed(None); days.len()]; self.spaces.len()];
+		};
+
+		self
+			.spaces
+			.iter()
+			.map(|space| {
+				let periods = space.periods(&start, &end);
+				days
+					.iter()
+					.map(|day| {
+						let times: Vec<(String, String)> = timeline::openings(&periods, *day)
+							.iter()
+							.map(|period| {
+								(
+									time(period.start().naive_local().time()),
+									time(period.end().naive_local().time()),
+								)
+							})
+							.collect();
+						if times.is_empty() {
+							// The reason for the closure at noon speaks for the day.
+							let note: Option<String> = periods
+								.iter()
+								.find(|period| {
+									let noon = day.and_hms_opt(12, 0, 0).unwrap_or_default();
+									period.start().naive_local() <= noon && noon < period.end().naive_local()
+								})
+								.and_then(|period| period.note().map(ToString::to_string));
+							DayHours::Closed(note)
+						} else {
+							DayHours::Open(times)
+						}
+					})
+					.collect()
+			})
+			.collect()
+	}
+
+	/// Lay the table out, from the top left, returning its boxes, width and
+	/// height.
+	fn layout(&self) -> (Vec<Block>, f64, f64) {
+		let days: Vec<NaiveDate> = self.days();
+		#[allow(clippy::cast_precision_loss)]
+		let width: f64 = NAME_WIDTH + TIME_WIDTH * 2.0 * days.len() as f64;
+		let mut blocks: Vec<Block> = Vec::new();
+		let mut y: f64 = MARGIN;
+
+		let mut block = |x: f64, y: f64, width: f64, rows: f64, text: &str, header: bool| {
+			blocks.push(Block {
+				x: MARGIN + x,
+				y,
+				width,
+				height: ROW_HEIGHT * rows,
+				text: text.to_string(),
+				size: FONT_SIZE,
+				header,
+				border: true,
+			});
+		};
+
+		let title: Option<Block> = self.title.as_ref().map(|title| Block {
+			x: MARGIN,
+			y,
+			width,
+			height: ROW_HEIGHT * 1.5,
+			text: title.clone(),
+			size: TITLE_SIZE,
+			header: false,
+			border: false,
+		});
+		if title.is_some() {
+			y += ROW_HEIGHT * 1.5;
+		}
+
+		block(0.0, y, NAME_WIDTH, 3.0, "Building", true);
+		for (index, day) in days.iter().enumerate() {
+			#[allow(clippy::cast_precision_loss)]
+			let x: f64 = NAME_WIDTH + TIME_WIDTH * 2.0 * index as f64;
+			block(
+				x,
+				y,
+				TIME_WIDTH * 2.0,
+				1.0,
+				&day.format("%A").to_string(),
+				true,
+			);
+			block(
+				x,
+				y + ROW_HEIGHT,
+				TIME_WIDTH * 2.0,
+				1.0,
+				&format!("{}/{}/{}", day.month(), day.day(), day.format("%y")),
+				true,
+			);
+			block(x, y + ROW_HEIGHT * 2.0, TIME_WIDTH, 1.0, "Open", true);
+			block(
+				x + TIME_WIDTH,
+				y + ROW_HEIGHT * 2.0,
+				TIME_WIDTH,
+				1.0,
+				"Close",
+				true,
+			);
+		}
+		y += ROW_HEIGHT * 3.0;
+
+		for (space, hours) in self.spaces.iter().zip(self.hours()) {
+			block(0.0, y, NAME_WIDTH, 1.0, space.name(), false);
+			for (index, hours) in hours.iter().enumerate() {
+				#[allow(clippy::cast_precision_loss)]
+				let x: f64 = NAME_WIDTH + TIME_WIDTH * 2.0 * index as f64;
+				match hours {
+					DayHours::Closed(note) => block(
+						x,
+						y,
+						TIME_WIDTH * 2.0,
+						1.0,
+						note.as_deref().unwrap_or("CLOSED"),
+						false,
+					),
+					DayHours::Open(times) => {
+						let join = |side: fn(&(String, String)) -> &str| {
+							times.iter().map(side).collect::<Vec<&str>>().join(", ")
+						};
+						block(x, y, TIME_WIDTH, 1.0, &join(|times| &times.0), false);
+						block(
+							x + TIME_WIDTH,
+							y,
+							TIME_WIDTH,
+							1.0,
+							&join(|times| &times.1),
+							false,
+						);
+					}
+				}
+			}
+			y += ROW_HEIGHT;
+		}
+
+		blocks.extend(title);
+		(blocks, width + MARGIN * 2.0, y + MARGIN)
+	}
+
+	/// The table as an SVG document.
+	#[must_use]
+	pub fn to_svg(&self) -> String {
+		let (blocks, width, height) = self.layout();
+		let mut svg: String = String::new();
+
+		// Writing to a `String` can't fail.
+		let _ = writeln!(
+			svg,
+			r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}" font-family="Helvetica, Arial, sans-serif">"#
+		);
+		if let Some(title) = &self.title {
+			let _ = writeln!(svg, "<title>{}</title>", escape(title));
+		}
+		let _ = writeln!(
+			svg,
+			r#"<rect width="{width}" height="{height}" fill="white"/>"#
+		);
+		for block in &blocks {
+			if block.border {
+				let fill: &str = if block.header { "#d9d9d9" } else { "white" };
+				let _ = writeln!(
+					svg,
+					r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{fill}" stroke="black" stroke-width="0.5"/>"#,
+					block.x, block.y, block.width, block.height
+				);
+			}
+			if !block.text.is_empty() {
+				let weight: &str = if block.header || !block.border {
+					r#" font-weight="bold""#
+				} else {
+					""
+				};
+				let _ = writeln!(
+					svg,
+					r#"<text x="{}" y="{}" font-size="{}"{weight} text-anchor="middle" dominant-baseline="central">{}</text>"#,
+					block.x + block.width / 2.0,
+					block.y + block.height / 2.0,
+					block.size,
+					escape(&block.text)
+				);
+			}
+		}
+		svg.push_str("</svg>\n");
+		svg
+	}
+
+	/// The table as a one-page PDF, in Helvetica.
+	#[must_use]
+	pub fn to_pdf(&self) -> Document {
+		let (blocks, width, height) = self.layout();
+		let mut operations: Vec<Operation> = vec![Operation::new("w", vec![0.5.into()])];
+
+		for block in &blocks {
+			// PDF measures from the bottom left.
+			let bottom: f64 = height - block.y - block.height;
+			if block.border {
+				let gray: f64 = if block.header { 0.85 } else { 1.0 };
+				operations.push(Operation::new("g", vec![gray.into()]));
+				operations.push(Operation::new(
+					"re",
+					vec![
+						block.x.into(),
+						bottom.into(),
+						block.width.into(),
+						block.height.into(),
+					],
+				));
+				operations.push(Operation::new("B", vec![]));
+				operations.push(Operation::new("g", vec![0.into()]));
+			}
+			if !block.text.is_empty() {
+				let font: &str = if block.header || !block.border {
+					"F2"
+				} else {
+					"F1"
+				};
+				operations.extend(vec![
+					Operation::new("BT", vec![]),
+					Operation::new("Tf", vec![font.into(), block.size.into()]),
+					Operation::new(
+						"Td",
+						vec![
+							(block.x + (block.width - text_width(&block.text, block.size)) / 2.0).into(),
+							(bottom + (block.height - block.size * 0.7) / 2.0).into(),
+						],
+					),
+					Operation::new("Tj", vec![Object::string_literal(latin1(&block.text))]),
+					Operation::new("ET", vec![]),
+				]);
+			}
+		}
+
+		let mut document: Document = Document::with_version("1.5");
+		let pages_id = document.new_object_id();
+		let font = |document: &mut Document, name: &str| {
+			document.add_object(dictionary! {
+				"Type" => "Font",
+				"Subtype" => "Type1",
+				"BaseFont" => name,
+				"Encoding" => "WinAnsiEncoding",
+			})
+		};
+		let regular = font(&mut document, "Helvetica");
+		let bold = font(&mut document, "Helvetica-Bold");
+		let content = Content { operations };
+		let content_id = document.add_object(Stream::new(
+			dictionary! {},
+			content.encode().unwrap_or_default(),
+		));
+		let page = document.add_object(dictionary! {
+			"Type" => "Page",
+			"Parent" => pages_id,
+			"Contents" => content_id,
+			"Resources" => dictionary! {
+				"Font" => dictionary! { "F1" => regular, "F2" => bold },
+			},
+			"MediaBox" => vec![0.into(), 0.into(), width.into(), height.into()],
+		});
+		document.objects.insert(
+			pages_id,
+			Object::Dictionary(dictionary! {
+				"Type" => "Pages",
+				"Kids" => vec![page.into()],
+				"Count" => 1,
+			}),
+		);
+		let catalog_id = document.add_object(dictionary! {
+			"Type" => "Catalog",
+			"Pages" => pages_id,
+		});
+		document.trailer.set("Root", catalog_id);
+		if let Some(title) = &self.title {
+			let info_id = document.add_object(dictionary! {
+				"Title" => Object::string_literal(latin1(title)),
+			});
+			document.trailer.set("Info", info_id);
+		}
+		document
+	}
+}
+
+/// Escape text for XML, whether SVG or HTML.
+pub(crate) fn escape(text: &str) -> String {
+	text
+		.replace('&', "&amp;")
+		.replace('<', "&lt;")
+		.replace('>', "&gt;")
+		.replace('"', "&quot;")
+}
+
+/// Text in the Latin-1 subset of `WinAnsiEncoding`, with `?` for anything
+/// else.
+fn latin1(text: &str) -> Vec<u8> {
+	text
+		.chars()
+		.map(|c| u8::try_from(u32::from(c)).unwrap_or(b'?'))
+		.collect()
+}
+
+/// Roughly how wide `text` is in Helvetica at `size`, for centring it.
+fn text_width(text: &str, size: f64) -> f64 {
+	let em: f64 = text
+		.chars()
+		.map(|c| match c {
+			' ' | '.' | ',' | ':' | ';' | 'i' | 'j' | 'l' | '/' | 'f' | 't' | 'I' => 0.278,
+			'm' | 'M' | 'W' => 0.833,
+			'w' => 0.722,
+			c if c.is_ascii_uppercase() => 0.667,
+			_ => 0.556,
+		})
+		.sum();
+	em * size
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{DayHours, Grid};
+	use crate::{Exception, Part, Reason, Schedule, Space, Specifier, Status};
+	use chrono::{DateTime, FixedOffset, NaiveDate};
+
+	fn space() -> Space<'static, FixedOffset> {
+		let weekly = |day: &str, time: &str| Specifier::Weekly {
+			day: day.to_string(),
+			time: time.to_string(),
+		};
+		let exact = |time: &str| Specifier::Exact(DateTime::parse_from_rfc3339(time).unwrap());
+
+		let mut locked = Exception::new()
+			.effective(exact("2019-10-12T00:00:00-05:00"))
+			.expires(exact("2019-10-13T00:00:00-05:00"));
+		*locked.effect_mut() = Some(Status::Closed(Reason::Exception(Some(
+			"LOCKED".to_string(),
+		))));
+
+		let schedule =
+			["Fri", "Sat", "Sun"]
+				.iter()
+				.fold(Schedule::new().exception(locked), |schedule, day| {
+					schedule.part(
+						Part::new()
+							.open(weekly(day, "07:00"))
+							.close(weekly(day, "21:30")),
+					)
+				});
+
+		Space::new("Boe Chapel & Annex").schedule(schedule)
+	}
+
+	#[test]
+	fn hours_svg_and_pdf() {
+		let spaces = vec![space()];
+		let grid = Grid::new(
+			&spaces,
+			NaiveDate::from_ymd_opt(2019, 10, 11).unwrap(),
+			NaiveDate::from_ymd_opt(2019, 10, 13).unwrap(),
+			FixedOffset::west_opt(5 * 3600).unwrap(),
+		)
+		.title("2019 Fall Break Building Schedule");
+
+		let open = DayHours::Open(vec![("7:00 a.m.".to_string(), "9:30 p.m.".to_string())]);
+		assert_eq!(
+			grid.hours(),
+			vec![vec![
+				open.clone(),
+				DayHours::Closed(Some("LOCKED".to_string())),
+				open
+			]]
+		);
+
+		let svg = grid.to_svg();
+		assert!(svg.starts_with("<svg "));
+		assert!(svg.contains(">Boe Chapel &amp; Annex</text>"));
+		assert!(svg.contains(">Saturday</text>"));
+		assert!(svg.contains(">10/12/19</text>"));
+		assert!(svg.contains(">LOCKED</text>"));
+		assert!(svg.trim_end().ends_with("</svg>"));
+
+		let mut bytes: Vec<u8> = Vec::new();
+		grid.to_pdf().save_to(&mut bytes).unwrap();
+		let pdf = crate::pdf::Pdf::from_bytes(&bytes).unwrap();
+		let text: Vec<String> = pdf
+			.page(1)
+			.unwrap()
+			.text()
+			.unwrap()
+			.iter()
+			.map(|run| run.text().trim().to_string())
+			.collect();
+		assert!(text.contains(&"Boe Chapel & Annex".to_string()));
+		assert!(text.contains(&"9:30 p.m.".to_string()));
+	}
+
+	#[test]
+	fn hours_across_midnight() {
+		let daily = |open: &str, close: &str| {
+			let part = Part::new()
+				.open(Specifier::Daily {
+					time: open.to_string(),
+				})
+				.close(Specifier::Daily {
+					time: close.to_string(),
+				});
+			Space::new("Lab").schedule(Schedule::new().part(part))
+		};
+		let spaces = vec![daily("00:00", "00:00"), daily("22:00", "02:00")];
+		let grid = Grid::new(
+			&spaces,
+			NaiveDate::from_ymd_opt(2019, 10, 11).unwrap(),
+			NaiveDate::from_ymd_opt(2019, 10, 13).unwrap(),
+			FixedOffset::west_opt(5 * 3600).unwrap(),
+		);
+
+		let hours =
+			|open: &str, close: &str| DayHours::Open(vec![(open.to_string(), close.to_string())]);
+		assert_eq!(
+			grid.hours(),
+			vec![
+				vec![hours("12:00 a.m.", "12:00 a.m."); 3],
+				// The night before the first day isn't part of it.
+				vec![hours("10:00 p.m.", "2:00 a.m."); 3],
+			]
+		);
+	}
+}
@@ -3,14 +3,35 @@ use chrono::{DateTime, TimeZone};
 
 #[allow(dead_code)]
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+	feature = "serde",
+	serde(bound(serialize = "", deserialize = "DateTime<Tz>: serde::Deserialize<'de>"))
+)]
 pub struct Schedule<'schedule, Tz: TimeZone> {
+	#[cfg_attr(
+		feature = "serde",
+		serde(default, skip_serializing_if = "Option::is_none")
+	)]
 	effective: Option<DateTime<Tz>>,
+	#[cfg_attr(
+		feature = "serde",
+		serde(default, skip_serializing_if = "Option::is_none")
+	)]
 	expires: Option<DateTime<Tz>>,
+	#[cfg_attr(
+		feature = "serde",
+		serde(default, skip_serializing_if = "Vec::is_empty")
+	)]
 	parts: Vec<Part<Tz>>,
+	#[cfg_attr(
+		feature = "serde",
+		serde(default, skip_serializing_if = "Vec::is_empty")
+	)]
 	exceptions: Vec<Exception<'schedule, Tz>>,
 }
 
-impl<'schedule, Tz: TimeZone> Default for Schedule<'schedule, Tz> {
+impl<Tz: TimeZone> Default for Schedule<'_, Tz> {
 	fn default() -> Self {
 		Self {
 			effective: None,
@@ -58,7 +79,7 @@ impl<'schedule, Tz: TimeZone> Schedule<'schedule, Tz> {
 		self
 	}
 
-	pub fn exceptions(&self) -> &Vec<Exception<Tz>> {
+	pub fn exceptions(&self) -> &Vec<Exception<'schedule, Tz>> {
 		&self.exceptions
 	}
 
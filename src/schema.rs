@@ -0,0 +1,259 @@
+//! A JSON Schema for spaces as the `serde` feature writes them, so editors
+//! can check and complete hours files by hand.  A copy is kept in
+//! `schema/space.schema.json`.
+
+use chrono::Weekday;
+
+use super::json::Value;
+use super::specifier::day_name;
+
+fn reference(definition: &str) -> Value {
+	Value::object(vec![("$ref", format!("#/definitions/{definition}").into())])
+}
+
+/// `members`, and a description unless it's empty.
+fn described(mut members: Vec<(&str, Value)>, description: &str) -> Value {
+	if !description.is_empty() {
+		members.insert(1, ("description", description.into()));
+	}
+	Value::object(members)
+}
+
+fn typed(kind: &str, description: &str) -> Value {
+	described(vec![("type", kind.into())], description)
+}
+
+fn array(items: Value, description: &str) -> Value {
+	described(
+		vec![("type", "array".into()), ("items", items)],
+		description,
+	)
+}
+
+/// An object with exactly `properties`, of which `required` must be given.
+fn object(description: &str, properties: Vec<(&str, Value)>, required: &[&str]) -> Value {
+	let mut members: Vec<(&str, Value)> = vec![
+		("type", "object".into()),
+		("properties", Value::object(properties)),
+	];
+	if !required.is_empty() {
+		members.push(("required", required.to_vec().into()));
+	}
+	members.push(("additionalProperties", false.into()));
+	described(members, description)
+}
+
+fn date_time(description: &str) -> Value {
+	described(
+		vec![("type", "string".into()), ("format", "date-time".into())],
+		description,
+	)
+}
+
+fn specifier() -> Value {
+	let weekly = object(
+		"The same day and time every week.",
+		vec![(
+			"weekly",
+			object(
+				"",
+				vec![("day", reference("day")), ("time", reference("time"))],
+				&["day", "time"],
+			),
+		)],
+		&["weekly"],
+	);
+	let daily = object(
+		"The same time every day.",
+		vec![(
+			"daily",
+			object("", vec![("time", reference("time"))], &["time"]),
+		)],
+		&["daily"],
+	);
+	let exact = object(
+		"One moment.",
+		vec![("exact", date_time("RFC 3339, with an offset."))],
+		&["exact"],
+	);
+
+	Value::object(vec![
+		("description", "When something happens.".into()),
+		("oneOf", Value::Array(vec![weekly, daily, exact])),
+	])
+}
+
+fn provenance() -> Value {
+	let coordinates = Value::object(vec![
+		("type", "array".into()),
+		("items", typed("number", "")),
+		("minItems", 2_u32.into()),
+		("maxItems", 2_u32.into()),
+	]);
+	let rectangle = object(
+		"Where on the page, in PDF user space.",
+		vec![
+			("lower_left", coordinates.clone()),
+			("upper_right", coordinates),
+		],
+		&["lower_left", "upper_right"],
+	);
+	let confidence = Value::object(vec![
+		("type", "number".into()),
+		("minimum", 0_u32.into()),
+		("maximum", 1_u32.into()),
+		(
+			"description",
+			"How sure the import was that it read the text right.".into(),
+		),
+	]);
+
+	object(
+		"Where an imported part or exception came from.",
+		vec![
+			("source", typed("string", "The file it was read from.")),
+			("page", typed("integer", "The page, counting from 1.")),
+			("bbox", rectangle),
+			("text", typed("string", "The text it was read from.")),
+			("confidence", confidence),
+		],
+		&["text", "confidence"],
+	)
+}
+
+/// The names a weekly specifier's day may have when it's deserialized.
+fn day() -> Value {
+	let week: Vec<Weekday> = (0..7)
+		.scan(Weekday::Mon, |day, _| {
+			let today = *day;
+			*day = day.succ();
+			Some(today)
+		})
+		.collect();
+	let days: Vec<String> = week
+		.iter()
+		.map(|day| day_name(*day).to_string())
+		.chain(week.iter().map(ToString::to_string))
+		.collect();
+	described(vec![("enum", days.into())], "A day of the week.")
+}
+
+fn time() -> Value {
+	described(
+		vec![
+			("type", "string".into()),
+			(
+				"pattern",
+				"^([01]?[0-9]|2[0-3]):[0-5][0-9](:[0-5][0-9])?$".into(),
+			),
+		],
+		"A time of day, e.g. 07:00 or 17:30:00.",
+	)
+}
+
+fn definitions() -> Vec<(&'static str, Value)> {
+	let effect = Value::object(vec![
+		("enum", vec!["open", "closed"].into()),
+		(
+			"description",
+			"Whether the space is open or closed while the exception applies.".into(),
+		),
+	]);
+
+	vec![
+		(
+			"space",
+			object(
+				"A place with hours.",
+				vec![
+					("$schema", typed("string", "The schema the file follows.")),
+					("name", typed("string", "")),
+					("schedules", array(reference("schedule"), "")),
+				],
+				&["name"],
+			),
+		),
+		(
+			"schedule",
+			object(
+				"Regular hours, and exceptions to them.",
+				vec![
+					("effective", date_time("When the schedule starts.")),
+					("expires", date_time("When the schedule ends.")),
+					(
+						"parts",
+						array(reference("part"), "When the space is regularly open."),
+					),
+					(
+						"exceptions",
+						array(
+							reference("exception"),
+							"Changes to the regular hours; earlier ones win.",
+						),
+					),
+				],
+				&[],
+			),
+		),
+		(
+			"part",
+			object(
+				"A time the space opens and the time it next closes.",
+				vec![
+					("open", reference("specifier")),
+					("close", reference("specifier")),
+					("notes", array(typed("string", ""), "")),
+					("provenance", reference("provenance")),
+				],
+				&[],
+			),
+		),
+		(
+			"exception",
+			object(
+				"A time the space is open or closed regardless of its parts.",
+				vec![
+					("effect", effect),
+					("note", typed("string", "Why, e.g. \"Thanksgiving\".")),
+					("effective", reference("specifier")),
+					("expires", reference("specifier")),
+					("provenance", reference("provenance")),
+				],
+				&[],
+			),
+		),
+		("specifier", specifier()),
+		("day", day()),
+		("time", time()),
+		("provenance", provenance()),
+	]
+}
+
+/// A JSON Schema (draft 7) for a space, as the `serde` feature reads and
+/// writes it.  Files may also say which schema they follow with `$schema`.
+#[must_use]
+pub fn json_schema() -> String {
+	let schema = Value::object(vec![
+		("$schema", "http://json-schema.org/draft-07/schema#".into()),
+		("title", "Space".into()),
+		("$ref", "#/definitions/space".into()),
+		("definitions", Value::object(definitions())),
+	]);
+
+	let mut schema: String = schema.pretty();
+	schema.push('\n');
+	schema
+}
+
+#[cfg(test)]
+mod tests {
+	use super::json_schema;
+
+	#[test]
+	fn schema_file_is_current() {
+		assert!(
+			json_schema() == include_str!("../schema/space.schema.json"),
+			"schema/space.schema.json is out of date; write `json_schema()` to it"
+		);
+	}
+}
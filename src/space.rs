@@ -1,14 +1,20 @@
-use super::{Exception, Part, Reason, Schedule, Status, StatusChange};
+use super::{timeline, Exception, Part, Reason, Schedule, Status, StatusChange};
 use chrono::{DateTime, TimeZone};
 
 #[allow(dead_code)]
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+	feature = "serde",
+	serde(bound(serialize = "", deserialize = "DateTime<Tz>: serde::Deserialize<'de>"))
+)]
 pub struct Space<'schedule, Tz: TimeZone> {
 	name: String,
+	#[cfg_attr(feature = "serde", serde(default))]
 	schedules: Vec<Schedule<'schedule, Tz>>,
 }
 
-impl<'schedule, Tz: TimeZone> Default for Space<'schedule, Tz> {
+impl<Tz: TimeZone> Default for Space<'_, Tz> {
 	fn default() -> Self {
 		Self {
 			name: String::new(),
@@ -18,6 +24,28 @@ impl<'schedule, Tz: TimeZone> Default for Space<'schedule, Tz> {
 }
 
 impl<'schedule, Tz: TimeZone> Space<'schedule, Tz> {
+	#[must_use]
+	pub fn new(name: &str) -> Self {
+		Self {
+			name: name.to_string(),
+			..Self::default()
+		}
+	}
+
+	#[must_use]
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	#[must_use]
+	pub fn schedules(&self) -> &Vec<Schedule<'schedule, Tz>> {
+		&self.schedules
+	}
+
+	pub fn schedules_mut(&mut self) -> &mut Vec<Schedule<'schedule, Tz>> {
+		&mut self.schedules
+	}
+
 	#[must_use]
 	pub fn schedule(mut self, schedule: Schedule<'schedule, Tz>) -> Self {
 		self.schedules.push(schedule);
@@ -29,14 +57,6 @@ impl<'schedule, Tz: TimeZone> Space<'schedule, Tz>
 where
 	DateTime<Tz>: core::convert::From<DateTime<chrono::offset::Local>>,
 {
-	#[must_use]
-	pub fn new(name: &str) -> Space<Tz> {
-		Space {
-			name: name.to_string(),
-			..Space::default()
-		}
-	}
-
 	/// Compute the status of the space at the current time
 	#[must_use]
 	pub fn status(&'schedule self) -> Status<'schedule, Tz> {
@@ -45,73 +65,64 @@ where
 		self.status_at(&DateTime::from(now))
 	}
 
-	/// Compute the status of the space at the given time
-	// TODO Make actually functional
+	/// Compute the status of the space at the given time.
+	///
+	/// This agrees with `periods`: exceptions override parts, and earlier
+	/// exceptions take precedence over later ones.
 	pub fn status_at(&'schedule self, time: &DateTime<Tz>) -> Status<'schedule, Tz> {
 		let active_schedules: Vec<&Schedule<'schedule, Tz>> = self
 			.schedules
 			.iter()
-			.filter(
-				|schedule| match (schedule.effective(), schedule.expires()) {
-					(Some(start), None) => start < time,
-					(Some(start), Some(end)) => start < time && time < end,
-					(None, Some(end)) => time < end,
-					(None, None) => true,
-				},
-			)
+			.filter(|schedule| timeline::in_window(schedule, time))
 			.collect();
 
 		// TODO consider selecting the "most specific" schedule?
 
-		let parts: Vec<&Part<Tz>> = active_schedules
-			.iter()
-			.flat_map(|schedule| schedule.parts())
-			.collect();
-
-		let exceptions: Vec<&Exception<Tz>> = active_schedules
+		let exception: Option<&Exception<Tz>> = active_schedules
 			.iter()
 			.flat_map(|schedule| schedule.exceptions())
-			.collect();
+			.filter(|exception| exception.effect().is_some())
+			.find(|exception| exception.applies_at(time));
 
-		let current_parts: Vec<&Part<Tz>> = parts
-			.iter()
-			.copied()
-			.filter(|p| p.applies_at(time))
-			.collect();
+		if let Some(effect) = exception.and_then(|exception| exception.effect().as_ref()) {
+			return effect.clone();
+		}
 
-		let current_exceptions: Vec<&Exception<Tz>> = exceptions
+		let part: Option<&Part<Tz>> = active_schedules
 			.iter()
-			.copied()
-			.filter(|e| e.applies_at(time))
-			.collect();
-
-		if !current_exceptions.is_empty() {
-			let exception = current_exceptions[0];
-
-			let effect = exception.effect();
-
-			if let Some(effect) = effect {
-				return (*effect).clone();
-			}
-		}
+			.flat_map(|schedule| schedule.parts())
+			.find(|part| part.applies_at(time));
 
-		if current_parts.is_empty() {
-			Status::Closed(Reason::Part(None))
-		} else {
-			let part = current_parts[0];
-			Status::Open(Reason::Part(Some(part)))
+		match part {
+			Some(part) => Status::Open(Reason::Part(Some(part))),
+			None => Status::Closed(Reason::Part(None)),
 		}
 	}
 
 	#[must_use]
-	pub fn next_status_change(&self) -> Option<StatusChange<Tz>> {
+	pub fn next_status_change(&self) -> Option<StatusChange<'_, Tz>> {
 		use chrono::offset::Local;
 		let now: DateTime<Local> = Local::now();
 		self.next_status_change_at(&DateTime::from(now))
 	}
 
-	// TODO Make actually functional
-	pub fn next_status_change_at(&self, _time: &DateTime<Tz>) -> Option<StatusChange<Tz>> {
-		None
+	/// The next time after `time` that the space opens or closes, looking
+	/// up to a year ahead.  A change made by an exception has its note as
+	/// the reason.
+	pub fn next_status_change_at(&self, time: &DateTime<Tz>) -> Option<StatusChange<'_, Tz>> {
+		let end: DateTime<Tz> = time.clone() + chrono::Duration::days(366);
+		let periods = self.periods(time, &end);
+		let open: bool = periods.first()?.is_open();
+		let period = periods.iter().find(|period| period.is_open() != open)?;
+
+		let reason: Reason<'_, Tz> = match period.note() {
+			Some(note) => Reason::Exception(Some(note.to_string())),
+			None => Reason::Part(None),
+		};
+		Some(if period.is_open() {
+			StatusChange::Opening(period.start().clone(), reason)
+		} else {
+			StatusChange::Closing(period.start().clone(), reason)
+		})
 	}
 }
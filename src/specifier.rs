@@ -15,39 +15,102 @@ pub enum Specifier<Tz: TimeZone> {
 	Exact(DateTime<Tz>),
 }
 
+/// A weekly or daily specifier's time, e.g. `07:00` or `07:00:30`.
+pub(crate) fn time_of_day(text: &str) -> Option<NaiveTime> {
+	NaiveTime::parse_from_str(text, "%H:%M")
+		.or_else(|_| NaiveTime::parse_from_str(text, "%H:%M:%S"))
+		.ok()
+}
+
+/// A time as specifiers write it, with seconds only if it has any, e.g.
+/// `07:00` or `07:00:30`.
+pub(crate) fn write_time(time: NaiveTime) -> String {
+	if time.second() == 0 {
+		time.format("%H:%M").to_string()
+	} else {
+		time.format("%H:%M:%S").to_string()
+	}
+}
+
+/// The start of `day` in `timezone`, the earlier if it happens twice.
+pub(crate) fn midnight<Tz: TimeZone>(timezone: &Tz, day: NaiveDate) -> Option<DateTime<Tz>> {
+	timezone
+		.from_local_datetime(&day.and_time(NaiveTime::MIN))
+		.earliest()
+}
+
+/// A day's full name, e.g. `Monday`.
+pub(crate) fn day_name(day: Weekday) -> &'static str {
+	match day {
+		Weekday::Mon => "Monday",
+		Weekday::Tue => "Tuesday",
+		Weekday::Wed => "Wednesday",
+		Weekday::Thu => "Thursday",
+		Weekday::Fri => "Friday",
+		Weekday::Sat => "Saturday",
+		Weekday::Sun => "Sunday",
+	}
+}
+
+/// A weekly specifier's day as a file may give it: its full name or its
+/// first three letters, capitalized, e.g. `Monday` or `Mon`.
+#[cfg(feature = "serde")]
+fn weekday(text: &str) -> Option<Weekday> {
+	let day: Weekday = text.parse().ok()?;
+	(text == day_name(day) || text == day.to_string()).then_some(day)
+}
+
+/// Localize a date and time in the time zone of `basis`.
+///
+/// A time which happens twice as the clocks go back is the earlier one, and
+/// one skipped as they go forward is read with the offset from before the
+/// change, so 02:30 on the night clocks jump from 02:00 to 03:00 is 03:30.
+fn at<Tz: TimeZone>(basis: &DateTime<Tz>, date: NaiveDate, time: NaiveTime) -> DateTime<Tz> {
+	let timezone: Tz = basis.timezone();
+	let local: NaiveDateTime = date.and_time(time);
+	timezone
+		.from_local_datetime(&local)
+		.earliest()
+		.unwrap_or_else(|| {
+			let offset: FixedOffset = timezone
+				.offset_from_utc_datetime(&(local - chrono::Duration::days(1)))
+				.fix();
+			timezone.from_utc_datetime(&(local - offset))
+		})
+}
+
 #[derive(Debug)]
 pub struct Instances<'iteration, Tz: TimeZone> {
 	specifier: &'iteration Specifier<Tz>,
 	basis: DateTime<Tz>,
+	/// Whether an exact specifier's only instance has been given.
+	done: bool,
 }
 
-impl<'iteration, Tz: TimeZone> Iterator for Instances<'iteration, Tz> {
+impl<Tz: TimeZone> Iterator for Instances<'_, Tz> {
 	type Item = chrono::DateTime<Tz>;
 
 	fn next(&mut self) -> Option<Self::Item> {
 		match self.specifier {
-			Specifier::Exact(dt) if dt != &self.basis => {
-				let next = dt.clone();
+			Specifier::Exact(_) if self.done => None,
+			Specifier::Exact(dt) => {
+				self.done = true;
 				self.basis = dt.clone();
-				Some(next)
+				Some(dt.clone())
 			}
-			Specifier::Exact(dt) if dt == &self.basis => None,
-			Specifier::Exact(_) => panic!(),
 			Specifier::Weekly { day, time } => {
 				let specifier_day: chrono::Weekday = day.parse().expect("invalid day specifier");
-				let specifier_time: chrono::NaiveTime = NaiveTime::parse_from_str(time, "%H:%M")
-					.or_else(|_| NaiveTime::parse_from_str(time, "%H:%M:%S"))
-					.expect("invalid time specifier");
+				let specifier_time: chrono::NaiveTime = time_of_day(time).expect("invalid time specifier");
 
 				// If the basis weekday is the same as the specifier, then return today's instance
 				if self.basis.weekday() == specifier_day {
-					let instance = self.basis.date().and_time(specifier_time).unwrap();
+					let instance = at(&self.basis, self.basis.date_naive(), specifier_time);
 
 					self.basis = self.basis.clone() + chrono::Duration::weeks(1);
 
 					Some(instance)
 				} else {
-					let basis_weekday: i64 = self.basis.date().weekday().num_days_from_monday().into();
+					let basis_weekday: i64 = self.basis.weekday().num_days_from_monday().into();
 					let next_instance_weekday: i64 = specifier_day.num_days_from_monday().into();
 					let mut difference = next_instance_weekday - basis_weekday;
 
@@ -58,9 +121,11 @@ impl<'iteration, Tz: TimeZone> Iterator for Instances<'iteration, Tz> {
 
 					let offset = chrono::Duration::days(difference);
 
-					let instance = (self.basis.date() + offset)
-						.and_time(specifier_time)
-						.unwrap();
+					let instance = at(
+						&self.basis,
+						self.basis.date_naive() + offset,
+						specifier_time,
+					);
 
 					self.basis = self.basis.clone() + chrono::Duration::weeks(1);
 
@@ -68,11 +133,9 @@ impl<'iteration, Tz: TimeZone> Iterator for Instances<'iteration, Tz> {
 				}
 			}
 			Specifier::Daily { time } => {
-				let specifier_time: chrono::NaiveTime = NaiveTime::parse_from_str(time, "%H:%M")
-					.or_else(|_| NaiveTime::parse_from_str(time, "%H:%M:%S"))
-					.expect("invalid time specifier");
+				let specifier_time: chrono::NaiveTime = time_of_day(time).expect("invalid time specifier");
 
-				let instance = self.basis.date().and_time(specifier_time).unwrap();
+				let instance = at(&self.basis, self.basis.date_naive(), specifier_time);
 
 				self.basis = self.basis.clone() + chrono::Duration::days(1);
 
@@ -83,17 +146,132 @@ impl<'iteration, Tz: TimeZone> Iterator for Instances<'iteration, Tz> {
 }
 
 impl<Tz: TimeZone> Specifier<Tz> {
-	pub fn instances(&self, basis: &DateTime<Tz>) -> Instances<Tz> {
+	pub fn instances(&self, basis: &DateTime<Tz>) -> Instances<'_, Tz> {
 		let specifier = self;
 		let basis = basis.clone();
-		Instances { specifier, basis }
+		Instances {
+			specifier,
+			basis,
+			done: false,
+		}
 	}
 
+	#[allow(dead_code)]
 	fn next(&self, n: usize, basis: &DateTime<Tz>) -> Vec<DateTime<Tz>> {
 		self.instances(basis).take(n).collect()
 	}
 }
 
+/// The editable form of a specifier: a table with exactly one of `weekly`,
+/// `daily` or `exact`, the same in every format (YAML would otherwise use tags).
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(
+	deny_unknown_fields,
+	bound(serialize = "", deserialize = "DateTime<Tz>: serde::Deserialize<'de>")
+)]
+struct Editable<Tz: TimeZone> {
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	weekly: Option<Weekly>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	daily: Option<Daily>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	exact: Option<DateTime<Tz>>,
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Weekly {
+	day: String,
+	time: String,
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Daily {
+	time: String,
+}
+
+#[cfg(feature = "serde")]
+impl<Tz: TimeZone> serde::Serialize for Specifier<Tz> {
+	fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		let mut editable = Editable {
+			weekly: None,
+			daily: None,
+			exact: None,
+		};
+		match self {
+			Specifier::Weekly { day, time } => {
+				editable.weekly = Some(Weekly {
+					day: day.clone(),
+					time: time.clone(),
+				});
+			}
+			Specifier::Daily { time } => editable.daily = Some(Daily { time: time.clone() }),
+			Specifier::Exact(dt) => editable.exact = Some(dt.clone()),
+		}
+		editable.serialize(serializer)
+	}
+}
+
+/// Check that a weekly or daily specifier's `day` and `time` can be read, so
+/// that a bad file is an error when it's read rather than a panic later.
+#[cfg(feature = "serde")]
+fn check<E: serde::de::Error>(day: Option<&str>, time: &str) -> Result<(), E> {
+	if let Some(day) = day {
+		if weekday(day).is_none() {
+			return Err(E::custom(format!(
+				"`{day}` isn't a day of the week, e.g. Monday or Mon"
+			)));
+		}
+	}
+	if time_of_day(time).is_none() {
+		return Err(E::custom(format!(
+			"`{time}` isn't a time of day, e.g. 07:00"
+		)));
+	}
+	Ok(())
+}
+
+#[cfg(feature = "serde")]
+impl<'de, Tz: TimeZone> serde::Deserialize<'de> for Specifier<Tz>
+where
+	DateTime<Tz>: serde::Deserialize<'de>,
+{
+	fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		use serde::de::Error;
+
+		match Editable::<Tz>::deserialize(deserializer)? {
+			Editable {
+				weekly: Some(Weekly { day, time }),
+				daily: None,
+				exact: None,
+			} => {
+				check::<D::Error>(Some(&day), &time)?;
+				Ok(Specifier::Weekly { day, time })
+			}
+			Editable {
+				weekly: None,
+				daily: Some(Daily { time }),
+				exact: None,
+			} => {
+				check::<D::Error>(None, &time)?;
+				Ok(Specifier::Daily { time })
+			}
+			Editable {
+				weekly: None,
+				daily: None,
+				exact: Some(dt),
+			} => Ok(Specifier::Exact(dt)),
+			_ => Err(D::Error::custom(
+				"expected exactly one of `weekly`, `daily` or `exact`",
+			)),
+		}
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	mod specifier {
@@ -109,6 +287,10 @@ mod tests {
 					.collect::<Vec<DateTime<chrono::Local>>>(),
 				vec![now]
 			);
+			assert_eq!(
+				s.instances(&now).collect::<Vec<DateTime<chrono::Local>>>(),
+				vec![now]
+			);
 		}
 
 		#[test]
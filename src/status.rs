@@ -1,12 +1,18 @@
 use chrono::{DateTime, TimeZone};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case", bound = ""))]
 pub enum Reason<'schedule, Tz: TimeZone> {
 	Exception(Option<String>),
+	/// Parts are written out, but can't be read back as a reference.
+	#[cfg_attr(feature = "serde", serde(skip_deserializing))]
 	Part(Option<&'schedule super::Part<Tz>>),
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case", bound = ""))]
 pub enum Status<'schedule, Tz: TimeZone> {
 	Open(Reason<'schedule, Tz>),
 	Closed(Reason<'schedule, Tz>),
@@ -0,0 +1,459 @@
+//! Hours as tables for people to read, in Markdown for wikis and as a
+//! standalone HTML page for signage.
+//!
+//! Each space gets a table with a row for each run of consecutive days with
+//! the same hours, e.g. `Mon–Fri | 7:00 AM – 11:00 PM`.  Days whose hours
+//! differ from the regular ones, because of a dated exception, are set in
+//! bold with the exception's note, and parts' notes become footnotes.
+
+use core::fmt::Write;
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, TimeZone};
+
+use super::listing::recurring;
+use super::locale::twelve_with_minutes;
+use super::render::escape;
+use super::specifier::midnight;
+use super::timeline;
+use super::{Period, Space};
+
+/// The hours of a space on one day, or a run of days.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Cell {
+	/// When the space opens and closes.
+	hours: Vec<(NaiveTime, NaiveTime)>,
+
+	/// Whether the hours differ from the regular ones, and why.
+	exception: Option<Vec<String>>,
+
+	/// The notes of the parts the space opens for.
+	notes: Vec<String>,
+}
+
+/// A run of days from `first` until `last`, inclusive.
+#[derive(Clone, Debug)]
+struct Row {
+	first: NaiveDate,
+	last: NaiveDate,
+	cell: Cell,
+}
+
+/// Write `time` as people read it, e.g. `7:00 AM`.
+fn time(time: NaiveTime) -> String {
+	twelve_with_minutes(time, "AM", "PM")
+}
+
+fn push_unique(list: &mut Vec<String>, text: &str) {
+	if !list.iter().any(|item| item == text) {
+		list.push(text.to_string());
+	}
+}
+
+/// Escape text for a Markdown table cell.
+fn escape_markdown(text: &str) -> String {
+	text.replace('\\', "\\\\").replace('|', "\\|")
+}
+
+/// Tables of the hours of `spaces` from `first` until `last`, inclusive.
+#[derive(Debug)]
+pub struct Table<'spaces, 'schedule, Tz: TimeZone> {
+	title: Option<String>,
+	spaces: &'spaces [Space<'schedule, Tz>],
+	first: NaiveDate,
+	last: NaiveDate,
+	timezone: Tz,
+}
+
+impl<'spaces, 'schedule, Tz: TimeZone> Table<'spaces, 'schedule, Tz> {
+	/// A table for each of `spaces` of its hours on each day from `first`
+	/// until `last`, in `timezone`.
+	#[must_use]
+	pub fn new(
+		spaces: &'spaces [Space<'schedule, Tz>],
+		first: NaiveDate,
+		last: NaiveDate,
+		timezone: Tz,
+	) -> Self {
+		Self {
+			title: None,
+			spaces,
+			first,
+			last,
+			timezone,
+		}
+	}
+
+	#[must_use]
+	pub fn title(mut self, title: &str) -> Self {
+		self.title = Some(title.to_string());
+		self
+	}
+
+	/// The notes of `space`'s parts which open it on the day from `start`
+	/// until `end`, given its `periods`.
+	fn notes(
+		space: &Space<'schedule, Tz>,
+		start: &DateTime<Tz>,
+		end: &DateTime<Tz>,
+		periods: &[Period<Tz>],
+	) -> Vec<String> {
+		let mut notes: Vec<String> = Vec::new();
+		for schedule in space.schedules() {
+			for part in schedule.parts() {
+				let Some(opens) = part.opens().filter(|_| !part.notes().is_empty()) else {
+					continue;
+				};
+				let Some(open) = opens.instances(start).next() else {
+					continue;
+				};
+				let in_effect = schedule
+					.effective()
+					.as_ref()
+					.is_none_or(|from| from <= &open)
+					&& schedule
+						.expires()
+						.as_ref()
+						.is_none_or(|until| &open < until);
+				let opened = periods
+					.iter()
+					.any(|period| period.is_open() && period.start() <= &open && &open < period.end());
+				if start <= &open && &open < end && in_effect && opened {
+					for note in part.notes() {
+						push_unique(&mut notes, note);
+					}
+				}
+			}
+		}
+		notes
+	}
+
+	/// The hours of `space` on each day, merged into runs of days.
+	fn rows(&self, space: &Space<'schedule, Tz>) -> Vec<Row> {
+		let (Some(start), Some(end)) = (
+			midnight(&self.timezone, self.first - Duration::days(1)),
+			midnight(&self.timezone, self.last + Duration::days(2)),
+		) else {
+			return Vec::new();
+		};
+		let actual = space.periods(&start, &end);
+		let regular = timeline::periods(space.schedules(), &start, &end, recurring);
+
+		let mut rows: Vec<Row> = Vec::new();
+		for day in self.first.iter_days().take_while(|day| *day <= self.last) {
+			let (Some(from), Some(until)) = (
+				midnight(&self.timezone, day),
+				midnight(&self.timezone, day + Duration::days(1)),
+			) else {
+				continue;
+			};
+			let hours = timeline::hours(&actual, day);
+			let exception = (hours != timeline::hours(&regular, day)).then(|| {
+				let mut reasons: Vec<String> = Vec::new();
+				for period in &actual {
+					if period.start() < &until && &from < period.end() {
+						if let Some(note) = period.note() {
+							push_unique(&mut reasons, note);
+						}
+					}
+				}
+				reasons
+			});
+			let cell = Cell {
+				hours,
+				exception,
+				notes: Self::notes(space, &from, &until, &actual),
+			};
+
+			match rows.last_mut() {
+				Some(row) if row.cell == cell => row.last = day,
+				_ => rows.push(Row {
+					first: day,
+					last: day,
+					cell,
+				}),
+			}
+		}
+		rows
+	}
+
+	/// The label of a run of days: weekdays for a week or less, e.g.
+	/// `Mon–Fri`, and with dates for longer, e.g. `Mon Nov 25 – Fri Nov 29`.
+	fn days(&self, row: &Row) -> String {
+		let format: &str = if self.last - self.first < Duration::days(7) {
+			"%a"
+		} else {
+			"%a %b %-d"
+		};
+		let first = row.first.format(format).to_string();
+		if row.first == row.last {
+			first
+		} else if format == "%a" {
+			format!("{first}–{}", row.last.format(format))
+		} else {
+			format!("{first} – {}", row.last.format(format))
+		}
+	}
+
+	/// The dates the tables cover, e.g. `Nov 25, 2019 – Dec 1, 2019`.
+	fn range(&self) -> String {
+		let format: &str = "%b %-d, %Y";
+		if self.first == self.last {
+			self.first.format(format).to_string()
+		} else {
+			format!(
+				"{} – {}",
+				self.first.format(format),
+				self.last.format(format)
+			)
+		}
+	}
+
+	/// The hours of a cell, e.g. `7:00 AM – 11:00 PM` or `Closed`.
+	fn hours(cell: &Cell) -> String {
+		if cell.hours.is_empty() {
+			"Closed".to_string()
+		} else {
+			cell
+				.hours
+				.iter()
+				.map(|(open, close)| format!("{} – {}", time(*open), time(*close)))
+				.collect::<Vec<String>>()
+				.join(", ")
+		}
+	}
+
+	/// The footnote numbers of a cell's notes, numbering new notes in
+	/// `footnotes` as they're found.
+	fn footnotes(cell: &Cell, footnotes: &mut Vec<String>) -> Vec<usize> {
+		cell
+			.notes
+			.iter()
+			.map(|note| {
+				footnotes
+					.iter()
+					.position(|footnote| footnote == note)
+					.unwrap_or_else(|| {
+						footnotes.push(note.clone());
+						footnotes.len() - 1
+					}) + 1
+			})
+			.collect()
+	}
+
+	/// The tables in Markdown, with footnotes at the end.
+	#[must_use]
+	pub fn to_markdown(&self) -> String {
+		let mut markdown = String::new();
+		if let Some(title) = &self.title {
+			let _ = writeln!(markdown, "# {title}\n");
+		}
+		let _ = writeln!(markdown, "{}", self.range());
+
+		let mut footnotes: Vec<String> = Vec::new();
+		for space in self.spaces {
+			let _ = writeln!(markdown, "\n## {}\n", space.name());
+			markdown.push_str("| Days | Hours |\n| --- | --- |\n");
+			for row in self.rows(space) {
+				let mut hours = escape_markdown(&Self::hours(&row.cell));
+				if let Some(reasons) = &row.cell.exception {
+					hours = format!("**{hours}**");
+					if !reasons.is_empty() {
+						let _ = write!(hours, " ({})", escape_markdown(&reasons.join("; ")));
+					}
+				}
+				for number in Self::footnotes(&row.cell, &mut footnotes) {
+					let _ = write!(hours, "[^{number}]");
+				}
+				let _ = writeln!(markdown, "| {} | {hours} |", self.days(&row));
+			}
+		}
+
+		if !footnotes.is_empty() {
+			markdown.push('\n');
+			for (index, note) in footnotes.iter().enumerate() {
+				let _ = writeln!(markdown, "[^{}]: {note}", index + 1);
+			}
+		}
+		markdown
+	}
+
+	/// The tables as a standalone HTML page, with a caption and headers for
+	/// screen readers and the notes listed after them.
+	#[must_use]
+	pub fn to_html(&self) -> String {
+		let title: String = escape(self.title.as_deref().unwrap_or("Hours"));
+		let mut html = format!(
+			"<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n<style>\n\
+			 table {{ border-collapse: collapse; margin-bottom: 1em; }}\n\
+			 th, td {{ border: 1px solid #888; padding: 0.25em 0.5em; text-align: left; }}\n\
+			 caption {{ font-weight: bold; text-align: left; }}\n\
+			 .exception {{ background: #fff3b0; }}\n\
+			 </style>\n</head>\n<body>\n<h1>{title}</h1>\n<p>{}</p>\n",
+			escape(&self.range())
+		);
+
+		let mut footnotes: Vec<String> = Vec::new();
+		for space in self.spaces {
+			let _ = writeln!(
+				html,
+				"<table>\n<caption>{}</caption>\n<thead>\n<tr><th scope=\"col\">Days</th><th scope=\"col\">Hours</th></tr>\n</thead>\n<tbody>",
+				escape(space.name())
+			);
+			for row in self.rows(space) {
+				let mut hours = escape(&Self::hours(&row.cell));
+				let mut class = "";
+				if let Some(reasons) = &row.cell.exception {
+					class = " class=\"exception\"";
+					hours = format!("<strong>{hours}</strong>");
+					if !reasons.is_empty() {
+						let _ = write!(hours, " ({})", escape(&reasons.join("; ")));
+					}
+				}
+				for number in Self::footnotes(&row.cell, &mut footnotes) {
+					let _ = write!(
+						hours,
+						"<sup><a href=\"#note-{number}\" aria-label=\"Note {number}\">{number}</a></sup>"
+					);
+				}
+				let _ = writeln!(
+					html,
+					"<tr{class}><th scope=\"row\">{}</th><td>{hours}</td></tr>",
+					escape(&self.days(&row))
+				);
+			}
+			html.push_str("</tbody>\n</table>\n");
+		}
+
+		if !footnotes.is_empty() {
+			html.push_str("<h2>Notes</h2>\n<ol>\n");
+			for (index, note) in footnotes.iter().enumerate() {
+				let _ = writeln!(html, "<li id=\"note-{}\">{}</li>", index + 1, escape(note));
+			}
+			html.push_str("</ol>\n");
+		}
+		html.push_str("</body>\n</html>\n");
+		html
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::Table;
+	use crate::{Exception, Part, Reason, Schedule, Space, Specifier, Status};
+	use chrono::{DateTime, FixedOffset, NaiveDate};
+
+	fn space() -> Space<'static, FixedOffset> {
+		let weekly = |day: &str, time: &str| Specifier::Weekly {
+			day: day.to_string(),
+			time: time.to_string(),
+		};
+		let exact = |time: &str| Specifier::Exact(DateTime::parse_from_rfc3339(time).unwrap());
+
+		let mut thanksgiving = Exception::new()
+			.effective(exact("2019-11-28T00:00:00-06:00"))
+			.expires(exact("2019-11-29T00:00:00-06:00"));
+		*thanksgiving.effect_mut() = Some(Status::Closed(Reason::Exception(Some(
+			"Thanksgiving".to_string(),
+		))));
+
+		let schedule = ["Mon", "Tue", "Wed", "Thu", "Fri"].iter().fold(
+			Schedule::new().exception(thanksgiving),
+			|schedule, day| {
+				schedule.part(
+					Part::new()
+						.open(weekly(day, "07:00"))
+						.close(weekly(day, "23:00")),
+				)
+			},
+		);
+		let schedule = schedule.part(
+			Part::new()
+				.open(weekly("Sat", "10:00"))
+				.close(weekly("Sat", "18:00"))
+				.note("Front desk only"),
+		);
+
+		Space::new("Rølvaag <Library>").schedule(schedule)
+	}
+
+	fn table<'spaces>(
+		spaces: &'spaces [Space<'static, FixedOffset>],
+		last: u32,
+	) -> Table<'spaces, 'static, FixedOffset> {
+		Table::new(
+			spaces,
+			NaiveDate::from_ymd_opt(2019, 11, 18).unwrap(),
+			NaiveDate::from_ymd_opt(2019, 11, last).unwrap(),
+			FixedOffset::west_opt(6 * 3600).unwrap(),
+		)
+		.title("Hours")
+	}
+
+	#[test]
+	fn markdown() {
+		let spaces = vec![space()];
+
+		assert_eq!(
+			table(&spaces, 24).to_markdown(),
+			"# Hours\n\
+			 \n\
+			 Nov 18, 2019 – Nov 24, 2019\n\
+			 \n\
+			 ## Rølvaag <Library>\n\
+			 \n\
+			 | Days | Hours |\n\
+			 | --- | --- |\n\
+			 | Mon–Fri | 7:00 AM – 11:00 PM |\n\
+			 | Sat | 10:00 AM – 6:00 PM[^1] |\n\
+			 | Sun | Closed |\n\
+			 \n\
+			 [^1]: Front desk only\n"
+		);
+
+		let markdown = table(&spaces, 30).to_markdown();
+		assert!(markdown.contains("| Mon Nov 25 – Wed Nov 27 | 7:00 AM – 11:00 PM |\n"));
+		assert!(markdown.contains("| Thu Nov 28 | **Closed** (Thanksgiving) |\n"));
+		assert!(markdown.contains("| Sat Nov 30 | 10:00 AM – 6:00 PM[^1] |\n"));
+	}
+
+	#[test]
+	fn across_midnight() {
+		let daily = |name: &str, open: &str, close: &str| {
+			let part = Part::new()
+				.open(Specifier::Daily {
+					time: open.to_string(),
+				})
+				.close(Specifier::Daily {
+					time: close.to_string(),
+				});
+			Space::new(name).schedule(Schedule::new().part(part))
+		};
+		let spaces = vec![
+			daily("Lab", "00:00", "00:00"),
+			daily("Night", "22:00", "02:00"),
+		];
+		let markdown = table(&spaces, 24).to_markdown();
+		assert!(markdown
+			.contains("## Lab\n\n| Days | Hours |\n| --- | --- |\n| Mon–Sun | 12:00 AM – 12:00 AM |\n"));
+		assert!(markdown
+			.contains("## Night\n\n| Days | Hours |\n| --- | --- |\n| Mon–Sun | 10:00 PM – 2:00 AM |\n"));
+	}
+
+	#[test]
+	fn html() {
+		let spaces = vec![space()];
+		let html = table(&spaces, 30).to_html();
+
+		assert!(html.starts_with("<!DOCTYPE html>\n<html lang=\"en\">"));
+		assert!(html.contains("<caption>Rølvaag &lt;Library&gt;</caption>"));
+		assert!(html.contains(
+			"<tr><th scope=\"row\">Mon Nov 18 – Fri Nov 22</th><td>7:00 AM – 11:00 PM</td></tr>"
+		));
+		assert!(html.contains(
+			"<tr class=\"exception\"><th scope=\"row\">Thu Nov 28</th><td><strong>Closed</strong> (Thanksgiving)</td></tr>"
+		));
+		assert!(html.contains("<a href=\"#note-1\" aria-label=\"Note 1\">1</a>"));
+		assert!(html.contains("<li id=\"note-1\">Front desk only</li>"));
+		assert!(html.ends_with("</body>\n</html>\n"));
+	}
+}
@@ -0,0 +1,445 @@
+//! When a space is open over a stretch of time.
+//!
+//! `Space::status_at` answers for a single moment; a timeline answers for a
+//! range, as a sequence of `Period`s, which is what posters, calendars and
+//! summaries need.
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, TimeZone};
+
+use super::specifier::midnight;
+use super::{Exception, Reason, Schedule, Space, Specifier, Status};
+
+/// Recurring specifiers are expanded at most this many times per range.
+const MAX_INSTANCES: usize = 10_000;
+
+/// A range of time, from the first until the second.
+type Span<Tz> = (DateTime<Tz>, DateTime<Tz>);
+
+/// A stretch of time during which a space is either open or closed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Period<Tz: TimeZone> {
+	start: DateTime<Tz>,
+	end: DateTime<Tz>,
+	open: bool,
+	note: Option<String>,
+}
+
+impl<Tz: TimeZone> Period<Tz> {
+	#[must_use]
+	pub fn start(&self) -> &DateTime<Tz> {
+		&self.start
+	}
+
+	#[must_use]
+	pub fn end(&self) -> &DateTime<Tz> {
+		&self.end
+	}
+
+	#[must_use]
+	pub fn is_open(&self) -> bool {
+		self.open
+	}
+
+	/// The reason given by the exception responsible for the period, if any,
+	/// e.g. `LOCKED`.
+	#[must_use]
+	pub fn note(&self) -> Option<&str> {
+		self.note.as_deref()
+	}
+}
+
+/// The open periods of `periods` which belong to `day`, in the local time of
+/// each.
+///
+/// Open time belongs to the day it starts on, so hours running past
+/// midnight, e.g. 22:00 until 02:00, are those of the day they open on; but
+/// a period running through whole days, like that of a space which never
+/// closes, opens again at each midnight in it.  `periods` should start before `day`, so that time
+/// carried over from the day before isn't taken for an opening.
+pub(crate) fn openings<Tz: TimeZone>(periods: &[Period<Tz>], day: NaiveDate) -> Vec<Period<Tz>> {
+	let mut openings: Vec<Period<Tz>> = Vec::new();
+	for period in periods.iter().filter(|period| period.open) {
+		let mut start: DateTime<Tz> = period.start.clone();
+		while start.naive_local().date() <= day {
+			let date: NaiveDate = start.naive_local().date();
+			let timezone: Tz = start.timezone();
+			// What's left after the last whole day goes with it.
+			let end: DateTime<Tz> = match (
+				midnight(&timezone, date + Duration::days(1)),
+				midnight(&timezone, date + Duration::days(2)),
+			) {
+				(Some(next), Some(after)) if period.end >= after => next,
+				_ => period.end.clone(),
+			};
+			if date == day {
+				openings.push(Period {
+					start: start.clone(),
+					end: end.clone(),
+					..period.clone()
+				});
+			}
+			if end == period.end {
+				break;
+			}
+			start = end;
+		}
+	}
+	openings
+}
+
+/// When the space opens and closes on `day`, by the clock, as `openings` has
+/// it.
+pub(crate) fn hours<Tz: TimeZone>(
+	periods: &[Period<Tz>],
+	day: NaiveDate,
+) -> Vec<(NaiveTime, NaiveTime)> {
+	openings(periods, day)
+		.iter()
+		.map(|period| {
+			(
+				period.start.naive_local().time(),
+				period.end.naive_local().time(),
+			)
+		})
+		.collect()
+}
+
+/// The times between `start` and `end` from an instance of `from` until the
+/// next instance of `until` after it, clipped to that range.
+fn spans<Tz: TimeZone>(
+	from: Option<&Specifier<Tz>>,
+	until: Option<&Specifier<Tz>>,
+	start: &DateTime<Tz>,
+	end: &DateTime<Tz>,
+) -> Vec<Span<Tz>> {
+	let clip = |from: DateTime<Tz>, until: DateTime<Tz>| -> Option<Span<Tz>> {
+		let from: DateTime<Tz> = if from < *start { start.clone() } else { from };
+		let until: DateTime<Tz> = if until > *end { end.clone() } else { until };
+		(from < until).then_some((from, until))
+	};
+
+	match (from, until) {
+		(None, None) => clip(start.clone(), end.clone()).into_iter().collect(),
+		(Some(from), None) => from
+			.instances(&(start.clone() - Duration::weeks(1)))
+			.take(MAX_INSTANCES)
+			.next()
+			.and_then(|from| clip(from, end.clone()))
+			.into_iter()
+			.collect(),
+		(None, Some(until)) => until
+			.instances(start)
+			.take(MAX_INSTANCES)
+			.find(|until| until > start)
+			.and_then(|until| clip(start.clone(), until))
+			.into_iter()
+			.collect(),
+		(Some(from), Some(until)) => {
+			let mut spans: Vec<Span<Tz>> = Vec::new();
+			// Start a week early to catch anything already under way.
+			for from in from
+				.instances(&(start.clone() - Duration::weeks(1)))
+				.take(MAX_INSTANCES)
+			{
+				if from >= *end {
+					break;
+				}
+				let until: Option<DateTime<Tz>> = until
+					.instances(&from)
+					.take(MAX_INSTANCES)
+					.find(|until| *until > from);
+				if let Some(span) = until.and_then(|until| clip(from, until)) {
+					spans.push(span);
+				}
+			}
+			spans
+		}
+	}
+}
+
+/// Whether `time` is within one of the spans from `from` until `until`, so
+/// that a single moment is judged just as a timeline would judge it.
+pub(crate) fn in_effect<Tz: TimeZone>(
+	from: Option<&Specifier<Tz>>,
+	until: Option<&Specifier<Tz>>,
+	time: &DateTime<Tz>,
+) -> bool {
+	let end: DateTime<Tz> = time.clone() + Duration::nanoseconds(1);
+	!spans(from, until, time, &end).is_empty()
+}
+
+/// A timeline under construction: contiguous periods from start to end.
+struct Timeline<Tz: TimeZone> {
+	periods: Vec<Period<Tz>>,
+}
+
+impl<Tz: TimeZone> Timeline<Tz> {
+	fn closed(start: &DateTime<Tz>, end: &DateTime<Tz>) -> Self {
+		Self {
+			periods: vec![Period {
+				start: start.clone(),
+				end: end.clone(),
+				open: false,
+				note: None,
+			}],
+		}
+	}
+
+	/// Mark the time from `start` to `end` as `open` or not, with `note`.
+	fn paint(&mut self, start: &DateTime<Tz>, end: &DateTime<Tz>, open: bool, note: Option<&str>) {
+		let mut periods: Vec<Period<Tz>> = Vec::with_capacity(self.periods.len() + 2);
+		for period in self.periods.drain(..) {
+			if period.end <= *start || period.start >= *end {
+				periods.push(period);
+				continue;
+			}
+			if period.start < *start {
+				periods.push(Period {
+					end: start.clone(),
+					..period.clone()
+				});
+			}
+			if period.end > *end {
+				periods.push(Period {
+					start: end.clone(),
+					..period.clone()
+				});
+			}
+		}
+		periods.push(Period {
+			start: start.clone(),
+			end: end.clone(),
+			open,
+			note: note.map(ToString::to_string),
+		});
+		periods.sort_by(|a, b| a.start.cmp(&b.start));
+		self.periods = periods;
+	}
+
+	/// The periods, with neighbours that say the same thing joined.
+	fn into_periods(self) -> Vec<Period<Tz>> {
+		let mut periods: Vec<Period<Tz>> = Vec::with_capacity(self.periods.len());
+		for period in self.periods {
+			match periods.last_mut() {
+				Some(last) if last.open == period.open && last.note == period.note => {
+					last.end = period.end;
+				}
+				_ => periods.push(period),
+			}
+		}
+		periods
+	}
+}
+
+/// The part of `start` to `end` in which `schedule` is in effect.
+fn window<Tz: TimeZone>(
+	schedule: &Schedule<'_, Tz>,
+	start: &DateTime<Tz>,
+	end: &DateTime<Tz>,
+) -> Option<Span<Tz>> {
+	let start: DateTime<Tz> = match schedule.effective() {
+		Some(effective) if effective > start => effective.clone(),
+		_ => start.clone(),
+	};
+	let end: DateTime<Tz> = match schedule.expires() {
+		Some(expires) if expires < end => expires.clone(),
+		_ => end.clone(),
+	};
+	(start < end).then_some((start, end))
+}
+
+/// Whether `schedule` is in effect at `time`.
+pub(crate) fn in_window<Tz: TimeZone>(schedule: &Schedule<'_, Tz>, time: &DateTime<Tz>) -> bool {
+	window(schedule, time, &(time.clone() + Duration::nanoseconds(1))).is_some()
+}
+
+impl<Tz: TimeZone> Space<'_, Tz> {
+	/// When the space is open and closed from `start` until `end`.
+	///
+	/// The periods are in order, cover the whole range, and alternate unless
+	/// their notes differ.  Parts open the space; exceptions then override
+	/// them, with earlier exceptions taking precedence over later ones.
+	/// `status_at` gives the same answer for any moment in the range.
+	#[must_use]
+	pub fn periods(&self, start: &DateTime<Tz>, end: &DateTime<Tz>) -> Vec<Period<Tz>> {
+		periods(self.schedules(), start, end, |_| true)
+	}
+
+	/// The periods the space opens for on `day`, in `timezone`.
+	///
+	/// Hours running past midnight are those of the day they open on, and a
+	/// space open through whole days opens again at each midnight, so that
+	/// each day can be written on its own.
+	#[must_use]
+	pub fn openings(&self, day: NaiveDate, timezone: &Tz) -> Vec<Period<Tz>> {
+		let (Some(start), Some(end)) = (
+			midnight(timezone, day - Duration::days(1)),
+			midnight(timezone, day + Duration::days(2)),
+		) else {
+			return Vec::new();
+		};
+		openings(&self.periods(&start, &end), day)
+	}
+}
+
+/// When `schedules` are open and closed from `start` until `end`, with only
+/// those of their exceptions which `exceptions` picks out.
+pub(crate) fn periods<Tz: TimeZone>(
+	schedules: &[Schedule<'_, Tz>],
+	start: &DateTime<Tz>,
+	end: &DateTime<Tz>,
+	exceptions: impl Fn(&Exception<'_, Tz>) -> bool,
+) -> Vec<Period<Tz>> {
+	if start >= end {
+		return Vec::new();
+	}
+
+	let mut timeline: Timeline<Tz> = Timeline::closed(start, end);
+	let windows: Vec<(&Schedule<'_, Tz>, Span<Tz>)> = schedules
+		.iter()
+		.filter_map(|schedule| window(schedule, start, end).map(|window| (schedule, window)))
+		.collect();
+
+	for (schedule, (start, end)) in &windows {
+		for part in schedule.parts() {
+			if let (Some(opens), Some(closes)) = (part.opens(), part.closes()) {
+				for (from, until) in spans(Some(opens), Some(closes), start, end) {
+					timeline.paint(&from, &until, true, None);
+				}
+			}
+		}
+	}
+
+	for (schedule, (start, end)) in windows.iter().rev() {
+		for exception in schedule.exceptions().iter().rev() {
+			if !exceptions(exception) {
+				continue;
+			}
+			let (open, note): (bool, Option<&str>) = match exception.effect() {
+				Some(Status::Open(reason)) => (true, note(reason)),
+				Some(Status::Closed(reason)) => (false, note(reason)),
+				None => continue,
+			};
+			for (from, until) in spans(
+				exception.effective_from(),
+				exception.expires_at(),
+				start,
+				end,
+			) {
+				timeline.paint(&from, &until, open, note);
+			}
+		}
+	}
+
+	timeline.into_periods()
+}
+
+fn note<'reason, Tz: TimeZone>(reason: &'reason Reason<'_, Tz>) -> Option<&'reason str> {
+	match reason {
+		Reason::Exception(note) => note.as_deref(),
+		Reason::Part(_) => None,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{Exception, Part, Reason, Schedule, Space, Specifier, Status};
+	use chrono::{DateTime, FixedOffset};
+
+	fn time(rfc3339: &str) -> DateTime<FixedOffset> {
+		DateTime::parse_from_rfc3339(rfc3339).unwrap()
+	}
+
+	#[test]
+	fn parts_and_exceptions() {
+		let weekly = |day: &str, time: &str| Specifier::Weekly {
+			day: day.to_string(),
+			time: time.to_string(),
+		};
+
+		let mut closed = Exception::new()
+			.effective(Specifier::Exact(time("2020-01-14T00:00:00-06:00")))
+			.expires(Specifier::Exact(time("2020-01-15T00:00:00-06:00")));
+		*closed.effect_mut() = Some(Status::Closed(Reason::Exception(Some(
+			"LOCKED".to_string(),
+		))));
+
+		let space: Space<FixedOffset> = Space::new("Boe Chapel").schedule(
+			Schedule::new()
+				.part(
+					Part::new()
+						.open(weekly("Mon", "07:00"))
+						.close(weekly("Mon", "17:00")),
+				)
+				.part(
+					Part::new()
+						.open(weekly("Tue", "22:00"))
+						.close(weekly("Wed", "02:00")),
+				)
+				.exception(closed),
+		);
+
+		let periods = space.periods(
+			&time("2020-01-13T00:00:00-06:00"),
+			&time("2020-01-16T00:00:00-06:00"),
+		);
+		let summary: Vec<(String, bool, Option<&str>)> = periods
+			.iter()
+			.map(|period| (period.start().to_rfc3339(), period.is_open(), period.note()))
+			.collect();
+
+		assert_eq!(
+			summary,
+			vec![
+				("2020-01-13T00:00:00-06:00".to_string(), false, None),
+				("2020-01-13T07:00:00-06:00".to_string(), true, None),
+				("2020-01-13T17:00:00-06:00".to_string(), false, None),
+				(
+					"2020-01-14T00:00:00-06:00".to_string(),
+					false,
+					Some("LOCKED")
+				),
+				("2020-01-15T00:00:00-06:00".to_string(), true, None),
+				("2020-01-15T02:00:00-06:00".to_string(), false, None),
+			]
+		);
+		assert_eq!(periods[5].end(), &time("2020-01-16T00:00:00-06:00"));
+	}
+
+	#[test]
+	fn status_at_agrees() {
+		let weekly = |day: &str, time: &str| Specifier::Weekly {
+			day: day.to_string(),
+			time: time.to_string(),
+		};
+		let part: Part<FixedOffset> = Part::new()
+			.open(weekly("Tue", "22:00"))
+			.close(weekly("Wed", "02:00"));
+		let space: Space<FixedOffset> =
+			Space::new("Tostrud Center").schedule(Schedule::new().part(part.clone()));
+
+		let start = time("2020-01-14T20:00:00-06:00");
+		let periods = space.periods(&start, &time("2020-01-15T04:00:00-06:00"));
+		for minutes in (0..8 * 60).step_by(30) {
+			let at: DateTime<FixedOffset> = start + chrono::Duration::minutes(minutes);
+			let period = periods
+				.iter()
+				.find(|period| period.start() <= &at && &at < period.end())
+				.unwrap();
+			let status = space.status_at(&at);
+			assert_eq!(
+				matches!(status, Status::Open(_)),
+				period.is_open(),
+				"at {at}"
+			);
+		}
+		assert_eq!(
+			space.status_at(&time("2020-01-15T01:00:00-06:00")),
+			Status::Open(Reason::Part(Some(&part)))
+		);
+		assert_eq!(
+			space.status_at(&time("2020-01-15T02:00:00-06:00")),
+			Status::Closed(Reason::Part(None))
+		);
+	}
+}
@@ -1,5 +1,5 @@
 use chrono::{DateTime, FixedOffset};
-use sked::{Exception, Part, Reason, Schedule, Space, Specifier, Status};
+use sked::{Exception, Part, Reason, Schedule, Space, Specifier, Status, StatusChange};
 
 #[cfg(test)]
 mod tests {
@@ -19,7 +19,7 @@ mod tests {
 		};
 	}
 
-	fn generate_space(name: &str) -> (Space<FixedOffset>, Part<FixedOffset>) {
+	fn generate_space(name: &str) -> (Space<'_, FixedOffset>, Part<FixedOffset>) {
 		let mut exception = Exception::new()
 			.effective(Specifier::Weekly {
 				day: "Thursday".to_string(),
@@ -169,4 +169,59 @@ mod tests {
 			Status::Closed(Reason::Part(None))
 		);
 	}
+
+	mod next_status_change {
+		use super::*;
+
+		fn at(time: &str) -> DateTime<FixedOffset> {
+			DateTime::parse_from_rfc3339(time).unwrap()
+		}
+
+		#[test]
+		fn opens_with_the_part() {
+			let (space, _) = generate_space("asdf");
+			assert_eq!(
+				space.next_status_change_at(&at("2020-01-15T12:00:00-06:00")),
+				Some(StatusChange::Opening(
+					at("2020-01-16T07:00:00-06:00"),
+					Reason::Part(None)
+				))
+			);
+		}
+
+		#[test]
+		fn closes_for_the_exception() {
+			let (space, _) = generate_space("asdf");
+			assert_eq!(
+				space.next_status_change_at(&at("2020-01-16T08:00:00-06:00")),
+				Some(StatusChange::Closing(
+					at("2020-01-16T10:15:00-06:00"),
+					Reason::Exception(Some("Closed for lunch.".to_string()))
+				))
+			);
+		}
+
+		#[test]
+		fn agrees_with_status_at() {
+			let (space, _) = generate_space("asdf");
+			let mut time = at("2020-01-15T12:00:00-06:00");
+			for _ in 0..4 {
+				let change = space.next_status_change_at(&time).unwrap();
+				let (StatusChange::Opening(when, _) | StatusChange::Closing(when, _)) = &change;
+				let opening = matches!(change, StatusChange::Opening(..));
+				assert_eq!(matches!(space.status_at(&time), Status::Open(_)), !opening);
+				assert_eq!(matches!(space.status_at(when), Status::Open(_)), opening);
+				time = *when;
+			}
+		}
+
+		#[test]
+		fn never_after_expiry() {
+			let (space, _) = generate_space("asdf");
+			assert_eq!(
+				space.next_status_change_at(&at("2020-02-01T00:00:00-06:00")),
+				None
+			);
+		}
+	}
 }
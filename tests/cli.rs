@@ -0,0 +1,301 @@
+#![cfg(feature = "cli")]
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+const CONFIG: &str = r#"
+[[spaces]]
+name = "Library"
+
+[[spaces.schedules]]
+effective = "2019-08-21T00:00:00+00:00"
+
+[[spaces.schedules.parts]]
+open = { daily = { time = "07:00" } }
+close = { daily = { time = "23:00" } }
+
+[[spaces.schedules.exceptions]]
+effect = "closed"
+note = "Thanksgiving"
+effective = { exact = "2019-11-28T00:00:00+00:00" }
+expires = { exact = "2019-11-29T00:00:00+00:00" }
+
+[[spaces]]
+name = "Gym"
+
+[[spaces.schedules]]
+
+[[spaces.schedules.parts]]
+open = { weekly = { day = "Tue", time = "22:00" } }
+close = { weekly = { day = "Wed", time = "02:00" } }
+
+[[spaces]]
+name = "Lab"
+
+[[spaces.schedules]]
+effective = "2019-12-01T00:00:00+00:00"
+
+[[spaces.schedules.parts]]
+open = { daily = { time = "00:00" } }
+close = { daily = { time = "00:00" } }
+
+[[spaces]]
+name = "Night"
+
+[[spaces.schedules]]
+effective = "2019-12-01T00:00:00+00:00"
+
+[[spaces.schedules.parts]]
+open = { daily = { time = "22:00" } }
+close = { daily = { time = "02:00" } }
+"#;
+
+fn config(name: &str) -> PathBuf {
+	let path = std::env::temp_dir().join(format!("sked-cli-{name}.toml"));
+	fs::write(&path, CONFIG).unwrap();
+	path
+}
+
+fn sked(config: &PathBuf, args: &[&str]) -> Output {
+	Command::new(env!("CARGO_BIN_EXE_sked"))
+		.arg("--config")
+		.arg(config)
+		.args(args)
+		.env("TZ", "UTC")
+		.output()
+		.unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+	String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+#[test]
+fn status_and_next() {
+	let config = config("status");
+
+	let output = sked(
+		&config,
+		&["status", "library", "--at", "2019-11-28T09:00:00+00:00"],
+	);
+	assert!(output.status.success());
+	assert_eq!(stdout(&output), "Library — Closed: Thanksgiving\n");
+
+	let output = sked(
+		&config,
+		&["status", "library", "--at", "2019-11-28T03:00-06:00"],
+	);
+	assert!(output.status.success());
+	assert_eq!(stdout(&output), "Library — Closed: Thanksgiving\n");
+
+	let output = sked(
+		&config,
+		&[
+			"next",
+			"Library",
+			"--json",
+			"--at",
+			"2019-11-28T09:00:00+00:00",
+		],
+	);
+	let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+	assert_eq!(json["change"]["opening"], true);
+	assert_eq!(json["change"]["at"], "2019-11-29T07:00:00+00:00");
+}
+
+#[test]
+fn week_and_open_now() {
+	let config = config("week");
+
+	let output = sked(&config, &["week", "Library", "--from", "2019-11-27"]);
+	assert_eq!(
+		stdout(&output).lines().take(3).collect::<Vec<&str>>(),
+		vec![
+			"Library",
+			"Wed Nov 27  7 AM–11 PM",
+			"Thu Nov 28  Closed: Thanksgiving"
+		]
+	);
+
+	let output = sked(&config, &["open-now", "--json", "--at", "2019-11-27T12:00"]);
+	assert_eq!(stdout(&output), "[\"Library\"]\n");
+}
+
+#[test]
+fn overnight() {
+	let config = config("overnight");
+	let at = "2019-11-27T01:00:00+00:00";
+
+	let output = sked(&config, &["status", "Gym", "--at", at]);
+	assert_eq!(stdout(&output), "Gym — Open\n");
+
+	let output = sked(&config, &["open-now", "--json", "--at", at]);
+	assert_eq!(stdout(&output), "[\"Gym\"]\n");
+
+	let output = sked(&config, &["next", "Gym", "--json", "--at", at]);
+	let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+	assert_eq!(json["change"]["opening"], false);
+	assert_eq!(json["change"]["at"], "2019-11-27T02:00:00+00:00");
+
+	let output = sked(&config, &["week", "Gym", "--from", "2019-11-26"]);
+	assert_eq!(
+		stdout(&output).lines().nth(1),
+		Some("Tue Nov 26  10 PM–2 AM")
+	);
+}
+
+#[test]
+fn week_across_midnight() {
+	let config = config("midnight");
+
+	let output = sked(&config, &["week", "Lab", "--from", "2019-12-02"]);
+	let days: Vec<String> = stdout(&output)
+		.lines()
+		.skip(1)
+		.map(ToString::to_string)
+		.collect();
+	assert_eq!(days.len(), 7);
+	assert!(
+		days.iter().all(|day| day.ends_with("  midnight–midnight")),
+		"{:?}",
+		days
+	);
+
+	// The night carried over from Sunday isn't an opening on Monday.
+	let output = sked(&config, &["week", "Night", "--from", "2019-12-02"]);
+	assert_eq!(
+		stdout(&output).lines().nth(1),
+		Some("Mon Dec  2  10 PM–2 AM")
+	);
+}
+
+#[test]
+fn exit_codes() {
+	let config = config("errors");
+
+	let output = sked(&config, &["status", "Pool"]);
+	assert_eq!(output.status.code(), Some(1));
+	assert!(String::from_utf8_lossy(&output.stderr).contains("no space called Pool"));
+
+	assert_eq!(sked(&config, &["status"]).status.code(), Some(2));
+	assert_eq!(
+		sked(&PathBuf::from("missing.toml"), &["open-now"])
+			.status
+			.code(),
+		Some(1)
+	);
+}
+
+#[test]
+fn bad_config() {
+	let path = std::env::temp_dir().join("sked-cli-bad.toml");
+	let check = |text: &str, args: &[&str], value: &str| {
+		fs::write(&path, text).unwrap();
+		let output = sked(&path, args);
+		assert_eq!(output.status.code(), Some(1));
+		let stderr = String::from_utf8_lossy(&output.stderr);
+		assert!(stderr.contains("sked-cli-bad.toml"), "{}", stderr);
+		assert!(stderr.contains(value), "{}", stderr);
+	};
+
+	let bad_day = CONFIG.to_string()
+		+ r#"
+[[spaces.schedules.parts]]
+open = { weekly = { day = "Mun", time = "07:00" } }
+close = { weekly = { day = "Mon", time = "17:00" } }
+"#;
+	check(
+		&bad_day,
+		&["status", "Library", "--at", "2019-11-25T09:00"],
+		"`Mun` isn't a day of the week",
+	);
+	check(&bad_day, &["open-now"], "`Mun` isn't a day of the week");
+	check(
+		&CONFIG.replace(r#"time = "07:00""#, r#"time = "7am""#),
+		&["open-now"],
+		"`7am` isn't a time of day",
+	);
+}
+
+#[test]
+fn import() {
+	let pdf = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("2019FallBreakBldgSch.pdf");
+	let out = std::env::temp_dir().join("sked-cli-import.toml");
+	let _ = fs::remove_file(&out);
+	let pdf = pdf.to_str().unwrap();
+
+	let output = sked(&out, &["import", pdf, "--offset", "-05:00", "--dry-run"]);
+	assert!(output.status.success());
+	let review = stdout(&output);
+	assert!(review.starts_with("Table on page 1, 26 rows by 11 columns:"));
+	assert!(
+		review.contains("Boe Chapel, 2019-10-11: \"7:00 a.m. - 9:30 p.m.\" -> 07:00–21:30 (1.00)")
+	);
+	assert!(!out.exists());
+
+	let output = sked(
+		&out,
+		&[
+			"import",
+			pdf,
+			"--offset",
+			"-05:00",
+			"--out",
+			out.to_str().unwrap(),
+		],
+	);
+	assert!(output.status.success());
+	assert!(stdout(&output).starts_with("Wrote 19 spaces to "));
+
+	let output = sked(
+		&out,
+		&["status", "Boe Chapel", "--at", "2019-10-12T09:00:00-05:00"],
+	);
+	assert_eq!(stdout(&output), "Boe Chapel — Open\n");
+
+	let missing = sked(&out, &["import", "missing.pdf"]);
+	assert_eq!(missing.status.code(), Some(1));
+}
+
+#[test]
+fn pdf_tools() {
+	let pdf = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("2019FallBreakBldgSch.pdf");
+	let pdf = pdf.to_str().unwrap();
+	let config = PathBuf::from("unused.toml");
+
+	let ops = stdout(&sked(&config, &["pdf", "ops", pdf, "--page", "1"]));
+	assert!(ops.starts_with("Page 1:\n     0  "));
+	// Names and strings as they're written, with where the text is within
+	// text objects.
+	assert!(ops.contains("     8     11.99   -11.96  /TT0 1 Tf\n"));
+	assert!(ops.contains("    10    216.28   488.20  [(O) -1.2 (p) 0.6 (e) -2.5 (n) 0.7 ( )] TJ\n"));
+	assert!(ops.contains("    12                     EMC\n"));
+
+	let output = sked(&config, &["pdf", "text", pdf, "--json"]);
+	let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+	assert_eq!(json[0]["page"], 1);
+	assert!(json[0]["runs"]
+		.as_array()
+		.unwrap()
+		.iter()
+		.any(|run| run["text"].as_str().unwrap().trim() == "Boe Chapel"));
+
+	let objects = stdout(&sked(&config, &["pdf", "objects", pdf]));
+	assert!(objects.contains("  Catalog -> "));
+	assert!(objects.contains("\nUnreferenced: "));
+
+	let grid = stdout(&sked(&config, &["pdf", "grid", pdf]));
+	assert!(grid.starts_with("Table on page 1:\n2019 Fall Break Building Schedule"));
+
+	assert_eq!(
+		sked(&config, &["pdf", "ops", pdf, "--page", "9"])
+			.status
+			.code(),
+		Some(1)
+	);
+	assert_eq!(
+		sked(&config, &["pdf", "grid", pdf, "--json"]).status.code(),
+		Some(2)
+	);
+}
@@ -0,0 +1,266 @@
+use chrono::{DateTime, FixedOffset};
+use sked::pdf::Pdf;
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn load(name: &str) -> Pdf {
+		Pdf::load(std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(name)).unwrap()
+	}
+
+	#[test]
+	fn info() {
+		let pdf: Pdf = load("2019ThanksgivingBreakSchedule.pdf");
+		let info = pdf.info();
+
+		assert_eq!(pdf.version(), "1.5");
+		assert_eq!(info.title(), None);
+		assert_eq!(info.creator(), Some("Acrobat PDFMaker 11 for Excel"));
+		assert_eq!(
+			info.creation_date(),
+			Some(&DateTime::<FixedOffset>::parse_from_rfc3339("2019-11-21T01:59:33-06:00").unwrap())
+		);
+	}
+
+	#[test]
+	fn pages() {
+		let pdf: Pdf = load("2019FallBreakBldgSch.pdf");
+		let pages: Vec<_> = pdf.pages().collect();
+		assert_eq!(pages.len(), 1);
+		assert_eq!(pages[0].number(), 1);
+
+		let operations = pages[0].operations().unwrap();
+		assert!(operations.map(Result::unwrap).count() > 0);
+
+		let text: Vec<String> = pages[0]
+			.text()
+			.unwrap()
+			.iter()
+			.map(|run| run.text().trim().to_string())
+			.collect();
+		assert!(text.contains(&"Alumni Hall".to_string()));
+		assert!(text.contains(&"7:00 a.m.".to_string()));
+	}
+
+	#[test]
+	fn from_bytes() {
+		let bytes = std::fs::read(
+			std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("2019FallBreakBldgSch.pdf"),
+		)
+		.unwrap();
+		let pdf: Pdf = Pdf::from_bytes(&bytes).unwrap();
+		assert!(pdf.page(1).is_some());
+		assert!(pdf.page(2).is_none());
+	}
+
+	#[test]
+	fn coordinate_space() {
+		let pdf: Pdf = load("2019FallBreakBldgSch.pdf");
+		let space = pdf.page(1).unwrap().coordinate_space().unwrap();
+
+		assert_eq!(space.rotation(), 0);
+		assert!(space.width() > space.height());
+
+		// Runs are positioned relative to the crop box.
+		for run in pdf.page(1).unwrap().text().unwrap() {
+			assert!(run.origin().x() >= 0.0 && run.origin().x() <= space.width());
+			assert!(run.origin().y() >= 0.0 && run.origin().y() <= space.height());
+		}
+	}
+
+	#[test]
+	fn tagged_tables() {
+		let pdf: Pdf = load("2019FallBreakBldgSch.pdf");
+		let tables = pdf.tables().unwrap();
+		assert_eq!(tables.len(), 1);
+
+		let table = &tables[0];
+		assert_eq!(table.page(), Some(1));
+		assert_eq!(table.columns(), 11);
+
+		let building = table.cell(2, 0).unwrap();
+		assert_eq!((building.text(), building.row_span()), ("Building", 3));
+
+		let friday = table.cell(2, 2).unwrap();
+		assert_eq!(
+			(friday.text(), friday.column(), friday.col_span()),
+			("Friday", 1, 2)
+		);
+		assert_eq!(table.cell(3, 1).unwrap().text(), "10/11/19");
+		assert_eq!(table.cell(4, 2).unwrap().text(), "Close");
+
+		let alumni: Vec<&str> = table.row(5).iter().map(|cell| cell.text()).collect();
+		assert_eq!(
+			alumni,
+			vec![
+				"Alumni Hall",
+				"7:00 a.m.",
+				"5:00 p.m.",
+				"CLOSED",
+				"CLOSED",
+				"7:00 a.m.",
+				"5:00 p.m.",
+				"7:00 a.m.",
+				"5:00 p.m."
+			]
+		);
+	}
+
+	#[test]
+	fn entries() {
+		use chrono::{NaiveDate, Weekday};
+		use sked::pdf::headers::Day;
+
+		let date = |month: u32, day: u32| Day::Date(NaiveDate::from_ymd_opt(2019, month, day).unwrap());
+		let find = |entries: &[sked::pdf::headers::Entry], space: &str, day: Day| -> String {
+			entries
+				.iter()
+				.find(|entry| entry.space() == space && entry.day() == day)
+				.map(|entry| entry.hours().to_string())
+				.unwrap_or_default()
+		};
+
+		let thanksgiving = load("2019ThanksgivingBreakSchedule.pdf").entries().unwrap();
+		assert_eq!(
+			find(&thanksgiving, "Boe Chapel", date(11, 26)),
+			"7:00 a.m. - 10:00 p.m."
+		);
+		assert_eq!(find(&thanksgiving, "Art Barn", date(11, 28)), "CLOSED");
+
+		// The dates are in a row of their own, under the weekdays.
+		let winter = load("2019WinterBreakBldgSch.pdf").entries().unwrap();
+		assert_eq!(find(&winter, "Buntrock Commons", date(12, 18)), "0800-2000");
+		assert_eq!(find(&winter, "Buntrock Commons", date(12, 21)), "1000-2000");
+		assert_eq!(
+			find(
+				&winter,
+				"Buntrock Commons",
+				Day::Date(NaiveDate::from_ymd_opt(2020, 1, 1).unwrap())
+			),
+			"1100-2100"
+		);
+
+		let academic = load("201920AcademicBldgHrs.pdf").entries().unwrap();
+		assert_eq!(
+			find(&academic, "Alumni Hall", Day::Weekday(Weekday::Wed)),
+			"7:00 a.m. - 5:00 p.m."
+		);
+		assert_eq!(
+			find(&academic, "Alumni Hall", Day::Weekday(Weekday::Sat)),
+			"CLOSED"
+		);
+	}
+
+	#[test]
+	fn import_review() {
+		use chrono::FixedOffset;
+		use sked::pdf::import::Import;
+
+		let pdf: Pdf = load("2019FallBreakBldgSch.pdf");
+		let import: Import<FixedOffset> =
+			Import::from_pdf(&pdf, &FixedOffset::west_opt(5 * 3600).unwrap()).unwrap();
+
+		assert_eq!(import.spaces().len(), 19);
+		let boe = import
+			.spaces()
+			.iter()
+			.find(|space| space.name() == "Boe Chapel")
+			.unwrap();
+		let exceptions = boe.schedules()[0].exceptions();
+		// Open then closed for the rest of the day, for each of five days.
+		assert_eq!(exceptions.len(), 10);
+		let provenance = exceptions[0].provenance().unwrap();
+		assert_eq!(provenance.text(), "7:00 a.m. - 9:30 p.m.");
+		assert_eq!(provenance.page(), Some(1));
+		assert!(provenance
+			.source()
+			.unwrap()
+			.ends_with("2019FallBreakBldgSch.pdf"));
+		assert!(provenance.bbox().is_some());
+
+		// Rolvaag Library's name is marked with a footnote about the book drop.
+		let review = import.review();
+		assert_eq!(review.items().len(), 95);
+		assert!(review.flagged().count() > 0);
+		assert!(review
+			.flagged()
+			.all(|item| item.space() == "Rolvaag Library"));
+		assert!(review
+			.to_string()
+			.contains("Rolvaag Library, 2019-10-11 (page 1)"));
+		assert!(review.to_json().contains("\"space\": \"Rolvaag Library\""));
+	}
+
+	#[test]
+	fn republish() {
+		use chrono::{FixedOffset, NaiveDate};
+		use sked::pdf::import::Import;
+		use sked::{DayHours, Grid};
+
+		let pdf: Pdf = load("2019FallBreakBldgSch.pdf");
+		let import: Import<FixedOffset> =
+			Import::from_pdf(&pdf, &FixedOffset::west_opt(5 * 3600).unwrap()).unwrap();
+		let grid = Grid::new(
+			import.spaces(),
+			NaiveDate::from_ymd_opt(2019, 10, 11).unwrap(),
+			NaiveDate::from_ymd_opt(2019, 10, 15).unwrap(),
+			FixedOffset::west_opt(5 * 3600).unwrap(),
+		)
+		.title("2019 Fall Break Building Schedule");
+
+		let boe = import
+			.spaces()
+			.iter()
+			.position(|space| space.name() == "Boe Chapel")
+			.unwrap();
+		assert_eq!(
+			grid.hours()[boe][0],
+			DayHours::Open(vec![("7:00 a.m.".to_string(), "9:30 p.m.".to_string())])
+		);
+
+		let svg = grid.to_svg();
+		assert!(svg.contains(">Boe Chapel</text>"));
+		assert!(svg.contains(">10/11/19</text>"));
+
+		let mut bytes: Vec<u8> = Vec::new();
+		grid.to_pdf().save_to(&mut bytes).unwrap();
+		let republished = Pdf::from_bytes(&bytes).unwrap();
+		assert_eq!(
+			republished.info().title(),
+			Some("2019 Fall Break Building Schedule")
+		);
+
+		// The republished document is untagged, so its table is read from where
+		// the text was drawn.
+		assert!(republished.structure().unwrap().is_none());
+		let tables = republished.tables().unwrap();
+		assert_eq!(tables.len(), 1);
+		let table = &tables[0];
+		assert_eq!((table.page(), table.columns()), (Some(1), 11));
+		let friday = table.cell(1, 1).unwrap();
+		assert_eq!(
+			(friday.text(), friday.column(), friday.col_span()),
+			("Friday", 1, 2)
+		);
+		let alumni: Vec<&str> = table.row(4).iter().map(|cell| cell.text()).collect();
+		assert_eq!(
+			alumni,
+			vec![
+				"Alumni Hall",
+				"7:00 a.m.",
+				"5:00 p.m.",
+				"CLOSED",
+				"CLOSED",
+				"7:00 a.m.",
+				"5:00 p.m.",
+				"7:00 a.m.",
+				"5:00 p.m."
+			]
+		);
+
+		let reimported: Import<FixedOffset> =
+			Import::from_pdf(&republished, &FixedOffset::west_opt(5 * 3600).unwrap()).unwrap();
+		assert_eq!(reimported.spaces().len(), import.spaces().len());
+	}
+}
@@ -0,0 +1,294 @@
+#![cfg(feature = "serde")]
+
+use chrono::{DateTime, FixedOffset};
+use serde_json::Value;
+use sked::{Exception, Part, Provenance, Reason, Schedule, Space, Specifier, Status};
+
+fn weekly(day: &str, time: &str) -> Specifier<FixedOffset> {
+	Specifier::Weekly {
+		day: day.to_string(),
+		time: time.to_string(),
+	}
+}
+
+fn generate_space() -> Space<'static, FixedOffset> {
+	let mut lunch = Exception::new()
+		.effective(weekly("Thursday", "10:15"))
+		.expires(weekly("Thursday", "11:00"));
+	*lunch.effect_mut() = Some(Status::Closed(Reason::Exception(Some(
+		"Closed for lunch.".to_string(),
+	))));
+
+	let mut holiday = Exception::new()
+		.effective(Specifier::Exact(
+			DateTime::parse_from_rfc3339("2023-11-23T00:00:00-05:00").unwrap(),
+		))
+		.expires(Specifier::Exact(
+			DateTime::parse_from_rfc3339("2023-11-24T00:00:00-05:00").unwrap(),
+		));
+	*holiday.effect_mut() = Some(Status::Closed(Reason::Exception(None)));
+	*holiday.provenance_mut() = Some(
+		Provenance::new("Thanksgiving: Closed")
+			.with_source("hours.pdf")
+			.with_page(2)
+			.with_confidence(0.75),
+	);
+
+	let mut schedule = Schedule::new()
+		.part(
+			Part::new()
+				.open(weekly("Thursday", "07:00"))
+				.close(weekly("Thursday", "17:00"))
+				.note("Front desk"),
+		)
+		.part(
+			Part::new()
+				.open(Specifier::Daily {
+					time: "20:00".to_string(),
+				})
+				.close(Specifier::Daily {
+					time: "22:00".to_string(),
+				}),
+		)
+		.exception(lunch)
+		.exception(holiday);
+	*schedule.effective_mut() =
+		Some(DateTime::parse_from_rfc3339("2023-08-21T00:00:00-05:00").unwrap());
+
+	Space::new("Library").schedule(schedule)
+}
+
+#[test]
+fn json_round_trip() {
+	let space = generate_space();
+	let json = serde_json::to_string(&space).unwrap();
+	let read: Space<FixedOffset> = serde_json::from_str(&json).unwrap();
+
+	assert_eq!(serde_json::to_string(&read).unwrap(), json);
+
+	let schedule = &read.schedules()[0];
+	assert_eq!(schedule.parts()[0], space.schedules()[0].parts()[0]);
+	assert_eq!(
+		schedule.exceptions()[0].effect(),
+		&Some(Status::Closed(Reason::Exception(Some(
+			"Closed for lunch.".to_string()
+		))))
+	);
+	assert_eq!(
+		schedule.exceptions()[1].provenance(),
+		space.schedules()[0].exceptions()[1].provenance()
+	);
+}
+
+#[test]
+fn yaml_round_trip() {
+	let yaml = serde_yaml::to_string(&generate_space()).unwrap();
+	assert!(yaml.contains("  - open:\n      weekly:\n"), "{}", yaml);
+
+	let read: Space<FixedOffset> = serde_yaml::from_str(&yaml).unwrap();
+	assert_eq!(serde_yaml::to_string(&read).unwrap(), yaml);
+}
+
+#[test]
+fn editable_shape() {
+	let value = serde_json::to_value(generate_space()).unwrap();
+	let schedule = &value["schedules"][0];
+
+	assert_eq!(
+		schedule["parts"][0]["open"],
+		serde_json::json!({ "weekly": { "day": "Thursday", "time": "07:00" } })
+	);
+	assert_eq!(
+		schedule["parts"][1]["close"],
+		serde_json::json!({ "daily": { "time": "22:00" } })
+	);
+	assert_eq!(
+		schedule["exceptions"][0],
+		serde_json::json!({
+			"effect": "closed",
+			"note": "Closed for lunch.",
+			"effective": { "weekly": { "day": "Thursday", "time": "10:15" } },
+			"expires": { "weekly": { "day": "Thursday", "time": "11:00" } },
+		})
+	);
+	assert_eq!(
+		schedule["exceptions"][1]["effective"],
+		serde_json::json!({ "exact": "2023-11-23T00:00:00-05:00" })
+	);
+	assert!(schedule.get("expires").is_none());
+}
+
+#[test]
+fn ambiguous_specifier() {
+	let error = serde_json::from_str::<Specifier<FixedOffset>>(
+		r#"{ "daily": { "time": "07:00" }, "exact": "2023-11-23T00:00:00-05:00" }"#,
+	)
+	.unwrap_err();
+	assert!(error.to_string().contains("exactly one"), "{}", error);
+}
+
+#[test]
+fn bad_day_or_time() {
+	let error = serde_json::from_str::<Specifier<FixedOffset>>(
+		r#"{ "weekly": { "day": "Mun", "time": "07:00" } }"#,
+	)
+	.unwrap_err();
+	assert!(error.to_string().contains("`Mun` isn't a day"), "{}", error);
+
+	let error = toml::from_str::<Space<FixedOffset>>(
+		r#"
+name = "Gym"
+
+[[schedules]]
+
+[[schedules.parts]]
+open = { daily = { time = "7am" } }
+close = { daily = { time = "13:00" } }
+"#,
+	)
+	.unwrap_err();
+	assert!(
+		error.to_string().contains("`7am` isn't a time"),
+		"{}",
+		error
+	);
+}
+
+#[test]
+fn bad_confidence() {
+	for confidence in &["7.5", "-0.1", "nan", "inf"] {
+		let error =
+			toml::from_str::<Provenance>(&format!("text = \"Closed\"\nconfidence = {confidence}"))
+				.unwrap_err();
+		assert!(
+			error.to_string().contains("confidence must be from 0 to 1"),
+			"{}",
+			error
+		);
+	}
+
+	let error = toml::from_str::<Provenance>(
+		r#"
+text = "Closed"
+confidence = 0.5
+bbox = { lower_left = [0.0, nan], upper_right = [1.0, 1.0] }
+"#,
+	)
+	.unwrap_err();
+	assert!(error.to_string().contains("finite"), "{}", error);
+
+	let provenance: Provenance = toml::from_str("text = \"Closed\"\nconfidence = 1").unwrap();
+	assert_eq!(provenance, Provenance::new("Closed"));
+}
+
+#[test]
+fn toml_by_hand() {
+	let text = r#"
+name = "Gym"
+
+[[schedules]]
+effective = "2023-08-21T00:00:00-05:00"
+
+[[schedules.parts]]
+open = { weekly = { day = "Saturday", time = "09:00" } }
+close = { weekly = { day = "Saturday", time = "13:00" } }
+
+[[schedules.exceptions]]
+effect = "open"
+note = "Extended hours"
+effective = { exact = "2023-12-09T13:00:00-05:00" }
+expires = { exact = "2023-12-09T16:00:00-05:00" }
+"#;
+	let space: Space<FixedOffset> = toml::from_str(text).unwrap();
+	assert_eq!(space.name(), "Gym");
+
+	let schedule = &space.schedules()[0];
+	assert_eq!(
+		schedule.parts()[0].opens(),
+		Some(&weekly("Saturday", "09:00"))
+	);
+	assert_eq!(
+		schedule.exceptions()[0].effect(),
+		&Some(Status::Open(Reason::Exception(Some(
+			"Extended hours".to_string()
+		))))
+	);
+
+	let written = toml::to_string(&space).unwrap();
+	let reread: Space<FixedOffset> = toml::from_str(&written).unwrap();
+	assert_eq!(toml::to_string(&reread).unwrap(), written);
+}
+
+/// Whether `value` follows `schema`, as far as the keywords `json_schema`
+/// uses go.
+fn follows(value: &Value, schema: &Value, root: &Value) -> bool {
+	if let Some(Value::String(reference)) = schema.get("$ref") {
+		let name: &str = reference.trim_start_matches("#/definitions/");
+		return follows(value, &root["definitions"][name], root);
+	}
+	if let Some(Value::Array(schemas)) = schema.get("oneOf") {
+		return schemas
+			.iter()
+			.filter(|schema| follows(value, schema, root))
+			.count()
+			== 1;
+	}
+	if let Some(Value::Array(options)) = schema.get("enum") {
+		return options.contains(value);
+	}
+
+	match (schema.get("type").and_then(Value::as_str), value) {
+		(Some("object"), Value::Object(members)) => {
+			let properties = &schema["properties"];
+			let required = schema["required"].as_array().cloned().unwrap_or_default();
+			required
+				.iter()
+				.all(|key| members.contains_key(key.as_str().unwrap()))
+				&& members.iter().all(|(key, value)| {
+					properties
+						.get(key)
+						.is_some_and(|property| follows(value, property, root))
+				})
+		}
+		(Some("array"), Value::Array(values)) => values
+			.iter()
+			.all(|value| follows(value, &schema["items"], root)),
+		(Some("string"), Value::String(_)) => true,
+		(Some("integer"), Value::Number(number)) => number.is_u64(),
+		(Some("number"), Value::Number(_)) => true,
+		_ => false,
+	}
+}
+
+#[test]
+fn follows_schema() {
+	let schema: Value = serde_json::from_str(&sked::json_schema()).unwrap();
+	let value: Value = serde_json::to_value(generate_space()).unwrap();
+	assert!(follows(&value, &schema, &schema));
+
+	let mut value: Value = value;
+	value["$schema"] = "schema/space.schema.json".into();
+	assert!(follows(&value, &schema, &schema));
+
+	value["schedules"][0]["parts"][0]["open"]["weekly"]["dya"] = "Friday".into();
+	assert!(!follows(&value, &schema, &schema));
+}
+
+#[test]
+fn schema_days_are_read() {
+	let schema: Value = serde_json::from_str(&sked::json_schema()).unwrap();
+	let read = |day: &Value| {
+		serde_json::from_value::<Specifier<FixedOffset>>(
+			serde_json::json!({ "weekly": { "day": day, "time": "07:00" } }),
+		)
+		.is_ok()
+	};
+
+	let days = schema["definitions"]["day"]["enum"].as_array().unwrap();
+	assert_eq!(days.len(), 14);
+	assert!(days.iter().all(read));
+	for day in ["monday", "MON", "Mo", "Mondays"] {
+		assert!(!days.contains(&day.into()));
+		assert!(!read(&day.into()), "{}", day);
+	}
+}
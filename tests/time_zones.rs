@@ -0,0 +1,95 @@
+use chrono::{DateTime, Local, NaiveDate, TimeZone};
+use sked::{Part, Reason, Schedule, Space, Specifier, Status, StatusChange};
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::sync::Once;
+
+	/// Run in Chicago, which leaves daylight time at 02:00 on 2019-11-03 and
+	/// starts it again at 02:00 on 2020-03-08.  Every test calls this before
+	/// anything reads the local time zone.
+	fn chicago() {
+		static ZONE: Once = Once::new();
+		ZONE.call_once(|| std::env::set_var("TZ", "America/Chicago"));
+	}
+
+	fn at(date: &str, time: &str) -> DateTime<Local> {
+		chicago();
+		let date: NaiveDate = date.parse().unwrap();
+		Local
+			.from_local_datetime(&date.and_time(time.parse().unwrap()))
+			.earliest()
+			.unwrap()
+	}
+
+	fn daily(open: &str, close: &str) -> Space<'static, Local> {
+		chicago();
+		let part = Part::new()
+			.open(Specifier::Daily {
+				time: open.to_string(),
+			})
+			.close(Specifier::Daily {
+				time: close.to_string(),
+			});
+		Space::new("Night").schedule(Schedule::new().part(part))
+	}
+
+	fn is_open(space: &Space<Local>, time: &DateTime<Local>) -> bool {
+		let open: bool = matches!(space.status_at(time), Status::Open(_));
+		let period: bool = space.periods(time, &(*time + chrono::Duration::minutes(1)))[0].is_open();
+		assert_eq!(open, period, "{}", time);
+		open
+	}
+
+	#[test]
+	fn clocks_go_back() {
+		// 01:30 happens twice on 2019-11-03; the space closes at the first.
+		let space = daily("18:00", "01:30");
+		assert!(is_open(&space, &at("2019-11-02", "20:00:00")));
+		assert!(is_open(&space, &at("2019-11-03", "01:15:00")));
+		let second: DateTime<Local> = at("2019-11-03", "01:15:00") + chrono::Duration::hours(1);
+		assert!(!is_open(&space, &second));
+		assert!(is_open(&space, &at("2019-11-03", "23:00:00")));
+
+		let periods = space.periods(&at("2019-11-02", "00:00:00"), &at("2019-11-05", "00:00:00"));
+		assert_eq!(periods.iter().filter(|period| period.is_open()).count(), 4);
+	}
+
+	#[test]
+	fn clocks_go_forward() {
+		// 02:30 doesn't happen on 2020-03-08; the space closes at 03:30 instead,
+		// two and a half hours after 00:00 by the clock before the change.
+		let space = daily("18:00", "02:30");
+		assert!(is_open(&space, &at("2020-03-08", "01:00:00")));
+		assert!(is_open(&space, &at("2020-03-08", "03:15:00")));
+		assert!(!is_open(&space, &at("2020-03-08", "03:45:00")));
+		assert!(is_open(&space, &at("2020-03-09", "02:15:00")));
+		assert!(!is_open(&space, &at("2020-03-09", "02:45:00")));
+	}
+
+	mod next_status_change {
+		use super::*;
+
+		#[test]
+		fn across_the_changes() {
+			let space = daily("18:00", "01:30");
+			assert!(matches!(
+				space.next_status_change_at(&at("2019-11-02", "20:00:00")),
+				Some(StatusChange::Closing(when, Reason::Part(None))) if when == at("2019-11-03", "01:30:00")
+			));
+
+			let space = daily("18:00", "02:30");
+			assert!(matches!(
+				space.next_status_change_at(&at("2020-03-08", "01:00:00")),
+				Some(StatusChange::Closing(when, Reason::Part(None))) if when == at("2020-03-08", "03:30:00")
+			));
+		}
+
+		#[test]
+		fn from_now() {
+			// A year ahead from now crosses both changes.
+			assert!(daily("18:00", "01:30").next_status_change().is_some());
+		}
+	}
+}